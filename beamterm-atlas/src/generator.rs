@@ -359,7 +359,7 @@ impl BitmapFontGenerator {
     }
 }
 
-fn attrs(font_family: &str, style: FontStyle) -> Attrs {
+fn attrs(font_family: &str, style: FontStyle) -> Attrs<'_> {
     let default_weight = Weight((Weight::NORMAL.0 + Weight::MEDIUM.0) / 2);
     let attrs = Attrs::new()
         .family(Family::Name(font_family))
@@ -26,6 +26,7 @@ use compact_str::{CompactString, ToCompactString};
 /// - The first 9 bits (0-8) represent the base glyph ID, allowing for 512 unique glyphs.
 /// - Emoji glyphs implicitly clear any other font style bits.
 /// - The fragment shader uses the glyph ID to decode the texture coordinates and effects.
+/// - Bits 14-15 are used by the renderer to flag palette-indexed cell colors.
 ///
 /// ## Glyph ID Encoding Examples
 ///
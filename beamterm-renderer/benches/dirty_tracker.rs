@@ -0,0 +1,85 @@
+//! Benchmarks the host-side cost of tracking dirty cells and producing upload
+//! ranges, for a 300x150 grid (45k cells).
+//!
+//! Run with `cargo bench -p beamterm-renderer --bench dirty_tracker`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use beamterm_renderer::DirtyTracker;
+
+const COLS: u16 = 300;
+const ROWS: u16 = 150;
+const ITERATIONS: u32 = 10_000;
+
+fn main() {
+    bench("clock: 5 cells", |dirty, _| {
+        for col in 0..5 {
+            dirty.mark(COLS - 8 + col, 0);
+        }
+    });
+
+    bench("scattered: 1000 cells", |dirty, frame| {
+        let mut seed = frame.wrapping_mul(2_654_435_761);
+        for _ in 0..1000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            dirty.mark((seed % COLS as u32) as u16, ((seed >> 16) % ROWS as u32) as u16);
+        }
+    });
+
+    bench("status line: 1 row", |dirty, _| {
+        dirty.mark_rows(ROWS - 1..ROWS);
+    });
+
+    bench("scroll: 1 line", |dirty, _| {
+        dirty.scroll(1);
+    });
+
+    bench("full screen: every cell", |dirty, _| {
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                dirty.mark(col, row);
+            }
+        }
+    });
+}
+
+/// Runs one frame per iteration: marks cells, then walks the dirty spans and
+/// merged ranges as the grid does when flushing, and clears the tracker.
+fn bench(name: &str, mut mark: impl FnMut(&mut DirtyTracker, u32)) {
+    let mut dirty = DirtyTracker::new(COLS, ROWS);
+    dirty.clear();
+
+    let mut uploaded_cells = 0;
+    let mut full_uploads = 0;
+    let start = Instant::now();
+    for frame in 0..ITERATIONS {
+        mark(&mut dirty, frame);
+
+        if dirty.requires_full_upload() {
+            full_uploads += 1;
+            uploaded_cells += COLS as usize * ROWS as usize;
+        } else {
+            for (row, span) in dirty.spans() {
+                black_box((row, span));
+            }
+            uploaded_cells += dirty.ranges().map(|range| range.len()).sum::<usize>();
+        }
+
+        dirty.clear();
+    }
+
+    report(name, start.elapsed(), uploaded_cells, full_uploads);
+}
+
+fn report(name: &str, elapsed: Duration, uploaded_cells: usize, full_uploads: u32) {
+    let per_frame = elapsed / ITERATIONS;
+    let cells_per_frame = uploaded_cells / ITERATIONS as usize;
+    println!(
+        "{name:<28} {per_frame:>10.2?}/frame {cells_per_frame:>7} cells/frame {full_uploads:>6} full uploads"
+    );
+}
@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+
+use compact_str::CompactString;
+
+use crate::gl::{select, CellBuffer, SelectionMode};
+
+/// Text of the screen rows as last mirrored, deciding what changed and what
+/// to announce.
+///
+/// Independent of `web_sys`, so that the diffing can be tested natively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenMirror {
+    /// Text of every row, without trailing whitespace.
+    rows: Vec<CompactString>,
+}
+
+/// Changes found by [`ScreenMirror::update`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenUpdate {
+    /// Rows whose text changed, in order.
+    pub changed_rows: Vec<usize>,
+    /// Text to announce, in reading order.
+    pub announcements: Vec<CompactString>,
+}
+
+/// Rate limiter of live region announcements.
+///
+/// Lines pushed in quick succession are combined into one announcement, made
+/// at most once per interval. Only the most recent lines are kept, so that
+/// floods of output, such as a long file being printed, don't queue up
+/// minutes of speech.
+#[derive(Debug, Clone)]
+pub struct Announcer {
+    /// Minimum time between announcements.
+    interval_ms: f64,
+    /// Maximum number of lines per announcement.
+    max_lines: usize,
+    /// Lines waiting to be announced.
+    pending: VecDeque<CompactString>,
+    /// Time of the last announcement.
+    last_ms: Option<f64>,
+}
+
+impl ScreenMirror {
+    /// Returns the text of every row.
+    pub fn rows(&self) -> &[CompactString] {
+        &self.rows
+    }
+
+    /// Mirrors the displayed rows of a cell buffer; see [`ScreenMirror::update`].
+    pub fn update_from(&mut self, buffer: &CellBuffer) -> ScreenUpdate {
+        let (cols, rows) = buffer.size();
+        let row_text = |row: u16| {
+            let query = select(SelectionMode::Linear)
+                .start((0, row))
+                .end((cols.saturating_sub(1), row))
+                .trim_trailing_whitespace(true);
+            buffer.get_text(query)
+        };
+
+        self.update((0..rows).map(row_text).collect())
+    }
+
+    /// Replaces the mirrored rows, returning the changed rows and the text to
+    /// announce.
+    ///
+    /// When the rows scrolled up, only the lines scrolled in are announced.
+    /// When a row only grew, as while typing at a prompt, only the appended
+    /// text is announced. A change in the number of rows, such as after a
+    /// resize, marks all rows as changed without announcing anything.
+    pub fn update(&mut self, rows: Vec<CompactString>) -> ScreenUpdate {
+        let old = std::mem::replace(&mut self.rows, rows);
+        let new = &self.rows;
+
+        if old.len() != new.len() {
+            let changed_rows = (0..new.len()).collect();
+            return ScreenUpdate { changed_rows, announcements: Vec::new() };
+        }
+
+        let changed_rows: Vec<usize> = (0..new.len()).filter(|&i| old[i] != new[i]).collect();
+        let announcements = match scroll_distance(&old, new) {
+            Some(lines) if changed_rows.len() > 1 => {
+                new[new.len() - lines..].iter().filter(|row| !row.is_empty()).cloned().collect()
+            },
+            _ => changed_rows.iter().filter_map(|&i| appended_text(&old[i], &new[i])).collect(),
+        };
+
+        ScreenUpdate { changed_rows, announcements }
+    }
+}
+
+impl Announcer {
+    /// Default minimum time between announcements.
+    pub const DEFAULT_INTERVAL_MS: f64 = 500.0;
+    /// Default maximum number of lines per announcement.
+    pub const DEFAULT_MAX_LINES: usize = 20;
+
+    /// Creates an announcer making at most one announcement per `interval_ms`
+    /// of at most `max_lines` lines.
+    pub fn new(interval_ms: f64, max_lines: usize) -> Self {
+        Self {
+            interval_ms,
+            max_lines: max_lines.max(1),
+            pending: VecDeque::new(),
+            last_ms: None,
+        }
+    }
+
+    /// Queues lines for the next announcement, dropping the oldest lines
+    /// beyond the maximum.
+    pub fn push(&mut self, lines: impl IntoIterator<Item = CompactString>) {
+        self.pending.extend(lines);
+        let excess = self.pending.len().saturating_sub(self.max_lines);
+        self.pending.drain(..excess);
+    }
+
+    /// Returns `true` if lines are waiting to be announced.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Returns the milliseconds until the pending lines may be announced, or
+    /// `None` if nothing is pending.
+    pub fn delay_ms(&self, now_ms: f64) -> Option<f64> {
+        self.is_pending().then(|| match self.last_ms {
+            Some(last_ms) => (last_ms + self.interval_ms - now_ms).max(0.0),
+            None => 0.0,
+        })
+    }
+
+    /// Returns the pending lines as one announcement, one line per row, if
+    /// the interval since the last announcement has passed.
+    pub fn poll(&mut self, now_ms: f64) -> Option<String> {
+        if self.delay_ms(now_ms)? > 0.0 {
+            return None;
+        }
+
+        self.last_ms = Some(now_ms);
+        let lines: Vec<CompactString> = self.pending.drain(..).collect();
+        Some(lines.join("\n"))
+    }
+}
+
+impl Default for Announcer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_INTERVAL_MS, Self::DEFAULT_MAX_LINES)
+    }
+}
+
+/// Returns the smallest number of lines the rows scrolled up by, requiring
+/// the rows kept on screen to contain text.
+fn scroll_distance(old: &[CompactString], new: &[CompactString]) -> Option<usize> {
+    let len = new.len();
+    (1..len).find(|&lines| {
+        let kept = &old[lines..];
+        kept == &new[..len - lines] && kept.iter().any(|row| !row.is_empty())
+    })
+}
+
+/// Returns the text to announce for a changed row: the appended text if the
+/// row only grew, or else the whole row. Blank text isn't announced.
+fn appended_text(old: &str, new: &str) -> Option<CompactString> {
+    let text = match new.strip_prefix(old) {
+        Some(appended) if !old.is_empty() => appended,
+        _ => new,
+    };
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&str]) -> Vec<CompactString> {
+        rows.iter().map(|&row| row.into()).collect()
+    }
+
+    #[test]
+    fn test_announces_changes() {
+        let mut mirror = ScreenMirror::default();
+        let update = mirror.update(rows(&["$", "", ""]));
+        assert_eq!(update.changed_rows, [0, 1, 2]);
+        assert!(update.announcements.is_empty());
+
+        // typed text is announced without the prompt
+        let update = mirror.update(rows(&["$ ls", "", ""]));
+        assert_eq!(update.changed_rows, [0]);
+        assert_eq!(update.announcements, ["ls"]);
+
+        let update = mirror.update(rows(&["$ ls", "a.txt  b.txt", "$"]));
+        assert_eq!(update.changed_rows, [1, 2]);
+        assert_eq!(update.announcements, ["a.txt  b.txt", "$"]);
+
+        // scrolling announces the new lines only
+        let update = mirror.update(rows(&["a.txt  b.txt", "$", "done"]));
+        assert_eq!(update.changed_rows, [0, 1, 2]);
+        assert_eq!(update.announcements, ["done"]);
+
+        assert_eq!(mirror.update(rows(&["x"])).changed_rows, [0]);
+    }
+
+    #[test]
+    fn test_announcements_are_rate_limited() {
+        let mut announcer = Announcer::new(100.0, 2);
+        assert_eq!(announcer.poll(0.0), None);
+
+        announcer.push(rows(&["one"]));
+        assert_eq!(announcer.poll(0.0).as_deref(), Some("one"));
+
+        announcer.push(rows(&["two", "three", "four"]));
+        assert_eq!(announcer.delay_ms(40.0), Some(60.0));
+        assert_eq!(announcer.poll(40.0), None);
+        assert_eq!(announcer.poll(100.0).as_deref(), Some("three\nfour"));
+        assert!(!announcer.is_pending());
+    }
+}
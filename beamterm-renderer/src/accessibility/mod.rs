@@ -0,0 +1,281 @@
+//! Screen reader support for the terminal canvas.
+//!
+//! A canvas is opaque to assistive technology. [`TerminalAccessibility`]
+//! therefore mirrors the displayed rows into a visually hidden DOM tree next to
+//! the canvas, one element per row, with the row holding the cursor marked as
+//! the current location and the cursor position described in words. Changed
+//! lines are announced through an `aria-live` region.
+//!
+//! Announcements are rate limited: lines changing in quick succession are
+//! combined, and only the most recent lines of a flood of output are read. The
+//! decisions of what changed and what to announce are made by [`ScreenMirror`]
+//! and [`Announcer`], which have no browser dependencies. Nothing is announced
+//! while the viewport is scrolled back into the scrollback.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::Terminal;
+//!
+//! let mut terminal = Terminal::builder("#canvas").accessibility(true).build()?;
+//!
+//! // the mirror is updated when rendering
+//! terminal.render_frame()?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod diff;
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use compact_str::CompactString;
+pub use diff::{Announcer, ScreenMirror, ScreenUpdate};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::{js, Error, TerminalGrid};
+
+/// Mirrors the terminal grid for screen readers; see the [module docs](self).
+pub struct TerminalAccessibility {
+    /// Visually hidden container of the row elements.
+    tree: web_sys::HtmlElement,
+    /// Element of every row.
+    rows: Vec<web_sys::HtmlElement>,
+    /// Description of the cursor position.
+    cursor_status: web_sys::HtmlElement,
+    /// Last mirrored cursor position, `None` while hidden.
+    cursor: Option<(u16, u16)>,
+    /// Text of the mirrored rows.
+    mirror: ScreenMirror,
+    /// Live region, shared with the announcement timer.
+    live: Rc<LiveRegion>,
+    /// Closure announcing pending lines once the rate limit allows.
+    on_timer: Closure<dyn FnMut()>,
+}
+
+/// The `aria-live` region and its pending announcements.
+struct LiveRegion {
+    element: web_sys::HtmlElement,
+    announcer: RefCell<Announcer>,
+    /// Handle of the pending announcement timer.
+    timer: Cell<Option<i32>>,
+}
+
+impl TerminalAccessibility {
+    /// Creates the hidden DOM tree and live region next to the canvas.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if the elements cannot be created.
+    pub fn new(canvas: &web_sys::HtmlCanvasElement) -> Result<Self, Error> {
+        let tree = create_hidden_element("div")?;
+        let _ = tree.set_attribute("role", "list");
+        let _ = tree.set_attribute("aria-label", "Terminal output");
+
+        let cursor_status = create_hidden_element("div")?;
+        let live_element = create_hidden_element("div")?;
+        let _ = live_element.set_attribute("aria-live", "polite");
+        let _ = live_element.set_attribute("aria-atomic", "true");
+
+        let next = canvas.next_sibling();
+        for element in [&tree, &cursor_status, &live_element] {
+            let inserted = match canvas.parent_node() {
+                Some(parent) => parent.insert_before(element, next.as_ref()),
+                None => js::document()?
+                    .body()
+                    .ok_or_else(|| Error::Callback("Document has no body".into()))?
+                    .append_child(element),
+            };
+            inserted.map_err(|_| Error::Callback("Failed to insert accessibility tree".into()))?;
+        }
+
+        let live = Rc::new(LiveRegion {
+            element: live_element,
+            announcer: RefCell::new(Announcer::default()),
+            timer: Cell::new(None),
+        });
+        let timer_live = live.clone();
+        let on_timer = Closure::wrap(Box::new(move || {
+            timer_live.timer.set(None);
+            timer_live.announce(js::now_ms());
+        }) as Box<dyn FnMut()>);
+
+        Ok(Self {
+            tree,
+            rows: Vec::new(),
+            cursor_status,
+            cursor: None,
+            mirror: ScreenMirror::default(),
+            live,
+            on_timer,
+        })
+    }
+
+    /// Sets the minimum time between announcements and the maximum number of
+    /// lines per announcement; see [`Announcer`].
+    pub fn set_rate_limit(&self, interval_ms: f64, max_lines: usize) {
+        *self.live.announcer.borrow_mut() = Announcer::new(interval_ms, max_lines);
+    }
+
+    /// Returns the text of the mirrored rows.
+    pub fn rows(&self) -> &[CompactString] {
+        self.mirror.rows()
+    }
+
+    /// Synchronizes the hidden tree with the displayed cells and cursor, and
+    /// queues the changed lines for announcement.
+    pub fn update(&mut self, grid: &TerminalGrid) {
+        let buffer = grid.cell_buffer();
+        let update = self.mirror.update_from(buffer);
+        self.sync_rows(&update);
+
+        let cursor = grid.cursor();
+        let cursor = cursor.is_visible().then(|| cursor.position());
+        if cursor != self.cursor || !update.changed_rows.is_empty() {
+            self.sync_cursor(cursor);
+        }
+
+        if buffer.viewport_offset() == 0 && !update.announcements.is_empty() {
+            self.live.announcer.borrow_mut().push(update.announcements);
+            self.schedule_announcement();
+        }
+    }
+
+    /// Removes the hidden elements and any pending announcement timer.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        self.live.clear_timer();
+        self.tree.remove();
+        self.cursor_status.remove();
+        self.live.element.remove();
+    }
+
+    /// Updates the text of the changed rows, adding or removing row elements
+    /// to match the number of rows.
+    fn sync_rows(&mut self, update: &ScreenUpdate) {
+        let rows = self.mirror.rows();
+        while self.rows.len() > rows.len() {
+            if let Some(row) = self.rows.pop() {
+                row.remove();
+            }
+        }
+        while self.rows.len() < rows.len() {
+            let Some(row) = js::document().ok().and_then(|d| d.create_element("div").ok()) else {
+                break;
+            };
+            let _ = row.set_attribute("role", "listitem");
+            let _ = self.tree.append_child(&row);
+            self.rows.push(row.unchecked_into());
+        }
+
+        for &idx in &update.changed_rows {
+            if let Some(row) = self.rows.get(idx) {
+                row.set_text_content(Some(&rows[idx]));
+            }
+        }
+    }
+
+    /// Marks the row of the cursor as the current location and describes the
+    /// cursor position.
+    fn sync_cursor(&mut self, cursor: Option<(u16, u16)>) {
+        if let Some(row) = self.cursor.and_then(|(_, row)| self.rows.get(row as usize)) {
+            let _ = row.remove_attribute("aria-current");
+        }
+        self.cursor = cursor;
+
+        let status = match cursor {
+            Some((col, row)) => {
+                if let Some(element) = self.rows.get(row as usize) {
+                    let _ = element.set_attribute("aria-current", "location");
+                }
+                format!("Cursor at row {}, column {}", row + 1, col + 1)
+            },
+            None => String::new(),
+        };
+        self.cursor_status.set_text_content(Some(&status));
+    }
+
+    /// Announces pending lines now if the rate limit allows, or else starts a
+    /// timer announcing them once it does.
+    fn schedule_announcement(&self) {
+        let now_ms = js::now_ms();
+        let Some(delay_ms) = self.live.announcer.borrow().delay_ms(now_ms) else {
+            return;
+        };
+
+        if delay_ms <= 0.0 {
+            self.live.clear_timer();
+            self.live.announce(now_ms);
+        } else if self.live.timer.get().is_none() {
+            let handle = web_sys::window().and_then(|window| {
+                window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(
+                        self.on_timer.as_ref().unchecked_ref(),
+                        delay_ms.ceil() as i32,
+                    )
+                    .ok()
+            });
+            self.live.timer.set(handle);
+        }
+    }
+}
+
+impl LiveRegion {
+    /// Replaces the text of the live region with the pending lines, if due.
+    fn announce(&self, now_ms: f64) {
+        if let Some(text) = self.announcer.borrow_mut().poll(now_ms) {
+            self.element.set_text_content(Some(&text));
+        }
+    }
+
+    /// Clears the pending announcement timer, if any.
+    fn clear_timer(&self) {
+        if let (Some(handle), Some(window)) = (self.timer.take(), web_sys::window()) {
+            window.clear_timeout_with_handle(handle);
+        }
+    }
+}
+
+/// Creates an element hidden visually, but not from assistive technology.
+fn create_hidden_element(tag: &str) -> Result<web_sys::HtmlElement, Error> {
+    let element = js::document()?
+        .create_element(tag)
+        .ok()
+        .and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
+        .ok_or_else(|| Error::Callback("Failed to create accessibility element".into()))?;
+
+    let style = element.style();
+    for (property, value) in [
+        ("position", "absolute"),
+        ("width", "1px"),
+        ("height", "1px"),
+        ("padding", "0"),
+        ("margin", "-1px"),
+        ("border", "0"),
+        ("overflow", "hidden"),
+        ("clip-path", "inset(50%)"),
+        ("white-space", "pre"),
+    ] {
+        let _ = style.set_property(property, value);
+    }
+
+    Ok(element)
+}
+
+impl Drop for TerminalAccessibility {
+    /// Automatically removes the hidden elements when dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for TerminalAccessibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TerminalAccessibility {{ rows: {} }}", self.rows.len())
+    }
+}
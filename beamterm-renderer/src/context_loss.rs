@@ -0,0 +1,243 @@
+//! WebGL context loss and restoration.
+//!
+//! Browsers may drop the WebGL context of a canvas at any time, such as after a
+//! GPU reset, when too many contexts are active, or when a tab is backgrounded
+//! on mobile. All textures, buffers and shaders are invalid from then on.
+//! [`ContextLossHandler`] listens to `webglcontextlost` and
+//! `webglcontextrestored` events on the canvas, letting the browser restore
+//! the context, after which the terminal recreates its GL resources from the
+//! retained CPU-side state on the next frame.
+//!
+//! The application is notified of both events through an optional callback.
+//! Nothing is drawn while the context is lost; applications rendering only on
+//! changes should render once the context is restored.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::{context_loss::ContextEvent, Terminal};
+//!
+//! let terminal = Terminal::builder("#canvas")
+//!     .context_loss_handler(|event| match event {
+//!         ContextEvent::Lost => { /* pause rendering */ },
+//!         ContextEvent::Restored => { /* render the next frame */ },
+//!     })
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::Error;
+
+/// Type alias for boxed context event callback functions.
+///
+/// Callbacks are invoked synchronously in the browser's event loop.
+pub type ContextEventCallback = Box<dyn FnMut(ContextEvent) + 'static>;
+
+/// Change of the WebGL context state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextEvent {
+    /// The context was lost; nothing is drawn until it is restored.
+    Lost,
+    /// The context was restored; GL resources are recreated on the next frame.
+    Restored,
+}
+
+/// Listens to WebGL context loss and restoration on the terminal canvas; see
+/// the [module docs](self).
+pub struct ContextLossHandler {
+    /// The canvas element this handler is attached to.
+    canvas: web_sys::HtmlCanvasElement,
+    /// Closure for `webglcontextlost` events.
+    on_lost: Closure<dyn FnMut(web_sys::Event)>,
+    /// Closure for `webglcontextrestored` events.
+    on_restored: Closure<dyn FnMut(web_sys::Event)>,
+    /// Context state shared with the event closures.
+    state: Rc<ContextState>,
+}
+
+/// Context state and the application callback.
+#[derive(Default)]
+struct ContextState {
+    status: Cell<ContextStatus>,
+    callback: RefCell<Option<ContextEventCallback>>,
+}
+
+/// Tracks whether the context is lost and whether GL resources must be
+/// recreated.
+///
+/// Independent of `web_sys`, so that the transitions can be tested natively.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ContextStatus {
+    lost: bool,
+    restore_pending: bool,
+}
+
+impl ContextLossHandler {
+    /// Attaches context loss and restoration listeners to the canvas.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if event listeners cannot be attached to the canvas.
+    pub fn new(canvas: &web_sys::HtmlCanvasElement) -> Result<Self, Error> {
+        let state = Rc::new(ContextState::default());
+
+        let lost_state = state.clone();
+        let on_lost = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            // the context is only restored if the default action is prevented
+            event.prevent_default();
+            lost_state.update(ContextEvent::Lost);
+        }) as Box<dyn FnMut(_)>);
+
+        let restored_state = state.clone();
+        let on_restored = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            restored_state.update(ContextEvent::Restored);
+        }) as Box<dyn FnMut(_)>);
+
+        canvas
+            .add_event_listener_with_callback("webglcontextlost", on_lost.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add context lost listener".into()))?;
+        canvas
+            .add_event_listener_with_callback(
+                "webglcontextrestored",
+                on_restored.as_ref().unchecked_ref(),
+            )
+            .map_err(|_| Error::Callback("Failed to add context restored listener".into()))?;
+
+        Ok(Self {
+            canvas: canvas.clone(),
+            on_lost,
+            on_restored,
+            state,
+        })
+    }
+
+    /// Sets the callback notified when the context is lost or restored.
+    pub fn set_callback<F>(&self, callback: F)
+    where
+        F: FnMut(ContextEvent) + 'static,
+    {
+        *self.state.callback.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Returns `true` while the context is lost.
+    pub fn is_lost(&self) -> bool {
+        self.state.status.get().lost
+    }
+
+    /// Returns `true`, once, if the context was restored since it was lost,
+    /// and the GL resources must be recreated.
+    pub(crate) fn take_restore(&self) -> bool {
+        let mut status = self.state.status.get();
+        let restore = status.take_restore();
+        self.state.status.set(status);
+        restore
+    }
+
+    /// Removes all event listeners from the canvas.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "webglcontextlost",
+            self.on_lost.as_ref().unchecked_ref(),
+        );
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "webglcontextrestored",
+            self.on_restored.as_ref().unchecked_ref(),
+        );
+    }
+}
+
+impl ContextState {
+    fn update(&self, event: ContextEvent) {
+        let mut status = self.status.get();
+        status.apply(event);
+        self.status.set(status);
+
+        // the callback is taken out while it runs, so that it may replace itself
+        let Some(mut callback) = self.callback.borrow_mut().take() else {
+            return;
+        };
+        callback(event);
+
+        let mut slot = self.callback.borrow_mut();
+        if slot.is_none() {
+            *slot = Some(callback);
+        }
+    }
+}
+
+impl ContextStatus {
+    fn apply(&mut self, event: ContextEvent) {
+        match event {
+            ContextEvent::Lost => {
+                self.lost = true;
+                self.restore_pending = true;
+            },
+            ContextEvent::Restored => self.lost = false,
+        }
+    }
+
+    fn take_restore(&mut self) -> bool {
+        !self.lost && std::mem::take(&mut self.restore_pending)
+    }
+}
+
+impl Drop for ContextLossHandler {
+    /// Automatically removes event listeners when handler is dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for ContextLossHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ContextLossHandler {{ lost: {} }}", self.is_lost())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_once_after_context_is_restored() {
+        let mut status = ContextStatus::default();
+        assert!(!status.take_restore());
+
+        status.apply(ContextEvent::Lost);
+        assert!(status.lost);
+        assert!(!status.take_restore());
+
+        status.apply(ContextEvent::Restored);
+        assert!(status.take_restore());
+        assert!(!status.take_restore());
+    }
+
+    #[test]
+    fn test_callback_may_replace_itself() {
+        let state = Rc::new(ContextState::default());
+        let events = Rc::new(RefCell::new(Vec::new()));
+
+        let (inner_state, inner_events) = (Rc::downgrade(&state), events.clone());
+        *state.callback.borrow_mut() = Some(Box::new(move |event| {
+            inner_events.borrow_mut().push(event);
+            let events = inner_events.clone();
+            let replacement = move |_| events.borrow_mut().push(ContextEvent::Restored);
+            *inner_state.upgrade().unwrap().callback.borrow_mut() = Some(Box::new(replacement));
+        }));
+
+        state.update(ContextEvent::Lost);
+        state.update(ContextEvent::Lost);
+        assert_eq!(*events.borrow(), [ContextEvent::Lost, ContextEvent::Restored]);
+    }
+}
@@ -0,0 +1,956 @@
+//! ANSI/VT escape sequence front-end for driving a terminal grid.
+//!
+//! beamterm itself is only a renderer; [`Emulator`] turns the output of a
+//! terminal application into cell updates. It parses a byte stream as written
+//! by a PTY and applies it to a [`CellBuffer`], covering the subset of xterm
+//! used by common full-screen applications:
+//!
+//! - SGR attributes, with the 16 ANSI colors, 256-color and truecolor
+//!   foregrounds and backgrounds
+//! - cursor movement, save/restore and shape (`DECSCUSR`)
+//! - erasing, inserting and deleting characters and lines
+//! - scroll regions (`DECSTBM`), index and reverse index
+//! - the alternate screen (modes 47, 1047 and 1049)
+//! - mouse tracking and encoding modes, for reporting mouse events
+//! - the window title (OSC 0 and 2), and DEC line drawing characters
+//! - hyperlinks (OSC 8), stored as the hyperlink ids of the printed cells
+//!
+//! Lines scrolled off the top of the full-screen region are pushed into the
+//! buffer's scrollback, unless the alternate screen is active. Replies to
+//! device status queries are collected in [`Emulator::take_responses`] and
+//! should be written back to the application.
+//!
+//! With the `emulator` feature, [`Terminal::write`](crate::Terminal::write()) feeds output to the
+//! terminal's emulator and keeps the rendered cursor in sync with it.
+//!
+//! # Example
+//! ```
+//! use beamterm_renderer::{emulator::Emulator, CellBuffer, GlyphLookup};
+//!
+//! let mut buffer = CellBuffer::new(GlyphLookup::default(), 20, 4);
+//! let mut emulator = Emulator::new();
+//!
+//! emulator.process(b"\x1b]2;demo\x07\x1b[1;32mok\x1b[0m\r\nnext line", &mut buffer);
+//! emulator.process(b"\x1b[6n", &mut buffer);
+//!
+//! assert_eq!(emulator.title(), "demo");
+//! assert_eq!(emulator.cursor_position(), (9, 1));
+//! assert_eq!(emulator.take_responses(), b"\x1b[2;10R");
+//! ```
+
+mod parser;
+mod pen;
+
+use std::ops::Range;
+
+use compact_str::CompactString;
+
+use self::{
+    parser::{Action, Params, Parser},
+    pen::Pen,
+};
+use crate::{
+    gl::{CellBuffer, CellDynamic, CursorShape, HyperlinkId},
+    keyboard::KittyFlags,
+    mouse::{MouseEncoding, MouseTracking},
+};
+
+/// Width of the default tab stops.
+const TAB_WIDTH: u16 = 8;
+
+/// Terminal emulator state machine; see the [module docs](self).
+///
+/// The emulator holds the terminal state that isn't part of the cells: cursor,
+/// graphic rendition, modes, scroll region and the saved main screen. The
+/// screen size is taken from the buffer on every call to [`Emulator::process`],
+/// so a resized buffer is picked up automatically.
+#[derive(Debug, Clone)]
+pub struct Emulator {
+    /// Escape sequence parser.
+    parser: Parser,
+    /// Screen size as `(cols, rows)`, as of the last processed input.
+    size: (u16, u16),
+    /// Cursor position as `(col, row)`.
+    cursor: (u16, u16),
+    /// Whether the cursor is past the last column, wrapping on the next print.
+    wrap_pending: bool,
+    /// Graphic rendition for printed characters.
+    pen: Pen,
+    /// Cursor state saved by `DECSC`.
+    saved_cursor: Option<SavedCursor>,
+    /// Scroll region as `top..bottom` rows; the full screen when `None`.
+    scroll_region: Option<Range<u16>>,
+    /// Tab stops, one flag per column.
+    tab_stops: Vec<bool>,
+    /// Whether G0 is the DEC special graphics character set.
+    line_drawing: bool,
+    /// Last printed character, repeated by `REP`.
+    last_char: Option<char>,
+    /// Terminal modes.
+    modes: Modes,
+    /// Cursor shape set by `DECSCUSR`.
+    cursor_shape: CursorShape,
+    /// Whether the cursor blinks, set by `DECSCUSR` and mode 12.
+    cursor_blinking: bool,
+    /// Main screen cells and size, saved while the alternate screen is active.
+    main_screen: Option<(Vec<CellDynamic>, (u16, u16))>,
+    /// Window title set by OSC 0 or 2.
+    title: CompactString,
+    /// Hyperlink of printed characters, set by OSC 8.
+    link: Option<HyperlinkId>,
+    /// Kitty keyboard flags pushed with `CSI > flags u`, restored when popped.
+    keyboard_flags_stack: Vec<u8>,
+    /// Replies to the application, such as cursor position reports.
+    responses: Vec<u8>,
+}
+
+/// Terminal modes set by the application with `SM`/`RM` and `DECSET`/`DECRST`.
+///
+/// Input handling depends on several of these modes; for example, cursor keys
+/// send `ESC O A` rather than `ESC [ A` in application cursor mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modes {
+    /// `DECCKM`: cursor keys send application sequences.
+    pub application_cursor: bool,
+    /// `DECKPAM`: the keypad sends application sequences.
+    pub application_keypad: bool,
+    /// `DECAWM`: printing past the last column wraps to the next line.
+    pub autowrap: bool,
+    /// `DECOM`: cursor addressing is relative to the scroll region.
+    pub origin: bool,
+    /// `IRM`: printed characters shift the rest of the line right.
+    pub insert: bool,
+    /// `LNM`: line feeds also return the cursor to the first column.
+    pub linefeed_newline: bool,
+    /// `DECTCEM`: the cursor is visible.
+    pub cursor_visible: bool,
+    /// Mode 2004: pasted text is wrapped in bracketed paste sequences.
+    pub bracketed_paste: bool,
+    /// Mode 1004: focus changes are reported to the application.
+    pub focus_events: bool,
+    /// Enhancement flags of the kitty keyboard protocol, set with `CSI > flags u`.
+    pub kitty_keyboard_flags: u8,
+    /// Modes 9, 1000, 1002 and 1003: which mouse events are reported.
+    pub mouse_tracking: MouseTracking,
+    /// Modes 1006, 1015 and 1016: how mouse reports are encoded.
+    pub mouse_encoding: MouseEncoding,
+}
+
+/// Cursor state saved by `DECSC` and restored by `DECRC`.
+#[derive(Debug, Clone, Copy)]
+struct SavedCursor {
+    position: (u16, u16),
+    pen: Pen,
+    origin: bool,
+    line_drawing: bool,
+}
+
+impl Default for Modes {
+    fn default() -> Self {
+        Self {
+            application_cursor: false,
+            application_keypad: false,
+            autowrap: true,
+            origin: false,
+            insert: false,
+            linefeed_newline: false,
+            cursor_visible: true,
+            bracketed_paste: false,
+            focus_events: false,
+            kitty_keyboard_flags: 0,
+            mouse_tracking: MouseTracking::Off,
+            mouse_encoding: MouseEncoding::Default,
+        }
+    }
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emulator {
+    /// Creates an emulator in its power-on state.
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::default(),
+            size: (0, 0),
+            cursor: (0, 0),
+            wrap_pending: false,
+            pen: Pen::default(),
+            saved_cursor: None,
+            scroll_region: None,
+            tab_stops: Vec::new(),
+            line_drawing: false,
+            last_char: None,
+            modes: Modes::default(),
+            cursor_shape: CursorShape::Block,
+            cursor_blinking: false,
+            main_screen: None,
+            title: CompactString::default(),
+            link: None,
+            keyboard_flags_stack: Vec::new(),
+            responses: Vec::new(),
+        }
+    }
+
+    /// Parses `bytes` and applies them to `buffer`.
+    ///
+    /// Escape sequences and UTF-8 characters may be split across calls.
+    pub fn process(&mut self, bytes: &[u8], buffer: &mut CellBuffer) {
+        self.sync_size(buffer.size());
+        if self.size.0 == 0 || self.size.1 == 0 {
+            return;
+        }
+
+        // the parser is taken out so that actions can borrow its buffers
+        let mut parser = std::mem::take(&mut self.parser);
+        parser.advance(bytes, |action| self.perform(action, buffer));
+        self.parser = parser;
+    }
+
+    /// Returns the cursor position as `(col, row)`.
+    pub fn cursor_position(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// Returns the cursor shape requested by the application.
+    pub fn cursor_shape(&self) -> CursorShape {
+        self.cursor_shape
+    }
+
+    /// Returns whether the application requested a blinking cursor.
+    pub fn is_cursor_blinking(&self) -> bool {
+        self.cursor_blinking
+    }
+
+    /// Returns the current terminal modes.
+    pub fn modes(&self) -> &Modes {
+        &self.modes
+    }
+
+    /// Returns whether the alternate screen is active.
+    pub fn is_alternate_screen(&self) -> bool {
+        self.main_screen.is_some()
+    }
+
+    /// Returns the window title, or an empty string if none was set.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Takes the pending replies to the application, such as cursor position
+    /// and device attribute reports. The replies should be written back to the
+    /// application's input.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// Resets the emulator to its power-on state, as `RIS` does. The buffer
+    /// contents are left as is.
+    pub fn reset(&mut self) {
+        *self = Self {
+            responses: std::mem::take(&mut self.responses),
+            ..Self::new()
+        };
+    }
+
+    fn sync_size(&mut self, size: (u16, u16)) {
+        if self.size == size {
+            return;
+        }
+
+        self.size = size;
+        self.cursor = (
+            self.cursor.0.min(size.0.saturating_sub(1)),
+            self.cursor.1.min(size.1.saturating_sub(1)),
+        );
+        self.wrap_pending = false;
+        if self.scroll_region.as_ref().is_some_and(|r| r.end > size.1) {
+            self.scroll_region = None;
+        }
+
+        let old_cols = self.tab_stops.len() as u16;
+        self.tab_stops.resize(size.0 as usize, false);
+        for col in (old_cols..size.0).filter(|col| col % TAB_WIDTH == 0) {
+            self.tab_stops[col as usize] = true;
+        }
+    }
+
+    fn perform(&mut self, action: Action, buffer: &mut CellBuffer) {
+        match action {
+            Action::Print(ch) => self.print(ch, buffer),
+            Action::Execute(byte) => self.execute(byte, buffer),
+            Action::Csi { params, intermediates, action } => {
+                self.csi_dispatch(params, intermediates, action, buffer)
+            },
+            Action::Esc { intermediates, action } => {
+                self.esc_dispatch(intermediates, action, buffer)
+            },
+            Action::Osc(data) => self.osc_dispatch(data, buffer),
+        }
+    }
+
+    fn print(&mut self, ch: char, buffer: &mut CellBuffer) {
+        let ch = if self.line_drawing { dec_special_graphics(ch) } else { ch };
+
+        if self.wrap_pending {
+            self.cursor.0 = 0;
+            self.linefeed(buffer);
+        }
+
+        let (col, row) = self.cursor;
+        if self.modes.insert {
+            buffer.insert_chars(col, row, 1, self.pen.blank());
+        }
+
+        let mut utf8 = [0; 4];
+        buffer.update_cell(col, row, self.pen.cell(ch.encode_utf8(&mut utf8)));
+        buffer.set_hyperlink(col, row, self.link);
+        self.last_char = Some(ch);
+
+        if col + 1 < self.size.0 {
+            self.cursor.0 += 1;
+        } else {
+            self.wrap_pending = self.modes.autowrap;
+        }
+    }
+
+    fn execute(&mut self, byte: u8, buffer: &mut CellBuffer) {
+        match byte {
+            0x08 => self.move_to(self.cursor.0.saturating_sub(1), self.cursor.1),
+            0x09 => self.tab_forward(1),
+            0x0A..=0x0C => {
+                self.linefeed(buffer);
+                if self.modes.linefeed_newline {
+                    self.cursor.0 = 0;
+                }
+            },
+            0x0D => self.move_to(0, self.cursor.1),
+            _ => {},
+        }
+    }
+
+    fn csi_dispatch(
+        &mut self,
+        params: &Params,
+        intermediates: &[u8],
+        action: u8,
+        buffer: &mut CellBuffer,
+    ) {
+        let (col, row) = self.cursor;
+        let (cols, rows) = self.size;
+        let n = params.get(0, 1);
+
+        match (params.private(), intermediates, action) {
+            (None, [], b'@') => buffer.insert_chars(col, row, n, self.pen.blank()),
+            (None, [], b'A') => {
+                let top = if row >= self.region().start { self.region().start } else { 0 };
+                self.move_to(col, row.saturating_sub(n).max(top));
+            },
+            (None, [], b'B' | b'e') => {
+                let bottom = if row < self.region().end { self.region().end } else { rows };
+                self.move_to(col, row.saturating_add(n).min(bottom - 1));
+            },
+            (None, [], b'C' | b'a') => self.move_to(col.saturating_add(n), row),
+            (None, [], b'D') => self.move_to(col.saturating_sub(n), row),
+            (None, [], b'E') => {
+                self.csi_dispatch(params, intermediates, b'B', buffer);
+                self.cursor.0 = 0;
+            },
+            (None, [], b'F') => {
+                self.csi_dispatch(params, intermediates, b'A', buffer);
+                self.cursor.0 = 0;
+            },
+            (None, [], b'G' | b'`') => self.move_to(n - 1, row),
+            (None, [], b'H' | b'f') => self.move_to_origin(params.get(1, 1) - 1, n - 1),
+            (None, [], b'd') => self.move_to_origin(col, n - 1),
+            (None, [], b'I') => self.tab_forward(n),
+            (None, [], b'Z') => self.tab_backward(n),
+            (None, [], b'J') => self.erase_display(params.get_raw(0), buffer),
+            (None, [], b'K') => {
+                let cols = match params.get_raw(0) {
+                    0 => col..cols,
+                    1 => 0..col + 1,
+                    _ => 0..cols,
+                };
+                buffer.fill_rect(cols, row..row + 1, self.pen.blank());
+            },
+            (None, [], b'L') if self.region().contains(&row) => {
+                let region = row..self.region().end;
+                buffer.scroll_region_down(region, n, self.pen.blank());
+                self.move_to(0, row);
+            },
+            (None, [], b'M') if self.region().contains(&row) => {
+                let region = row..self.region().end;
+                buffer.scroll_region_up(region, n, self.pen.blank());
+                self.move_to(0, row);
+            },
+            (None, [], b'P') => buffer.delete_chars(col, row, n, self.pen.blank()),
+            (None, [], b'X') => {
+                let end = col.saturating_add(n).min(cols);
+                buffer.fill_rect(col..end, row..row + 1, self.pen.blank());
+            },
+            (None, [], b'S') => self.scroll_up(n, buffer),
+            (None, [], b'T') => self.scroll_down(n, buffer),
+            (None, [], b'b') => {
+                if let Some(ch) = self.last_char {
+                    (0..n.min(cols.saturating_mul(rows))).for_each(|_| self.print(ch, buffer));
+                }
+            },
+            (None, [], b'g') => match params.get_raw(0) {
+                0 => self.tab_stops[col as usize] = false,
+                3 => self.tab_stops.fill(false),
+                _ => {},
+            },
+            (None, [], b'm') => self.pen.apply_sgr(params.iter()),
+            (None, [], b'r') => {
+                let top = params.get(0, 1) - 1;
+                let bottom = params.get(1, rows).min(rows);
+                if top + 1 < bottom {
+                    self.scroll_region = (top > 0 || bottom < rows).then_some(top..bottom);
+                    self.move_to_origin(0, 0);
+                }
+            },
+            (None, [], b's') => self.save_cursor(),
+            (None, [], b'u') => self.restore_cursor(),
+            (None, [], b'n') => match params.get_raw(0) {
+                5 => self.responses.extend_from_slice(b"\x1b[0n"),
+                6 => {
+                    let top = if self.modes.origin { self.region().start } else { 0 };
+                    let report = format!("\x1b[{};{}R", row - top + 1, col + 1);
+                    self.responses.extend_from_slice(report.as_bytes());
+                },
+                _ => {},
+            },
+            (None, [], b'c') if params.get_raw(0) == 0 => {
+                // VT220 with ANSI color
+                self.responses.extend_from_slice(b"\x1b[?62;22c");
+            },
+            (None | Some(b'?'), [], b'h' | b'l') => {
+                let enabled = action == b'h';
+                for mode in params.iter().map(|p| p[0]) {
+                    if params.private().is_some() {
+                        self.set_private_mode(mode, enabled, buffer);
+                    } else {
+                        self.set_mode(mode, enabled);
+                    }
+                }
+            },
+            (None, [b' '], b'q') => {
+                let style = params.get_raw(0);
+                self.cursor_shape = match style {
+                    0..=2 => CursorShape::Block,
+                    3 | 4 => CursorShape::Underline,
+                    _ => CursorShape::Bar,
+                };
+                self.cursor_blinking = style == 0 || style % 2 == 1;
+            },
+            (None, [b'!'], b'p') => self.soft_reset(),
+            (Some(marker), [], b'u') => self.kitty_keyboard(marker, params),
+            _ => {},
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], action: u8, buffer: &mut CellBuffer) {
+        match (intermediates, action) {
+            ([], b'7') => self.save_cursor(),
+            ([], b'8') => self.restore_cursor(),
+            ([], b'D') => self.linefeed(buffer),
+            ([], b'E') => {
+                self.linefeed(buffer);
+                self.cursor.0 = 0;
+            },
+            ([], b'H') => self.tab_stops[self.cursor.0 as usize] = true,
+            ([], b'M') => self.reverse_index(buffer),
+            ([], b'c') => {
+                self.leave_alternate_screen(buffer);
+                self.reset();
+                self.sync_size(buffer.size());
+                self.erase_display(2, buffer);
+            },
+            ([], b'=') => self.modes.application_keypad = true,
+            ([], b'>') => self.modes.application_keypad = false,
+            ([b'('], charset) => self.line_drawing = charset == b'0',
+            ([b'#'], b'8') => {
+                // DECALN: fill the screen with 'E' for screen alignment tests
+                let (cols, rows) = self.size;
+                buffer.fill_rect(0..cols, 0..rows, Pen::default().cell("E"));
+                self.scroll_region = None;
+                self.move_to(0, 0);
+            },
+            _ => {},
+        }
+    }
+
+    fn osc_dispatch(&mut self, data: &[u8], buffer: &mut CellBuffer) {
+        let mut parts = data.splitn(2, |&b| b == b';');
+        let command = parts.next().unwrap_or_default();
+        let payload = parts.next().unwrap_or_default();
+
+        match command {
+            b"0" | b"2" => self.title = String::from_utf8_lossy(payload).into(),
+            b"8" => self.hyperlink(payload, buffer),
+            _ => {},
+        }
+    }
+
+    /// Starts or ends a hyperlink with `OSC 8 ; params ; uri`, where params
+    /// are `key=value` pairs separated by `:`. An empty uri ends the link.
+    fn hyperlink(&mut self, payload: &[u8], buffer: &mut CellBuffer) {
+        let payload = String::from_utf8_lossy(payload);
+        let Some((params, uri)) = payload.split_once(';') else {
+            return;
+        };
+
+        self.link = if uri.is_empty() {
+            None
+        } else {
+            let id = params.split(':').find_map(|param| param.strip_prefix("id="));
+            buffer.register_hyperlink(uri, id)
+        };
+    }
+
+    /// Handles the kitty keyboard protocol's flag stack and queries.
+    fn kitty_keyboard(&mut self, marker: u8, params: &Params) {
+        const MAX_STACK_DEPTH: usize = 16;
+
+        let flags = &mut self.modes.kitty_keyboard_flags;
+        match marker {
+            b'>' => {
+                if self.keyboard_flags_stack.len() == MAX_STACK_DEPTH {
+                    self.keyboard_flags_stack.remove(0);
+                }
+                self.keyboard_flags_stack.push(*flags);
+                *flags = params.get_raw(0) as u8 & KittyFlags::ALL;
+            },
+            b'<' => {
+                for _ in 0..params.get(0, 1) {
+                    *flags = self.keyboard_flags_stack.pop().unwrap_or(0);
+                }
+            },
+            b'=' => {
+                let value = params.get_raw(0) as u8 & KittyFlags::ALL;
+                match params.get(1, 1) {
+                    1 => *flags = value,
+                    2 => *flags |= value,
+                    3 => *flags &= !value,
+                    _ => {},
+                }
+            },
+            b'?' => {
+                let report = format!("\x1b[?{}u", *flags);
+                self.responses.extend_from_slice(report.as_bytes());
+            },
+            _ => {},
+        }
+    }
+
+    fn set_mode(&mut self, mode: u16, enabled: bool) {
+        match mode {
+            4 => self.modes.insert = enabled,
+            20 => self.modes.linefeed_newline = enabled,
+            _ => {},
+        }
+    }
+
+    fn set_private_mode(&mut self, mode: u16, enabled: bool, buffer: &mut CellBuffer) {
+        match mode {
+            1 => self.modes.application_cursor = enabled,
+            6 => {
+                self.modes.origin = enabled;
+                self.move_to_origin(0, 0);
+            },
+            7 => self.modes.autowrap = enabled,
+            12 => self.cursor_blinking = enabled,
+            25 => self.modes.cursor_visible = enabled,
+            9 | 1000 | 1002 | 1003 => {
+                let tracking = match mode {
+                    9 => MouseTracking::X10,
+                    1000 => MouseTracking::Normal,
+                    1002 => MouseTracking::ButtonEvent,
+                    _ => MouseTracking::AnyEvent,
+                };
+                if enabled {
+                    self.modes.mouse_tracking = tracking;
+                } else if self.modes.mouse_tracking == tracking {
+                    self.modes.mouse_tracking = MouseTracking::Off;
+                }
+            },
+            1006 | 1015 | 1016 => {
+                let encoding = match mode {
+                    1006 => MouseEncoding::Sgr,
+                    1015 => MouseEncoding::Urxvt,
+                    _ => MouseEncoding::SgrPixels,
+                };
+                if enabled {
+                    self.modes.mouse_encoding = encoding;
+                } else if self.modes.mouse_encoding == encoding {
+                    self.modes.mouse_encoding = MouseEncoding::Default;
+                }
+            },
+            1004 => self.modes.focus_events = enabled,
+            2004 => self.modes.bracketed_paste = enabled,
+            47 | 1047 | 1049 => {
+                if enabled {
+                    if mode == 1049 {
+                        self.save_cursor();
+                    }
+                    self.enter_alternate_screen(buffer);
+                } else {
+                    self.leave_alternate_screen(buffer);
+                    if mode == 1049 {
+                        self.restore_cursor();
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn enter_alternate_screen(&mut self, buffer: &mut CellBuffer) {
+        if self.main_screen.is_none() {
+            self.main_screen = Some((buffer.cells().to_vec(), self.size));
+            self.erase_display(2, buffer);
+        }
+    }
+
+    fn leave_alternate_screen(&mut self, buffer: &mut CellBuffer) {
+        let Some((cells, (saved_cols, saved_rows))) = self.main_screen.take() else {
+            return;
+        };
+
+        let (cols, rows) = self.size;
+        buffer.fill_rect(0..cols, 0..rows, Pen::default().blank());
+        for row in 0..rows.min(saved_rows) {
+            for col in 0..cols.min(saved_cols) {
+                let saved = cells[row as usize * saved_cols as usize + col as usize];
+                if let Some(cell) = buffer.cell_mut(col, row) {
+                    *cell = saved;
+                }
+            }
+        }
+    }
+
+    fn erase_display(&mut self, mode: u16, buffer: &mut CellBuffer) {
+        let (col, row) = self.cursor;
+        let (cols, rows) = self.size;
+        let blank = self.pen.blank();
+
+        match mode {
+            0 => {
+                buffer.fill_rect(col..cols, row..row + 1, blank);
+                buffer.fill_rect(0..cols, row + 1..rows, blank);
+            },
+            1 => {
+                buffer.fill_rect(0..cols, 0..row, blank);
+                buffer.fill_rect(0..col + 1, row..row + 1, blank);
+            },
+            2 => buffer.fill_rect(0..cols, 0..rows, blank),
+            3 => buffer.clear_scrollback(),
+            _ => {},
+        }
+    }
+
+    /// Moves the cursor down a line, scrolling the region at its bottom margin.
+    fn linefeed(&mut self, buffer: &mut CellBuffer) {
+        self.wrap_pending = false;
+        let row = self.cursor.1;
+        if row + 1 == self.region().end {
+            self.scroll_up(1, buffer);
+        } else if row + 1 < self.size.1 {
+            self.cursor.1 += 1;
+        }
+    }
+
+    /// Moves the cursor up a line, scrolling the region at its top margin.
+    fn reverse_index(&mut self, buffer: &mut CellBuffer) {
+        self.wrap_pending = false;
+        let row = self.cursor.1;
+        if row == self.region().start {
+            self.scroll_down(1, buffer);
+        } else if row > 0 {
+            self.cursor.1 -= 1;
+        }
+    }
+
+    fn scroll_up(&mut self, count: u16, buffer: &mut CellBuffer) {
+        let region = self.region();
+        let (cols, rows) = self.size;
+
+        if region == (0..rows) && self.main_screen.is_none() {
+            buffer.push_lines_to_scrollback(count);
+            // exposed lines take the current background color
+            let count = count.min(rows);
+            buffer.fill_rect(0..cols, rows - count..rows, self.pen.blank());
+        } else {
+            buffer.scroll_region_up(region, count, self.pen.blank());
+        }
+    }
+
+    fn scroll_down(&mut self, count: u16, buffer: &mut CellBuffer) {
+        buffer.scroll_region_down(self.region(), count, self.pen.blank());
+    }
+
+    /// Returns the scroll region as a range of rows.
+    fn region(&self) -> Range<u16> {
+        self.scroll_region.clone().unwrap_or(0..self.size.1)
+    }
+
+    /// Moves the cursor to an absolute position, clamped to the screen.
+    fn move_to(&mut self, col: u16, row: u16) {
+        let (cols, rows) = self.size;
+        self.cursor = (col.min(cols - 1), row.min(rows - 1));
+        self.wrap_pending = false;
+    }
+
+    /// Moves the cursor to a position relative to the origin, which is the top
+    /// of the scroll region in origin mode.
+    fn move_to_origin(&mut self, col: u16, row: u16) {
+        if self.modes.origin {
+            let region = self.region();
+            self.move_to(col, (region.start + row).min(region.end - 1));
+        } else {
+            self.move_to(col, row);
+        }
+    }
+
+    fn tab_forward(&mut self, count: u16) {
+        let last = self.size.0 - 1;
+        for _ in 0..count {
+            let next = (self.cursor.0 + 1..last).find(|&col| self.tab_stops[col as usize]);
+            self.cursor.0 = next.unwrap_or(last);
+        }
+    }
+
+    fn tab_backward(&mut self, count: u16) {
+        for _ in 0..count {
+            let prev = (0..self.cursor.0).rev().find(|&col| self.tab_stops[col as usize]);
+            self.cursor.0 = prev.unwrap_or(0);
+        }
+        self.wrap_pending = false;
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved_cursor = Some(SavedCursor {
+            position: self.cursor,
+            pen: self.pen,
+            origin: self.modes.origin,
+            line_drawing: self.line_drawing,
+        });
+    }
+
+    fn restore_cursor(&mut self) {
+        let saved = self.saved_cursor.unwrap_or(SavedCursor {
+            position: (0, 0),
+            pen: Pen::default(),
+            origin: false,
+            line_drawing: false,
+        });
+
+        self.pen = saved.pen;
+        self.modes.origin = saved.origin;
+        self.line_drawing = saved.line_drawing;
+        self.move_to(saved.position.0, saved.position.1);
+    }
+
+    /// `DECSTR`: resets modes and rendition, keeping the screen and cursor position.
+    fn soft_reset(&mut self) {
+        self.pen = Pen::default();
+        self.scroll_region = None;
+        self.saved_cursor = None;
+        self.line_drawing = false;
+        self.modes = Modes {
+            bracketed_paste: self.modes.bracketed_paste,
+            focus_events: self.modes.focus_events,
+            mouse_tracking: self.modes.mouse_tracking,
+            mouse_encoding: self.modes.mouse_encoding,
+            ..Modes::default()
+        };
+    }
+}
+
+/// Maps a character of the DEC special graphics set to its Unicode equivalent.
+fn dec_special_graphics(ch: char) -> char {
+    const GRAPHICS: [char; 31] = [
+        '◆', '▒', '␉', '␌', '␍', '␊', '°', '±', '␤', '␋', '┘', '┐', '┌', '└', '┼', '⎺', '⎻', '─',
+        '⎼', '⎽', '├', '┤', '┴', '┬', '│', '≤', '≥', 'π', '≠', '£', '·',
+    ];
+
+    match ch {
+        '`'..='~' => GRAPHICS[ch as usize - '`' as usize],
+        _ => ch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use beamterm_data::FontAtlasData;
+
+    use super::*;
+    use crate::gl::{select, Color, GlyphLookup, SelectionMode};
+
+    fn screen(emulator: &mut Emulator, cols: u16, rows: u16, input: &[u8]) -> CellBuffer {
+        let glyphs = GlyphLookup::new(&FontAtlasData::default());
+        let mut buffer = CellBuffer::new(glyphs, cols, rows);
+        emulator.process(input, &mut buffer);
+        buffer
+    }
+
+    /// Returns the screen text, one line per row with trailing spaces removed.
+    fn text(buffer: &CellBuffer) -> String {
+        let (cols, rows) = buffer.size();
+        (0..rows)
+            .map(|row| {
+                let line = (0..cols)
+                    .map(|col| buffer.symbol(buffer.cell(col, row).unwrap()).into_owned())
+                    .collect::<String>();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Recorded output of terminal applications, with the expected screen
+    /// contents at 20x6 cells. Fixture inputs use `\e`, `\r`, `\n` and `\t`
+    /// escapes in place of control characters, with line breaks ignored, and the
+    /// expected screens mark the end of each line with `|`.
+    #[test]
+    fn test_conformance_fixtures() {
+        let fixtures = [
+            ("cursor_movement", include_str!("fixtures/cursor_movement.vt")),
+            ("erase", include_str!("fixtures/erase.vt")),
+            ("scroll_region", include_str!("fixtures/scroll_region.vt")),
+            ("alternate_screen", include_str!("fixtures/alternate_screen.vt")),
+            ("insert_delete", include_str!("fixtures/insert_delete.vt")),
+            ("wrap_and_tabs", include_str!("fixtures/wrap_and_tabs.vt")),
+        ];
+
+        for (name, fixture) in fixtures {
+            let (input, expected) = fixture
+                .split_once("\n--- screen ---\n")
+                .unwrap_or_else(|| panic!("{name}: missing screen separator"));
+
+            let input = input
+                .replace('\n', "")
+                .replace("\\e", "\x1b")
+                .replace("\\r", "\r")
+                .replace("\\n", "\n")
+                .replace("\\t", "\t");
+            let expected = expected
+                .lines()
+                .map(|line| line.trim_end_matches('|').trim_end())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let buffer = screen(&mut Emulator::new(), 20, 6, input.as_bytes());
+            assert_eq!(text(&buffer), expected, "fixture {name}");
+        }
+    }
+
+    #[test]
+    fn test_sgr_colors_are_written_to_cells() {
+        let input = b"\x1b[31;44mA\x1b[38;5;200;48;2;1;2;3mB\x1b[7mC\x1b[0mD";
+        let buffer = screen(&mut Emulator::new(), 10, 1, input);
+
+        let colors = |col| {
+            let cell = buffer.cell(col, 0).unwrap();
+            (cell.fg(), cell.bg())
+        };
+        assert_eq!(colors(0), (Color::Indexed(1), Color::Indexed(4)));
+        assert_eq!(colors(1), (Color::Indexed(200), Color::Rgb(0x010203)));
+        assert_eq!(colors(2), (Color::Rgb(0x010203), Color::Indexed(200)));
+        assert_eq!(colors(3), (Color::DefaultFg, Color::DefaultBg));
+    }
+
+    #[test]
+    fn test_lines_scrolled_off_screen_enter_scrollback() {
+        let mut emulator = Emulator::new();
+        let mut buffer = CellBuffer::new(GlyphLookup::default(), 10, 2);
+        buffer.set_scrollback_capacity(10);
+
+        emulator.process(b"one\r\ntwo\r\nthree\r\nfour", &mut buffer);
+        assert_eq!(text(&buffer), "three\nfour");
+        assert_eq!(buffer.scrollback_len(), 2);
+
+        // the alternate screen doesn't scroll into the scrollback
+        emulator.process(b"\x1b[?1049h\x1b[H1\r\n2\r\n3", &mut buffer);
+        assert_eq!(text(&buffer), "2\n3");
+        assert_eq!(buffer.scrollback_len(), 2);
+
+        emulator.process(b"\x1b[?1049l", &mut buffer);
+        assert_eq!(text(&buffer), "three\nfour");
+        assert_eq!(emulator.cursor_position(), (4, 1));
+    }
+
+    #[test]
+    fn test_modes_title_and_cursor_shape() {
+        let mut emulator = Emulator::new();
+        let input = b"\x1b[?1h\x1b=\x1b[?2004h\x1b[?25l\x1b[5 q\x1b]0;vim\x1b\\";
+        let mut buffer = screen(&mut emulator, 10, 2, input);
+
+        let modes = emulator.modes();
+        assert!(modes.application_cursor && modes.application_keypad && modes.bracketed_paste);
+        assert!(!modes.cursor_visible);
+        assert_eq!(emulator.cursor_shape(), CursorShape::Bar);
+        assert!(emulator.is_cursor_blinking());
+        assert_eq!(emulator.title(), "vim");
+
+        emulator.process(b"\x1b[>1u\x1b[>11u\x1b[=4;2u\x1b[?u", &mut buffer);
+        assert_eq!(emulator.modes().kitty_keyboard_flags, 15);
+        emulator.process(b"\x1b[<u", &mut buffer);
+        assert_eq!(emulator.modes().kitty_keyboard_flags, 1);
+        assert_eq!(emulator.take_responses(), b"\x1b[?15u");
+
+        let mut buffer = screen(&mut emulator, 10, 2, b"\x1b[<u\x1b[!p");
+        assert_eq!(
+            *emulator.modes(),
+            Modes {
+                bracketed_paste: true,
+                ..Modes::default()
+            }
+        );
+
+        emulator.process(b"\x1b[?1002h\x1b[?1006h\x1b[?1000l", &mut buffer);
+        assert_eq!(emulator.modes().mouse_tracking, MouseTracking::ButtonEvent);
+        assert_eq!(emulator.modes().mouse_encoding, MouseEncoding::Sgr);
+        emulator.process(b"\x1b[?1002l\x1b[?1016h", &mut buffer);
+        assert_eq!(emulator.modes().mouse_tracking, MouseTracking::Off);
+        assert_eq!(emulator.modes().mouse_encoding, MouseEncoding::SgrPixels);
+
+        emulator.process(b"\x1b(0lqk\x1b(B", &mut buffer);
+        let query = select(SelectionMode::Linear).start((0, 0)).end((2, 0));
+        assert_eq!(buffer.get_text(query), "┌─┐");
+    }
+
+    #[test]
+    fn test_osc8_hyperlinks() {
+        let input = b"a \x1b]8;;https://x.io\x1b\\link\x1b]8;;\x1b\\ b \
+            \x1b]8;id=1;https://y.io\x07A\x1b]8;id=2:k=v;https://y.io\x07B\x1b]8;;\x07";
+        let buffer = screen(&mut Emulator::new(), 20, 1, input);
+
+        let (query, url) = buffer.link_at((3, 0)).unwrap();
+        assert_eq!(query.range(), Some(((2, 0), (5, 0))));
+        assert_eq!(url, "https://x.io");
+        assert_eq!(buffer.displayed_hyperlink(7, 0), None);
+
+        // distinct id parameters make distinct links to the same URL
+        let (a, b) = (buffer.displayed_hyperlink(9, 0), buffer.displayed_hyperlink(10, 0));
+        assert_ne!(a, b);
+        assert_eq!(buffer.hyperlink_url(b.unwrap()), Some("https://y.io"));
+        assert_eq!(buffer.displayed_hyperlink(11, 0), None);
+    }
+
+    #[test]
+    fn test_resized_buffer_clamps_cursor() {
+        let mut emulator = Emulator::new();
+        let mut buffer = screen(&mut emulator, 10, 5, b"\x1b[2;4r\x1b[5;10H");
+        assert_eq!(emulator.cursor_position(), (9, 4));
+
+        buffer.resize(4, 3);
+        emulator.process(b"x", &mut buffer);
+        assert_eq!(emulator.cursor_position(), (3, 2));
+        assert_eq!(text(&buffer), "\n\n   x");
+    }
+}
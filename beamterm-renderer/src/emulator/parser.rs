@@ -0,0 +1,394 @@
+/// Maximum number of CSI parameters; further parameters are ignored.
+const MAX_PARAMS: usize = 32;
+
+/// Maximum length of an OSC string; longer strings are truncated.
+const MAX_OSC_LEN: usize = 4096;
+
+/// Escape sequence parser state machine, after the DEC ANSI parser model.
+///
+/// Bytes are decoded as UTF-8; malformed input decodes to U+FFFD. Sequences
+/// the emulator doesn't act on, such as DCS strings, are consumed and ignored.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Parser {
+    state: State,
+    params: Params,
+    intermediates: Vec<u8>,
+    osc: Vec<u8>,
+    utf8: Utf8Decoder,
+}
+
+/// Parsed unit of terminal input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Action<'a> {
+    /// Printable character.
+    Print(char),
+    /// C0 control character.
+    Execute(u8),
+    /// Control sequence: `CSI params intermediates final`.
+    Csi {
+        params: &'a Params,
+        intermediates: &'a [u8],
+        action: u8,
+    },
+    /// Escape sequence: `ESC intermediates final`.
+    Esc { intermediates: &'a [u8], action: u8 },
+    /// Operating system command, with the payload split at `;`.
+    Osc(&'a [u8]),
+}
+
+/// Numeric CSI parameters; each parameter may have `:`-separated subparameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Params {
+    /// Parameter values, with subparameters flattened in.
+    values: Vec<u16>,
+    /// Whether each value is a subparameter of the preceding value.
+    is_sub: Vec<bool>,
+    /// Private marker, such as `?` in `CSI ? 25 h`.
+    private: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    CsiEntry,
+    CsiParam,
+    CsiIntermediate,
+    CsiIgnore,
+    OscString,
+    /// DCS, SOS, PM and APC strings, which are ignored.
+    IgnoredString,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Utf8Decoder {
+    code_point: u32,
+    remaining: u8,
+}
+
+impl Parser {
+    /// Feeds bytes to the parser, calling `perform` for each parsed action.
+    pub(super) fn advance(&mut self, bytes: &[u8], mut perform: impl FnMut(Action)) {
+        for &byte in bytes {
+            self.advance_byte(byte, &mut perform);
+        }
+    }
+
+    fn advance_byte(&mut self, byte: u8, perform: &mut impl FnMut(Action)) {
+        // an incomplete UTF-8 sequence is interrupted by any non-continuation byte
+        if self.utf8.remaining > 0 && byte & 0xC0 != 0x80 {
+            self.utf8 = Utf8Decoder::default();
+            perform(Action::Print(char::REPLACEMENT_CHARACTER));
+        }
+
+        // controls which are recognized in every state
+        match byte {
+            0x18 | 0x1A => {
+                self.state = State::Ground;
+                return;
+            },
+            0x1B => {
+                if self.state == State::OscString {
+                    self.dispatch_osc(perform); // ESC \ terminates the string
+                }
+                self.enter(State::Escape);
+                return;
+            },
+            _ => {},
+        }
+
+        match self.state {
+            State::Ground => self.ground(byte, perform),
+            State::Escape => match byte {
+                0x00..=0x1F => perform(Action::Execute(byte)),
+                0x20..=0x2F => {
+                    self.intermediates.push(byte);
+                    self.state = State::EscapeIntermediate;
+                },
+                b'[' => self.enter(State::CsiEntry),
+                b']' => self.enter(State::OscString),
+                b'P' | b'X' | b'^' | b'_' => self.state = State::IgnoredString,
+                0x30..=0x7E => self.dispatch_esc(byte, perform),
+                _ => {},
+            },
+            State::EscapeIntermediate => match byte {
+                0x00..=0x1F => perform(Action::Execute(byte)),
+                0x20..=0x2F => self.intermediates.push(byte),
+                0x30..=0x7E => self.dispatch_esc(byte, perform),
+                _ => {},
+            },
+            State::CsiEntry | State::CsiParam => match byte {
+                0x00..=0x1F => perform(Action::Execute(byte)),
+                b'0'..=b'9' | b';' | b':' => {
+                    self.params.push_byte(byte);
+                    self.state = State::CsiParam;
+                },
+                b'<'..=b'?' if self.state == State::CsiEntry => {
+                    self.params.private = Some(byte);
+                    self.state = State::CsiParam;
+                },
+                b'<'..=b'?' => self.state = State::CsiIgnore,
+                0x20..=0x2F => {
+                    self.intermediates.push(byte);
+                    self.state = State::CsiIntermediate;
+                },
+                0x40..=0x7E => self.dispatch_csi(byte, perform),
+                _ => {},
+            },
+            State::CsiIntermediate => match byte {
+                0x00..=0x1F => perform(Action::Execute(byte)),
+                0x20..=0x2F => self.intermediates.push(byte),
+                0x30..=0x3F => self.state = State::CsiIgnore,
+                0x40..=0x7E => self.dispatch_csi(byte, perform),
+                _ => {},
+            },
+            State::CsiIgnore => match byte {
+                0x00..=0x1F => perform(Action::Execute(byte)),
+                0x40..=0x7E => self.state = State::Ground,
+                _ => {},
+            },
+            State::OscString => match byte {
+                0x07 => self.dispatch_osc(perform),
+                0x00..=0x1F => {},
+                _ if self.osc.len() < MAX_OSC_LEN => self.osc.push(byte),
+                _ => {},
+            },
+            State::IgnoredString => {},
+        }
+    }
+
+    fn ground(&mut self, byte: u8, perform: &mut impl FnMut(Action)) {
+        match byte {
+            0x00..=0x1F => perform(Action::Execute(byte)),
+            0x20..=0x7F => perform(Action::Print(byte as char)),
+            _ => {
+                if let Some(ch) = self.utf8.push(byte) {
+                    perform(Action::Print(ch));
+                }
+            },
+        }
+    }
+
+    fn enter(&mut self, state: State) {
+        self.state = state;
+        self.params.clear();
+        self.intermediates.clear();
+        self.osc.clear();
+    }
+
+    fn dispatch_esc(&mut self, action: u8, perform: &mut impl FnMut(Action)) {
+        perform(Action::Esc {
+            intermediates: &self.intermediates,
+            action,
+        });
+        self.state = State::Ground;
+    }
+
+    fn dispatch_csi(&mut self, action: u8, perform: &mut impl FnMut(Action)) {
+        self.params.finish();
+        perform(Action::Csi {
+            params: &self.params,
+            intermediates: &self.intermediates,
+            action,
+        });
+        self.state = State::Ground;
+    }
+
+    fn dispatch_osc(&mut self, perform: &mut impl FnMut(Action)) {
+        perform(Action::Osc(&self.osc));
+        self.state = State::Ground;
+    }
+}
+
+impl Params {
+    /// Returns the private marker of the sequence, such as `?`.
+    pub(super) fn private(&self) -> Option<u8> {
+        self.private
+    }
+
+    /// Returns the parameter at `index`, or `default` if missing or zero.
+    pub(super) fn get(&self, index: usize, default: u16) -> u16 {
+        match self.iter().nth(index).map(|p| p[0]) {
+            Some(0) | None => default,
+            Some(value) => value,
+        }
+    }
+
+    /// Returns the parameter at `index` as given, or `0` if missing.
+    pub(super) fn get_raw(&self, index: usize) -> u16 {
+        self.iter().nth(index).map_or(0, |p| p[0])
+    }
+
+    /// Iterates over the parameters, each with its subparameters.
+    pub(super) fn iter(&self) -> impl Iterator<Item = &[u16]> + '_ {
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            if start >= self.values.len() {
+                return None;
+            }
+
+            let len = 1 + self.is_sub[start + 1..].iter().take_while(|sub| **sub).count();
+            let param = &self.values[start..start + len];
+            start += len;
+            Some(param)
+        })
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.is_sub.clear();
+        self.private = None;
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        if self.values.is_empty() {
+            self.values.push(0);
+            self.is_sub.push(false);
+        }
+
+        match byte {
+            b';' | b':' if self.values.len() < MAX_PARAMS => {
+                self.values.push(0);
+                self.is_sub.push(byte == b':');
+            },
+            b'0'..=b'9' => {
+                let value = self.values.last_mut().expect("params are not empty");
+                *value = value.saturating_mul(10).saturating_add((byte - b'0') as u16);
+            },
+            _ => {},
+        }
+    }
+
+    fn finish(&mut self) {
+        // a sequence without parameters has a single default parameter
+        if self.values.is_empty() {
+            self.values.push(0);
+            self.is_sub.push(false);
+        }
+    }
+}
+
+impl Utf8Decoder {
+    /// Decodes the next byte of a multi-byte sequence, returning the character
+    /// once complete.
+    fn push(&mut self, byte: u8) -> Option<char> {
+        match (self.remaining, byte) {
+            (0, 0xC2..=0xDF) => self.start(byte & 0x1F, 1),
+            (0, 0xE0..=0xEF) => self.start(byte & 0x0F, 2),
+            (0, 0xF0..=0xF4) => self.start(byte & 0x07, 3),
+            (0, _) => Some(char::REPLACEMENT_CHARACTER),
+            (_, _) => {
+                self.code_point = (self.code_point << 6) | (byte & 0x3F) as u32;
+                self.remaining -= 1;
+                if self.remaining > 0 {
+                    return None;
+                }
+                Some(char::from_u32(self.code_point).unwrap_or(char::REPLACEMENT_CHARACTER))
+            },
+        }
+    }
+
+    fn start(&mut self, bits: u8, remaining: u8) -> Option<char> {
+        self.code_point = bits as u32;
+        self.remaining = remaining;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Owned {
+        Print(char),
+        Execute(u8),
+        Csi(Option<u8>, Vec<Vec<u16>>, Vec<u8>, char),
+        Esc(Vec<u8>, char),
+        Osc(String),
+    }
+
+    fn parse(input: &[u8]) -> Vec<Owned> {
+        let mut actions = Vec::new();
+        Parser::default().advance(input, |action| {
+            actions.push(match action {
+                Action::Print(ch) => Owned::Print(ch),
+                Action::Execute(byte) => Owned::Execute(byte),
+                Action::Csi { params, intermediates, action } => Owned::Csi(
+                    params.private(),
+                    params.iter().map(<[u16]>::to_vec).collect(),
+                    intermediates.to_vec(),
+                    action as char,
+                ),
+                Action::Esc { intermediates, action } => {
+                    Owned::Esc(intermediates.to_vec(), action as char)
+                },
+                Action::Osc(data) => Owned::Osc(String::from_utf8_lossy(data).into()),
+            })
+        });
+        actions
+    }
+
+    #[test]
+    fn test_csi_params_and_subparams() {
+        assert_eq!(parse(b"\x1b[H"), [Owned::Csi(None, vec![vec![0]], vec![], 'H')]);
+        assert_eq!(
+            parse(b"\x1b[1;38:2::255:0:10m"),
+            [Owned::Csi(None, vec![vec![1], vec![38, 2, 0, 255, 0, 10]], vec![], 'm')]
+        );
+        assert_eq!(
+            parse(b"\x1b[?1049h\x1b[2 q"),
+            [
+                Owned::Csi(Some(b'?'), vec![vec![1049]], vec![], 'h'),
+                Owned::Csi(None, vec![vec![2]], vec![b' '], 'q'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_controls_inside_sequences_execute() {
+        assert_eq!(
+            parse(b"a\x1b[2\nA"),
+            [
+                Owned::Print('a'),
+                Owned::Execute(b'\n'),
+                Owned::Csi(None, vec![vec![2]], vec![], 'A')
+            ]
+        );
+        // CAN aborts the sequence
+        assert_eq!(parse(b"\x1b[3\x18x"), [Owned::Print('x')]);
+    }
+
+    #[test]
+    fn test_esc_and_osc() {
+        assert_eq!(parse(b"\x1b(B\x1b7"), [Owned::Esc(vec![b'('], 'B'), Owned::Esc(vec![], '7')]);
+        assert_eq!(
+            parse(b"\x1b]0;title\x07\x1b]2;other\x1b\\"),
+            [
+                Owned::Osc("0;title".into()),
+                Owned::Osc("2;other".into()),
+                Owned::Esc(vec![], '\\'),
+            ]
+        );
+        // DCS strings are ignored up to the string terminator
+        assert_eq!(parse(b"\x1bPq#0;2\x1b\\x"), [Owned::Esc(vec![], '\\'), Owned::Print('x')]);
+    }
+
+    #[test]
+    fn test_utf8_decoding() {
+        assert_eq!(
+            parse("é漢🦀".as_bytes()),
+            [Owned::Print('é'), Owned::Print('漢'), Owned::Print('🦀')]
+        );
+        assert_eq!(
+            parse(b"\xe6\xbca\xff"),
+            [
+                Owned::Print(char::REPLACEMENT_CHARACTER),
+                Owned::Print('a'),
+                Owned::Print(char::REPLACEMENT_CHARACTER),
+            ]
+        );
+    }
+}
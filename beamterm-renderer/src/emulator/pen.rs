@@ -0,0 +1,152 @@
+use beamterm_data::{FontStyle, GlyphEffect};
+
+use crate::gl::{CellData, Color};
+
+const BOLD: u16 = FontStyle::Bold.style_mask();
+const ITALIC: u16 = FontStyle::Italic.style_mask();
+const UNDERLINE: u16 = GlyphEffect::Underline as u16;
+const STRIKETHROUGH: u16 = GlyphEffect::Strikethrough as u16;
+
+/// Graphic rendition applied to printed characters, as set by SGR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Pen {
+    fg: Color,
+    bg: Color,
+    style_bits: u16,
+    inverse: bool,
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Self {
+            fg: Color::DefaultFg,
+            bg: Color::DefaultBg,
+            style_bits: 0,
+            inverse: false,
+        }
+    }
+}
+
+impl Pen {
+    /// Returns the cell data for `symbol` drawn with this pen.
+    pub(super) fn cell<'a>(&self, symbol: &'a str) -> CellData<'a> {
+        let (fg, bg) = if self.inverse { (self.bg, self.fg) } else { (self.fg, self.bg) };
+        CellData::new_with_colors(symbol, self.style_bits, fg, bg)
+    }
+
+    /// Returns the cell data for erased cells, which keep the current background
+    /// color but no other attributes.
+    pub(super) fn blank(&self) -> CellData<'static> {
+        let bg = if self.inverse { self.fg } else { self.bg };
+        CellData::new_with_colors(" ", 0, Color::DefaultFg, bg)
+    }
+
+    /// Applies the parameters of an SGR sequence.
+    ///
+    /// Extended colors are accepted in both the `38;5;n` and the ITU `38:5:n`
+    /// forms, including the optional color space id of `38:2:id:r:g:b`.
+    /// Unsupported attributes, such as blink or faint, are ignored.
+    pub(super) fn apply_sgr<'a>(&mut self, mut params: impl Iterator<Item = &'a [u16]>) {
+        while let Some(param) = params.next() {
+            match param[0] {
+                0 => *self = Self::default(),
+                1 => self.style_bits |= BOLD,
+                3 => self.style_bits |= ITALIC,
+                4 if param.get(1) == Some(&0) => self.style_bits &= !UNDERLINE,
+                4 | 21 => self.style_bits |= UNDERLINE,
+                7 => self.inverse = true,
+                9 => self.style_bits |= STRIKETHROUGH,
+                22 => self.style_bits &= !BOLD,
+                23 => self.style_bits &= !ITALIC,
+                24 => self.style_bits &= !UNDERLINE,
+                27 => self.inverse = false,
+                29 => self.style_bits &= !STRIKETHROUGH,
+                n @ 30..=37 => self.fg = Color::Indexed((n - 30) as u8),
+                39 => self.fg = Color::DefaultFg,
+                n @ 40..=47 => self.bg = Color::Indexed((n - 40) as u8),
+                49 => self.bg = Color::DefaultBg,
+                n @ 90..=97 => self.fg = Color::Indexed((n - 90 + 8) as u8),
+                n @ 100..=107 => self.bg = Color::Indexed((n - 100 + 8) as u8),
+                38 => {
+                    if let Some(color) = extended_color(param, &mut params) {
+                        self.fg = color;
+                    }
+                },
+                48 => {
+                    if let Some(color) = extended_color(param, &mut params) {
+                        self.bg = color;
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+/// Parses the color of an extended color parameter, taking its arguments from
+/// the subparameters if present, otherwise from the following parameters.
+fn extended_color<'a>(
+    param: &[u16],
+    params: &mut impl Iterator<Item = &'a [u16]>,
+) -> Option<Color> {
+    if param.len() > 1 {
+        return match param[1..] {
+            [5, index, ..] => Some(Color::Indexed(index.min(255) as u8)),
+            [2, _, r, g, b, ..] => Some(rgb(r, g, b)),
+            [2, r, g, b] => Some(rgb(r, g, b)),
+            _ => None,
+        };
+    }
+
+    match params.next()?[0] {
+        5 => Some(Color::Indexed(params.next()?[0].min(255) as u8)),
+        2 => {
+            let mut channel = || params.next().map(|p| p[0]);
+            Some(rgb(channel()?, channel()?, channel()?))
+        },
+        _ => None,
+    }
+}
+
+fn rgb(r: u16, g: u16, b: u16) -> Color {
+    let channel = |c: u16| c.min(255) as u32;
+    Color::Rgb((channel(r) << 16) | (channel(g) << 8) | channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sgr(params: &[&[u16]]) -> Pen {
+        let mut pen = Pen::default();
+        pen.apply_sgr(params.iter().copied());
+        pen
+    }
+
+    #[test]
+    fn test_sgr_colors() {
+        let pen = sgr(&[&[31], &[104]]);
+        assert_eq!((pen.fg, pen.bg), (Color::Indexed(1), Color::Indexed(12)));
+
+        let pen = sgr(&[&[38], &[5], &[208], &[48], &[2], &[1], &[2], &[3]]);
+        assert_eq!((pen.fg, pen.bg), (Color::Indexed(208), Color::Rgb(0x010203)));
+
+        let pen = sgr(&[&[38, 2, 0, 255, 128, 0], &[48, 2, 16, 32, 48]]);
+        assert_eq!((pen.fg, pen.bg), (Color::Rgb(0xff8000), Color::Rgb(0x102030)));
+
+        let pen = sgr(&[&[31], &[39]]);
+        assert_eq!(pen.fg, Color::DefaultFg);
+    }
+
+    #[test]
+    fn test_sgr_attributes() {
+        let pen = sgr(&[&[1], &[3], &[4], &[9]]);
+        assert_eq!(pen.style_bits, BOLD | ITALIC | UNDERLINE | STRIKETHROUGH);
+
+        let pen = sgr(&[&[1], &[4], &[22], &[4, 0]]);
+        assert_eq!(pen.style_bits, 0);
+
+        let pen = sgr(&[&[1], &[7], &[32], &[0]]);
+        assert_eq!(pen, Pen::default());
+    }
+}
@@ -80,6 +80,10 @@ impl Error {
         Self::Resource(format!("WebGL error: {message}"))
     }
 
+    pub fn framebuffer_read_failed() -> Self {
+        Self::Resource("Failed to read pixels from the framebuffer".to_string())
+    }
+
     pub fn element_creation_failed(element_type: &str) -> Self {
         Self::Resource(format!("Failed to create element: {element_type}"))
     }
@@ -92,4 +96,20 @@ impl Error {
     pub fn deserialization_failed(message: String) -> Self {
         Self::Data(format!("Failed to deserialize: {message}"))
     }
+
+    pub fn theme_parse_failed(format: &str, detail: &str) -> Self {
+        Self::Data(format!("Failed to parse {format} theme: {detail}"))
+    }
+
+    pub fn png_encoding_failed(detail: &str) -> Self {
+        Self::Data(format!("Failed to encode PNG: {detail}"))
+    }
+
+    pub fn palette_slot_invalid(slot: u32) -> Self {
+        Self::Data(format!("Invalid palette slot: {slot}"))
+    }
+
+    pub fn search_pattern_invalid(detail: &str) -> Self {
+        Self::Data(format!("Invalid search pattern: {detail}"))
+    }
 }
@@ -0,0 +1,561 @@
+//! Export of the terminal screen as SVG, HTML, ANSI text and PNG.
+//!
+//! [`ScreenExport`] encodes the cells displayed by a [`CellBuffer`] as an SVG
+//! document, a standalone HTML snippet or text with ANSI escape sequences,
+//! with colors resolved against a [`Palette`] and font styles and line
+//! decorations preserved. Adjacent cells sharing the same colors and style are
+//! merged into runs, keeping the output compact. The export can be restricted
+//! to a selection with [`ScreenExport::region`], which is how selections are
+//! copied as styled text. With the `png` feature, `encode_png` encodes an
+//! [`RgbaImage`], such as one read back from the canvas or rendered by the
+//! [`headless`](crate::headless) renderer.
+//!
+//! The encoders are independent of WebGL and run natively.
+//!
+//! # Example
+//! ```
+//! use beamterm_renderer::{export::ScreenExport, CellBuffer, CellData, GlyphLookup, Palette};
+//!
+//! let mut buffer = CellBuffer::new(GlyphLookup::default(), 10, 1);
+//! buffer.update_cell(0, 0, CellData::new_with_style_bits("$", 0, 0x00ff00, 0x000000));
+//!
+//! let palette = Palette::default();
+//! let svg = ScreenExport::new(&buffer, &palette, (8, 16)).to_svg();
+//! assert!(svg.contains(r##"<text x="0" y="12" textLength="8" fill="#00ff00""##));
+//! ```
+
+use std::{fmt::Write, ops::Range};
+
+use beamterm_data::Glyph;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::{
+    gl::{CellBuffer, CellQuery, Palette, TerminalGrid, GL},
+    headless::RgbaImage,
+    Error,
+};
+
+/// Encodes the displayed cells of a [`CellBuffer`]; see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct ScreenExport<'a> {
+    buffer: &'a CellBuffer,
+    palette: &'a Palette,
+    cell_size: (i32, i32),
+    font_family: &'a str,
+    font_size: f32,
+    region: Option<CellQuery>,
+}
+
+/// Consecutive cells of a row sharing colors and style.
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    col: u16,
+    len: u16,
+    text: String,
+    fg: u32,
+    bg: u32,
+    style: u16,
+}
+
+impl<'a> ScreenExport<'a> {
+    /// Fraction of the cell height used as the font size when not set explicitly.
+    const FONT_SIZE_FRACTION: f32 = 0.8;
+
+    /// Creates an exporter for the cells displayed by `buffer`, laid out on a
+    /// grid of `cell_size` pixels.
+    pub fn new(buffer: &'a CellBuffer, palette: &'a Palette, cell_size: (i32, i32)) -> Self {
+        Self {
+            buffer,
+            palette,
+            cell_size,
+            font_family: "monospace",
+            font_size: cell_size.1 as f32 * Self::FONT_SIZE_FRACTION,
+            region: None,
+        }
+    }
+
+    /// Creates an exporter for the screen of a terminal grid, using the grid's
+    /// palette and the font of its atlas.
+    pub fn from_grid(grid: &'a TerminalGrid) -> Self {
+        let atlas = grid.atlas();
+        Self::new(grid.cell_buffer(), grid.palette(), grid.cell_size())
+            .font(atlas.font_name(), atlas.font_size())
+    }
+
+    /// Sets the CSS font family and the font size in pixels.
+    pub fn font(mut self, family: &'a str, size_px: f32) -> Self {
+        self.font_family = family;
+        self.font_size = size_px;
+        self
+    }
+
+    /// Restricts the export to the cells of a selection, trimming trailing
+    /// whitespace from each row if the query does. Cells keep their position
+    /// on the screen in the SVG export.
+    pub fn region(mut self, query: CellQuery) -> Self {
+        self.region = Some(query);
+        self
+    }
+
+    /// Returns the size of the exported image in pixels.
+    pub fn size(&self) -> (i32, i32) {
+        let (cols, rows) = self.buffer.size();
+        (cols as i32 * self.cell_size.0, rows as i32 * self.cell_size.1)
+    }
+
+    /// Encodes the screen as an SVG document.
+    ///
+    /// Backgrounds other than the default background are drawn as rectangles
+    /// behind `<text>` elements, which are stretched to the width of their cells.
+    pub fn to_svg(&self) -> String {
+        let (width, height) = self.size();
+        let (cell_w, cell_h) = self.cell_size;
+        let baseline = cell_h * 4 / 5;
+
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="{}" font-size="{}">"#,
+            escape(self.font_family),
+            self.font_size,
+        );
+        let _ = write!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            hex(self.palette.background())
+        );
+
+        for (row, runs) in self.rows() {
+            let y = row as i32 * cell_h;
+            for run in runs.iter().filter(|run| run.bg != self.palette.background()) {
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{}" y="{y}" width="{}" height="{cell_h}" fill="{}"/>"#,
+                    run.col as i32 * cell_w,
+                    run.len as i32 * cell_w,
+                    hex(run.bg),
+                );
+            }
+
+            for run in runs.iter().filter(|run| !run.text.trim_end().is_empty()) {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}" textLength="{}" fill="{}"{} xml:space="preserve">{}</text>"#,
+                    run.col as i32 * cell_w,
+                    y + baseline,
+                    run.len as i32 * cell_w,
+                    hex(run.fg),
+                    svg_style(run.style),
+                    escape(&run.text),
+                );
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Encodes the screen as a standalone HTML `<pre>` element with inline styles.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            r#"<pre style="margin:0;padding:0;font-family:{};font-size:{}px;line-height:{}px;color:{};background-color:{}">"#,
+            escape(self.font_family),
+            self.font_size,
+            self.cell_size.1,
+            hex(self.palette.foreground()),
+            hex(self.palette.background()),
+        );
+
+        for (i, (_, runs)) in self.rows().into_iter().enumerate() {
+            if i > 0 {
+                html.push('\n');
+            }
+
+            for run in runs {
+                let _ = write!(
+                    html,
+                    r#"<span style="color:{};background-color:{}{}">{}</span>"#,
+                    hex(run.fg),
+                    hex(run.bg),
+                    css_style(run.style),
+                    escape(&run.text),
+                );
+            }
+        }
+
+        html.push_str("</pre>");
+        html
+    }
+
+    /// Encodes the screen as text with ANSI escape sequences, with rows
+    /// separated by `\n`.
+    ///
+    /// Colors are encoded as 24-bit SGR sequences, except for the default
+    /// foreground and background of the palette, which are left to the
+    /// receiving terminal. Rows ending with styled text are followed by a reset.
+    pub fn to_ansi(&self) -> String {
+        const RESET: &str = "0";
+
+        let mut ansi = String::new();
+        for (i, (_, runs)) in self.rows().into_iter().enumerate() {
+            if i > 0 {
+                ansi.push('\n');
+            }
+
+            let mut current = String::from(RESET);
+            for run in runs {
+                let sgr = self.sgr(&run);
+                if sgr != current {
+                    let _ = write!(ansi, "\x1b[{sgr}m");
+                    current = sgr;
+                }
+                ansi.push_str(&run.text);
+            }
+            if current != RESET {
+                ansi.push_str("\x1b[0m");
+            }
+        }
+
+        ansi
+    }
+
+    /// Returns the SGR parameters selecting the colors and style of a run.
+    fn sgr(&self, run: &Run) -> String {
+        let mut sgr = String::from("0");
+        for (flag, param) in [
+            (Glyph::BOLD_FLAG, ";1"),
+            (Glyph::ITALIC_FLAG, ";3"),
+            (Glyph::UNDERLINE_FLAG, ";4"),
+            (Glyph::STRIKETHROUGH_FLAG, ";9"),
+        ] {
+            if run.style & flag != 0 {
+                sgr.push_str(param);
+            }
+        }
+
+        let rgb = |color: u32| (color >> 16 & 0xFF, color >> 8 & 0xFF, color & 0xFF);
+        if run.fg != self.palette.foreground() {
+            let (r, g, b) = rgb(run.fg);
+            let _ = write!(sgr, ";38;2;{r};{g};{b}");
+        }
+        if run.bg != self.palette.background() {
+            let (r, g, b) = rgb(run.bg);
+            let _ = write!(sgr, ";48;2;{r};{g};{b}");
+        }
+        sgr
+    }
+
+    /// Returns the runs of each exported row.
+    fn rows(&self) -> Vec<(u16, Vec<Run>)> {
+        let (cols, rows) = self.buffer.size();
+        let Some(query) = self.region else {
+            return (0..rows).map(|row| (row, self.runs(row, 0..cols))).collect();
+        };
+        let Some((start, end)) = query.range() else {
+            return Vec::new();
+        };
+
+        // the selected cells of each row are contiguous
+        let mut spans: Vec<(u16, Range<u16>)> = Vec::new();
+        for (idx, _) in self.buffer.cell_iter(start, end, query.mode) {
+            let (col, row) = ((idx % cols as usize) as u16, (idx / cols as usize) as u16);
+            match spans.last_mut() {
+                Some((last_row, span)) if *last_row == row => span.end = col + 1,
+                _ => spans.push((row, col..col + 1)),
+            }
+        }
+
+        let trim = query.trim_trailing_whitespace;
+        spans
+            .into_iter()
+            .map(|(row, span)| {
+                let mut runs = self.runs(row, span);
+                if trim {
+                    trim_trailing_whitespace(&mut runs);
+                }
+                (row, runs)
+            })
+            .collect()
+    }
+
+    /// Returns the runs of the displayed cells `cols` of a row.
+    fn runs(&self, row: u16, cols: Range<u16>) -> Vec<Run> {
+        let mut runs: Vec<Run> = Vec::new();
+        for col in cols {
+            let Some(cell) = self.buffer.displayed_cell(col, row) else {
+                continue;
+            };
+
+            let fg = self.palette.resolve(cell.fg());
+            let bg = self.palette.resolve(cell.bg());
+            let style = cell.get_style() & STYLE_MASK;
+            let symbol = self.buffer.symbol(&cell);
+
+            match runs.last_mut() {
+                Some(run) if (run.fg, run.bg, run.style) == (fg, bg, style) => {
+                    run.len += 1;
+                    run.text.push_str(&symbol);
+                },
+                _ => runs.push(Run {
+                    col,
+                    len: 1,
+                    text: symbol.into(),
+                    fg,
+                    bg,
+                    style,
+                }),
+            }
+        }
+        runs
+    }
+}
+
+/// Removes trailing whitespace from the last runs of a row, dropping runs
+/// left empty.
+fn trim_trailing_whitespace(runs: &mut Vec<Run>) {
+    while let Some(run) = runs.last_mut() {
+        let trimmed = run.text.trim_end().len();
+        run.len = run.len.saturating_sub(run.text[trimmed..].chars().count() as u16);
+        run.text.truncate(trimmed);
+
+        if !run.text.is_empty() {
+            break;
+        }
+        runs.pop();
+    }
+}
+
+/// Style bits affecting the exported text.
+const STYLE_MASK: u16 =
+    Glyph::BOLD_FLAG | Glyph::ITALIC_FLAG | Glyph::UNDERLINE_FLAG | Glyph::STRIKETHROUGH_FLAG;
+
+fn svg_style(style: u16) -> String {
+    let mut attrs = String::new();
+    if style & Glyph::BOLD_FLAG != 0 {
+        attrs.push_str(r#" font-weight="bold""#);
+    }
+    if style & Glyph::ITALIC_FLAG != 0 {
+        attrs.push_str(r#" font-style="italic""#);
+    }
+    if let Some(decoration) = text_decoration(style) {
+        let _ = write!(attrs, r#" text-decoration="{decoration}""#);
+    }
+    attrs
+}
+
+fn css_style(style: u16) -> String {
+    let mut css = String::new();
+    if style & Glyph::BOLD_FLAG != 0 {
+        css.push_str(";font-weight:bold");
+    }
+    if style & Glyph::ITALIC_FLAG != 0 {
+        css.push_str(";font-style:italic");
+    }
+    if let Some(decoration) = text_decoration(style) {
+        let _ = write!(css, ";text-decoration:{decoration}");
+    }
+    css
+}
+
+fn text_decoration(style: u16) -> Option<&'static str> {
+    let underline = style & Glyph::UNDERLINE_FLAG != 0;
+    let strikethrough = style & Glyph::STRIKETHROUGH_FLAG != 0;
+    match (underline, strikethrough) {
+        (true, true) => Some("underline line-through"),
+        (true, false) => Some("underline"),
+        (false, true) => Some("line-through"),
+        (false, false) => None,
+    }
+}
+
+fn hex(rgb: u32) -> String {
+    format!("#{:06x}", rgb & 0xFF_FFFF)
+}
+
+/// Escapes text for use in XML and HTML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Encodes an image as a PNG file.
+///
+/// Requires the `png` feature; in the browser, [`crate::Terminal::screenshot_png`]
+/// uses the browser's PNG encoder instead.
+///
+/// # Errors
+/// Returns `Error::Data` if the image cannot be encoded.
+#[cfg(feature = "png")]
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, Error> {
+    let encoding_failed = |e: png::EncodingError| Error::png_encoding_failed(&e.to_string());
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(encoding_failed)?;
+    writer.write_image_data(image.pixels()).map_err(encoding_failed)?;
+    writer.finish().map_err(encoding_failed)?;
+    Ok(png)
+}
+
+/// Encodes the content of the canvas as a PNG file with the browser's encoder,
+/// returning a promise resolving to a `Blob`.
+///
+/// The content is captured when called, which must be in the same task as the
+/// frame was rendered in, as the drawing buffer is not preserved once it has
+/// been presented.
+pub(crate) fn canvas_png(canvas: &web_sys::HtmlCanvasElement) -> Result<js_sys::Promise, Error> {
+    let mut result = Ok(());
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let on_blob = Closure::once_into_js(move |blob: JsValue| {
+            let _ = match blob.is_null() {
+                true => reject.call1(&JsValue::NULL, &"Failed to encode PNG".into()),
+                false => resolve.call1(&JsValue::NULL, &blob),
+            };
+        });
+        result = canvas.to_blob(on_blob.unchecked_ref());
+    });
+
+    result.map_err(|_| Error::png_encoding_failed("the canvas cannot be read"))?;
+    Ok(promise)
+}
+
+/// Reads the pixels of the current framebuffer, which spans `size` pixels.
+///
+/// Must be called in the same task as the frame was rendered in, as the
+/// drawing buffer is not preserved once it has been presented.
+pub(crate) fn read_framebuffer(gl: &GL, size: (i32, i32)) -> Result<RgbaImage, Error> {
+    let (width, height) = (size.0.max(0), size.1.max(0));
+    let mut pixels = vec![0; width as usize * height as usize * 4];
+    gl.read_pixels_with_opt_u8_array(
+        0,
+        0,
+        width,
+        height,
+        GL::RGBA,
+        GL::UNSIGNED_BYTE,
+        Some(&mut pixels),
+    )
+    .map_err(|_| Error::framebuffer_read_failed())?;
+
+    // the framebuffer origin is at the bottom-left
+    let stride = width as usize * 4;
+    let mut flipped = Vec::with_capacity(pixels.len());
+    for row in pixels.chunks(stride.max(1)).rev() {
+        flipped.extend_from_slice(row);
+    }
+
+    Ok(RgbaImage::new(width as u32, height as u32, flipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::{select, CellData, Color, GlyphLookup, SelectionMode};
+
+    fn buffer() -> CellBuffer {
+        let mut buffer = CellBuffer::new(GlyphLookup::default(), 4, 2);
+        let cell = |s, style, fg: Color, bg: Color| CellData::new_with_colors(s, style, fg, bg);
+        let (fg, bg) = (Color::DefaultFg, Color::DefaultBg);
+        buffer.update_cells(
+            [
+                cell("a", Glyph::BOLD_FLAG, fg, bg),
+                cell("<", Glyph::BOLD_FLAG, fg, bg),
+                cell("c", 0, Color::Indexed(1), Color::Rgb(0x0000ff)),
+                cell(" ", 0, fg, bg),
+                cell("d", Glyph::UNDERLINE_FLAG | Glyph::STRIKETHROUGH_FLAG, fg, bg),
+                cell(" ", 0, fg, bg),
+                cell(" ", 0, fg, bg),
+                cell(" ", 0, fg, bg),
+            ]
+            .into_iter(),
+        );
+        buffer
+    }
+
+    #[test]
+    fn test_svg_merges_runs_and_resolves_colors() {
+        let (buffer, palette) = (buffer(), Palette::default());
+        let svg = ScreenExport::new(&buffer, &palette, (10, 20)).font("Hack", 14.0).to_svg();
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40""#)
+        );
+        assert!(svg.contains(r#"font-family="Hack" font-size="14""#));
+        assert!(svg.contains(r##"<rect x="20" y="0" width="10" height="20" fill="#0000ff"/>"##));
+        assert!(svg.contains(
+            r##"<text x="0" y="16" textLength="20" fill="#ffffff" font-weight="bold" xml:space="preserve">a&lt;</text>"##
+        ));
+        assert!(svg.contains(r##"fill="#cd0000" xml:space="preserve">c</text>"##));
+        assert!(svg.contains(r#"text-decoration="underline line-through""#));
+        assert_eq!(svg.matches("<text").count(), 3); // blank runs are omitted
+        assert!(svg.ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_html_spans() {
+        let (buffer, palette) = (buffer(), Palette::default());
+        let html = ScreenExport::new(&buffer, &palette, (10, 20)).to_html();
+
+        assert!(html.starts_with(r#"<pre style="margin:0;padding:0;font-family:monospace;font-size:16px;line-height:20px;"#));
+        assert!(html.contains(
+            r#"<span style="color:#ffffff;background-color:#000000;font-weight:bold">a&lt;</span>"#
+        ));
+        assert!(html.contains(
+            "<span style=\"color:#ffffff;background-color:#000000\"> </span>\n<span style=\"color:#ffffff;background-color:#000000;text-decoration:underline line-through\">d</span>"
+        ));
+        assert!(html.ends_with("   </span></pre>"));
+    }
+
+    #[test]
+    fn test_ansi_and_region() {
+        let (buffer, palette) = (buffer(), Palette::default());
+        let export = ScreenExport::new(&buffer, &palette, (10, 20));
+
+        assert_eq!(
+            export.to_ansi(),
+            "\x1b[0;1ma<\x1b[0;38;2;205;0;0;48;2;0;0;255mc\x1b[0m \n\x1b[0;4;9md\x1b[0m   "
+        );
+
+        let query = select(SelectionMode::Linear).start((1, 0)).end((1, 1));
+        let region = export.clone().region(query.trim_trailing_whitespace(true));
+        assert_eq!(
+            region.to_ansi(),
+            "\x1b[0;1m<\x1b[0;38;2;205;0;0;48;2;0;0;255mc\x1b[0m\n\x1b[0;4;9md\x1b[0m"
+        );
+        assert!(region.to_html().contains("\">c</span>\n<span"));
+
+        let block = export.region(select(SelectionMode::Block).start((0, 0)).end((0, 1)));
+        assert_eq!(block.to_ansi(), "\x1b[0;1ma\x1b[0m\n\x1b[0;4;9md\x1b[0m");
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_png_round_trip() {
+        let image = crate::headless::HeadlessRenderer::new(Default::default()).render(&buffer());
+        let png = encode_png(&image).unwrap();
+
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+
+        assert_eq!((info.width, info.height), (image.width(), image.height()));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(pixels, image.pixels());
+        assert!(png.len() < image.pixels().len() / 4);
+    }
+}
@@ -0,0 +1,99 @@
+//! Canvas focus tracking for the terminal cursor.
+
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::{Error, TerminalGrid};
+
+/// Tracks input focus of the terminal canvas.
+///
+/// Forwards `focus` and `blur` events to the [`TerminalGrid`], which draws an
+/// unfocused cursor as a hollow block. Canvases are not focusable by default,
+/// so a `tabindex` is assigned to canvases without one; the handler is thus
+/// only created for terminals taking keyboard input, or with focus tracking
+/// enabled explicitly.
+pub(crate) struct CanvasFocusHandler {
+    /// The canvas element this handler is attached to.
+    canvas: web_sys::HtmlCanvasElement,
+    /// Closure for focus events.
+    on_focus: Closure<dyn FnMut(web_sys::FocusEvent)>,
+    /// Closure for blur events.
+    on_blur: Closure<dyn FnMut(web_sys::FocusEvent)>,
+}
+
+impl CanvasFocusHandler {
+    /// Attaches focus listeners to the canvas and initializes the grid's focus
+    /// state from the currently focused element.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if event listeners cannot be attached to the canvas.
+    pub(crate) fn new(
+        canvas: &web_sys::HtmlCanvasElement,
+        grid: Rc<RefCell<TerminalGrid>>,
+    ) -> Result<Self, Error> {
+        if !canvas.has_attribute("tabindex") {
+            canvas.set_tab_index(0);
+        }
+
+        let focused = crate::js::document()
+            .ok()
+            .and_then(|d| d.active_element())
+            .is_some_and(|active| active == ***canvas);
+        grid.borrow_mut().set_focused(focused);
+
+        let on_focus = create_focus_event_closure(grid.clone(), true);
+        let on_blur = create_focus_event_closure(grid, false);
+
+        canvas
+            .add_event_listener_with_callback("focus", on_focus.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add focus listener".into()))?;
+        canvas
+            .add_event_listener_with_callback("blur", on_blur.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add blur listener".into()))?;
+
+        Ok(Self {
+            canvas: canvas.clone(),
+            on_focus,
+            on_blur,
+        })
+    }
+
+    /// Removes all event listeners from the canvas.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub(crate) fn cleanup(&self) {
+        let _ = self
+            .canvas
+            .remove_event_listener_with_callback("focus", self.on_focus.as_ref().unchecked_ref());
+        let _ = self
+            .canvas
+            .remove_event_listener_with_callback("blur", self.on_blur.as_ref().unchecked_ref());
+    }
+}
+
+fn create_focus_event_closure(
+    grid: Rc<RefCell<TerminalGrid>>,
+    focused: bool,
+) -> Closure<dyn FnMut(web_sys::FocusEvent)> {
+    Closure::wrap(Box::new(move |_: web_sys::FocusEvent| {
+        grid.borrow_mut().set_focused(focused);
+    }) as Box<dyn FnMut(_)>)
+}
+
+impl Drop for CanvasFocusHandler {
+    /// Automatically removes event listeners when handler is dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for CanvasFocusHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CanvasFocusHandler")
+    }
+}
@@ -1,10 +1,13 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::borrow::Cow;
 
-use beamterm_data::{FontAtlasData, FontStyle, Glyph};
-use compact_str::{CompactString, ToCompactString};
+use beamterm_data::FontAtlasData;
+use compact_str::CompactString;
 use web_sys::console;
 
-use crate::{error::Error, gl::GL};
+use crate::{
+    error::Error,
+    gl::{GlyphLookup, GL},
+};
 
 /// A texture atlas containing font glyphs for efficient WebGL text rendering.
 ///
@@ -22,10 +25,12 @@ use crate::{error::Error, gl::GL};
 pub struct FontAtlas {
     /// The underlying texture
     texture: crate::gl::texture::Texture,
-    /// Symbol to 3d texture index
-    glyph_coords: HashMap<CompactString, u16>,
-    /// Base glyph identifier to symbol mapping
-    symbol_lookup: HashMap<u16, CompactString>,
+    /// Symbol to glyph identifier mapping
+    glyphs: GlyphLookup,
+    /// The name of the font
+    font_name: CompactString,
+    /// The font size in pixels
+    font_size: f32,
     /// The size of each character cell in pixels
     cell_size: (i32, i32),
     /// The number of slices in the atlas texture
@@ -34,6 +39,8 @@ pub struct FontAtlas {
     underline: beamterm_data::LineDecoration,
     /// Strikethrough configuration  
     strikethrough: beamterm_data::LineDecoration,
+    /// The atlas data, retained for recreating the texture after a context loss
+    data: FontAtlasData,
 }
 
 impl FontAtlas {
@@ -58,30 +65,37 @@ impl FontAtlas {
         );
 
         let (cell_width, cell_height) = config.cell_size;
-        let mut layers = HashMap::new();
-        let mut symbol_lookup = HashMap::new();
 
         // we only store the normal-styled glyphs (incl emoji) in the atlas lookup,
         // as the correct layer id can be derived from the base glyph id plus font style
-        config.glyphs.iter()
-            .filter(|g| g.style == FontStyle::Normal) // only normal style glyphs
-            .filter(|g| !g.is_ascii())                // only non-ascii glyphs
-            .for_each(|g| {
-                symbol_lookup.insert(g.id, g.symbol.clone());
-                layers.insert(g.symbol.clone(), g.id);
-            });
+        let glyphs = GlyphLookup::new(&config);
 
         Ok(Self {
             texture,
-            glyph_coords: layers,
-            symbol_lookup,
+            glyphs,
+            font_name: config.font_name.clone(),
+            font_size: config.font_size,
             cell_size: (cell_width, cell_height),
             num_slices: num_slices as u32,
             underline: config.underline,
             strikethrough: config.strikethrough,
+            data: config,
         })
     }
 
+    /// Recreates the atlas texture from the retained atlas data, after the
+    /// WebGL context was lost and restored.
+    pub(crate) fn restore(&mut self, gl: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+        self.texture = crate::gl::texture::Texture::from_font_atlas_data(gl, GL::RGBA, &self.data)?;
+        Ok(())
+    }
+
+    /// Deletes the atlas texture, returning the retained atlas data.
+    pub fn into_data(self, gl: &web_sys::WebGl2RenderingContext) -> FontAtlasData {
+        self.texture.delete(gl);
+        self.data
+    }
+
     /// Binds the atlas texture to the specified texture unit
     pub fn bind(&self, gl: &web_sys::WebGl2RenderingContext, texture_unit: u32) {
         self.texture.bind(gl, texture_unit);
@@ -92,6 +106,16 @@ impl FontAtlas {
         (w - 2 * FontAtlasData::PADDING, h - 2 * FontAtlasData::PADDING)
     }
 
+    /// Returns the name of the font rasterized into the atlas
+    pub fn font_name(&self) -> &str {
+        &self.font_name
+    }
+
+    /// Returns the font size in pixels
+    pub fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
     /// Returns the underline configuration
     pub fn underline(&self) -> beamterm_data::LineDecoration {
         self.underline
@@ -102,30 +126,18 @@ impl FontAtlas {
         self.strikethrough
     }
 
+    /// Returns the symbol and glyph identifier lookup of the atlas
+    pub fn glyphs(&self) -> &GlyphLookup {
+        &self.glyphs
+    }
+
     /// Returns the symbol for the given glyph ID, if it exists
-    pub fn get_symbol(&self, glyph_id: u16) -> Option<Cow<str>> {
-        let base_glyph_id = glyph_id & (Glyph::GLYPH_ID_MASK | Glyph::EMOJI_FLAG);
-
-        if (0x20..0x80).contains(&base_glyph_id) {
-            // ASCII characters are directly mapped to their code point
-            let ch = base_glyph_id as u8 as char;
-            Some(Cow::from(ch.to_compact_string()))
-        } else {
-            self.symbol_lookup.get(&base_glyph_id).map(|s| Cow::from(s.as_str()))
-        }
+    pub fn get_symbol(&self, glyph_id: u16) -> Option<Cow<'_, str>> {
+        self.glyphs.get_symbol(glyph_id)
     }
 
     /// Returns the base glyph identifier for the given key
     pub fn get_base_glyph_id(&self, key: &str) -> Option<u16> {
-        if key.len() == 1 {
-            let ch = key.chars().next().unwrap();
-            if ch.is_ascii() {
-                // 0x00..0x7f double as layer
-                let id = ch as u16;
-                return Some(id);
-            }
-        }
-
-        self.glyph_coords.get(key).copied()
+        self.glyphs.get_base_glyph_id(key)
     }
 }
@@ -49,3 +49,28 @@ pub(super) fn buffer_upload_array<T>(gl: &GL, target: u32, data: &[T], usage: u3
         gl.buffer_data_with_array_buffer_view(target, &view, usage);
     }
 }
+
+/// Uploads an array of elements into a region of the currently bound WebGL buffer.
+///
+/// Unlike [`buffer_upload_array`], the buffer is not reallocated; the data
+/// overwrites the existing contents starting at `offset` bytes.
+///
+/// # Parameters
+/// * `gl` - WebGL context
+/// * `target` - Buffer target (e.g., GL::ARRAY_BUFFER)
+/// * `offset` - Destination offset in bytes
+/// * `data` - Reference to array to upload
+///
+/// # Safety
+/// Requires that T:
+/// - Has a stable memory layout (use #[repr(C)] or #[repr(transparent)])
+/// - Contains only copy types
+/// - Has no padding issues that would cause UB
+pub(super) fn buffer_upload_sub_array<T>(gl: &GL, target: u32, offset: usize, data: &[T]) {
+    unsafe {
+        let data_ptr = data.as_ptr() as *const u8;
+        let size = std::mem::size_of_val(data);
+        let view = js_sys::Uint8Array::view(slice::from_raw_parts(data_ptr, size));
+        gl.buffer_sub_data_with_i32_and_array_buffer_view(target, offset as i32, &view);
+    }
+}
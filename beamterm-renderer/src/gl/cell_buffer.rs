@@ -0,0 +1,907 @@
+use std::{borrow::Cow, cmp::min, collections::HashMap, ops::Range};
+
+use beamterm_data::{FontAtlasData, FontStyle, Glyph};
+use compact_str::{CompactString, ToCompactString};
+
+use crate::gl::{
+    hyperlink::HyperlinkRegistry,
+    region,
+    scrollback::{copy_row, row_source, RowSource, Scrollback},
+    CellData, CellDynamic, CellIterator, Color, DirtyTracker, HyperlinkId,
+};
+
+/// Maps symbols to base glyph identifiers of a font atlas, and back.
+///
+/// ASCII symbols map directly to their code point; all other symbols are
+/// looked up from the normal-styled glyphs of the atlas, as the identifiers
+/// of styled variants are derived from the base glyph id plus style bits.
+#[derive(Debug, Clone, Default)]
+pub struct GlyphLookup {
+    /// Symbol to base glyph identifier mapping
+    glyph_ids: HashMap<CompactString, u16>,
+    /// Base glyph identifier to symbol mapping
+    symbols: HashMap<u16, CompactString>,
+}
+
+impl GlyphLookup {
+    /// Creates the glyph lookup for the glyphs of a font atlas.
+    pub fn new(atlas: &FontAtlasData) -> Self {
+        let mut lookup = Self::default();
+        atlas.glyphs.iter()
+            .filter(|g| g.style == FontStyle::Normal) // only normal style glyphs
+            .filter(|g| !g.is_ascii())                // only non-ascii glyphs
+            .for_each(|g| {
+                lookup.symbols.insert(g.id, g.symbol.clone());
+                lookup.glyph_ids.insert(g.symbol.clone(), g.id);
+            });
+
+        lookup
+    }
+
+    /// Returns the number of non-ASCII symbols in the lookup.
+    pub fn len(&self) -> usize {
+        self.glyph_ids.len()
+    }
+
+    /// Returns `true` if the lookup holds no non-ASCII symbols.
+    pub fn is_empty(&self) -> bool {
+        self.glyph_ids.is_empty()
+    }
+
+    /// Returns the symbol for the given glyph ID, if it exists
+    pub fn get_symbol(&self, glyph_id: u16) -> Option<Cow<'_, str>> {
+        let base_glyph_id = glyph_id & (Glyph::GLYPH_ID_MASK | Glyph::EMOJI_FLAG);
+
+        if (0x20..0x80).contains(&base_glyph_id) {
+            // ASCII characters are directly mapped to their code point
+            let ch = base_glyph_id as u8 as char;
+            Some(Cow::from(ch.to_compact_string()))
+        } else {
+            self.symbols.get(&base_glyph_id).map(|s| Cow::from(s.as_str()))
+        }
+    }
+
+    /// Returns the base glyph identifier for the given key
+    pub fn get_base_glyph_id(&self, key: &str) -> Option<u16> {
+        if key.len() == 1 {
+            let ch = key.chars().next().unwrap();
+            if ch.is_ascii() {
+                // 0x00..0x7f double as layer
+                let id = ch as u16;
+                return Some(id);
+            }
+        }
+
+        self.glyph_ids.get(key).copied()
+    }
+}
+
+/// Host-side model of the terminal cells, independent of WebGL.
+///
+/// `CellBuffer` owns the screen cells, the scrollback and the viewport into
+/// it, and resolves symbols to glyphs and back. [`TerminalGrid`] mirrors the
+/// displayed cells to the GPU, using the buffer's [`DirtyTracker`] to upload
+/// only what changed; on its own, the buffer can be used and tested natively.
+///
+/// Screen coordinates address the live screen, while viewport coordinates
+/// address what is displayed; they differ only while the viewport is scrolled
+/// back into the scrollback.
+///
+/// # Example
+/// ```
+/// use beamterm_data::FontAtlasData;
+/// use beamterm_renderer::{select, CellBuffer, CellData, GlyphLookup, SelectionMode};
+///
+/// let glyphs = GlyphLookup::new(&FontAtlasData::default());
+/// let mut buffer = CellBuffer::new(glyphs, 10, 2);
+///
+/// for (i, ch) in ["h", "i"].into_iter().enumerate() {
+///     buffer.update_cell(i as u16, 1, CellData::new_with_style_bits(ch, 0, 0xffffff, 0));
+/// }
+///
+/// let query = select(SelectionMode::Linear).start((0, 1)).end((9, 1));
+/// assert_eq!(buffer.get_text(query.trim_trailing_whitespace(true)), "hi");
+/// ```
+///
+/// [`TerminalGrid`]: crate::TerminalGrid
+#[derive(Debug, Clone)]
+pub struct CellBuffer {
+    /// Symbol and glyph id resolution.
+    glyphs: GlyphLookup,
+    /// Screen cells in row-major order.
+    cells: Vec<CellDynamic>,
+    /// Hyperlink id of every screen cell; `0` for cells without a link.
+    links: Vec<u16>,
+    /// URLs of the hyperlink ids.
+    hyperlinks: HyperlinkRegistry,
+    /// Size in cells as `(cols, rows)`.
+    size: (u16, u16),
+    /// Fallback glyph for missing symbols.
+    fallback_glyph: u16,
+    /// Lines pushed off the top of the screen.
+    scrollback: Scrollback,
+    /// Number of lines the viewport is scrolled back into the scrollback.
+    viewport_offset: usize,
+    /// Viewport cells changed since the last GPU upload.
+    dirty: DirtyTracker,
+    /// Lines the displayed content moved up since the last flush; negative when moved down.
+    pending_scroll: i32,
+    /// Incremented whenever the displayed cells change.
+    revision: u64,
+}
+
+impl CellBuffer {
+    /// Creates a buffer of `cols` by `rows` cells, filled with spaces.
+    pub fn new(glyphs: GlyphLookup, cols: u16, rows: u16) -> Self {
+        Self {
+            glyphs,
+            cells: vec![blank_cell(); cols as usize * rows as usize],
+            links: vec![0; cols as usize * rows as usize],
+            hyperlinks: HyperlinkRegistry::default(),
+            size: (cols, rows),
+            fallback_glyph: ' ' as u16,
+            scrollback: Scrollback::default(),
+            viewport_offset: 0,
+            dirty: DirtyTracker::new(cols, rows),
+            pending_scroll: 0,
+            revision: 0,
+        }
+    }
+
+    /// Returns the glyph lookup used to resolve symbols.
+    pub fn glyphs(&self) -> &GlyphLookup {
+        &self.glyphs
+    }
+
+    /// Returns the size of the buffer in cells as `(cols, rows)`.
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Returns the total number of cells on the screen.
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns the screen cells in row-major order.
+    pub fn cells(&self) -> &[CellDynamic] {
+        &self.cells
+    }
+
+    /// Returns the screen cell at the specified cell coordinates.
+    pub fn cell(&self, x: u16, y: u16) -> Option<&CellDynamic> {
+        self.index(x, y).map(|idx| &self.cells[idx])
+    }
+
+    /// Returns a mutable reference to the screen cell at the specified cell
+    /// coordinates. The cell is marked dirty.
+    pub fn cell_mut(&mut self, x: u16, y: u16) -> Option<&mut CellDynamic> {
+        let idx = self.index(x, y)?;
+        self.mark_dirty(idx);
+        Some(&mut self.cells[idx])
+    }
+
+    /// Sets the fallback glyph for missing characters.
+    pub fn set_fallback_glyph(&mut self, fallback: &str) {
+        self.fallback_glyph = self.glyphs.get_base_glyph_id(fallback).unwrap_or(' ' as u16);
+    }
+
+    /// Returns the base glyph identifier for a given symbol.
+    pub fn base_glyph_id(&self, symbol: &str) -> Option<u16> {
+        self.glyphs.get_base_glyph_id(symbol)
+    }
+
+    /// Resolves cell data to a cell, substituting the fallback glyph for
+    /// symbols missing from the font atlas.
+    pub fn resolve_cell(&self, data: CellData) -> CellDynamic {
+        let glyph_id = self.glyphs.get_base_glyph_id(data.symbol).unwrap_or(self.fallback_glyph);
+        CellDynamic::new_with_colors(glyph_id | data.style_bits, data.fg, data.bg)
+    }
+
+    /// Updates the screen cells from cell data in row-major order. Cells
+    /// beyond the end of the screen are ignored.
+    ///
+    /// Only cells which differ from the current content are marked dirty.
+    pub fn update_cells<'a>(&mut self, cells: impl Iterator<Item = CellData<'a>>) {
+        let cell_count = self.cells.len();
+        for (idx, data) in cells.take(cell_count).enumerate() {
+            self.update_cell_by_index(idx, data);
+        }
+    }
+
+    /// Updates the screen cells at the given `(x, y, data)` positions.
+    pub fn update_cells_by_position<'a>(
+        &mut self,
+        cells: impl Iterator<Item = (u16, u16, CellData<'a>)>,
+    ) {
+        for (x, y, data) in cells {
+            self.update_cell(x, y, data);
+        }
+    }
+
+    /// Updates a single screen cell; cells outside the screen are ignored.
+    pub fn update_cell(&mut self, x: u16, y: u16, data: CellData) {
+        if let Some(idx) = self.index(x, y) {
+            self.update_cell_by_index(idx, data);
+        }
+    }
+
+    pub(crate) fn update_cell_by_index(&mut self, idx: usize, data: CellData) {
+        if idx >= self.cells.len() {
+            return;
+        }
+
+        let cell = self.resolve_cell(data);
+        self.set_cell(idx, cell);
+        self.links[idx] = 0;
+    }
+
+    /// Resizes the screen, preserving the content of the top-left cells and
+    /// filling new cells with spaces. Marks every cell dirty.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        if self.size == (cols, rows) {
+            return;
+        }
+
+        let old_size = (self.size.0 as i32, self.size.1 as i32);
+        let new_size = (cols as i32, rows as i32);
+        self.cells = resize_cell_grid(&self.cells, old_size, new_size, blank_cell());
+        self.links = resize_cell_grid(&self.links, old_size, new_size, 0);
+        self.size = (cols, rows);
+        self.pending_scroll = 0;
+        self.dirty.resize(cols, rows);
+        self.revision += 1;
+    }
+
+    /// Returns the maximum number of lines retained in the scrollback.
+    pub fn scrollback_capacity(&self) -> usize {
+        self.scrollback.capacity()
+    }
+
+    /// Sets the maximum number of lines retained in the scrollback; `0`
+    /// disables the scrollback. Shrinking the capacity drops the oldest lines.
+    pub fn set_scrollback_capacity(&mut self, lines: usize) {
+        self.scrollback.set_capacity(lines);
+        if self.viewport_offset > self.scrollback.len() {
+            self.scroll_viewport_to(self.scrollback.len());
+        }
+    }
+
+    /// Returns the number of lines currently held in the scrollback.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Removes all lines from the scrollback and returns the viewport to the screen.
+    pub fn clear_scrollback(&mut self) {
+        self.scroll_viewport_to(0);
+        self.scrollback.clear();
+    }
+
+    /// Scrolls the screen content up by `count` lines, pushing the top lines
+    /// into the scrollback. The rows exposed at the bottom are cleared to
+    /// spaces in the default colors.
+    ///
+    /// A viewport scrolled back into the scrollback stays on the lines it
+    /// displays, so new output does not move the history being read.
+    pub fn push_lines_to_scrollback(&mut self, count: u16) {
+        let (cols, rows) = (self.size.0 as usize, self.size.1 as usize);
+        let count = (count as usize).min(rows);
+        if count == 0 {
+            return;
+        }
+
+        let lines = self.cells.chunks_exact(cols).zip(self.links.chunks_exact(cols));
+        for (line, links) in lines.take(count) {
+            self.scrollback.push(line, links);
+        }
+
+        let shifted = count * cols;
+        self.cells.copy_within(shifted.., 0);
+        self.links.copy_within(shifted.., 0);
+        let len = self.cells.len();
+        self.cells[len - shifted..].fill(blank_cell());
+        self.links[len - shifted..].fill(0);
+
+        if self.viewport_offset == 0 {
+            self.pending_scroll += count as i32;
+            self.dirty.scroll(count as i32);
+            self.revision += 1;
+        } else if self.viewport_offset + count <= self.scrollback.len() {
+            self.viewport_offset += count;
+        } else {
+            // the top of the viewport was evicted from the scrollback
+            self.viewport_offset = self.scrollback.len();
+            self.dirty.mark_all();
+            self.revision += 1;
+        }
+    }
+
+    /// Returns the number of lines the viewport is scrolled back into the
+    /// scrollback; `0` when the screen is displayed.
+    pub fn viewport_offset(&self) -> usize {
+        self.viewport_offset
+    }
+
+    /// Scrolls the viewport by `lines`; positive values scroll back towards
+    /// older lines. The offset is clamped to the scrollback length.
+    pub fn scroll_viewport_by(&mut self, lines: i32) {
+        let offset = (self.viewport_offset as i64 + lines as i64).max(0);
+        self.scroll_viewport_to(offset as usize);
+    }
+
+    /// Scrolls the viewport to an absolute number of lines back into the
+    /// scrollback; `0` displays the screen. The offset is clamped to the
+    /// scrollback length.
+    pub fn scroll_viewport_to(&mut self, offset: usize) {
+        let offset = offset.min(self.scrollback.len());
+        if offset == self.viewport_offset {
+            return;
+        }
+
+        // scrolling back moves the displayed content down
+        let lines = self.viewport_offset as i32 - offset as i32;
+        self.pending_scroll += lines;
+        self.dirty.scroll(lines);
+        self.viewport_offset = offset;
+        self.revision += 1;
+    }
+
+    /// Returns a counter incremented whenever the displayed cells change, by
+    /// writing cells, scrolling or resizing. Used to detect outdated search
+    /// results.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns the cell displayed at the given viewport coordinates, taking
+    /// the scrollback into account.
+    pub fn displayed_cell(&self, x: u16, y: u16) -> Option<CellDynamic> {
+        let idx = self.index(x, y)?;
+        self.displayed_cell_by_index(idx)
+    }
+
+    fn displayed_cell_by_index(&self, idx: usize) -> Option<CellDynamic> {
+        let cols = self.size.0 as usize;
+        if idx >= self.cells.len() {
+            return None;
+        }
+
+        let (row, col) = (idx / cols, idx % cols);
+        match row_source(row, self.viewport_offset, self.scrollback.len()) {
+            RowSource::Scrollback(line) => {
+                let line = self.scrollback.line(line)?;
+                Some(line.get(col).copied().unwrap_or_else(blank_cell))
+            },
+            RowSource::Screen(row) => self.cells.get(row * cols + col).copied(),
+        }
+    }
+
+    /// Copies the columns `span` of a viewport row into `dst`.
+    pub(crate) fn copy_displayed_span(&self, row: u16, span: Range<u16>, dst: &mut [CellDynamic]) {
+        let cols = self.size.0 as usize;
+        let (start, end) = (span.start as usize, span.end as usize);
+
+        match row_source(row as usize, self.viewport_offset, self.scrollback.len()) {
+            RowSource::Scrollback(line) => {
+                let src = self.scrollback.line(line).unwrap_or_default();
+                copy_row(dst, src.get(start..).unwrap_or_default(), blank_cell());
+            },
+            RowSource::Screen(row) => {
+                dst.copy_from_slice(&self.cells[row * cols + start..row * cols + end]);
+            },
+        }
+    }
+
+    /// Returns the id of the hyperlink to `url`, registering it if new. Links
+    /// with the same URL share an id unless their OSC 8 `id` parameters
+    /// differ.
+    ///
+    /// When all ids are taken, the ids of links no longer present in the
+    /// screen or scrollback are freed; returns `None` if none are.
+    pub fn register_hyperlink(&mut self, url: &str, id: Option<&str>) -> Option<HyperlinkId> {
+        if self.hyperlinks.is_full() {
+            let mut in_use = vec![false; u16::MAX as usize + 1];
+            let ids = self.links.iter().copied().chain(self.scrollback.links());
+            ids.for_each(|id| in_use[id as usize] = true);
+            self.hyperlinks.retain(&in_use);
+        }
+
+        self.hyperlinks.register(url, id.unwrap_or_default())
+    }
+
+    /// Sets or clears the hyperlink of a screen cell. Writing to a cell
+    /// clears its hyperlink.
+    pub fn set_hyperlink(&mut self, x: u16, y: u16, link: Option<HyperlinkId>) {
+        if let Some(idx) = self.index(x, y) {
+            self.links[idx] = link.map_or(0, HyperlinkId::raw);
+        }
+    }
+
+    /// Returns the hyperlink of the cell displayed at the given viewport
+    /// coordinates, taking the scrollback into account.
+    pub fn displayed_hyperlink(&self, x: u16, y: u16) -> Option<HyperlinkId> {
+        self.displayed_link_by_index(self.index(x, y)?)
+    }
+
+    /// Returns the URL of a hyperlink.
+    pub fn hyperlink_url(&self, link: HyperlinkId) -> Option<&str> {
+        self.hyperlinks.url(link)
+    }
+
+    pub(super) fn displayed_link_by_index(&self, idx: usize) -> Option<HyperlinkId> {
+        let cols = self.size.0 as usize;
+        let (row, col) = (idx / cols, idx % cols);
+        let raw = match row_source(row, self.viewport_offset, self.scrollback.len()) {
+            RowSource::Scrollback(line) => self.scrollback.line_links(line)?.get(col).copied()?,
+            RowSource::Screen(row) => self.links.get(row * cols + col).copied()?,
+        };
+
+        HyperlinkId::from_raw(raw)
+    }
+
+    /// Returns the viewport cells changed since the tracker was last cleared.
+    pub fn dirty(&self) -> &DirtyTracker {
+        &self.dirty
+    }
+
+    pub(crate) fn dirty_mut(&mut self) -> &mut DirtyTracker {
+        &mut self.dirty
+    }
+
+    /// Returns and resets the number of lines the displayed content moved up
+    /// since the last call; negative when it moved down.
+    pub(crate) fn take_pending_scroll(&mut self) -> i32 {
+        std::mem::take(&mut self.pending_scroll)
+    }
+
+    /// Scrolls the rows `rows` up by `count` lines, filling the rows exposed
+    /// at the bottom of the region with `fill`.
+    ///
+    /// Unlike [`CellBuffer::push_lines_to_scrollback`], lines scrolled out of
+    /// the region are discarded. Regions are clipped to the screen.
+    pub fn scroll_region_up(&mut self, rows: Range<u16>, count: u16, fill: CellData) {
+        let fill = self.resolve_cell(fill);
+        let cols = self.size.0 as usize;
+        region::scroll_up(&mut self.cells, cols, to_usize(&rows), count as usize, fill);
+        region::scroll_up(&mut self.links, cols, to_usize(&rows), count as usize, 0);
+        self.mark_rows_dirty(rows);
+    }
+
+    /// Scrolls the rows `rows` down by `count` lines, filling the rows exposed
+    /// at the top of the region with `fill`. Regions are clipped to the screen.
+    pub fn scroll_region_down(&mut self, rows: Range<u16>, count: u16, fill: CellData) {
+        let fill = self.resolve_cell(fill);
+        let cols = self.size.0 as usize;
+        region::scroll_down(&mut self.cells, cols, to_usize(&rows), count as usize, fill);
+        region::scroll_down(&mut self.links, cols, to_usize(&rows), count as usize, 0);
+        self.mark_rows_dirty(rows);
+    }
+
+    /// Inserts `count` blank lines at `row`, shifting the lines below it down;
+    /// lines shifted past the bottom of the screen are discarded.
+    pub fn insert_lines(&mut self, row: u16, count: u16, fill: CellData) {
+        self.scroll_region_down(row..self.size.1, count, fill);
+    }
+
+    /// Deletes `count` lines at `row`, shifting the lines below it up and
+    /// filling the bottom of the screen with `fill`.
+    pub fn delete_lines(&mut self, row: u16, count: u16, fill: CellData) {
+        self.scroll_region_up(row..self.size.1, count, fill);
+    }
+
+    /// Inserts `count` cells at `(col, row)`, shifting the rest of the row
+    /// right; cells shifted past the end of the row are discarded.
+    pub fn insert_chars(&mut self, col: u16, row: u16, count: u16, fill: CellData) {
+        let fill = self.resolve_cell(fill);
+        if let Some(cells) = self.row_mut(row) {
+            region::insert_chars(cells, col as usize, count as usize, fill);
+            region::insert_chars(self.row_links_mut(row), col as usize, count as usize, 0);
+            self.mark_span_dirty(row, col..self.size.0);
+        }
+    }
+
+    /// Deletes `count` cells at `(col, row)`, shifting the rest of the row
+    /// left and filling the end of the row with `fill`.
+    pub fn delete_chars(&mut self, col: u16, row: u16, count: u16, fill: CellData) {
+        let fill = self.resolve_cell(fill);
+        if let Some(cells) = self.row_mut(row) {
+            region::delete_chars(cells, col as usize, count as usize, fill);
+            region::delete_chars(self.row_links_mut(row), col as usize, count as usize, 0);
+            self.mark_span_dirty(row, col..self.size.0);
+        }
+    }
+
+    /// Fills the rectangle spanning columns `cols` and rows `rows` with `fill`.
+    /// Regions are clipped to the screen.
+    pub fn fill_rect(&mut self, cols: Range<u16>, rows: Range<u16>, fill: CellData) {
+        let fill = self.resolve_cell(fill);
+        let width = self.size.0 as usize;
+        region::fill_rect(&mut self.cells, width, to_usize(&cols), to_usize(&rows), fill);
+        region::fill_rect(&mut self.links, width, to_usize(&cols), to_usize(&rows), 0);
+        for row in rows.start..rows.end.min(self.size.1) {
+            self.mark_span_dirty(row, cols.clone());
+        }
+    }
+
+    /// Copies the rectangle spanning columns `cols` and rows `rows` so that its
+    /// top-left cell lands on `dest` as `(col, row)`. Overlapping rectangles are
+    /// handled, and the copy is clipped to the screen.
+    pub fn copy_rect(&mut self, cols: Range<u16>, rows: Range<u16>, dest: (u16, u16)) {
+        let width = self.size.0 as usize;
+        let dest = (dest.0 as usize, dest.1 as usize);
+        let (dest_cols, dest_rows) =
+            region::copy_rect(&mut self.cells, width, to_usize(&cols), to_usize(&rows), dest);
+        region::copy_rect(&mut self.links, width, to_usize(&cols), to_usize(&rows), dest);
+
+        let dest_cols = dest_cols.start as u16..dest_cols.end as u16;
+        for row in dest_rows {
+            self.mark_span_dirty(row as u16, dest_cols.clone());
+        }
+    }
+
+    /// Returns the symbols of the cells yielded by the iterator.
+    pub(super) fn get_symbols(&self, selection: CellIterator) -> CompactString {
+        let mut text = CompactString::new("");
+
+        for (idx, require_newline_after) in selection {
+            text.push_str(&self.get_cell_symbol(idx));
+            if require_newline_after {
+                text.push('\n'); // add newline after each row
+            }
+        }
+
+        text
+    }
+
+    /// Returns the symbol of a cell, or the fallback symbol if its glyph is unknown.
+    pub fn symbol(&self, cell: &CellDynamic) -> Cow<'_, str> {
+        let glyph_id = cell.glyph_id();
+        self.glyphs.get_symbol(glyph_id).unwrap_or_else(|| self.fallback_symbol())
+    }
+
+    /// Returns the first and last displayed cell of the word at a cell, as
+    /// `(col, row)` pairs.
+    ///
+    /// Words are runs of cells that are neither whitespace nor contain one of
+    /// the `separators`, and continue across the rows of a logical line; see
+    /// [`CellBuffer::line_bounds`]. A run of whitespace is returned as a
+    /// whole, while a separator is returned on its own.
+    pub fn word_bounds(&self, (x, y): (u16, u16), separators: &str) -> ((u16, u16), (u16, u16)) {
+        let cols = self.size.0 as usize;
+        let Some(origin) = self.index(x, y) else {
+            return ((x, y), (x, y));
+        };
+
+        let class = |idx: usize| {
+            let symbol = self.get_cell_symbol(idx);
+            if symbol.trim().is_empty() {
+                WordClass::Whitespace
+            } else if symbol.chars().any(|c| separators.contains(c)) {
+                WordClass::Separator
+            } else {
+                WordClass::Word
+            }
+        };
+
+        let ((_, first_row), (_, last_row)) = self.line_bounds(y);
+        let line = first_row as usize * cols..(last_row as usize + 1) * cols;
+
+        let word = class(origin);
+        let (mut start, mut end) = (origin, origin);
+        if word != WordClass::Separator {
+            while start > line.start && class(start - 1) == word {
+                start -= 1;
+            }
+            while end + 1 < line.end && class(end + 1) == word {
+                end += 1;
+            }
+        }
+
+        let cell = |idx: usize| ((idx % cols) as u16, (idx / cols) as u16);
+        (cell(start), cell(end))
+    }
+
+    /// Returns the first and last displayed cell of the logical line at a
+    /// row, as `(col, row)` pairs.
+    ///
+    /// Soft wraps are not recorded, so a row whose last cell is not blank is
+    /// assumed to continue on the next row.
+    pub fn line_bounds(&self, row: u16) -> ((u16, u16), (u16, u16)) {
+        let (cols, rows) = self.size;
+        let last_col = cols.saturating_sub(1);
+        let row = row.min(rows.saturating_sub(1));
+
+        let continues = |row: u16| match self.index(last_col, row) {
+            Some(idx) => !self.get_cell_symbol(idx).trim().is_empty(),
+            None => false,
+        };
+
+        let mut first = row;
+        while first > 0 && continues(first - 1) {
+            first -= 1;
+        }
+        let mut last = row;
+        while last + 1 < rows && continues(last) {
+            last += 1;
+        }
+
+        ((0, first), (last_col, last))
+    }
+
+    pub(super) fn get_cell_symbol(&self, idx: usize) -> Cow<'_, str> {
+        match self.displayed_cell_by_index(idx) {
+            Some(cell) => self.symbol(&cell),
+            None => self.fallback_symbol(),
+        }
+    }
+
+    fn fallback_symbol(&self) -> Cow<'_, str> {
+        self.glyphs.get_symbol(self.fallback_glyph).unwrap_or(Cow::Borrowed(" "))
+    }
+
+    /// Returns the row-major index of a screen cell.
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        let (cols, rows) = self.size;
+        (x < cols && y < rows).then(|| y as usize * cols as usize + x as usize)
+    }
+
+    /// Returns the cells of a screen row.
+    fn row_mut(&mut self, row: u16) -> Option<&mut [CellDynamic]> {
+        let cols = self.size.0 as usize;
+        self.cells.chunks_exact_mut(cols).nth(row as usize)
+    }
+
+    /// Returns the hyperlink ids of a screen row, which must exist.
+    fn row_links_mut(&mut self, row: u16) -> &mut [u16] {
+        let cols = self.size.0 as usize;
+        &mut self.links[row as usize * cols..(row as usize + 1) * cols]
+    }
+
+    /// Replaces the screen cell at `idx`, marking it dirty if it changed.
+    fn set_cell(&mut self, idx: usize, cell: CellDynamic) {
+        if self.cells[idx] != cell {
+            self.cells[idx] = cell;
+            self.mark_dirty(idx);
+        }
+    }
+
+    /// Marks the screen cell at `idx` dirty if it is visible in the viewport.
+    fn mark_dirty(&mut self, idx: usize) {
+        let cols = self.size.0 as usize;
+        let col = (idx % cols) as u16;
+        self.mark_span_dirty((idx / cols) as u16, col..col + 1);
+    }
+
+    /// Marks the columns `cols` of a screen row dirty if visible in the viewport.
+    fn mark_span_dirty(&mut self, row: u16, cols: Range<u16>) {
+        let row = row as usize + self.viewport_offset;
+        if row < self.size.1 as usize {
+            self.dirty.mark_span(row as u16, cols);
+            self.revision += 1;
+        }
+    }
+
+    /// Marks the screen rows `rows` dirty where visible in the viewport.
+    fn mark_rows_dirty(&mut self, rows: Range<u16>) {
+        for row in rows.start..rows.end.min(self.size.1) {
+            self.mark_span_dirty(row, 0..self.size.0);
+        }
+    }
+}
+
+fn to_usize(range: &Range<u16>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
+
+/// Kind of cell, as considered by [`CellBuffer::word_bounds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WordClass {
+    Word,
+    Whitespace,
+    Separator,
+}
+
+/// Returns the cell used for rows cleared by scrolling.
+pub(crate) fn blank_cell() -> CellDynamic {
+    CellDynamic::new_with_colors(' ' as u16, Color::DefaultFg, Color::DefaultBg)
+}
+
+fn resize_cell_grid<T: Copy>(
+    cells: &[T],
+    old_size: (i32, i32),
+    new_size: (i32, i32),
+    blank: T,
+) -> Vec<T> {
+    let new_len = new_size.0 * new_size.1;
+    let mut new_cells = vec![blank; new_len as usize];
+
+    for y in 0..min(old_size.1, new_size.1) {
+        for x in 0..min(old_size.0, new_size.0) {
+            let new_idx = (y * new_size.0 + x) as usize;
+            let old_idx = (y * old_size.0 + x) as usize;
+            new_cells[new_idx] = cells[old_idx];
+        }
+    }
+
+    new_cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::{select, SelectionMode};
+
+    fn buffer(rows: &[&str]) -> CellBuffer {
+        let cols = rows[0].len() as u16;
+        let mut buffer = CellBuffer::new(GlyphLookup::default(), cols, rows.len() as u16);
+        let symbols = rows.iter().flat_map(|row| row.split("")).filter(|s| !s.is_empty());
+        buffer.update_cells(symbols.map(|s| CellData::new_with_style_bits(s, 0, 0xffffff, 0)));
+        buffer.dirty_mut().clear();
+        buffer
+    }
+
+    fn text(buffer: &CellBuffer) -> CompactString {
+        let (cols, rows) = buffer.size();
+        buffer.get_text(select(SelectionMode::Block).start((0, 0)).end((cols - 1, rows - 1)))
+    }
+
+    #[test]
+    fn test_blank_cells_use_default_colors() {
+        let mut buffer = CellBuffer::new(GlyphLookup::default(), 2, 2);
+        assert!(buffer.cells.iter().all(|&cell| cell == blank_cell()));
+
+        buffer.resize(3, 3);
+        assert!(buffer.cells.iter().all(|&cell| cell == blank_cell()));
+    }
+
+    #[test]
+    fn test_update_cells_marks_changed_cells() {
+        let mut buffer = buffer(&["abc", "def"]);
+        assert_eq!(text(&buffer), "abc\ndef");
+
+        let cell = |s| CellData::new_with_style_bits(s, 0, 0xffffff, 0);
+        buffer.update_cells([cell("a"), cell("x"), cell("c")].into_iter());
+        buffer.update_cells_by_position([(2, 1, cell("y")), (9, 9, cell("z"))].into_iter());
+
+        assert_eq!(text(&buffer), "axc\ndey");
+        assert_eq!(buffer.dirty().spans().collect::<Vec<_>>(), [(0, 1..2), (1, 2..3)]);
+    }
+
+    #[test]
+    fn test_region_ops_mark_affected_rows() {
+        let mut buffer = buffer(&["abcd", "efgh", "ijkl", "mnop", "qrst"]);
+        let fill = CellData::new_with_style_bits(".", 0, 0xffffff, 0);
+        let spans = |buffer: &mut CellBuffer| {
+            let spans = buffer.dirty().spans().collect::<Vec<_>>();
+            buffer.dirty_mut().clear();
+            spans
+        };
+
+        buffer.scroll_region_up(1..3, 1, fill);
+        assert_eq!(spans(&mut buffer), [(1, 0..4), (2, 0..4)]);
+
+        buffer.insert_chars(1, 3, 2, fill);
+        assert_eq!(spans(&mut buffer), [(3, 1..4)]);
+
+        buffer.fill_rect(1..3, 0..2, fill);
+        assert_eq!(spans(&mut buffer), [(0, 1..3), (1, 1..3)]);
+
+        buffer.copy_rect(0..2, 0..1, (2, 4));
+        assert_eq!(spans(&mut buffer), [(4, 2..4)]);
+
+        buffer.fill_rect(0..9, 4..9, fill);
+        assert_eq!(spans(&mut buffer), [(4, 0..4)]);
+
+        assert_eq!(
+            text(&buffer),
+            "a..d
+i..l
+....
+m..n
+...."
+        );
+    }
+
+    #[test]
+    fn test_region_ops_follow_viewport_offset() {
+        let mut buffer = buffer(&["aa", "bb", "cc"]);
+        buffer.set_scrollback_capacity(10);
+        buffer.push_lines_to_scrollback(1);
+        buffer.scroll_viewport_by(1);
+        buffer.dirty_mut().clear();
+        let fill = CellData::new_with_style_bits(".", 0, 0xffffff, 0);
+
+        // screen rows are displayed one row further down
+        buffer.fill_rect(0..1, 0..1, fill);
+        assert_eq!(buffer.dirty().spans().collect::<Vec<_>>(), [(1, 0..1)]);
+
+        buffer.dirty_mut().clear();
+        buffer.scroll_region_up(1..3, 1, fill);
+        assert_eq!(buffer.dirty().spans().collect::<Vec<_>>(), [(2, 0..2)]);
+
+        // the last screen row is below the viewport
+        buffer.dirty_mut().clear();
+        buffer.insert_chars(0, 2, 1, fill);
+        buffer.copy_rect(0..2, 0..1, (0, 2));
+        assert!(buffer.dirty().is_clean());
+    }
+
+    #[test]
+    fn test_missing_symbols_use_fallback_glyph() {
+        let mut buffer = buffer(&["ab"]);
+        buffer.set_fallback_glyph("?");
+        buffer.update_cell(0, 0, CellData::new_with_style_bits("🦀", 0, 0, 0));
+
+        assert_eq!(buffer.cell(0, 0).unwrap().glyph_id(), '?' as u16);
+        assert_eq!(text(&buffer), "?b");
+    }
+
+    #[test]
+    fn test_resize_preserves_top_left_cells() {
+        let mut buffer = buffer(&["abc", "def", "ghi"]);
+        buffer.resize(2, 4);
+
+        assert_eq!(buffer.cell_count(), 8);
+        assert_eq!(text(&buffer), "ab\nde\ngh\n  ");
+        assert!(buffer.dirty().requires_full_upload());
+    }
+
+    #[test]
+    fn test_get_text_follows_selection_mode() {
+        let buffer = buffer(&["ab  ", "cd  ", "efgh"]);
+
+        let linear = select(SelectionMode::Linear).start((1, 0)).end((1, 2));
+        assert_eq!(buffer.get_text(linear), "b  \ncd  \nef");
+        assert_eq!(buffer.get_text(linear.trim_trailing_whitespace(true)), "b\ncd\nef");
+
+        let block = select(SelectionMode::Block).start((1, 1)).end((2, 2));
+        assert_eq!(buffer.get_text(block), "d \nfg");
+    }
+
+    #[test]
+    fn test_word_and_line_bounds() {
+        let buffer = buffer(&["ls -la /tm", "p/x  foo  ", "bar       "]);
+
+        assert_eq!(buffer.word_bounds((4, 0), " /"), ((3, 0), (5, 0)));
+        assert_eq!(buffer.word_bounds((2, 0), " /"), ((2, 0), (2, 0)));
+        assert_eq!(buffer.word_bounds((7, 0), " /"), ((7, 0), (7, 0)));
+
+        // words continue across rows ending in a non-blank cell
+        assert_eq!(buffer.word_bounds((9, 0), " /"), ((8, 0), (0, 1)));
+        assert_eq!(buffer.word_bounds((9, 0), " "), ((7, 0), (2, 1)));
+        assert_eq!(buffer.word_bounds((4, 1), " "), ((3, 1), (4, 1)));
+        assert_eq!(buffer.word_bounds((9, 1), " "), ((8, 1), (9, 1)));
+
+        assert_eq!(buffer.line_bounds(1), ((0, 0), (9, 1)));
+        assert_eq!(buffer.line_bounds(2), ((0, 2), (9, 2)));
+    }
+
+    #[test]
+    fn test_viewport_displays_scrollback() {
+        let mut buffer = buffer(&["aa", "bb", "cc"]);
+        buffer.set_scrollback_capacity(10);
+
+        buffer.push_lines_to_scrollback(2);
+        assert_eq!(text(&buffer), "cc\n  \n  ");
+        assert_eq!(buffer.take_pending_scroll(), 2);
+
+        buffer.scroll_viewport_by(5);
+        assert_eq!(buffer.viewport_offset(), 2);
+        assert_eq!(text(&buffer), "aa\nbb\ncc");
+        assert_eq!(buffer.displayed_cell(0, 2).unwrap().glyph_id(), 'c' as u16);
+
+        // new output keeps the viewport on the history being read
+        buffer.push_lines_to_scrollback(1);
+        assert_eq!(buffer.viewport_offset(), 3);
+        assert_eq!(text(&buffer), "aa\nbb\ncc");
+
+        // edits below the displayed rows are not dirty
+        buffer.dirty_mut().clear();
+        buffer.update_cell(0, 0, CellData::new_with_style_bits("x", 0, 0, 0));
+        assert!(buffer.dirty().is_clean());
+
+        buffer.clear_scrollback();
+        assert_eq!((buffer.viewport_offset(), buffer.scrollback_len()), (0, 0));
+    }
+}
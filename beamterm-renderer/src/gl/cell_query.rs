@@ -1,21 +1,21 @@
 use compact_str::{CompactString, CompactStringExt};
 
-use crate::gl::TerminalGrid;
+use crate::gl::CellBuffer;
 
 /// Configuration for querying and extracting text from terminal cells.
 ///
 /// Defines the selection mode, coordinate range, and text processing options
 /// for extracting content from the terminal grid.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct CellQuery {
     pub(crate) mode: SelectionMode,
     pub(super) start: Option<(u16, u16)>,
     pub(super) end: Option<(u16, u16)>,
-    pub(super) trim_trailing_whitespace: bool,
+    pub(crate) trim_trailing_whitespace: bool,
 }
 
 /// Defines how cells are selected in the terminal grid.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SelectionMode {
     /// Rectangular selection of cells.
     ///
@@ -107,16 +107,41 @@ impl CellQuery {
 
     /// Returns the normalized selection range if both start and end are defined.
     ///
-    /// The returned range has coordinates ordered so that the first tuple
-    /// contains the minimum coordinates and the second contains the maximum.
+    /// For block selections, the first tuple contains the minimum coordinates
+    /// and the second contains the maximum. Linear selections are ordered by
+    /// reading order instead, keeping the column of each end.
     pub fn range(&self) -> Option<((u16, u16), (u16, u16))> {
-        if let (Some(start), Some(end)) = (self.start, self.end) {
-            Some((
+        let (start, end) = self.start.zip(self.end)?;
+        match self.mode {
+            SelectionMode::Block => Some((
                 (start.0.min(end.0), start.1.min(end.1)),
                 (start.0.max(end.0), start.1.max(end.1)),
-            ))
-        } else {
-            None
+            )),
+            SelectionMode::Linear if (start.1, start.0) <= (end.1, end.0) => Some((start, end)),
+            SelectionMode::Linear => Some((end, start)),
+        }
+    }
+
+    /// Returns the start and end positions as set, without normalizing them.
+    pub(crate) fn endpoints(&self) -> Option<((u16, u16), (u16, u16))> {
+        self.start.zip(self.end)
+    }
+
+    /// Returns `true` if the cell at `(col, row)` is selected, in a grid
+    /// `cols` cells wide.
+    pub(crate) fn contains(&self, col: u16, row: u16, cols: u16) -> bool {
+        let Some((start, end)) = self.range() else {
+            return false;
+        };
+
+        match self.mode {
+            SelectionMode::Block => {
+                (start.0..=end.0).contains(&col) && (start.1..=end.1).contains(&row)
+            },
+            SelectionMode::Linear => {
+                let idx = |(col, row): (u16, u16)| row as usize * cols as usize + col as usize;
+                (idx(start)..=idx(end)).contains(&idx((col, row)))
+            },
         }
     }
 
@@ -182,7 +207,7 @@ impl Iterator for LinearCellIterator {
         let idx = self.current_idx;
 
         // Check if we need a newline before this cell (except for the first cell)
-        let is_row_start = idx % self.cols as usize == 0;
+        let is_row_start = idx.is_multiple_of(self.cols as usize);
         let is_first_cell = idx == self.current_idx;
         let needs_newline_before = is_row_start && !is_first_cell;
 
@@ -193,7 +218,7 @@ impl Iterator for LinearCellIterator {
 
         // Check if NEXT cell will need a newline before it
         let needs_newline_after = if self.current_idx <= self.end_idx {
-            self.current_idx % self.cols as usize == 0
+            self.current_idx.is_multiple_of(self.cols as usize)
         } else {
             false
         };
@@ -260,7 +285,7 @@ impl LinearCellIterator {
     }
 }
 
-impl TerminalGrid {
+impl CellBuffer {
     /// Zero-allocation iterator over cell indices for a given selection range and mode.
     ///
     /// Creates an efficient iterator that yields cell indices and newline indicators
@@ -274,7 +299,7 @@ impl TerminalGrid {
         end: (u16, u16),
         mode: SelectionMode,
     ) -> CellIterator {
-        let cols = self.terminal_size().0;
+        let cols = self.size().0;
         let max_cells = self.cell_count();
 
         match mode {
@@ -287,7 +312,7 @@ impl TerminalGrid {
         }
     }
 
-    /// Extracts text content from the terminal based on the selection query.
+    /// Extracts text content from the viewport based on the selection query.
     ///
     /// Retrieves the text within the selection range, optionally trimming
     /// trailing whitespace from each line based on the query configuration.
@@ -0,0 +1,188 @@
+use crate::gl::{Color, Palette};
+
+/// Shape of the terminal cursor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    /// Filled cell; the glyph under the cursor is drawn in the cursor text color.
+    #[default]
+    Block,
+    /// Outline of the cell.
+    HollowBlock,
+    /// Vertical bar at the left edge of the cell.
+    Bar,
+    /// Horizontal line at the bottom of the cell.
+    Underline,
+}
+
+/// Terminal cursor state.
+///
+/// The cursor is drawn by the cell shader on top of the cell at its position,
+/// without modifying any cell data. Blinking is driven by a time uniform, so a
+/// blinking cursor only requires re-rendering, not re-uploading cells.
+///
+/// The cursor is hidden until it is positioned with [`crate::Terminal::set_cursor`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    /// Cell position as `(col, row)`.
+    position: (u16, u16),
+    /// Cursor shape while the terminal has focus.
+    shape: CursorShape,
+    /// Whether the cursor is drawn at all.
+    visible: bool,
+    /// Whether the cursor blinks while the terminal has focus.
+    blinking: bool,
+    /// Cursor color; falls back to the palette cursor color, then the cell foreground.
+    color: Option<Color>,
+    /// Whether the canvas has input focus.
+    focused: bool,
+    /// Whether to draw a hollow block while the canvas is unfocused.
+    hollow_when_unfocused: bool,
+}
+
+impl Cursor {
+    /// Duration of each on and off phase of a blinking cursor, in milliseconds.
+    pub const BLINK_INTERVAL_MS: u32 = 530;
+
+    /// Stroke width of the bar, underline and hollow block shapes, in pixels.
+    pub(crate) const THICKNESS_PX: f32 = 2.0;
+
+    /// Creates a hidden block cursor at the top-left cell.
+    pub fn new() -> Self {
+        Self {
+            position: (0, 0),
+            shape: CursorShape::Block,
+            visible: false,
+            blinking: false,
+            color: None,
+            focused: true,
+            hollow_when_unfocused: true,
+        }
+    }
+
+    /// Returns the cursor position as `(col, row)`.
+    pub fn position(&self) -> (u16, u16) {
+        self.position
+    }
+
+    /// Returns the configured cursor shape.
+    pub fn shape(&self) -> CursorShape {
+        self.shape
+    }
+
+    /// Returns whether the cursor is visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns whether the cursor blinks while focused.
+    pub fn is_blinking(&self) -> bool {
+        self.blinking
+    }
+
+    /// Returns the configured cursor color, if any.
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Returns whether the canvas has input focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Returns the shape as drawn: a hollow block while unfocused, unless disabled
+    /// with [`Cursor::set_hollow_when_unfocused`].
+    pub fn effective_shape(&self) -> CursorShape {
+        if !self.focused && self.hollow_when_unfocused {
+            CursorShape::HollowBlock
+        } else {
+            self.shape
+        }
+    }
+
+    /// Returns the blink interval in milliseconds, or `None` for a steady cursor.
+    /// Unfocused cursors never blink.
+    pub fn effective_blink_interval(&self) -> Option<u32> {
+        (self.blinking && self.focused).then_some(Self::BLINK_INTERVAL_MS)
+    }
+
+    /// Moves the cursor, sets its shape and makes it visible.
+    pub fn set(&mut self, col: u16, row: u16, shape: CursorShape) {
+        self.position = (col, row);
+        self.shape = shape;
+        self.visible = true;
+    }
+
+    /// Shows or hides the cursor.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Enables or disables blinking.
+    pub fn set_blinking(&mut self, blinking: bool) {
+        self.blinking = blinking;
+    }
+
+    /// Sets the cursor color. With `None`, the palette's cursor color is used if
+    /// set, otherwise the foreground color of the cell under the cursor.
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.color = color;
+    }
+
+    /// Sets whether the cursor is drawn as a hollow block while unfocused.
+    pub fn set_hollow_when_unfocused(&mut self, enabled: bool) {
+        self.hollow_when_unfocused = enabled;
+    }
+
+    /// Updates the input focus state.
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Resolves the cursor color against the palette; `None` means the cell
+    /// foreground color is used.
+    pub(crate) fn resolved_color(&self, palette: &Palette) -> Option<u32> {
+        self.color.map(|c| palette.resolve(c)).or(palette.cursor())
+    }
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfocused_cursor_is_hollow_and_steady() {
+        let mut cursor = Cursor::new();
+        cursor.set(3, 4, CursorShape::Bar);
+        cursor.set_blinking(true);
+
+        assert_eq!(cursor.effective_shape(), CursorShape::Bar);
+        assert_eq!(cursor.effective_blink_interval(), Some(Cursor::BLINK_INTERVAL_MS));
+
+        cursor.set_focused(false);
+        assert_eq!(cursor.effective_shape(), CursorShape::HollowBlock);
+        assert_eq!(cursor.effective_blink_interval(), None);
+
+        cursor.set_hollow_when_unfocused(false);
+        assert_eq!(cursor.effective_shape(), CursorShape::Bar);
+    }
+
+    #[test]
+    fn test_resolved_color_fallbacks() {
+        let mut palette = Palette::default();
+        let mut cursor = Cursor::new();
+        assert_eq!(cursor.resolved_color(&palette), None);
+
+        palette.set_cursor(Some(0x00ff00));
+        assert_eq!(cursor.resolved_color(&palette), Some(0x00ff00));
+
+        palette.set_color(4, 0x0000aa);
+        cursor.set_color(Some(Color::Indexed(4)));
+        assert_eq!(cursor.resolved_color(&palette), Some(0x0000aa));
+    }
+}
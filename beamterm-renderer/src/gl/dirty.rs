@@ -0,0 +1,231 @@
+use std::ops::Range;
+
+/// Tracks the cells changed since the last GPU upload.
+///
+/// Changes are recorded as a single column span per row, which covers every
+/// changed cell of the row. A few scattered changes therefore upload a few
+/// short spans, while most of the grid changing falls back to a full upload;
+/// see [`DirtyTracker::requires_full_upload`].
+///
+/// The tracker is host-side only and independent of WebGL, so it can be
+/// benchmarked natively.
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::DirtyTracker;
+///
+/// let mut dirty = DirtyTracker::new(80, 24);
+/// dirty.clear(); // new trackers start out fully dirty
+///
+/// dirty.mark(10, 2);
+/// dirty.mark(12, 2);
+/// dirty.mark(0, 3);
+///
+/// let spans: Vec<_> = dirty.spans().collect();
+/// assert_eq!(spans, [(2, 10..13), (3, 0..1)]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DirtyTracker {
+    /// Grid size in cells.
+    size: (u16, u16),
+    /// Dirty columns of each row as `(start, end)`; clean when `start >= end`.
+    spans: Vec<(u16, u16)>,
+    /// Sum of the widths of all spans.
+    dirty_cells: usize,
+}
+
+impl DirtyTracker {
+    /// Fraction of dirty cells above which a full upload is preferred over
+    /// uploading individual spans.
+    pub const FULL_UPLOAD_RATIO: f32 = 0.5;
+
+    const CLEAN: (u16, u16) = (u16::MAX, 0);
+
+    /// Creates a tracker for a grid of `cols` by `rows` cells, with every cell
+    /// marked dirty.
+    pub fn new(cols: u16, rows: u16) -> Self {
+        let mut tracker = Self {
+            size: (cols, rows),
+            spans: vec![Self::CLEAN; rows as usize],
+            dirty_cells: 0,
+        };
+        tracker.mark_all();
+        tracker
+    }
+
+    /// Returns the grid size in cells as `(cols, rows)`.
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Changes the grid size, marking every cell dirty.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        *self = Self::new(cols, rows);
+    }
+
+    /// Returns `true` if no cells are dirty.
+    pub fn is_clean(&self) -> bool {
+        self.dirty_cells == 0
+    }
+
+    /// Returns the number of cells covered by dirty spans.
+    pub fn dirty_cell_count(&self) -> usize {
+        self.dirty_cells
+    }
+
+    /// Returns `true` if enough of the grid is dirty that uploading the whole
+    /// grid is cheaper than uploading the dirty spans individually.
+    pub fn requires_full_upload(&self) -> bool {
+        let cells = self.size.0 as usize * self.size.1 as usize;
+        self.dirty_cells as f32 > cells as f32 * Self::FULL_UPLOAD_RATIO
+    }
+
+    /// Marks a single cell dirty. Cells outside the grid are ignored.
+    pub fn mark(&mut self, col: u16, row: u16) {
+        self.mark_span(row, col..col.saturating_add(1));
+    }
+
+    /// Marks the columns `cols` of a row dirty. Columns outside the grid are ignored.
+    pub fn mark_span(&mut self, row: u16, cols: Range<u16>) {
+        let end = cols.end.min(self.size.0);
+        let Some(span) = self.spans.get_mut(row as usize) else {
+            return;
+        };
+        if cols.start >= end {
+            return;
+        }
+
+        let width = |(start, end): (u16, u16)| end.saturating_sub(start) as usize;
+        let old = *span;
+        *span = (old.0.min(cols.start), old.1.max(end));
+        self.dirty_cells += width(*span) - width(old);
+    }
+
+    /// Marks every cell of the given rows dirty.
+    pub fn mark_rows(&mut self, rows: Range<u16>) {
+        for row in rows.start..rows.end.min(self.size.1) {
+            self.mark_span(row, 0..self.size.0);
+        }
+    }
+
+    /// Marks every cell dirty.
+    pub fn mark_all(&mut self) {
+        self.spans.fill((0, self.size.0));
+        self.dirty_cells = self.size.0 as usize * self.size.1 as usize;
+    }
+
+    /// Moves the tracked spans along with content scrolling up by `lines`, or
+    /// down when negative. Rows scrolled into view are marked dirty.
+    pub fn scroll(&mut self, lines: i32) {
+        let rows = self.size.1 as usize;
+        let n = (lines.unsigned_abs() as usize).min(rows);
+        if n == 0 {
+            return;
+        }
+
+        if lines > 0 {
+            self.spans.rotate_left(n);
+            self.spans[rows - n..].fill(Self::CLEAN);
+        } else {
+            self.spans.rotate_right(n);
+            self.spans[..n].fill(Self::CLEAN);
+        }
+
+        self.dirty_cells = self.spans.iter().map(|&(s, e)| e.saturating_sub(s) as usize).sum();
+        let exposed = if lines > 0 { rows - n..rows } else { 0..n };
+        self.mark_rows(exposed.start as u16..exposed.end as u16);
+    }
+
+    /// Returns the dirty span of each dirty row as `(row, cols)`, in row order.
+    pub fn spans(&self) -> impl Iterator<Item = (u16, Range<u16>)> + '_ {
+        self.spans
+            .iter()
+            .enumerate()
+            .filter(|(_, (start, end))| start < end)
+            .map(|(row, &(start, end))| (row as u16, start..end))
+    }
+
+    /// Returns the dirty spans as ranges of row-major cell indices, merging
+    /// spans which are contiguous in memory, such as consecutive full rows.
+    pub fn ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let cols = self.size.0 as usize;
+        let mut spans = self
+            .spans()
+            .map(move |(row, span)| {
+                let first = row as usize * cols;
+                first + span.start as usize..first + span.end as usize
+            })
+            .peekable();
+
+        std::iter::from_fn(move || {
+            let mut range = spans.next()?;
+            while let Some(next) = spans.next_if(|next| next.start == range.end) {
+                range.end = next.end;
+            }
+            Some(range)
+        })
+    }
+
+    /// Marks every cell clean.
+    pub fn clear(&mut self) {
+        self.spans.fill(Self::CLEAN);
+        self.dirty_cells = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_cover_marked_cells() {
+        let mut dirty = DirtyTracker::new(10, 4);
+        assert!(dirty.requires_full_upload());
+        dirty.clear();
+        assert!(dirty.is_clean());
+
+        dirty.mark(7, 1);
+        dirty.mark(2, 1);
+        dirty.mark_span(3, 8..20);
+        dirty.mark(0, 9); // outside the grid
+
+        assert_eq!(dirty.spans().collect::<Vec<_>>(), [(1, 2..8), (3, 8..10)]);
+        assert_eq!(dirty.dirty_cell_count(), 8);
+        assert!(!dirty.requires_full_upload());
+
+        dirty.mark_rows(0..2);
+        assert_eq!(dirty.dirty_cell_count(), 22);
+        assert!(dirty.requires_full_upload());
+    }
+
+    #[test]
+    fn test_ranges_merge_contiguous_spans() {
+        let mut dirty = DirtyTracker::new(10, 4);
+        dirty.clear();
+
+        dirty.mark_span(0, 5..10);
+        dirty.mark_rows(1..2);
+        dirty.mark_span(2, 0..3);
+        dirty.mark(4, 3);
+
+        assert_eq!(dirty.ranges().collect::<Vec<_>>(), [5..23, 34..35]);
+    }
+
+    #[test]
+    fn test_scroll_moves_spans_and_marks_exposed_rows() {
+        let mut dirty = DirtyTracker::new(10, 4);
+        dirty.clear();
+        dirty.mark(3, 1);
+        dirty.mark(4, 0);
+
+        dirty.scroll(1);
+        assert_eq!(dirty.spans().collect::<Vec<_>>(), [(0, 3..4), (3, 0..10)]);
+
+        dirty.scroll(-2);
+        assert_eq!(dirty.spans().collect::<Vec<_>>(), [(0, 0..10), (1, 0..10), (2, 3..4)]);
+        assert_eq!(dirty.dirty_cell_count(), 21);
+
+        dirty.scroll(-10);
+        assert_eq!(dirty.dirty_cell_count(), 40);
+    }
+}
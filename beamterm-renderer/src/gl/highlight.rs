@@ -0,0 +1,231 @@
+use compact_str::CompactString;
+
+use crate::gl::{CellQuery, Color, Palette, SelectionMode};
+
+/// Regions of the grid drawn highlighted in a single color.
+///
+/// Highlights mark cells without modifying their data, e.g. for search
+/// matches or diagnostics. The color is blended over the cell background,
+/// below the selection. Regions are interpreted like selections, in viewport
+/// coordinates.
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::{select, Color, Highlight, SelectionMode};
+///
+/// let matches = Highlight::new(Color::Rgb(0xe0af68))
+///     .region(select(SelectionMode::Linear).start((4, 0)).end((9, 0)))
+///     .region(select(SelectionMode::Linear).start((0, 3)).end((5, 3)))
+///     .opacity(0.5)
+///     .priority(1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Highlighted regions; queries without both ends are ignored.
+    regions: Vec<CellQuery>,
+    /// Highlight color.
+    color: Color,
+    /// Opacity of the highlight over the cell background.
+    opacity: f32,
+    /// Highlights of higher priority are drawn over those of lower priority.
+    priority: i32,
+}
+
+/// Named [`Highlight`]s of a terminal grid.
+///
+/// Where highlights overlap, the one with the highest priority is drawn; of
+/// equal priorities, the one added last.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Highlights {
+    /// Highlights in insertion order.
+    entries: Vec<(CompactString, Highlight)>,
+}
+
+impl Highlight {
+    /// Opacity of a new highlight.
+    pub const DEFAULT_OPACITY: f32 = 0.4;
+
+    /// Creates a highlight without regions, at the default opacity and priority `0`.
+    pub fn new(color: Color) -> Self {
+        Self {
+            regions: Vec::new(),
+            color,
+            opacity: Self::DEFAULT_OPACITY,
+            priority: 0,
+        }
+    }
+
+    /// Adds a highlighted region.
+    pub fn region(mut self, query: CellQuery) -> Self {
+        self.regions.push(query);
+        self
+    }
+
+    /// Adds highlighted regions.
+    pub fn regions(mut self, queries: impl IntoIterator<Item = CellQuery>) -> Self {
+        self.regions.extend(queries);
+        self
+    }
+
+    /// Sets the opacity over the cell background, from `0.0` (invisible) to
+    /// `1.0` (opaque).
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the priority; highlights of higher priority are drawn over
+    /// overlapping highlights of lower priority.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the highlighted regions.
+    pub fn queries(&self) -> &[CellQuery] {
+        &self.regions
+    }
+
+    /// Returns the highlight color.
+    pub fn color(&self) -> Color {
+        self.color
+    }
+}
+
+impl Highlights {
+    /// Adds a highlight under `name`, replacing and returning any highlight
+    /// of the same name.
+    pub fn insert(&mut self, name: &str, highlight: Highlight) -> Option<Highlight> {
+        match self.entries.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => Some(std::mem::replace(existing, highlight)),
+            None => {
+                self.entries.push((name.into(), highlight));
+                None
+            },
+        }
+    }
+
+    /// Removes and returns the highlight named `name`.
+    pub fn remove(&mut self, name: &str) -> Option<Highlight> {
+        let idx = self.entries.iter().position(|(n, _)| n == name)?;
+        Some(self.entries.remove(idx).1)
+    }
+
+    /// Returns the highlight named `name`.
+    pub fn get(&self, name: &str) -> Option<&Highlight> {
+        self.entries.iter().find(|(n, _)| n == name).map(|(_, h)| h)
+    }
+
+    /// Removes all highlights.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the number of highlights.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no highlights.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the names of the highlights, in insertion order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Resolves the highlight of every cell of a grid of `(cols, rows)`
+    /// cells, in row-major order, as RGBA with the opacity in alpha. Cells
+    /// without highlight are transparent.
+    pub(crate) fn rasterize(&self, (cols, rows): (u16, u16), palette: &Palette) -> Vec<[u8; 4]> {
+        let mut cells = vec![[0; 4]; cols as usize * rows as usize];
+
+        // highest priority last, so that it is painted on top
+        let mut highlights: Vec<_> = self.entries.iter().map(|(_, h)| h).collect();
+        highlights.sort_by_key(|h| h.priority);
+
+        let idx = |(col, row): (u16, u16)| row as usize * cols as usize + col as usize;
+        for highlight in highlights {
+            let [_, r, g, b] = palette.resolve(highlight.color).to_be_bytes();
+            let rgba = [r, g, b, (highlight.opacity * 255.0).round() as u8];
+
+            for query in &highlight.regions {
+                let Some((start, end)) = clipped_range(query, (cols, rows)) else {
+                    continue;
+                };
+
+                match query.mode {
+                    SelectionMode::Block => {
+                        for row in start.1..=end.1 {
+                            cells[idx((start.0, row))..=idx((end.0, row))].fill(rgba);
+                        }
+                    },
+                    SelectionMode::Linear => cells[idx(start)..=idx(end)].fill(rgba),
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+/// Returns the range of a query, as in [`CellQuery::range`], clipped to a
+/// grid of `(cols, rows)` cells.
+fn clipped_range(query: &CellQuery, (cols, rows): (u16, u16)) -> Option<((u16, u16), (u16, u16))> {
+    let (start, end) = query.range()?;
+    if cols == 0 || rows == 0 || start.0 >= cols || start.1 >= rows {
+        return None;
+    }
+
+    let end = match query.mode {
+        SelectionMode::Linear if end.1 >= rows => (cols - 1, rows - 1),
+        _ => (end.0.min(cols - 1), end.1.min(rows - 1)),
+    };
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::select;
+
+    const RED: [u8; 4] = [0xff, 0, 0, 0xff];
+    const BLUE: [u8; 4] = [0, 0, 0xff, 0x66];
+
+    #[test]
+    fn test_named_highlights() {
+        let mut highlights = Highlights::default();
+        let red = Highlight::new(Color::Rgb(0xff0000));
+        assert_eq!(highlights.insert("search", red.clone()), None);
+        assert_eq!(highlights.insert("lint", red.clone()), None);
+        assert_eq!(highlights.insert("search", red.clone().priority(2)), Some(red.clone()));
+
+        assert_eq!(highlights.names().collect::<Vec<_>>(), ["search", "lint"]);
+        assert_eq!(highlights.get("search"), Some(&red.clone().priority(2)));
+        assert_eq!(highlights.remove("lint"), Some(red));
+        assert_eq!(highlights.remove("lint"), None);
+        assert_eq!(highlights.len(), 1);
+    }
+
+    #[test]
+    fn test_rasterize_by_priority() {
+        let mut highlights = Highlights::default();
+        let linear = select(SelectionMode::Linear).start((1, 1)).end((1, 0));
+        let block = select(SelectionMode::Block).start((0, 0)).end((0, 9));
+
+        highlights.insert(
+            "current",
+            Highlight::new(Color::Rgb(0xff0000)).region(linear).opacity(1.0).priority(1),
+        );
+        highlights.insert("matches", Highlight::new(Color::Rgb(0x0000ff)).region(block));
+        let cells = highlights.rasterize((3, 2), &Palette::default());
+
+        #[rustfmt::skip]
+        assert_eq!(cells, [
+            BLUE, RED, RED,
+            RED,  RED, [0; 4],
+        ]);
+    }
+}
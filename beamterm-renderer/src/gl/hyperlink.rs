@@ -0,0 +1,272 @@
+use std::{collections::HashMap, num::NonZeroU16};
+#[cfg(feature = "regex")]
+use std::{ops::Range, sync::OnceLock};
+
+use compact_str::CompactString;
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+#[cfg(feature = "regex")]
+use crate::gl::search::LineText;
+use crate::gl::{select, CellBuffer, CellQuery, SelectionMode};
+
+/// Identifies a hyperlink of a [`CellBuffer`], such as one set by OSC 8.
+///
+/// Cells carry the id of their hyperlink, which resolves to its URL through
+/// [`CellBuffer::hyperlink_url`]. Ids are registered with
+/// [`CellBuffer::register_hyperlink`]; once all ids are taken, the ids of
+/// links no longer present in the screen or scrollback are reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HyperlinkId(NonZeroU16);
+
+/// URLs of the hyperlink ids of a [`CellBuffer`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HyperlinkRegistry {
+    /// URL of every id, indexed by the id minus one; `None` once freed.
+    urls: Vec<Option<CompactString>>,
+    /// Ids by OSC 8 `id` parameter and URL.
+    ids: HashMap<(CompactString, CompactString), HyperlinkId>,
+    /// Freed ids, reused before new ids are allocated.
+    free: Vec<HyperlinkId>,
+}
+
+impl HyperlinkId {
+    pub(crate) fn from_raw(raw: u16) -> Option<Self> {
+        NonZeroU16::new(raw).map(Self)
+    }
+
+    pub(crate) fn raw(self) -> u16 {
+        self.0.get()
+    }
+}
+
+impl HyperlinkRegistry {
+    /// Returns the id of the link to `url` with the OSC 8 `id` parameter
+    /// `key`, registering it if new. Returns `None` if all ids are taken.
+    pub(crate) fn register(&mut self, url: &str, key: &str) -> Option<HyperlinkId> {
+        let entry = (CompactString::from(key), CompactString::from(url));
+        if let Some(&id) = self.ids.get(&entry) {
+            return Some(id);
+        }
+
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None if !self.is_full() => {
+                self.urls.push(None);
+                HyperlinkId::from_raw(self.urls.len() as u16)?
+            },
+            None => return None,
+        };
+
+        self.urls[id.raw() as usize - 1] = Some(entry.1.clone());
+        self.ids.insert(entry, id);
+        Some(id)
+    }
+
+    /// Returns `true` if all ids are taken.
+    pub(crate) fn is_full(&self) -> bool {
+        self.free.is_empty() && self.urls.len() == u16::MAX as usize
+    }
+
+    /// Returns the URL of a link.
+    pub(crate) fn url(&self, id: HyperlinkId) -> Option<&str> {
+        self.urls.get(id.raw() as usize - 1)?.as_deref()
+    }
+
+    /// Frees the ids for which `in_use`, indexed by raw id, is `false`.
+    pub(crate) fn retain(&mut self, in_use: &[bool]) {
+        let is_used = |id: HyperlinkId| in_use.get(id.raw() as usize).copied().unwrap_or(false);
+
+        self.ids.retain(|_, id| is_used(*id));
+        for (idx, url) in self.urls.iter_mut().enumerate() {
+            let id = HyperlinkId::from_raw(idx as u16 + 1).expect("ids start at one");
+            if url.is_some() && !is_used(id) {
+                *url = None;
+                self.free.push(id);
+            }
+        }
+    }
+}
+
+impl CellBuffer {
+    /// Returns the hyperlink at a displayed cell and the cells it spans, as a
+    /// [`SelectionMode::Linear`] query in viewport coordinates.
+    ///
+    /// Cells with a hyperlink id take precedence; otherwise, with the `regex`
+    /// feature, URLs are detected in the text of the logical line, see
+    /// [`CellBuffer::line_bounds`]. A hyperlink spans the adjacent cells with
+    /// the same id, in reading order.
+    pub fn link_at(&self, (x, y): (u16, u16)) -> Option<(CellQuery, CompactString)> {
+        let (cols, rows) = self.size();
+        if x >= cols || y >= rows {
+            return None;
+        }
+
+        let cols = cols as usize;
+        let idx = y as usize * cols + x as usize;
+        let cell = |idx: usize| ((idx % cols) as u16, (idx / cols) as u16);
+        let span = |first: usize, last: usize| {
+            select(SelectionMode::Linear).start(cell(first)).end(cell(last))
+        };
+
+        if let Some(link) = self.displayed_link_by_index(idx) {
+            let url = self.hyperlink_url(link)?;
+            let is_link = |idx: usize| self.displayed_link_by_index(idx) == Some(link);
+
+            let (mut first, mut last) = (idx, idx);
+            while first > 0 && is_link(first - 1) {
+                first -= 1;
+            }
+            while last + 1 < self.cell_count() && is_link(last + 1) {
+                last += 1;
+            }
+            return Some((span(first, last), url.into()));
+        }
+
+        let ((first, last), url) = self.detected_link_at(idx)?;
+        Some((span(first, last), url))
+    }
+
+    /// Returns the URL detected around a displayed cell, with the indices of
+    /// its first and last cell.
+    #[cfg(feature = "regex")]
+    fn detected_link_at(&self, idx: usize) -> Option<((usize, usize), CompactString)> {
+        let cols = self.size().0 as usize;
+        let ((_, first_row), (_, last_row)) = self.line_bounds((idx / cols) as u16);
+        let line_start = first_row as usize * cols;
+        let line = LineText::new(self, line_start..(last_row as usize + 1) * cols, false);
+
+        let text = line.as_str();
+        let url = detect_urls(text)
+            .map(|bytes| (line.cells(bytes.clone()), bytes))
+            .find(|&((first, last), _)| (first..=last).contains(&idx));
+
+        url.map(|(cells, bytes)| (cells, text[bytes].into()))
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn detected_link_at(&self, _idx: usize) -> Option<((usize, usize), CompactString)> {
+        None
+    }
+}
+
+/// Returns the byte ranges of the URLs in `text`, excluding trailing
+/// punctuation and unbalanced closing brackets.
+#[cfg(feature = "regex")]
+pub(crate) fn detect_urls(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    let url = URL.get_or_init(|| {
+        Regex::new(r#"\b(?:(?:https?|ftp|file)://|mailto:)[^\s<>"'`]+"#).expect("valid URL pattern")
+    });
+
+    url.find_iter(text).map(|m| m.start()..m.start() + trim_url(m.as_str()).len())
+}
+
+/// Removes trailing punctuation and unbalanced closing brackets from a URL.
+#[cfg(feature = "regex")]
+fn trim_url(mut url: &str) -> &str {
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let trimmed = match trimmed.chars().last() {
+            Some(close @ (')' | ']' | '}')) => {
+                let open = match close {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if trimmed.matches(close).count() > trimmed.matches(open).count() {
+                    &trimmed[..trimmed.len() - 1]
+                } else {
+                    trimmed
+                }
+            },
+            _ => trimmed,
+        };
+
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::{CellData, GlyphLookup};
+
+    fn buffer(rows: &[&str]) -> CellBuffer {
+        let cols = rows[0].len() as u16;
+        let mut buffer = CellBuffer::new(GlyphLookup::default(), cols, rows.len() as u16);
+        let symbols = rows.iter().flat_map(|row| row.split("")).filter(|s| !s.is_empty());
+        buffer.update_cells(symbols.map(|s| CellData::new_with_style_bits(s, 0, 0xffffff, 0)));
+        buffer
+    }
+
+    #[cfg(feature = "regex")]
+    fn urls(text: &str) -> Vec<&str> {
+        detect_urls(text).map(|r| &text[r]).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_detect_urls() {
+        assert_eq!(urls("see https://example.com/a?b=1."), ["https://example.com/a?b=1"]);
+        assert_eq!(
+            urls("(https://en.wikipedia.org/wiki/Rust_(language))"),
+            ["https://en.wikipedia.org/wiki/Rust_(language)"]
+        );
+        assert_eq!(urls("<file:///tmp/x>, mailto:a@b.org!"), ["file:///tmp/x", "mailto:a@b.org"]);
+        assert!(urls("no links, just http and example.com").is_empty());
+    }
+
+    #[test]
+    fn test_link_at() {
+        let mut buffer = buffer(&["go http://a.io", "/x then docs  "]);
+        let range = |link: Option<(CellQuery, CompactString)>| {
+            link.map(|(query, url)| (query.range().unwrap(), url))
+        };
+
+        // detected URLs continue on rows ending in a non-blank cell
+        #[cfg(feature = "regex")]
+        {
+            let expected = Some((((3, 0), (1, 1)), "http://a.io/x".into()));
+            assert_eq!(range(buffer.link_at((5, 0))), expected);
+            assert_eq!(range(buffer.link_at((0, 1))), expected);
+        }
+        #[cfg(not(feature = "regex"))]
+        assert_eq!(range(buffer.link_at((5, 0))), None);
+        assert_eq!(range(buffer.link_at((2, 0))), None);
+
+        // explicit links take precedence
+        let docs = buffer.register_hyperlink("https://docs.rs", None).unwrap();
+        (8..12).for_each(|x| buffer.set_hyperlink(x, 1, Some(docs)));
+        assert_eq!(buffer.register_hyperlink("https://docs.rs", None), Some(docs));
+        assert_eq!(
+            range(buffer.link_at((9, 1))),
+            Some((((8, 1), (11, 1)), "https://docs.rs".into()))
+        );
+
+        // rewriting a cell clears its link
+        buffer.update_cell(8, 1, CellData::new_with_style_bits("D", 0, 0xffffff, 0));
+        assert_eq!(buffer.displayed_hyperlink(8, 1), None);
+        assert_eq!(buffer.displayed_hyperlink(9, 1), Some(docs));
+    }
+
+    #[test]
+    fn test_registry_reuses_freed_ids() {
+        let mut registry = HyperlinkRegistry::default();
+        let a = registry.register("https://a", "").unwrap();
+        let b = registry.register("https://b", "").unwrap();
+        assert_eq!(registry.register("https://a", "1"), HyperlinkId::from_raw(3));
+
+        let mut in_use = vec![false; 4];
+        in_use[b.raw() as usize] = true;
+        registry.retain(&in_use);
+
+        assert_eq!(registry.url(a), None);
+        assert_eq!(registry.url(b), Some("https://b"));
+        assert_eq!(registry.register("https://c", "").map(HyperlinkId::raw), Some(3));
+        assert_eq!(registry.register("https://d", "").map(HyperlinkId::raw), Some(1));
+    }
+}
@@ -2,10 +2,20 @@
 
 mod atlas;
 mod buffer;
+mod cell_buffer;
 mod cell_query;
 mod context;
+mod cursor;
+mod dirty;
+mod highlight;
+mod hyperlink;
+mod palette;
 mod program;
+mod region;
 mod renderer;
+mod scrollback;
+#[cfg(feature = "regex")]
+mod search;
 mod selection;
 mod terminal_grid;
 mod texture;
@@ -13,9 +23,17 @@ mod ubo;
 
 pub use atlas::FontAtlas;
 use buffer::*;
+pub use cell_buffer::{CellBuffer, GlyphLookup};
 pub use cell_query::*;
+pub use cursor::*;
+pub use dirty::DirtyTracker;
+pub use highlight::{Highlight, Highlights};
+pub use hyperlink::HyperlinkId;
+pub use palette::*;
 pub(crate) use program::*;
 pub use renderer::*;
+#[cfg(feature = "regex")]
+pub use search::{SearchQuery, SearchResults};
 pub use selection::*;
 pub use terminal_grid::*;
 
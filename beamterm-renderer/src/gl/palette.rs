@@ -0,0 +1,315 @@
+use crate::Error;
+
+/// Color of a terminal cell's foreground or background.
+///
+/// Cells either carry a direct RGB color, or refer to an entry in the terminal's
+/// [`Palette`]. Palette-indexed colors are resolved on the GPU, so changing the
+/// palette recolors all indexed cells without re-uploading any cell data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// Direct RGB color as `0xRRGGBB`.
+    Rgb(u32),
+    /// Palette color; 0-15 are the ANSI colors, 16-255 the xterm extended colors.
+    Indexed(u8),
+    /// The palette's default foreground color.
+    DefaultFg,
+    /// The palette's default background color.
+    DefaultBg,
+}
+
+/// Indexed color palette used for resolving [`Color::Indexed`], [`Color::DefaultFg`]
+/// and [`Color::DefaultBg`].
+///
+/// The palette holds the 256 xterm colors plus the default foreground and background
+/// colors. It is stored in a uniform buffer on the GPU; see [`crate::Terminal::set_palette`].
+///
+/// Color themes may additionally specify cursor and selection colors; these are
+/// optional, as not every theme format defines them. Palettes can be imported from
+/// common terminal theme formats via the [`crate::theme`] module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    /// ANSI (0-15) and xterm extended (16-255) colors as `0xRRGGBB`.
+    colors: [u32; 256],
+    /// Default foreground color as `0xRRGGBB`.
+    foreground: u32,
+    /// Default background color as `0xRRGGBB`.
+    background: u32,
+    /// Cursor color, if specified by the theme.
+    cursor: Option<u32>,
+    /// Color of the text under the cursor, if specified by the theme.
+    cursor_text: Option<u32>,
+    /// Selection background color, if specified by the theme.
+    selection_background: Option<u32>,
+    /// Selection foreground color, if specified by the theme.
+    selection_foreground: Option<u32>,
+}
+
+impl Color {
+    /// High byte tagging a palette color in the packed `u32` representation.
+    pub const INDEXED_TAG: u32 = 0x01;
+
+    /// Decodes a color from its packed `u32` representation.
+    ///
+    /// Values with [`Color::INDEXED_TAG`] in the high byte refer to a palette
+    /// slot in the lower bits: 0-255 for the indexed colors, 256 for the default
+    /// foreground and 257 for the default background. All other values are
+    /// treated as `0xAARRGGBB`, ignoring the alpha byte.
+    ///
+    /// # Errors
+    /// Returns `Error::Data` if a palette color refers to a slot beyond
+    /// [`Palette::DEFAULT_BG_SLOT`].
+    pub fn from_packed(packed: u32) -> Result<Self, Error> {
+        if packed >> 24 != Self::INDEXED_TAG {
+            return Ok(Color::Rgb(packed & 0xFF_FFFF));
+        }
+
+        let slot = packed & 0xFF_FFFF;
+        u16::try_from(slot)
+            .ok()
+            .and_then(Self::from_palette_slot)
+            .ok_or_else(|| Error::palette_slot_invalid(slot))
+    }
+
+    /// Encodes the color into its packed `u32` representation.
+    pub fn to_packed(self) -> u32 {
+        match self {
+            Color::Rgb(rgb) => rgb & 0xFF_FFFF,
+            indexed => {
+                (Self::INDEXED_TAG << 24) | indexed.palette_slot().unwrap_or_default() as u32
+            },
+        }
+    }
+
+    /// Returns the color of a palette slot, or `None` if the slot is out of range.
+    pub(crate) fn from_palette_slot(slot: u16) -> Option<Self> {
+        match slot {
+            0..=255 => Some(Color::Indexed(slot as u8)),
+            Palette::DEFAULT_FG_SLOT => Some(Color::DefaultFg),
+            Palette::DEFAULT_BG_SLOT => Some(Color::DefaultBg),
+            _ => None,
+        }
+    }
+
+    /// Returns the palette slot of the color, or `None` for direct RGB colors.
+    pub(crate) fn palette_slot(self) -> Option<u16> {
+        match self {
+            Color::Rgb(_) => None,
+            Color::Indexed(index) => Some(index as u16),
+            Color::DefaultFg => Some(Palette::DEFAULT_FG_SLOT),
+            Color::DefaultBg => Some(Palette::DEFAULT_BG_SLOT),
+        }
+    }
+}
+
+impl From<u32> for Color {
+    fn from(rgb: u32) -> Self {
+        Color::Rgb(rgb)
+    }
+}
+
+impl Palette {
+    /// Number of palette slots: 256 indexed colors plus default fg and bg.
+    pub const SLOT_COUNT: usize = 258;
+    /// Palette slot of the default foreground color.
+    pub const DEFAULT_FG_SLOT: u16 = 256;
+    /// Palette slot of the default background color.
+    pub const DEFAULT_BG_SLOT: u16 = 257;
+
+    /// The xterm default ANSI colors.
+    pub const XTERM_ANSI: [u32; 16] = [
+        0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5, 0x7f7f7f,
+        0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+    ];
+
+    /// Creates a palette from the 16 ANSI colors and the default foreground and
+    /// background colors. Colors 16-255 are filled with the standard xterm 6x6x6
+    /// color cube and grayscale ramp.
+    pub fn new(ansi: [u32; 16], foreground: u32, background: u32) -> Self {
+        let mut colors = [0; 256];
+        colors[..16].copy_from_slice(&ansi);
+
+        const CUBE_LEVELS: [u32; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+        for (i, color) in colors[16..232].iter_mut().enumerate() {
+            let r = CUBE_LEVELS[i / 36];
+            let g = CUBE_LEVELS[(i / 6) % 6];
+            let b = CUBE_LEVELS[i % 6];
+            *color = (r << 16) | (g << 8) | b;
+        }
+
+        for (i, color) in colors[232..].iter_mut().enumerate() {
+            let level = 8 + 10 * i as u32;
+            *color = (level << 16) | (level << 8) | level;
+        }
+
+        Self {
+            colors,
+            foreground,
+            background,
+            cursor: None,
+            cursor_text: None,
+            selection_background: None,
+            selection_foreground: None,
+        }
+    }
+
+    /// Returns the color at the given palette index.
+    pub fn color(&self, index: u8) -> u32 {
+        self.colors[index as usize]
+    }
+
+    /// Sets the color at the given palette index.
+    pub fn set_color(&mut self, index: u8, rgb: u32) {
+        self.colors[index as usize] = rgb & 0xFF_FFFF;
+    }
+
+    /// Returns all 256 indexed colors.
+    pub fn colors(&self) -> &[u32; 256] {
+        &self.colors
+    }
+
+    /// Returns the default foreground color.
+    pub fn foreground(&self) -> u32 {
+        self.foreground
+    }
+
+    /// Sets the default foreground color.
+    pub fn set_foreground(&mut self, rgb: u32) {
+        self.foreground = rgb & 0xFF_FFFF;
+    }
+
+    /// Returns the default background color.
+    pub fn background(&self) -> u32 {
+        self.background
+    }
+
+    /// Sets the default background color.
+    pub fn set_background(&mut self, rgb: u32) {
+        self.background = rgb & 0xFF_FFFF;
+    }
+
+    /// Returns the cursor color, if specified.
+    pub fn cursor(&self) -> Option<u32> {
+        self.cursor
+    }
+
+    /// Sets the cursor color.
+    pub fn set_cursor(&mut self, rgb: Option<u32>) {
+        self.cursor = rgb.map(|c| c & 0xFF_FFFF);
+    }
+
+    /// Returns the color of the text under the cursor, if specified.
+    pub fn cursor_text(&self) -> Option<u32> {
+        self.cursor_text
+    }
+
+    /// Sets the color of the text under the cursor.
+    pub fn set_cursor_text(&mut self, rgb: Option<u32>) {
+        self.cursor_text = rgb.map(|c| c & 0xFF_FFFF);
+    }
+
+    /// Returns the selection background color, if specified.
+    pub fn selection_background(&self) -> Option<u32> {
+        self.selection_background
+    }
+
+    /// Sets the selection background color.
+    pub fn set_selection_background(&mut self, rgb: Option<u32>) {
+        self.selection_background = rgb.map(|c| c & 0xFF_FFFF);
+    }
+
+    /// Returns the selection foreground color, if specified.
+    pub fn selection_foreground(&self) -> Option<u32> {
+        self.selection_foreground
+    }
+
+    /// Sets the selection foreground color.
+    pub fn set_selection_foreground(&mut self, rgb: Option<u32>) {
+        self.selection_foreground = rgb.map(|c| c & 0xFF_FFFF);
+    }
+
+    /// Resolves a [`Color`] to its RGB value using this palette. The high byte
+    /// of [`Color::Rgb`] values is ignored.
+    pub fn resolve(&self, color: Color) -> u32 {
+        match color {
+            Color::Rgb(rgb) => rgb & 0xFF_FFFF,
+            Color::Indexed(index) => self.color(index),
+            Color::DefaultFg => self.foreground,
+            Color::DefaultBg => self.background,
+        }
+    }
+
+    /// Returns the color of a palette slot, including the default fg/bg slots.
+    pub(crate) fn slot(&self, slot: usize) -> u32 {
+        match slot {
+            0..=255 => self.colors[slot],
+            256 => self.foreground,
+            _ => self.background,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new(Self::XTERM_ANSI, 0xffffff, 0x000000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xterm_extended_colors() {
+        let palette = Palette::default();
+
+        assert_eq!(palette.color(16), 0x000000);
+        assert_eq!(palette.color(196), 0xff0000);
+        assert_eq!(palette.color(231), 0xffffff);
+        assert_eq!(palette.color(232), 0x080808);
+        assert_eq!(palette.color(255), 0xeeeeee);
+    }
+
+    #[test]
+    fn test_color_packed_round_trip() {
+        let colors = [
+            Color::Rgb(0x123456),
+            Color::Indexed(0),
+            Color::Indexed(255),
+            Color::DefaultFg,
+            Color::DefaultBg,
+        ];
+
+        for color in colors {
+            assert_eq!(Color::from_packed(color.to_packed()).unwrap(), color);
+        }
+    }
+
+    #[test]
+    fn test_color_from_packed_argb() {
+        assert_eq!(Color::from_packed(0xff_1a1b26).unwrap(), Color::Rgb(0x1a1b26));
+        assert_eq!(Color::from_packed(0x00_1a1b26).unwrap(), Color::Rgb(0x1a1b26));
+        assert_eq!(Color::from_packed(0x01_000101).unwrap(), Color::DefaultBg);
+        assert!(Color::from_packed(0x01_000102).is_err());
+        assert!(Color::from_packed(0x01_010000).is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut palette = Palette::default();
+        palette.set_color(1, 0xaa0000);
+        palette.set_foreground(0xc0c0c0);
+
+        assert_eq!(palette.resolve(Color::Rgb(0x010203)), 0x010203);
+        assert_eq!(palette.resolve(Color::Indexed(1)), 0xaa0000);
+        assert_eq!(palette.resolve(Color::DefaultFg), 0xc0c0c0);
+        assert_eq!(palette.resolve(Color::DefaultBg), 0x000000);
+    }
+
+    #[test]
+    fn test_resolve_masks_rgb_high_byte() {
+        let palette = Palette::default();
+
+        assert_eq!(palette.resolve(Color::Rgb(0xff_1a1b26)), 0x1a1b26);
+        assert_eq!(palette.resolve(Color::Rgb(0x01_000101)), 0x000101);
+    }
+}
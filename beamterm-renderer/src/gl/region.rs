@@ -0,0 +1,198 @@
+use std::ops::Range;
+
+// Region operations on row-major cell grids `cols` cells wide, applied to the
+// cells as well as to per-cell data kept alongside them. Regions are clipped
+// to the grid, so out-of-range regions are no-ops rather than panics.
+
+/// Scrolls the rows `rows` up by `count` lines, filling the rows exposed at
+/// the bottom of the region with `fill`.
+pub(crate) fn scroll_up<T: Copy>(
+    cells: &mut [T],
+    cols: usize,
+    rows: Range<usize>,
+    count: usize,
+    fill: T,
+) {
+    let rows = clip(rows, cells.len() / cols.max(1));
+    let count = count.min(rows.len());
+
+    let (start, end) = (rows.start * cols, rows.end * cols);
+    cells.copy_within(start + count * cols..end, start);
+    cells[end - count * cols..end].fill(fill);
+}
+
+/// Scrolls the rows `rows` down by `count` lines, filling the rows exposed at
+/// the top of the region with `fill`.
+pub(crate) fn scroll_down<T: Copy>(
+    cells: &mut [T],
+    cols: usize,
+    rows: Range<usize>,
+    count: usize,
+    fill: T,
+) {
+    let rows = clip(rows, cells.len() / cols.max(1));
+    let count = count.min(rows.len());
+
+    let (start, end) = (rows.start * cols, rows.end * cols);
+    cells.copy_within(start..end - count * cols, start + count * cols);
+    cells[start..start + count * cols].fill(fill);
+}
+
+/// Inserts `count` cells at `col`, shifting the rest of the row right; cells
+/// shifted past the end of the row are discarded.
+pub(crate) fn insert_chars<T: Copy>(row: &mut [T], col: usize, count: usize, fill: T) {
+    let col = col.min(row.len());
+    let count = count.min(row.len() - col);
+
+    row.copy_within(col..row.len() - count, col + count);
+    row[col..col + count].fill(fill);
+}
+
+/// Deletes `count` cells at `col`, shifting the rest of the row left and
+/// filling the end of the row with `fill`.
+pub(crate) fn delete_chars<T: Copy>(row: &mut [T], col: usize, count: usize, fill: T) {
+    let col = col.min(row.len());
+    let count = count.min(row.len() - col);
+
+    row.copy_within(col + count.., col);
+    let len = row.len();
+    row[len - count..].fill(fill);
+}
+
+/// Fills the rectangle spanning columns `rect_cols` and rows `rect_rows`.
+pub(crate) fn fill_rect<T: Copy>(
+    cells: &mut [T],
+    cols: usize,
+    rect_cols: Range<usize>,
+    rect_rows: Range<usize>,
+    fill: T,
+) {
+    let rect_cols = clip(rect_cols, cols);
+    for row in clip(rect_rows, cells.len() / cols.max(1)) {
+        let first = row * cols;
+        cells[first + rect_cols.start..first + rect_cols.end].fill(fill);
+    }
+}
+
+/// Copies the rectangle spanning columns `src_cols` and rows `src_rows` so
+/// that its top-left cell lands on `dest` as `(col, row)`. Overlapping
+/// rectangles are copied as if through an intermediate buffer.
+///
+/// Returns the destination rectangle after clipping, as `(cols, rows)`.
+pub(crate) fn copy_rect<T: Copy>(
+    cells: &mut [T],
+    cols: usize,
+    src_cols: Range<usize>,
+    src_rows: Range<usize>,
+    dest: (usize, usize),
+) -> (Range<usize>, Range<usize>) {
+    let rows = cells.len() / cols.max(1);
+    let src_cols = clip(src_cols, cols);
+    let src_rows = clip(src_rows, rows);
+
+    // clip the copied size to the space available at the destination
+    let width = src_cols.len().min(cols.saturating_sub(dest.0));
+    let height = src_rows.len().min(rows.saturating_sub(dest.1));
+
+    let copy_row = |cells: &mut [T], i: usize| {
+        let src = (src_rows.start + i) * cols + src_cols.start;
+        let dst = (dest.1 + i) * cols + dest.0;
+        cells.copy_within(src..src + width, dst);
+    };
+
+    // copy rows in the direction which doesn't overwrite unread source rows
+    if dest.1 > src_rows.start {
+        (0..height).rev().for_each(|i| copy_row(cells, i));
+    } else {
+        (0..height).for_each(|i| copy_row(cells, i));
+    }
+
+    (dest.0..dest.0 + width, dest.1..dest.1 + height)
+}
+
+/// Clips a range to `0..len`.
+fn clip(range: Range<usize>, len: usize) -> Range<usize> {
+    range.start.min(len)..range.end.clamp(range.start.min(len), len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::CellDynamic;
+
+    const FILL: u16 = '.' as u16;
+
+    /// Creates a grid from rows of ASCII text.
+    fn grid(rows: &[&str]) -> Vec<CellDynamic> {
+        rows.iter()
+            .flat_map(|row| row.chars())
+            .map(|c| CellDynamic::new(c as u16, 0, 0))
+            .collect()
+    }
+
+    fn text(cells: &[CellDynamic], cols: usize) -> Vec<String> {
+        cells
+            .chunks(cols)
+            .map(|row| row.iter().map(|c| c.glyph_id() as u8 as char).collect())
+            .collect()
+    }
+
+    fn fill() -> CellDynamic {
+        CellDynamic::new(FILL, 0, 0)
+    }
+
+    #[test]
+    fn test_scroll_region() {
+        let mut cells = grid(&["aaa", "bbb", "ccc", "ddd"]);
+        scroll_up(&mut cells, 3, 1..3, 1, fill());
+        assert_eq!(text(&cells, 3), ["aaa", "ccc", "...", "ddd"]);
+
+        let mut cells = grid(&["aaa", "bbb", "ccc", "ddd"]);
+        scroll_down(&mut cells, 3, 0..4, 2, fill());
+        assert_eq!(text(&cells, 3), ["...", "...", "aaa", "bbb"]);
+
+        // counts larger than the region clear it
+        let mut cells = grid(&["aaa", "bbb", "ccc", "ddd"]);
+        scroll_up(&mut cells, 3, 2..9, 5, fill());
+        assert_eq!(text(&cells, 3), ["aaa", "bbb", "...", "..."]);
+    }
+
+    #[test]
+    fn test_insert_and_delete_chars() {
+        let mut row = grid(&["abcdef"]);
+        insert_chars(&mut row, 1, 2, fill());
+        assert_eq!(text(&row, 6), ["a..bcd"]);
+
+        delete_chars(&mut row, 0, 3, fill());
+        assert_eq!(text(&row, 6), ["bcd..."]);
+
+        delete_chars(&mut row, 4, 9, fill());
+        insert_chars(&mut row, 9, 1, fill());
+        assert_eq!(text(&row, 6), ["bcd..."]);
+    }
+
+    #[test]
+    fn test_fill_rect_clips_to_grid() {
+        let mut cells = grid(&["abcd", "efgh", "ijkl"]);
+        fill_rect(&mut cells, 4, 2..10, 1..10, fill());
+        assert_eq!(text(&cells, 4), ["abcd", "ef..", "ij.."]);
+    }
+
+    #[test]
+    fn test_copy_rect_handles_overlap() {
+        let mut cells = grid(&["abcd", "efgh", "ijkl", "mnop"]);
+        let dest = copy_rect(&mut cells, 4, 0..2, 0..2, (1, 1));
+        assert_eq!(dest, (1..3, 1..3));
+        assert_eq!(text(&cells, 4), ["abcd", "eabh", "iefl", "mnop"]);
+
+        let mut cells = grid(&["abcd", "efgh", "ijkl", "mnop"]);
+        copy_rect(&mut cells, 4, 1..4, 1..4, (0, 0));
+        assert_eq!(text(&cells, 4), ["fghd", "jklh", "nopl", "mnop"]);
+
+        // the destination is clipped to the grid
+        let mut cells = grid(&["abcd", "efgh"]);
+        let dest = copy_rect(&mut cells, 4, 0..4, 0..2, (3, 1));
+        assert_eq!(dest, (3..4, 1..2));
+        assert_eq!(text(&cells, 4), ["abcd", "efga"]);
+    }
+}
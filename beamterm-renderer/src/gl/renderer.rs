@@ -23,6 +23,10 @@ pub struct Renderer {
     canvas: web_sys::HtmlCanvasElement,
     state: GlState,
     canvas_padding_color: (f32, f32, f32),
+    /// Ratio of canvas backing store pixels to CSS pixels for [`Renderer::resize_css`].
+    pixel_ratio: f32,
+    /// CSS size of the canvas, once set by [`Renderer::resize_css`].
+    css_size: Option<(i32, i32)>,
 }
 
 impl Renderer {
@@ -84,6 +88,8 @@ impl Renderer {
             canvas,
             state,
             canvas_padding_color: (0.0, 0.0, 0.0),
+            pixel_ratio: 1.0,
+            css_size: None,
         };
         renderer.resize(width as _, height as _);
         Ok(renderer)
@@ -104,6 +110,52 @@ impl Renderer {
         self.state.viewport(&self.gl, 0, 0, width, height);
     }
 
+    /// Resizes the canvas to a size in CSS pixels, with a backing store of
+    /// that size times the pixel ratio.
+    ///
+    /// Unlike [`Renderer::resize`], this sets the CSS size of the canvas, so
+    /// that a backing store larger than the displayed size is drawn at the
+    /// resolution of HiDPI displays.
+    pub fn resize_css(&mut self, width: i32, height: i32) {
+        let style = self.canvas.style();
+        let _ = style.set_property("width", &format!("{width}px"));
+        let _ = style.set_property("height", &format!("{height}px"));
+        self.css_size = Some((width, height));
+
+        let ratio = self.pixel_ratio;
+        let backing = |size: i32| (size as f32 * ratio).round() as i32;
+        self.resize(backing(width), backing(height));
+    }
+
+    /// Returns the ratio of canvas backing store pixels to CSS pixels.
+    pub fn pixel_ratio(&self) -> f32 {
+        self.pixel_ratio
+    }
+
+    /// Sets the ratio of canvas backing store pixels to CSS pixels, and
+    /// resizes the backing store if the CSS size was set by
+    /// [`Renderer::resize_css`].
+    pub fn set_pixel_ratio(&mut self, ratio: f32) {
+        self.pixel_ratio = ratio;
+        if let Some((width, height)) = self.css_size {
+            self.resize_css(width, height);
+        }
+    }
+
+    /// Returns the CSS size of the canvas, if set by [`Renderer::resize_css`].
+    pub fn css_size(&self) -> Option<(i32, i32)> {
+        self.css_size
+    }
+
+    /// Resets the tracked WebGL state and viewport after the context was lost
+    /// and restored, as the restored context starts out with default state.
+    pub fn restore(&mut self) {
+        self.state = GlState::new(&self.gl);
+
+        let (width, height) = self.canvas_size();
+        self.state.viewport(&self.gl, 0, 0, width, height);
+    }
+
     /// Clears the framebuffer with the specified color.
     ///
     /// Sets the clear color and clears both the color and depth buffers.
@@ -0,0 +1,280 @@
+use std::{collections::VecDeque, ops::Range};
+
+use crate::gl::CellDynamic;
+
+/// Ring buffer of lines pushed off the top of the terminal grid.
+///
+/// Holds at most `capacity` lines; pushing onto a full buffer evicts the oldest
+/// line. Lines keep the width of the grid at the time they were pushed, so the
+/// history survives resizes without being reflowed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scrollback {
+    lines: VecDeque<Box<[CellDynamic]>>,
+    /// Hyperlink ids of the cells of each line; `None` for lines without links.
+    links: VecDeque<Option<Box<[u16]>>>,
+    capacity: usize,
+}
+
+/// Where a row of the viewport reads its cells from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RowSource {
+    /// Line in the scrollback, indexed from the oldest line.
+    Scrollback(usize),
+    /// Row of the visible screen.
+    Screen(usize),
+}
+
+/// Maps rows of the viewport to slots of the GPU instance buffer.
+///
+/// Scrolling the viewport rotates the ring instead of moving instance data, so
+/// only the rows scrolled into view need to be uploaded. The vertex shader
+/// resolves the display row of each slot from the ring offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RowRing {
+    /// Slot holding the top row of the viewport.
+    offset: u16,
+    /// Number of rows in the ring.
+    rows: u16,
+}
+
+impl Scrollback {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            links: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of lines retained.
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of lines currently retained.
+    pub(crate) fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Changes the capacity, dropping the oldest lines if it shrinks.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.lines.len().saturating_sub(capacity);
+        self.lines.drain(..excess);
+        self.links.drain(..excess);
+    }
+
+    /// Appends a line and the hyperlink ids of its cells, evicting the oldest
+    /// line when full. Returns the number of evicted lines.
+    pub(crate) fn push(&mut self, line: &[CellDynamic], links: &[u16]) -> usize {
+        if self.capacity == 0 {
+            return 0;
+        }
+
+        let links = links.iter().any(|&id| id != 0).then(|| links.into());
+        if self.lines.len() < self.capacity {
+            self.lines.push_back(line.into());
+            self.links.push_back(links);
+            return 0;
+        }
+
+        self.links.pop_front();
+        self.links.push_back(links);
+
+        // reuse the allocation of the evicted line when the width is unchanged
+        let mut oldest = self.lines.pop_front().expect("full scrollback is not empty");
+        if oldest.len() == line.len() {
+            oldest.copy_from_slice(line);
+        } else {
+            oldest = line.into();
+        }
+        self.lines.push_back(oldest);
+        1
+    }
+
+    /// Returns the line at `index`, counting from the oldest line.
+    pub(crate) fn line(&self, index: usize) -> Option<&[CellDynamic]> {
+        self.lines.get(index).map(|line| &**line)
+    }
+
+    /// Returns the hyperlink ids of the line at `index`, if it has any links.
+    pub(crate) fn line_links(&self, index: usize) -> Option<&[u16]> {
+        self.links.get(index)?.as_deref()
+    }
+
+    /// Returns the hyperlink ids of all lines with links.
+    pub(crate) fn links(&self) -> impl Iterator<Item = u16> + '_ {
+        self.links.iter().flatten().flat_map(|links| links.iter().copied())
+    }
+
+    /// Removes all lines.
+    pub(crate) fn clear(&mut self) {
+        self.lines.clear();
+        self.links.clear();
+    }
+}
+
+/// Resolves the source of a viewport row when scrolled `viewport_offset` lines
+/// back into a scrollback holding `scrollback_len` lines.
+pub(crate) fn row_source(row: usize, viewport_offset: usize, scrollback_len: usize) -> RowSource {
+    debug_assert!(viewport_offset <= scrollback_len);
+
+    if row < viewport_offset {
+        RowSource::Scrollback(scrollback_len - viewport_offset + row)
+    } else {
+        RowSource::Screen(row - viewport_offset)
+    }
+}
+
+/// Copies `src` into `dst`, truncating or padding with `blank` on width mismatch.
+pub(crate) fn copy_row(dst: &mut [CellDynamic], src: &[CellDynamic], blank: CellDynamic) {
+    let n = dst.len().min(src.len());
+    dst[..n].copy_from_slice(&src[..n]);
+    dst[n..].fill(blank);
+}
+
+impl RowRing {
+    pub(crate) fn new(rows: u16) -> Self {
+        Self { offset: 0, rows }
+    }
+
+    /// Returns the slot holding the top row of the viewport.
+    pub(crate) fn offset(&self) -> u16 {
+        self.offset
+    }
+
+    /// Returns the number of rows in the ring.
+    pub(crate) fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    /// Returns the instance buffer slot of a viewport row.
+    pub(crate) fn slot(&self, row: u16) -> u16 {
+        ((row as u32 + self.offset as u32) % self.rows.max(1) as u32) as u16
+    }
+
+    /// Maps a range of row-major viewport cell indices to ranges of the
+    /// instance buffer, splitting it where the ring wraps around.
+    pub(crate) fn cell_ranges(
+        &self,
+        range: Range<usize>,
+        cols: usize,
+    ) -> impl Iterator<Item = Range<usize>> {
+        let total = self.rows as usize * cols;
+        let start = (range.start + self.offset as usize * cols) % total.max(1);
+        let end = start + range.len();
+
+        let (head, tail) = if end <= total {
+            (start..end, None)
+        } else {
+            (start..total, Some(0..end - total))
+        };
+        std::iter::once(head).chain(tail)
+    }
+
+    /// Rotates the ring for content moving up by `lines` (down when negative),
+    /// returning the viewport rows which must be re-uploaded.
+    ///
+    /// Returns `None` when every row is exposed and a full upload is cheaper.
+    pub(crate) fn rotate(&mut self, lines: i32) -> Option<Range<u16>> {
+        let rows = self.rows as i32;
+        if lines.abs() >= rows {
+            return None;
+        }
+
+        self.offset = (self.offset as i32 + lines).rem_euclid(rows) as u16;
+        let exposed = if lines >= 0 { rows - lines..rows } else { 0..-lines };
+        Some(exposed.start as u16..exposed.end as u16)
+    }
+
+    /// Resets the ring so that slots match viewport rows.
+    pub(crate) fn reset(&mut self, rows: u16) {
+        *self = Self::new(rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(glyph: u16, width: usize) -> Vec<CellDynamic> {
+        vec![CellDynamic::new(glyph, 0xffffff, 0); width]
+    }
+
+    fn glyph(cell: &CellDynamic) -> u16 {
+        cell.glyph_id()
+    }
+
+    #[test]
+    fn test_scrollback_evicts_oldest_lines() {
+        let mut scrollback = Scrollback::new(3);
+        let evicted: usize = (0..5).map(|i| scrollback.push(&line(i, 4), &[0; 4])).sum();
+
+        assert_eq!(evicted, 2);
+        assert_eq!(scrollback.len(), 3);
+        assert_eq!(glyph(&scrollback.line(0).unwrap()[0]), 2);
+        assert_eq!(glyph(&scrollback.line(2).unwrap()[0]), 4);
+
+        scrollback.push(&line(5, 6), &[0, 7, 0, 0, 0, 0]);
+        assert_eq!(scrollback.line(2).unwrap().len(), 6);
+        assert_eq!(scrollback.line_links(1), None);
+        assert_eq!(scrollback.line_links(2), Some(&[0, 7, 0, 0, 0, 0][..]));
+
+        scrollback.set_capacity(1);
+        assert_eq!(scrollback.len(), 1);
+        assert_eq!(glyph(&scrollback.line(0).unwrap()[0]), 5);
+        assert_eq!(scrollback.links().collect::<Vec<_>>(), [0, 7, 0, 0, 0, 0]);
+
+        scrollback.set_capacity(0);
+        assert_eq!(scrollback.push(&line(6, 4), &[0; 4]), 0);
+        assert_eq!(scrollback.len(), 0);
+    }
+
+    #[test]
+    fn test_row_source() {
+        assert_eq!(row_source(0, 0, 10), RowSource::Screen(0));
+        assert_eq!(row_source(0, 2, 10), RowSource::Scrollback(8));
+        assert_eq!(row_source(1, 2, 10), RowSource::Scrollback(9));
+        assert_eq!(row_source(2, 2, 10), RowSource::Screen(0));
+    }
+
+    #[test]
+    fn test_copy_row_pads_and_truncates() {
+        let blank = CellDynamic::new(' ' as u16, 0, 0);
+        let mut dst = line(0, 4);
+
+        copy_row(&mut dst, &line(1, 2), blank);
+        assert_eq!(dst.iter().map(glyph).collect::<Vec<_>>(), [1, 1, ' ' as u16, ' ' as u16]);
+
+        copy_row(&mut dst, &line(2, 6), blank);
+        assert!(dst.iter().all(|c| glyph(c) == 2));
+    }
+
+    #[test]
+    fn test_row_ring_rotation() {
+        let mut ring = RowRing::new(5);
+
+        // content moves up: the bottom rows are new
+        assert_eq!(ring.rotate(2), Some(3..5));
+        assert_eq!(ring.offset(), 2);
+        assert_eq!(ring.slot(0), 2);
+        assert_eq!(ring.slot(4), 1);
+
+        // content moves down: the top rows are new
+        assert_eq!(ring.rotate(-3), Some(0..3));
+        assert_eq!(ring.offset(), 4);
+        assert_eq!(ring.slot(1), 0);
+
+        assert_eq!(ring.rotate(5), None);
+        assert_eq!(ring.offset(), 4);
+
+        // viewport rows 0-1 are in slots 4 and 0
+        let ranges: Vec<_> = ring.cell_ranges(5..15, 10).collect();
+        assert_eq!(ranges, [45..50, 0..5]);
+        let mut ranges = ring.cell_ranges(12..20, 10);
+        assert_eq!((ranges.next(), ranges.next()), (Some(2..10), None));
+
+        ring.reset(3);
+        assert_eq!((ring.offset(), ring.rows()), (0, 3));
+    }
+}
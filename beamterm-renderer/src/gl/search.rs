@@ -0,0 +1,379 @@
+use std::ops::Range;
+
+use compact_str::CompactString;
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    error::Error,
+    gl::{select, CellBuffer, CellQuery, SelectionMode},
+};
+
+/// Query for finding text in the displayed cells of a terminal grid.
+///
+/// Patterns are matched against the symbols of the cells, as resolved by the
+/// font atlas, so matches report cell coordinates regardless of how many
+/// bytes each symbol takes. Rows whose last cell is not blank are taken to
+/// continue on the next row, so matches may span several rows; see
+/// [`CellBuffer::line_bounds`].
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::SearchQuery;
+///
+/// let query = SearchQuery::new("error|warn").regex(true).case_sensitive(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Text or regular expression to find.
+    pattern: CompactString,
+    /// Whether upper and lower case letters are distinguished.
+    case_sensitive: bool,
+    /// Whether the pattern is a regular expression.
+    regex: bool,
+}
+
+/// Matches of a [`SearchQuery`], with the match navigated to.
+///
+/// Matches are [`SelectionMode::Linear`] cell queries in reading order; they
+/// can be selected, highlighted or have their text extracted like any other
+/// query. As matches are in viewport coordinates, they are outdated once the
+/// displayed cells change; see [`SearchResults::refresh`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResults {
+    /// Matches in reading order.
+    matches: Vec<CellQuery>,
+    /// Index of the current match.
+    current: Option<usize>,
+    /// Query the matches were found for.
+    query: Option<SearchQuery>,
+    /// [`CellBuffer::revision`] the matches were found in.
+    revision: u64,
+}
+
+/// Compiled pattern of a [`SearchQuery`].
+#[derive(Debug)]
+enum Matcher {
+    /// Plain text, lowercased unless case-sensitive.
+    Text(CompactString),
+    Regex(Regex),
+}
+
+impl SearchQuery {
+    /// Creates a case-insensitive query for plain text.
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.into(),
+            case_sensitive: false,
+            regex: false,
+        }
+    }
+
+    /// Sets whether upper and lower case letters are distinguished.
+    pub fn case_sensitive(mut self, enabled: bool) -> Self {
+        self.case_sensitive = enabled;
+        self
+    }
+
+    /// Sets whether the pattern is a regular expression, in the syntax of the
+    /// `regex` crate.
+    pub fn regex(mut self, enabled: bool) -> Self {
+        self.regex = enabled;
+        self
+    }
+
+    /// Returns the pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    fn matcher(&self) -> Result<Matcher, Error> {
+        if self.regex {
+            RegexBuilder::new(&self.pattern)
+                .case_insensitive(!self.case_sensitive)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| Error::search_pattern_invalid(&e.to_string()))
+        } else if self.case_sensitive {
+            Ok(Matcher::Text(self.pattern.clone()))
+        } else {
+            Ok(Matcher::Text(self.pattern.to_lowercase()))
+        }
+    }
+
+    /// Returns `true` if cell symbols must be lowercased before matching.
+    fn folds_case(&self) -> bool {
+        !self.regex && !self.case_sensitive
+    }
+}
+
+impl Matcher {
+    /// Returns the byte ranges of the non-empty, non-overlapping matches.
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = (usize, usize)> + 'a> {
+        match self {
+            Matcher::Text(text) if text.is_empty() => Box::new(std::iter::empty()),
+            Matcher::Text(text) => {
+                Box::new(haystack.match_indices(text.as_str()).map(|(i, m)| (i, i + m.len())))
+            },
+            Matcher::Regex(regex) => Box::new(
+                regex
+                    .find_iter(haystack)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| (m.start(), m.end())),
+            ),
+        }
+    }
+}
+
+/// Text of a range of displayed cells, mapping bytes back to cells.
+pub(super) struct LineText {
+    text: String,
+    /// Row-major index of the cell of every byte of the text.
+    byte_cells: Vec<usize>,
+}
+
+impl LineText {
+    /// Collects the symbols of the displayed cells `cells`, lowercased if
+    /// `fold_case` is set.
+    pub(super) fn new(buffer: &CellBuffer, cells: Range<usize>, fold_case: bool) -> Self {
+        let mut text = String::new();
+        let mut byte_cells = Vec::new();
+        for idx in cells {
+            let symbol = buffer.get_cell_symbol(idx);
+            if fold_case {
+                text.extend(symbol.chars().flat_map(char::to_lowercase));
+            } else {
+                text.push_str(&symbol);
+            }
+            byte_cells.resize(text.len(), idx);
+        }
+
+        Self { text, byte_cells }
+    }
+
+    pub(super) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the first and last cell of a non-empty byte range.
+    pub(super) fn cells(&self, bytes: Range<usize>) -> (usize, usize) {
+        (self.byte_cells[bytes.start], self.byte_cells[bytes.end - 1])
+    }
+}
+
+impl SearchResults {
+    /// Returns `true` if the displayed cells changed since the matches were
+    /// found, such as by new output, scrolling the viewport or resizing.
+    pub fn is_outdated(&self, buffer: &CellBuffer) -> bool {
+        self.revision != buffer.revision()
+    }
+
+    /// Finds the matches of the query again if outdated. Navigation restarts
+    /// at the first or last match.
+    pub fn refresh(&mut self, buffer: &CellBuffer) {
+        if !self.is_outdated(buffer) {
+            return;
+        }
+
+        *self = match &self.query {
+            // the query compiled before, so it compiles again
+            Some(query) => buffer.find(query).unwrap_or_default(),
+            None => Self::default(),
+        };
+    }
+
+    /// Returns all matches, in reading order.
+    pub fn matches(&self) -> &[CellQuery] {
+        &self.matches
+    }
+
+    /// Returns the number of matches.
+    pub fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Returns `true` if nothing matched.
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// Returns the current match, if navigated to one.
+    pub fn current(&self) -> Option<CellQuery> {
+        self.current.map(|idx| self.matches[idx])
+    }
+
+    /// Returns the index of the current match, if navigated to one.
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Moves to and returns the next match, wrapping around after the last
+    /// one. Starts at the first match.
+    pub fn next_match(&mut self) -> Option<CellQuery> {
+        let len = self.matches.len();
+        self.current = match self.current {
+            _ if len == 0 => None,
+            Some(idx) => Some((idx + 1) % len),
+            None => Some(0),
+        };
+        self.current()
+    }
+
+    /// Moves to and returns the previous match, wrapping around before the
+    /// first one. Starts at the last match.
+    pub fn previous_match(&mut self) -> Option<CellQuery> {
+        let len = self.matches.len();
+        self.current = match self.current {
+            _ if len == 0 => None,
+            Some(idx) => Some((idx + len - 1) % len),
+            None => Some(len - 1),
+        };
+        self.current()
+    }
+}
+
+impl CellBuffer {
+    /// Finds the matches of a query among the displayed cells.
+    ///
+    /// # Errors
+    /// Returns an error if the query is an invalid regular expression.
+    pub fn find(&self, query: &SearchQuery) -> Result<SearchResults, Error> {
+        let matcher = query.matcher()?;
+        let (cols, rows) = self.size();
+        let cols = cols as usize;
+
+        let mut matches = Vec::new();
+        let mut row = 0;
+        while row < rows {
+            let (_, (_, last_row)) = self.line_bounds(row);
+            let cells = row as usize * cols..(last_row as usize + 1) * cols;
+            let line = LineText::new(self, cells, query.folds_case());
+
+            let cell = |idx: usize| ((idx % cols) as u16, (idx / cols) as u16);
+            matches.extend(matcher.find_iter(line.as_str()).map(|(start, end)| {
+                let (first, last) = line.cells(start..end);
+                select(SelectionMode::Linear).start(cell(first)).end(cell(last))
+            }));
+
+            row = last_row + 1;
+        }
+
+        Ok(SearchResults {
+            matches,
+            current: None,
+            query: Some(query.clone()),
+            revision: self.revision(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use beamterm_data::FontAtlasData;
+
+    use super::*;
+    use crate::gl::{CellData, GlyphLookup};
+
+    fn buffer(glyphs: GlyphLookup, rows: &[&[&str]]) -> CellBuffer {
+        let cols = rows[0].len() as u16;
+        let mut buffer = CellBuffer::new(glyphs, cols, rows.len() as u16);
+        let symbols = rows.iter().flat_map(|row| row.iter());
+        buffer.update_cells(symbols.map(|s| CellData::new_with_style_bits(s, 0, 0xffffff, 0)));
+        buffer
+    }
+
+    fn ranges(results: &SearchResults) -> Vec<((u16, u16), (u16, u16))> {
+        results.matches().iter().filter_map(CellQuery::range).collect()
+    }
+
+    #[test]
+    fn test_find_text() {
+        let rows: &[&[&str]] = &[
+            &["a", "b", "A", "B", " "], // not continued
+            &["x", "a", "b", "x", "a"], // continued on the next row
+            &["b", " ", " ", " ", " "],
+        ];
+        let buffer = buffer(GlyphLookup::default(), rows);
+
+        let results = buffer.find(&SearchQuery::new("ab")).unwrap();
+        assert_eq!(
+            ranges(&results),
+            [((0, 0), (1, 0)), ((2, 0), (3, 0)), ((1, 1), (2, 1)), ((4, 1), (0, 2)),]
+        );
+
+        let results = buffer.find(&SearchQuery::new("AB").case_sensitive(true)).unwrap();
+        assert_eq!(ranges(&results), [((2, 0), (3, 0))]);
+
+        let results = buffer.find(&SearchQuery::new("")).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_find_regex_in_multibyte_symbols() {
+        let atlas = FontAtlasData::default();
+        let glyphs = GlyphLookup::new(&atlas);
+        let wide = atlas
+            .glyphs
+            .iter()
+            .map(|g| g.symbol.as_str())
+            .find(|s| s.len() > 1 && glyphs.get_base_glyph_id(s).is_some())
+            .expect("atlas with a multi-byte symbol");
+
+        let rows: &[&[&str]] = &[&[wide, "x", "1", "2", " ", "x", wide, "7", " "]];
+        let buffer = buffer(glyphs, rows);
+
+        let query = SearchQuery::new(r"X\d+").regex(true);
+        let results = buffer.find(&query).unwrap();
+        assert_eq!(ranges(&results), [((1, 0), (3, 0))]);
+
+        let query = SearchQuery::new(&format!("x{wide}")).case_sensitive(true);
+        assert_eq!(ranges(&buffer.find(&query).unwrap()), [((5, 0), (6, 0))]);
+
+        let invalid = SearchQuery::new("(").regex(true);
+        assert!(buffer.find(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_navigate_matches() {
+        let rows: &[&[&str]] = &[&["a", " ", "a", " ", "a", " "]];
+        let mut results =
+            buffer(GlyphLookup::default(), rows).find(&SearchQuery::new("a")).unwrap();
+        let col = |query: Option<CellQuery>| query.and_then(|q| q.range()).map(|r| r.0 .0);
+
+        assert_eq!(col(results.current()), None);
+        assert_eq!(col(results.next_match()), Some(0));
+        assert_eq!(col(results.next_match()), Some(2));
+        assert_eq!(col(results.previous_match()), Some(0));
+        assert_eq!(col(results.previous_match()), Some(4));
+        assert_eq!(col(results.next_match()), Some(0));
+        assert_eq!(results.current_index(), Some(0));
+
+        let mut empty = SearchResults::default();
+        assert_eq!(empty.next_match(), None);
+        assert_eq!(empty.previous_match(), None);
+    }
+
+    #[test]
+    fn test_refresh_after_scrolling() {
+        let rows: &[&[&str]] = &[&["a", " ", " "], &[" ", "a", " "]];
+        let mut buffer = buffer(GlyphLookup::default(), rows);
+        buffer.set_scrollback_capacity(10);
+        let col_row = |query: Option<CellQuery>| query.and_then(|q| q.range()).map(|r| r.0);
+
+        let mut results = buffer.find(&SearchQuery::new("a")).unwrap();
+        assert_eq!(col_row(results.next_match()), Some((0, 0)));
+        assert!(!results.is_outdated(&buffer));
+
+        // the first row moves to the scrollback, out of view
+        buffer.push_lines_to_scrollback(1);
+        assert!(results.is_outdated(&buffer));
+        results.refresh(&buffer);
+        assert_eq!(col_row(results.next_match()), Some((1, 0)));
+        assert_eq!(results.len(), 1);
+
+        // scrolling back brings it into view again, shifted down a row
+        buffer.scroll_viewport_by(1);
+        results.refresh(&buffer);
+        assert_eq!(col_row(results.next_match()), Some((0, 0)));
+        assert_eq!(col_row(results.next_match()), Some((1, 1)));
+    }
+}
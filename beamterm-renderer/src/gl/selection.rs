@@ -12,7 +12,7 @@ use web_sys::console;
 use crate::{
     gl::{
         cell_query::{select, CellQuery, SelectionMode},
-        TerminalGrid,
+        Color, Palette, TerminalGrid,
     },
     Error,
 };
@@ -27,6 +27,29 @@ pub(crate) struct SelectionTracker {
     query: Rc<RefCell<Option<CellQuery>>>,
 }
 
+/// Appearance of selected cells.
+///
+/// Selected cells are highlighted by the fragment shader, which blends the
+/// highlight color over their background without modifying the cell data.
+/// Unless a color is set, the palette's selection colors are used.
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::{Color, SelectionHighlight};
+///
+/// let mut highlight = SelectionHighlight::new();
+/// highlight.set_color(Some(Color::Rgb(0x264f78)));
+/// highlight.set_opacity(0.8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionHighlight {
+    /// Highlight color; falls back to the palette selection background, then
+    /// the default foreground.
+    color: Option<Color>,
+    /// Opacity of the highlight over the cell background.
+    opacity: f32,
+}
+
 /// Tracks terminal dimensions for coordinate calculations.
 ///
 /// Maintains the current terminal size in cells and provides shared access
@@ -89,6 +112,57 @@ impl SelectionTracker {
     }
 }
 
+impl SelectionHighlight {
+    /// Opacity of the default highlight.
+    pub const DEFAULT_OPACITY: f32 = 0.4;
+
+    /// Creates a highlight in the palette's selection color, at the default opacity.
+    pub fn new() -> Self {
+        Self {
+            color: None,
+            opacity: Self::DEFAULT_OPACITY,
+        }
+    }
+
+    /// Returns the highlight color, if set.
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Sets the highlight color; `None` uses the palette selection background,
+    /// or the default foreground color if the palette has none.
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.color = color;
+    }
+
+    /// Returns the opacity of the highlight.
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Sets the opacity of the highlight over the cell background, from `0.0`
+    /// (invisible) to `1.0` (opaque).
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Resolves the highlight color against the palette.
+    pub(crate) fn resolved_color(&self, palette: &Palette) -> u32 {
+        match self.color {
+            Some(color) => palette.resolve(color),
+            None => palette
+                .selection_background()
+                .unwrap_or_else(|| palette.resolve(Color::DefaultFg)),
+        }
+    }
+}
+
+impl Default for SelectionHighlight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TerminalDimensions {
     /// Creates a new terminal dimensions tracker.
     ///
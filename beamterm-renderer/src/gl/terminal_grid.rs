@@ -1,14 +1,21 @@
-use std::{borrow::Cow, cmp::min, fmt::Debug, ops::Index};
+use std::{
+    fmt::Debug,
+    ops::{Index, Range},
+};
 
 use beamterm_data::{FontAtlasData, FontStyle, Glyph, GlyphEffect};
-use compact_str::{CompactString, CompactStringExt};
+use compact_str::CompactString;
 use web_sys::{console, WebGl2RenderingContext};
 
+#[cfg(feature = "regex")]
+use crate::gl::{SearchQuery, SearchResults};
 use crate::{
     error::Error,
     gl::{
-        buffer_upload_array, selection::SelectionTracker, ubo::UniformBufferObject, CellIterator,
-        Drawable, FontAtlas, RenderContext, ShaderProgram, GL,
+        buffer_upload_array, buffer_upload_sub_array, cell_buffer::blank_cell, scrollback::RowRing,
+        selection::SelectionTracker, texture::CellTexture, ubo::UniformBufferObject, CellBuffer,
+        CellIterator, CellQuery, Color, Cursor, CursorShape, Drawable, FontAtlas, Highlights,
+        Palette, RenderContext, SelectionHighlight, SelectionMode, ShaderProgram, GL,
     },
     mat4::Mat4,
 };
@@ -23,10 +30,8 @@ use crate::{
 pub struct TerminalGrid {
     /// Shader program for rendering the terminal cells.
     shader: ShaderProgram,
-    /// Terminal cell instance data
-    cells: Vec<CellDynamic>,
-    /// Terminal size in cells
-    terminal_size: (u16, u16),
+    /// Terminal cells, mirrored to the instance buffer
+    buffer: CellBuffer,
     /// Size of the canvas in pixels
     canvas_size_px: (i32, i32),
     /// Buffers for the terminal grid
@@ -35,16 +40,61 @@ pub struct TerminalGrid {
     ubo_vertex: UniformBufferObject,
     /// shared state for the fragment shader
     ubo_fragment: UniformBufferObject,
+    /// indexed color palette for the fragment shader
+    ubo_palette: UniformBufferObject,
+    /// Color palette for resolving indexed cell colors.
+    palette: Palette,
+    /// cursor state for the fragment shader
+    ubo_cursor: UniformBufferObject,
+    /// Cursor drawn on top of the cell grid.
+    cursor: Cursor,
+    /// Timestamp in milliseconds from which the cursor blink phase is measured.
+    cursor_blink_epoch: Option<f64>,
+    /// Indicates whether the cursor state must be re-uploaded to the GPU.
+    cursor_pending_upload: bool,
     /// Font atlas for rendering text.
     atlas: FontAtlas,
     /// Uniform location for the texture sampler.
     sampler_loc: web_sys::WebGlUniformLocation,
-    /// Fallback glyph for missing symbols.
-    fallback_glyph: u16,
     /// Selection tracker for managing cell selections.
     selection: SelectionTracker,
-    /// Indicates whether there are cells pending flush to the GPU.
-    cells_pending_flush: bool,
+    /// selection state for the fragment shader
+    ubo_selection: UniformBufferObject,
+    /// Appearance of selected cells.
+    selection_highlight: SelectionHighlight,
+    /// Selection rendered by the last upload to the GPU.
+    uploaded_selection: Option<CellQuery>,
+    /// Hyperlink under the mouse pointer, underlined by the shader.
+    link_hover: SelectionTracker,
+    /// Hovered hyperlink rendered by the last upload to the GPU.
+    uploaded_link_hover: Option<CellQuery>,
+    /// Indicates whether the selection state must be re-uploaded to the GPU.
+    selection_pending_upload: bool,
+    /// Named highlights drawn below the selection.
+    highlights: Highlights,
+    /// Highlight color of every viewport cell, for the fragment shader.
+    highlight_texture: CellTexture,
+    /// Uniform location for the highlight texture sampler.
+    highlights_loc: web_sys::WebGlUniformLocation,
+    /// Indicates whether the highlight texture must be re-uploaded to the GPU.
+    highlights_pending_upload: bool,
+    /// Viewport offset the cursor was last uploaded with.
+    cursor_viewport_offset: usize,
+    /// Mapping of viewport rows to rows of the instance buffer.
+    row_ring: RowRing,
+    /// Copy of the instance buffer contents, in instance buffer order.
+    staging: Vec<CellDynamic>,
+    /// In-progress IME composition drawn over the cells.
+    composition: Option<Composition>,
+}
+
+/// IME composition text, resolved to underlined cells starting at `position`.
+#[derive(Debug, Clone)]
+struct Composition {
+    /// First cell of the composition, as `(col, row)` in the viewport.
+    position: (u16, u16),
+    /// Cells drawn over the row, clipped to the end of the row.
+    cells: Vec<CellDynamic>,
 }
 
 #[derive(Debug)]
@@ -56,6 +106,78 @@ struct TerminalBuffers {
     indices: web_sys::WebGlBuffer,
 }
 
+/// WebGL objects of the terminal grid, recreated after a context loss.
+struct GlResources {
+    shader: ShaderProgram,
+    buffers: TerminalBuffers,
+    ubo_vertex: UniformBufferObject,
+    ubo_fragment: UniformBufferObject,
+    ubo_palette: UniformBufferObject,
+    ubo_cursor: UniformBufferObject,
+    ubo_selection: UniformBufferObject,
+    sampler_loc: web_sys::WebGlUniformLocation,
+    highlights_loc: web_sys::WebGlUniformLocation,
+    highlight_texture: CellTexture,
+}
+
+impl GlResources {
+    /// Creates the buffers, shader, uniform buffers and highlight texture for
+    /// a grid of `(cols, rows)` cells, with the instance buffer holding `cells`.
+    fn create(
+        gl: &WebGl2RenderingContext,
+        cell_size: (i32, i32),
+        (cols, rows): (i32, i32),
+        cells: &[CellDynamic],
+    ) -> Result<Self, Error> {
+        // create and setup the Vertex Array Object
+        let vao = create_vao(gl)?;
+        gl.bind_vertex_array(Some(&vao));
+
+        let cell_pos = CellStatic::create_grid(cols, rows);
+        let buffers = setup_buffers(gl, vao, &cell_pos, cells, cell_size)?;
+
+        // unbind VAO to prevent accidental modification
+        gl.bind_vertex_array(None);
+
+        // setup shader and uniform data
+        let shader =
+            ShaderProgram::create(gl, TerminalGrid::VERTEX_GLSL, TerminalGrid::FRAGMENT_GLSL)?;
+        shader.use_program(gl);
+
+        let ubo_vertex = UniformBufferObject::new(gl, CellVertexUbo::BINDING_POINT)?;
+        ubo_vertex.bind_to_shader(gl, &shader, "VertUbo")?;
+        let ubo_fragment = UniformBufferObject::new(gl, CellFragmentUbo::BINDING_POINT)?;
+        ubo_fragment.bind_to_shader(gl, &shader, "FragUbo")?;
+        let ubo_palette = UniformBufferObject::new(gl, CellPaletteUbo::BINDING_POINT)?;
+        ubo_palette.bind_to_shader(gl, &shader, "PaletteUbo")?;
+        let ubo_cursor = UniformBufferObject::new(gl, CellCursorUbo::BINDING_POINT)?;
+        ubo_cursor.bind_to_shader(gl, &shader, "CursorUbo")?;
+        let ubo_selection = UniformBufferObject::new(gl, CellSelectionUbo::BINDING_POINT)?;
+        ubo_selection.bind_to_shader(gl, &shader, "SelectionUbo")?;
+
+        let sampler_loc = gl
+            .get_uniform_location(&shader.program, "u_sampler")
+            .ok_or(Error::uniform_location_failed("u_sampler"))?;
+        let highlights_loc = gl
+            .get_uniform_location(&shader.program, "u_highlights")
+            .ok_or(Error::uniform_location_failed("u_highlights"))?;
+        let highlight_texture = CellTexture::new(gl)?;
+
+        Ok(Self {
+            shader,
+            buffers,
+            ubo_vertex,
+            ubo_fragment,
+            ubo_palette,
+            ubo_cursor,
+            ubo_selection,
+            sampler_loc,
+            highlights_loc,
+            highlight_texture,
+        })
+    }
+}
+
 impl TerminalBuffers {
     fn upload_instance_data<T>(&self, gl: &WebGl2RenderingContext, cell_data: &[T]) {
         gl.bind_vertex_array(Some(&self.vao));
@@ -65,6 +187,15 @@ impl TerminalBuffers {
 
         gl.bind_vertex_array(None);
     }
+
+    fn upload_instance_range<T>(&self, gl: &WebGl2RenderingContext, first: usize, data: &[T]) {
+        gl.bind_vertex_array(Some(&self.vao));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.instance_cell));
+
+        buffer_upload_sub_array(gl, GL::ARRAY_BUFFER, first * size_of::<T>(), data);
+
+        gl.bind_vertex_array(None);
+    }
 }
 
 impl TerminalGrid {
@@ -76,35 +207,25 @@ impl TerminalGrid {
         atlas: FontAtlas,
         screen_size: (i32, i32),
     ) -> Result<Self, Error> {
-        // create and setup the Vertex Array Object
-        let vao = create_vao(gl)?;
-        gl.bind_vertex_array(Some(&vao));
-
         // prepare vertex, index and instance buffers
         let cell_size = atlas.cell_size();
         let (cols, rows) = (screen_size.0 / cell_size.0, screen_size.1 / cell_size.1);
 
-        // let fill_glyphs = Self::fill_glyphs(&atlas);
-        // let cell_data = create_terminal_cell_data(cols, rows, &fill_glyphs);
-        let cell_data = create_terminal_cell_data(cols, rows, &[' ' as u16]);
-        let cell_pos = CellStatic::create_grid(cols, rows);
-        let buffers = setup_buffers(gl, vao, &cell_pos, &cell_data, cell_size)?;
-
-        // unbind VAO to prevent accidental modification
-        gl.bind_vertex_array(None);
+        let buffer = CellBuffer::new(atlas.glyphs().clone(), cols as u16, rows as u16);
+        let cell_data = buffer.cells().to_vec();
 
-        // setup shader and uniform data
-        let shader = ShaderProgram::create(gl, Self::VERTEX_GLSL, Self::FRAGMENT_GLSL)?;
-        shader.use_program(gl);
-
-        let ubo_vertex = UniformBufferObject::new(gl, CellVertexUbo::BINDING_POINT)?;
-        ubo_vertex.bind_to_shader(gl, &shader, "VertUbo")?;
-        let ubo_fragment = UniformBufferObject::new(gl, CellFragmentUbo::BINDING_POINT)?;
-        ubo_fragment.bind_to_shader(gl, &shader, "FragUbo")?;
-
-        let sampler_loc = gl
-            .get_uniform_location(&shader.program, "u_sampler")
-            .ok_or(Error::uniform_location_failed("u_sampler"))?;
+        let GlResources {
+            shader,
+            buffers,
+            ubo_vertex,
+            ubo_fragment,
+            ubo_palette,
+            ubo_cursor,
+            ubo_selection,
+            sampler_loc,
+            highlights_loc,
+            highlight_texture,
+        } = GlResources::create(gl, cell_size, (cols, rows), &cell_data)?;
 
         console::log_2(&"terminal cells".into(), &cell_data.len().into());
 
@@ -112,27 +233,82 @@ impl TerminalGrid {
         console::log_1(&format!("terminal size {cols}x{rows}").into());
         let grid = Self {
             shader,
-            terminal_size: (cols as u16, rows as u16),
+            buffer,
             canvas_size_px: screen_size,
-            cells: cell_data,
+            staging: cell_data,
             buffers,
             ubo_vertex,
             ubo_fragment,
+            ubo_palette,
+            palette: Palette::default(),
+            ubo_cursor,
+            cursor: Cursor::new(),
+            cursor_blink_epoch: None,
+            cursor_pending_upload: true,
             atlas,
             sampler_loc,
-            fallback_glyph: ' ' as u16,
             selection: SelectionTracker::new(),
-            cells_pending_flush: false,
+            ubo_selection,
+            selection_highlight: SelectionHighlight::new(),
+            uploaded_selection: None,
+            link_hover: SelectionTracker::new(),
+            uploaded_link_hover: None,
+            selection_pending_upload: true,
+            highlights: Highlights::default(),
+            highlight_texture,
+            highlights_loc,
+            highlights_pending_upload: true,
+            cursor_viewport_offset: 0,
+            row_ring: RowRing::new(rows as u16),
+            composition: None,
         };
 
         grid.upload_ubo_data(gl);
+        grid.upload_palette(gl);
 
         Ok(grid)
     }
 
+    /// Recreates all WebGL resources after the context was lost and restored,
+    /// from the retained atlas data, cells, palette, cursor, selection and
+    /// highlights. Everything is uploaded again on the next flush.
+    ///
+    /// # Errors
+    /// Returns an error if a resource cannot be created, such as when the
+    /// context was lost again.
+    pub fn restore(&mut self, gl: &WebGl2RenderingContext) -> Result<(), Error> {
+        self.atlas.restore(gl)?;
+
+        let (cols, rows) = self.buffer.size();
+        self.row_ring.reset(rows);
+        self.staging = self.buffer.cells().to_vec();
+
+        let resources =
+            GlResources::create(gl, self.cell_size(), (cols as i32, rows as i32), &self.staging)?;
+        self.shader = resources.shader;
+        self.buffers = resources.buffers;
+        self.ubo_vertex = resources.ubo_vertex;
+        self.ubo_fragment = resources.ubo_fragment;
+        self.ubo_palette = resources.ubo_palette;
+        self.ubo_cursor = resources.ubo_cursor;
+        self.ubo_selection = resources.ubo_selection;
+        self.sampler_loc = resources.sampler_loc;
+        self.highlights_loc = resources.highlights_loc;
+        self.highlight_texture = resources.highlight_texture;
+
+        self.upload_ubo_data(gl);
+        self.upload_palette(gl);
+
+        self.buffer.dirty_mut().mark_all();
+        self.cursor_pending_upload = true;
+        self.selection_pending_upload = true;
+        self.highlights_pending_upload = true;
+        Ok(())
+    }
+
     /// Sets the fallback glyph for missing characters.
     pub fn set_fallback_glyph(&mut self, fallback: &str) {
-        self.fallback_glyph = self.atlas.get_base_glyph_id(fallback).unwrap_or(' ' as u16);
+        self.buffer.set_fallback_glyph(fallback);
     }
 
     /// Returns the [`FontAtlas`] used by this terminal grid.
@@ -140,6 +316,65 @@ impl TerminalGrid {
         &self.atlas
     }
 
+    /// Replaces the font atlas, such as with one rasterized for another pixel
+    /// scale, and returns the previous atlas, whose texture is kept until
+    /// dropped with [`FontAtlas::into_data`].
+    ///
+    /// The cells keep their glyph ids, so both atlases must contain the same
+    /// glyphs. The grid is resized to fit the canvas with the new cell size.
+    /// On failure, the grid keeps the previous atlas.
+    pub fn set_atlas(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        mut atlas: FontAtlas,
+    ) -> Result<FontAtlas, Error> {
+        self.swap_atlas(gl, &mut atlas)?;
+        Ok(atlas)
+    }
+
+    /// Swaps the font atlas with `atlas`, leaving the previous atlas in its
+    /// place. On failure, the grid keeps the previous atlas and `atlas` is
+    /// left unchanged.
+    pub(crate) fn swap_atlas(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        atlas: &mut FontAtlas,
+    ) -> Result<(), Error> {
+        std::mem::swap(&mut self.atlas, atlas);
+        if let Err(e) = self.apply_cell_size(gl) {
+            std::mem::swap(&mut self.atlas, atlas);
+            // best effort, as the failure is likely to recur
+            let _ = self.apply_cell_size(gl);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Sizes the cell quad and the grid for the cell size of the atlas.
+    fn apply_cell_size(&mut self, gl: &WebGl2RenderingContext) -> Result<(), Error> {
+        // the cell quad is sized in pixels
+        gl.bind_vertex_array(Some(&self.buffers.vao));
+        gl.delete_buffer(Some(&self.buffers.vertices));
+        let vertices = create_quad_buffer(gl, self.cell_size());
+        gl.bind_vertex_array(None);
+        self.buffers.vertices = vertices?;
+
+        self.cursor_pending_upload = true;
+        self.resize(gl, self.canvas_size_px)
+    }
+
+    /// Returns the cell buffer mirrored by this grid.
+    pub fn cell_buffer(&self) -> &CellBuffer {
+        &self.buffer
+    }
+
+    /// Returns a mutable reference to the cell buffer mirrored by this grid.
+    ///
+    /// Changes to the buffer are uploaded on the next flush.
+    pub fn cell_buffer_mut(&mut self) -> &mut CellBuffer {
+        &mut self.buffer
+    }
+
     /// Returns the unpadded cell dimensions in pixels.
     pub fn cell_size(&self) -> (i32, i32) {
         self.atlas.cell_size()
@@ -147,14 +382,206 @@ impl TerminalGrid {
 
     /// Returns the size of the terminal grid in cells.
     pub fn terminal_size(&self) -> (u16, u16) {
-        self.terminal_size
+        self.buffer.size()
+    }
+
+    /// Returns the color palette used for resolving indexed cell colors.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Replaces the color palette and uploads it to the GPU.
+    ///
+    /// Cells using [`Color::Indexed`], [`Color::DefaultFg`] or [`Color::DefaultBg`]
+    /// are recolored on the next frame; no cell data is re-uploaded.
+    pub fn set_palette(&mut self, gl: &WebGl2RenderingContext, palette: Palette) {
+        self.palette = palette;
+        self.upload_palette(gl);
+
+        // the palette may define the cursor and selection colors
+        self.cursor_pending_upload = true;
+        self.selection_pending_upload = true;
+        self.highlights_pending_upload = true;
+    }
+
+    /// Returns the named highlights drawn over the cells.
+    pub fn highlights(&self) -> &Highlights {
+        &self.highlights
+    }
+
+    /// Returns a mutable reference to the named highlights.
+    ///
+    /// Changes are uploaded on the next flush; no cell data is re-uploaded.
+    pub fn highlights_mut(&mut self) -> &mut Highlights {
+        self.highlights_pending_upload = true;
+        &mut self.highlights
+    }
+
+    /// Returns the appearance of selected cells.
+    pub fn selection_highlight(&self) -> &SelectionHighlight {
+        &self.selection_highlight
+    }
+
+    /// Sets the appearance of selected cells, uploaded on the next flush.
+    pub fn set_selection_highlight(&mut self, highlight: SelectionHighlight) {
+        self.selection_highlight = highlight;
+        self.selection_pending_upload = true;
+    }
+
+    /// Returns the cursor state.
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    /// Returns a mutable reference to the cursor state.
+    ///
+    /// Changes are uploaded on the next frame, and restart the blink cycle so
+    /// that a moving cursor stays visible.
+    pub fn cursor_mut(&mut self) -> &mut Cursor {
+        self.cursor_pending_upload = true;
+        self.cursor_blink_epoch = None;
+        &mut self.cursor
+    }
+
+    /// Sets the in-progress IME composition text, drawn underlined over the
+    /// cells starting at `position`, as `(col, row)`, without modifying the
+    /// cell data. An empty `text` clears the composition.
+    ///
+    /// The composition is clipped to the end of the row; one cell is used per
+    /// character.
+    pub fn set_composition(&mut self, position: (u16, u16), text: &str) {
+        self.clear_composition();
+
+        let (cols, rows) = self.buffer.size();
+        if text.is_empty() || position.0 >= cols || position.1 >= rows {
+            return;
+        }
+
+        let mut utf8 = [0; 4];
+        let cells: Vec<_> = text
+            .chars()
+            .take((cols - position.0) as usize)
+            .map(|ch| {
+                let symbol = ch.encode_utf8(&mut utf8);
+                let underline = GlyphEffect::Underline as u16;
+                let data = CellData::new_with_colors(
+                    symbol,
+                    underline,
+                    Color::DefaultFg,
+                    Color::DefaultBg,
+                );
+                self.buffer.resolve_cell(data)
+            })
+            .collect();
+
+        let (col, row) = position;
+        self.buffer.dirty_mut().mark_span(row, col..col + cells.len() as u16);
+        self.composition = Some(Composition { position, cells });
+    }
+
+    /// Removes the IME composition text, restoring the cells underneath.
+    pub fn clear_composition(&mut self) {
+        if let Some(composition) = self.composition.take() {
+            let (col, row) = composition.position;
+            let end = col + composition.cells.len() as u16;
+            self.buffer.dirty_mut().mark_span(row, col..end);
+        }
+    }
+
+    /// Updates the input focus state, which controls the unfocused cursor style.
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        if self.cursor.is_focused() != focused {
+            self.cursor_mut().set_focused(focused);
+        }
+    }
+
+    /// Uploads the cursor state, including the blink phase at the given time.
+    ///
+    /// Called once per frame; the cursor uniforms are only re-uploaded when
+    /// the cursor changed, the viewport scrolled or the cursor is blinking.
+    ///
+    /// # Parameters
+    /// * `gl` - WebGL2 rendering context
+    /// * `now_ms` - Current time in milliseconds, e.g. from `performance.now()`
+    pub(crate) fn update_cursor(&mut self, gl: &WebGl2RenderingContext, now_ms: f64) {
+        let epoch = *self.cursor_blink_epoch.get_or_insert(now_ms);
+        let blinking = self.cursor.is_visible() && self.cursor.effective_blink_interval().is_some();
+
+        // the cursor follows the screen content
+        let viewport_offset = self.buffer.viewport_offset();
+        let scrolled = viewport_offset != self.cursor_viewport_offset;
+        if !self.cursor_pending_upload && !blinking && !scrolled {
+            return;
+        }
+
+        let elapsed_secs = ((now_ms - epoch) / 1000.0) as f32;
+        let cursor_ubo = CellCursorUbo::new(
+            &self.cursor,
+            &self.palette,
+            self.cell_size(),
+            viewport_offset,
+            elapsed_secs,
+        );
+        self.ubo_cursor.upload_data(gl, &cursor_ubo);
+
+        self.cursor_pending_upload = false;
+        self.cursor_viewport_offset = viewport_offset;
+    }
+
+    /// Returns the maximum number of lines retained in the scrollback.
+    pub fn scrollback_capacity(&self) -> usize {
+        self.buffer.scrollback_capacity()
+    }
+
+    /// Sets the maximum number of lines retained in the scrollback; `0`
+    /// disables the scrollback. Shrinking the capacity drops the oldest lines.
+    pub fn set_scrollback_capacity(&mut self, lines: usize) {
+        self.buffer.set_scrollback_capacity(lines);
+    }
+
+    /// Returns the number of lines currently held in the scrollback.
+    pub fn scrollback_len(&self) -> usize {
+        self.buffer.scrollback_len()
+    }
+
+    /// Removes all lines from the scrollback and returns the viewport to the screen.
+    pub fn clear_scrollback(&mut self) {
+        self.buffer.clear_scrollback();
+    }
+
+    /// Scrolls the screen content up by `count` lines, pushing the top lines
+    /// into the scrollback; see [`CellBuffer::push_lines_to_scrollback`].
+    pub fn push_lines_to_scrollback(&mut self, count: u16) {
+        self.buffer.push_lines_to_scrollback(count);
+    }
+
+    /// Returns the number of lines the viewport is scrolled back into the
+    /// scrollback; `0` when the screen is displayed.
+    pub fn viewport_offset(&self) -> usize {
+        self.buffer.viewport_offset()
+    }
+
+    /// Scrolls the viewport by `lines`; positive values scroll back towards
+    /// older lines. The offset is clamped to the scrollback length.
+    pub fn scroll_viewport_by(&mut self, lines: i32) {
+        self.buffer.scroll_viewport_by(lines);
+    }
+
+    /// Scrolls the viewport to an absolute number of lines back into the
+    /// scrollback; `0` displays the screen. The offset is clamped to the
+    /// scrollback length.
+    ///
+    /// Scrolling rotates the GPU instance buffer, so only the rows scrolled
+    /// into view are uploaded on the next flush.
+    pub fn scroll_viewport_to(&mut self, offset: usize) {
+        self.buffer.scroll_viewport_to(offset);
     }
 
     /// Returns a mutable reference to the cell data at the specified cell coordinates.
+    ///
+    /// The cell is marked dirty and re-uploaded on the next flush.
     pub fn cell_data_mut(&mut self, x: u16, y: u16) -> Option<&mut CellDynamic> {
-        let (cols, _) = self.terminal_size;
-        let idx = y as usize * cols as usize + x as usize;
-        self.cells.get_mut(idx)
+        self.buffer.cell_mut(x, y)
     }
 
     /// Returns the active selection state of the terminal grid.
@@ -162,28 +589,86 @@ impl TerminalGrid {
         self.selection.clone()
     }
 
-    /// Returns the symbols in the specified block range as a `CompactString`.
-    pub(super) fn get_symbols(&self, selection: CellIterator) -> CompactString {
-        let (cols, rows) = self.terminal_size;
-        let mut text = CompactString::new("");
+    /// Returns the tracker of the hyperlink under the mouse pointer, which
+    /// is underlined while set.
+    pub(crate) fn link_hover_tracker(&self) -> SelectionTracker {
+        self.link_hover.clone()
+    }
+
+    /// Zero-allocation iterator over cell indices for a given selection range and mode.
+    ///
+    /// See [`CellBuffer::cell_iter`].
+    pub fn cell_iter(
+        &self,
+        start: (u16, u16),
+        end: (u16, u16),
+        mode: SelectionMode,
+    ) -> CellIterator {
+        self.buffer.cell_iter(start, end, mode)
+    }
+
+    /// Extracts text content from the terminal based on the selection query.
+    ///
+    /// See [`CellBuffer::get_text`].
+    pub fn get_text(&self, selection: CellQuery) -> CompactString {
+        self.buffer.get_text(selection)
+    }
+
+    /// Finds the matches of a query among the displayed cells.
+    ///
+    /// See [`CellBuffer::find`].
+    #[cfg(feature = "regex")]
+    pub fn find(&self, query: &SearchQuery) -> Result<SearchResults, Error> {
+        self.buffer.find(query)
+    }
+
+    /// Returns the hyperlink at a viewport cell and the cells it spans.
+    ///
+    /// See [`CellBuffer::link_at`].
+    pub fn link_at(&self, cell: (u16, u16)) -> Option<(CellQuery, CompactString)> {
+        self.buffer.link_at(cell)
+    }
 
-        for (idx, require_newline_after) in selection {
-            text.push_str(&self.get_cell_symbol(idx));
-            if require_newline_after {
-                text.push('\n'); // add newline after each row
+    /// Copies the columns `span` of a viewport row into its slot of the
+    /// staging buffer.
+    fn stage_span(&mut self, row: u16, span: Range<u16>) {
+        let cols = self.buffer.size().0 as usize;
+        let first = self.row_ring.slot(row) as usize * cols;
+        let (start, end) = (span.start as usize, span.end as usize);
+        let dst = &mut self.staging[first + start..first + end];
+
+        self.buffer.copy_displayed_span(row, span.clone(), dst);
+
+        if let Some(composition) = self.composition.as_ref().filter(|c| c.position.1 == row) {
+            let first = composition.position.0;
+            for (col, cell) in span.zip(dst.iter_mut()) {
+                if let Some(overlay) =
+                    col.checked_sub(first).and_then(|i| composition.cells.get(i as usize))
+                {
+                    *cell = *overlay;
+                }
             }
         }
-
-        text
     }
 
-    fn get_cell_symbol(&self, idx: usize) -> Cow<str> {
-        if idx < self.cells.len() {
-            let glyph_id = self.cells[idx].glyph_id();
-            self.atlas.get_symbol(glyph_id).unwrap_or_else(|| self.fallback_symbol())
-        } else {
-            self.fallback_symbol()
+    /// Uploads the selection range, hovered link and highlight colors, if changed.
+    fn upload_selection(&mut self, gl: &WebGl2RenderingContext) {
+        let selection = self.selection.get_query().filter(|q| q.range().is_some());
+        let link_hover = self.link_hover.get_query().filter(|q| q.range().is_some());
+        if selection == self.uploaded_selection
+            && link_hover == self.uploaded_link_hover
+            && !self.selection_pending_upload
+        {
+            return;
         }
+
+        let selection_ubo =
+            CellSelectionUbo::new(selection, link_hover, &self.selection_highlight, &self.palette);
+        self.ubo_selection.upload_data(gl, &selection_ubo);
+
+        self.uploaded_selection = selection;
+        self.uploaded_link_hover = link_hover;
+        self.selection_pending_upload = false;
     }
 
     /// Uploads uniform buffer data for screen and cell dimensions.
@@ -195,137 +680,137 @@ impl TerminalGrid {
     /// # Parameters
     /// * `gl` - WebGL2 rendering context
     fn upload_ubo_data(&self, gl: &WebGl2RenderingContext) {
-        let vertex_ubo = CellVertexUbo::new(self.canvas_size_px, self.cell_size());
-        self.ubo_vertex.upload_data(gl, &vertex_ubo);
+        self.upload_vertex_ubo(gl);
 
         let fragment_ubo = CellFragmentUbo::new(&self.atlas);
         self.ubo_fragment.upload_data(gl, &fragment_ubo);
     }
 
+    /// Uploads the projection, cell size and row ring offset for the vertex shader.
+    fn upload_vertex_ubo(&self, gl: &WebGl2RenderingContext) {
+        let vertex_ubo = CellVertexUbo::new(self.canvas_size_px, self.cell_size(), self.row_ring);
+        self.ubo_vertex.upload_data(gl, &vertex_ubo);
+    }
+
+    /// Uploads the color palette to the palette uniform buffer.
+    fn upload_palette(&self, gl: &WebGl2RenderingContext) {
+        let palette_ubo = CellPaletteUbo::new(&self.palette);
+        self.ubo_palette.upload_data(gl, &palette_ubo);
+    }
+
+    /// Uploads the highlight color of every viewport cell, if changed.
+    fn upload_highlights(&mut self, gl: &WebGl2RenderingContext) -> Result<(), Error> {
+        if !self.highlights_pending_upload {
+            return Ok(());
+        }
+
+        let size = self.buffer.size();
+        let texels = self.highlights.rasterize(size, &self.palette);
+        self.highlight_texture.upload(gl, size, &texels)?;
+
+        self.highlights_pending_upload = false;
+        Ok(())
+    }
+
     /// Returns the total number of cells in the terminal grid.
     pub fn cell_count(&self) -> usize {
-        self.cells.len()
+        self.buffer.cell_count()
     }
 
     /// Updates the content of terminal cells with new data.
     ///
-    /// This method efficiently updates the dynamic instance buffer with new
-    /// cell data. The iterator must provide exactly the same number of cells
-    /// as the grid contains, in row-major order.
+    /// The iterator must provide exactly the same number of cells as the grid
+    /// contains, in row-major order. Changed cells are marked dirty and uploaded
+    /// to the GPU on the next render.
     ///
     /// # Parameters
-    /// * `gl` - WebGL2 rendering context
     /// * `cells` - Iterator providing `CellData` for each cell in the grid
     ///
     /// # Returns
     /// * `Ok(())` - Successfully updated cell data
-    /// * `Err(Error)` - Failed to update buffer or other WebGL error
+    /// * `Err(Error)` - Failed to update the cell data
     pub fn update_cells<'a>(
         &mut self,
-        gl: &WebGl2RenderingContext,
         cells: impl Iterator<Item = CellData<'a>>,
     ) -> Result<(), Error> {
-        // update instance buffer with new cell data
-        let atlas = &self.atlas;
-
-        let fallback_glyph = self.fallback_glyph;
-        self.cells.iter_mut().zip(cells).for_each(|(cell, data)| {
-            let glyph_id = atlas.get_base_glyph_id(data.symbol).unwrap_or(fallback_glyph);
-
-            *cell = CellDynamic::new(glyph_id | data.style_bits, data.fg, data.bg);
-        });
-
-        self.cells_pending_flush = true;
+        // only cells which differ from the current content are marked dirty
+        self.buffer.update_cells(cells);
         Ok(())
     }
 
     pub(crate) fn update_cells_by_position<'a>(
         &mut self,
-        gl: &WebGl2RenderingContext,
         cells: impl Iterator<Item = (u16, u16, CellData<'a>)>,
     ) -> Result<(), Error> {
-        // update instance buffer with new cell data by position
-        let atlas = &self.atlas;
-
-        let cell_count = self.cells.len();
-        let fallback_glyph = self.fallback_glyph;
-        let w = self.terminal_size.0 as usize;
-        cells
-            .map(|(x, y, cell)| (w * y as usize + x as usize, cell))
-            .filter(|(idx, _)| *idx < cell_count)
-            .for_each(|(idx, cell)| {
-                let glyph_id = atlas.get_base_glyph_id(cell.symbol).unwrap_or(fallback_glyph);
-                self.cells[idx] = CellDynamic::new(glyph_id | cell.style_bits, cell.fg, cell.bg);
-            });
-
-        self.cells_pending_flush = true;
-
+        self.buffer.update_cells_by_position(cells);
         Ok(())
     }
 
     pub(crate) fn update_cell(&mut self, x: u16, y: u16, cell_data: CellData) {
-        let (cols, _) = self.terminal_size;
-        let idx = y as usize * cols as usize + x as usize;
-        self.update_cell_by_index(idx, cell_data);
-
-        self.cells_pending_flush = true;
+        self.buffer.update_cell(x, y, cell_data);
     }
 
     pub(crate) fn update_cell_by_index(&mut self, idx: usize, cell_data: CellData) {
-        if idx >= self.cells.len() {
-            return;
-        }
-
-        let atlas = &self.atlas;
-        let fallback_glyph = self.fallback_glyph;
-        let glyph_id = atlas.get_base_glyph_id(cell_data.symbol).unwrap_or(fallback_glyph);
-
-        self.cells[idx] =
-            CellDynamic::new(glyph_id | cell_data.style_bits, cell_data.fg, cell_data.bg);
-
-        self.cells_pending_flush = true;
+        self.buffer.update_cell_by_index(idx, cell_data);
     }
 
     /// Flushes pending cell updates to the GPU.
+    ///
+    /// Only the dirty spans of the viewport are uploaded, unless most of the
+    /// viewport changed. Scrolling rotates the instance buffer, so that only
+    /// the rows scrolled into view need to be uploaded.
     pub(crate) fn flush_cells(&mut self, gl: &WebGl2RenderingContext) -> Result<(), Error> {
-        if !self.cells_pending_flush {
-            return Ok(()); // no pending updates to flush
+        let scroll = self.buffer.take_pending_scroll();
+
+        // the selection and highlights are drawn by the shader, independent of the cells
+        self.upload_selection(gl);
+        self.upload_highlights(gl)?;
+
+        // the composition stays in place while the content scrolls beneath it
+        if let Some(row) = self.composition.as_ref().map(|c| c.position.1).filter(|_| scroll != 0) {
+            let rows = self.buffer.size().1 as i32;
+            let scrolled_row = (row as i32 - scroll).clamp(0, rows - 1) as u16;
+            self.buffer.dirty_mut().mark_rows(row..row + 1);
+            self.buffer.dirty_mut().mark_rows(scrolled_row..scrolled_row + 1);
         }
 
-        // If there's an active selection, flip the colors of the selected cells.
-        // This ensures that the selected cells are rendered with inverted colors
-        // during the GPU upload process.
-        self.flip_selected_cell_colors();
+        if self.buffer.dirty().is_clean() && scroll == 0 {
+            return Ok(()); // no pending updates to flush
+        }
 
-        self.buffers.upload_instance_data(gl, &self.cells);
+        let (cols, rows) = self.buffer.size();
+        let ring_offset = self.row_ring.offset();
+        let full_upload =
+            self.buffer.dirty().requires_full_upload() || self.row_ring.rotate(scroll).is_none();
 
-        // Restore the original colors of the selected cells after the upload.
-        // This ensures that the internal state of the cells remains consistent.
-        self.flip_selected_cell_colors();
+        if full_upload {
+            self.row_ring.reset(rows);
+            self.staging.resize(self.buffer.cell_count(), blank_cell());
+            for row in 0..rows {
+                self.stage_span(row, 0..cols);
+            }
 
-        self.cells_pending_flush = false;
-        Ok(())
-    }
+            self.buffers.upload_instance_data(gl, &self.staging);
+        } else {
+            let dirty = std::mem::take(self.buffer.dirty_mut());
+            for (row, span) in dirty.spans() {
+                self.stage_span(row, span);
+            }
 
-    fn flip_selected_cell_colors(&mut self) {
-        if let Some(iter) = self.selected_cells_iter() {
-            iter.for_each(|(idx, _)| self.cells[idx].flip_colors());
+            for range in dirty.ranges() {
+                for slots in self.row_ring.cell_ranges(range, cols as usize) {
+                    self.buffers.upload_instance_range(gl, slots.start, &self.staging[slots]);
+                }
+            }
+            *self.buffer.dirty_mut() = dirty;
         }
-    }
 
-    fn selected_cells_iter(&self) -> Option<CellIterator> {
-        self.selection
-            .get_query()
-            .and_then(|query| query.range())
-            .map(|(start, end)| self.cell_iter(start, end, self.selection.mode()))
-    }
-
-    fn flip_cell_colors(&mut self, x: u16, y: u16) {
-        let (cols, _) = self.terminal_size;
-        let idx = y as usize * cols as usize + x as usize;
-        if idx < self.cells.len() {
-            self.cells[idx].flip_colors();
+        if self.row_ring.offset() != ring_offset {
+            self.upload_vertex_ubo(gl);
         }
+
+        self.buffer.dirty_mut().clear();
+        Ok(())
     }
 
     /// Resizes the terminal grid to fit the new canvas dimensions.
@@ -356,10 +841,18 @@ impl TerminalGrid {
         let cell_size = self.atlas.cell_size();
         let cols = canvas_size.0 / cell_size.0;
         let rows = canvas_size.1 / cell_size.1;
-        if self.terminal_size == (cols as u16, rows as u16) {
+        if self.buffer.size() == (cols as u16, rows as u16) {
             return Ok(()); // no change in terminal size
         }
 
+        // resize cell data; the whole buffer is re-staged on the next flush
+        self.buffer.resize(cols as u16, rows as u16);
+        self.highlights_pending_upload = true;
+
+        if gl.is_context_lost() {
+            return Ok(()); // buffers are recreated by restore()
+        }
+
         // update buffers; bind VAO to ensure correct state
         gl.bind_vertex_array(Some(&self.buffers.vao));
 
@@ -367,32 +860,25 @@ impl TerminalGrid {
         gl.delete_buffer(Some(&self.buffers.instance_cell));
         gl.delete_buffer(Some(&self.buffers.instance_pos));
 
-        // resize cell data vector
-        let current_size = (self.terminal_size.0 as i32, self.terminal_size.1 as i32);
-        let cell_data = resize_cell_grid(&self.cells, current_size, (cols, rows));
-        self.cells = cell_data;
-
         let cell_pos = CellStatic::create_grid(cols, rows);
 
         // re-create buffers with new data
-        self.buffers.instance_cell = create_dynamic_instance_buffer(gl, &self.cells)?;
+        self.buffers.instance_cell = create_dynamic_instance_buffer(gl, self.buffer.cells())?;
         self.buffers.instance_pos = create_static_instance_buffer(gl, &cell_pos)?;
 
         // unbind VAO
         gl.bind_vertex_array(None);
 
-        self.terminal_size = (cols as u16, rows as u16);
+        // the new instance buffer holds the screen in viewport order
+        self.row_ring.reset(rows as u16);
+        self.upload_vertex_ubo(gl);
 
         Ok(())
     }
 
     /// Returns the base glyph identifier for a given symbol.
     pub fn base_glyph_id(&self, symbol: &str) -> Option<u16> {
-        self.atlas.get_base_glyph_id(symbol)
-    }
-
-    fn fallback_symbol(&self) -> Cow<str> {
-        self.atlas.get_symbol(self.fallback_glyph).unwrap_or(Cow::Borrowed(" "))
+        self.buffer.base_glyph_id(symbol)
     }
 
     fn fill_glyphs(atlas: &FontAtlas) -> Vec<u16> {
@@ -433,29 +919,6 @@ impl TerminalGrid {
     }
 }
 
-fn resize_cell_grid(
-    cells: &[CellDynamic],
-    old_size: (i32, i32),
-    new_size: (i32, i32),
-) -> Vec<CellDynamic> {
-    let new_len = new_size.0 * new_size.1;
-
-    let mut new_cells = Vec::with_capacity(new_len as usize);
-    for _ in 0..new_len {
-        new_cells.push(CellDynamic::new(' ' as u16, 0xFFFFFF, 0x000000));
-    }
-
-    for y in 0..min(old_size.1, new_size.1) {
-        for x in 0..min(old_size.0, new_size.0) {
-            let new_idx = (y * new_size.0 + x) as usize;
-            let old_idx = (y * old_size.0 + x) as usize;
-            new_cells[new_idx] = cells[old_idx];
-        }
-    }
-
-    new_cells
-}
-
 fn create_vao(gl: &WebGl2RenderingContext) -> Result<web_sys::WebGlVertexArrayObject, Error> {
     gl.create_vertex_array().ok_or(Error::vertex_array_creation_failed())
 }
@@ -467,6 +930,22 @@ fn setup_buffers(
     cell_data: &[CellDynamic],
     cell_size: (i32, i32),
 ) -> Result<TerminalBuffers, Error> {
+    let indices = [0, 1, 2, 0, 3, 1];
+
+    Ok(TerminalBuffers {
+        vao,
+        vertices: create_quad_buffer(gl, cell_size)?,
+        instance_pos: create_static_instance_buffer(gl, cell_pos)?,
+        instance_cell: create_dynamic_instance_buffer(gl, cell_data)?,
+        indices: create_buffer_u8(gl, GL::ELEMENT_ARRAY_BUFFER, &indices, GL::STATIC_DRAW)?,
+    })
+}
+
+/// Creates the vertex buffer of the quad drawn for every cell.
+fn create_quad_buffer(
+    gl: &WebGl2RenderingContext,
+    cell_size: (i32, i32),
+) -> Result<web_sys::WebGlBuffer, Error> {
     let (w, h) = (cell_size.0 as f32, cell_size.1 as f32);
 
     // let overlap = 0.5;
@@ -479,15 +958,8 @@ fn setup_buffers(
         w + overlap, h + overlap, 1.0, 1.0, // bottom-right
            -overlap,    -overlap, 0.0, 0.0  // top-left
     ];
-    let indices = [0, 1, 2, 0, 3, 1];
 
-    Ok(TerminalBuffers {
-        vao,
-        vertices: create_buffer_f32(gl, GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW)?,
-        instance_pos: create_static_instance_buffer(gl, cell_pos)?,
-        instance_cell: create_dynamic_instance_buffer(gl, cell_data)?,
-        indices: create_buffer_u8(gl, GL::ELEMENT_ARRAY_BUFFER, &indices, GL::STATIC_DRAW)?,
-    })
+    create_buffer_f32(gl, GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW)
 }
 
 fn create_buffer_u8(
@@ -599,15 +1071,20 @@ impl Drawable for TerminalGrid {
 
         gl.bind_vertex_array(Some(&self.buffers.vao));
 
+        self.highlight_texture.bind(gl, 1);
         self.atlas.bind(gl, 0);
         self.ubo_vertex.bind(context.gl);
         self.ubo_fragment.bind(context.gl);
+        self.ubo_palette.bind(context.gl);
+        self.ubo_cursor.bind(context.gl);
+        self.ubo_selection.bind(context.gl);
         gl.uniform1i(Some(&self.sampler_loc), 0);
+        gl.uniform1i(Some(&self.highlights_loc), 1);
     }
 
     fn draw(&self, context: &mut RenderContext) {
         let gl = context.gl;
-        let cell_count = self.cells.len() as i32;
+        let cell_count = self.buffer.cell_count() as i32;
         gl.draw_elements_instanced_with_i32(GL::TRIANGLES, 6, GL::UNSIGNED_BYTE, 0, cell_count);
     }
 
@@ -615,9 +1092,15 @@ impl Drawable for TerminalGrid {
         let gl = context.gl;
         gl.bind_vertex_array(None);
         gl.bind_texture(GL::TEXTURE_2D_ARRAY, None);
+        gl.active_texture(GL::TEXTURE1);
+        gl.bind_texture(GL::TEXTURE_2D, None);
+        gl.active_texture(GL::TEXTURE0);
 
         self.ubo_vertex.unbind(gl);
         self.ubo_fragment.unbind(gl);
+        self.ubo_palette.unbind(gl);
+        self.ubo_cursor.unbind(gl);
+        self.ubo_selection.unbind(gl);
     }
 }
 
@@ -625,19 +1108,20 @@ impl Drawable for TerminalGrid {
 ///
 /// `CellData` represents the visual content of one terminal cell, including
 /// the character to display and its foreground and background colors.
-/// Colors are specified as RGB values packed into 32-bit integers.
+/// Colors are either RGB values packed into 32-bit integers, or references
+/// into the terminal's [`Palette`] (see [`Color`]).
 ///
 /// # Color Format
-/// Colors use the format 0xRRGGBB where:
+/// RGB colors use the format 0xRRGGBB where:
 /// - RR: Red component
 /// - GG: Green component  
 /// - BB: Blue component
 #[derive(Debug, Copy, Clone)]
 pub struct CellData<'a> {
-    symbol: &'a str,
-    style_bits: u16,
-    fg: u32,
-    bg: u32,
+    pub(super) symbol: &'a str,
+    pub(super) style_bits: u16,
+    pub(super) fg: Color,
+    pub(super) bg: Color,
 }
 
 impl<'a> CellData<'a> {
@@ -680,8 +1164,25 @@ impl<'a> CellData<'a> {
     /// # Panics
     /// Debug builds will panic if `style_bits` contains any invalid bits.
     pub fn new_with_style_bits(symbol: &'a str, style_bits: u16, fg: u32, bg: u32) -> Self {
-        // emoji and glyph base mask should not intersect with style bits
-        debug_assert!(0x81FF & style_bits == 0, "Invalid style bits: {style_bits:#04x}");
+        Self::new_with_colors(symbol, style_bits, Color::Rgb(fg), Color::Rgb(bg))
+    }
+
+    /// Creates new cell data with pre-encoded style bits and [`Color`] values.
+    ///
+    /// Unlike [`CellData::new_with_style_bits`], the colors may refer to the
+    /// terminal's [`Palette`], in which case they follow palette changes.
+    ///
+    /// # Parameters
+    /// * `symbol` - Character to display
+    /// * `style_bits` - Pre-encoded style flags, see [`CellData::new_with_style_bits`]
+    /// * `fg` - Foreground color
+    /// * `bg` - Background color
+    ///
+    /// # Panics
+    /// Debug builds will panic if `style_bits` contains any invalid bits.
+    pub fn new_with_colors(symbol: &'a str, style_bits: u16, fg: Color, bg: Color) -> Self {
+        // glyph base mask and palette flags should not intersect with style bits
+        debug_assert!(0xC1FF & style_bits == 0, "Invalid style bits: {style_bits:#04x}");
         Self { symbol, style_bits, fg, bg }
     }
 }
@@ -725,6 +1226,10 @@ struct CellStatic {
 /// - Bytes 2-4: Foreground color RGB (3 bytes)
 /// - Bytes 5-7: Background color RGB (3 bytes)
 ///
+/// When bit 14 (foreground) or bit 15 (background) of the glyph id is set,
+/// the first two bytes of the color hold a little-endian palette slot instead
+/// of RGB; see [`Palette`].
+///
 /// This compact layout minimizes GPU memory usage and allows efficient
 /// instanced rendering of the entire terminal grid.
 ///
@@ -739,7 +1244,7 @@ struct CellStatic {
 ///
 /// # Buffer Upload
 /// Uploaded to GPU using `GL::DYNAMIC_DRAW` for efficient updates.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C, align(4))]
 pub struct CellDynamic {
     /// Packed cell data:
@@ -769,6 +1274,13 @@ impl CellStatic {
 }
 
 impl CellDynamic {
+    /// Glyph id flag marking the foreground color as a palette slot.
+    pub const FG_INDEXED_FLAG: u16 = 0x4000;
+    /// Glyph id flag marking the background color as a palette slot.
+    pub const BG_INDEXED_FLAG: u16 = 0x8000;
+
+    const COLOR_FLAGS: u16 = Self::FG_INDEXED_FLAG | Self::BG_INDEXED_FLAG;
+
     #[inline]
     pub fn new(glyph_id: u16, fg: u32, bg: u32) -> Self {
         let mut data = [0; 8];
@@ -791,18 +1303,25 @@ impl CellDynamic {
         Self { data }
     }
 
+    /// Creates a new cell with colors that may refer to the terminal's [`Palette`].
+    #[inline]
+    pub fn new_with_colors(glyph_id: u16, fg: Color, bg: Color) -> Self {
+        let mut cell = Self::new(glyph_id & !Self::COLOR_FLAGS, 0, 0);
+        cell.set_fg(fg);
+        cell.set_bg(bg);
+        cell
+    }
+
     /// Overwrites the current cell style bits with the provided style bits.
     pub fn style(&mut self, style_bits: u16) {
-        let glyph_id = u16::from_le_bytes([self.data[0], self.data[1]]);
-        let glyph_id = glyph_id & (Glyph::GLYPH_ID_MASK | Glyph::EMOJI_FLAG);
+        let glyph_id = self.glyph_id();
+        let glyph_id = glyph_id & (Glyph::GLYPH_ID_MASK | Glyph::EMOJI_FLAG | Self::COLOR_FLAGS);
         let glyph_id = glyph_id | style_bits;
 
-        let glyph_id = glyph_id.to_le_bytes();
-        self.data[0] = glyph_id[0];
-        self.data[1] = glyph_id[1];
+        self.set_glyph_id(glyph_id);
     }
 
-    /// Sets the foreground color of the cell.
+    /// Swaps the foreground and background colors of the cell.
     pub fn flip_colors(&mut self) {
         // swap foreground and background colors
         let fg = [self.data[2], self.data[3], self.data[4]];
@@ -812,31 +1331,70 @@ impl CellDynamic {
         self.data[5] = fg[0]; // R
         self.data[6] = fg[1]; // G
         self.data[7] = fg[2]; // B
+
+        // the palette flags follow their colors
+        let glyph_id = self.glyph_id();
+        let fg_indexed = glyph_id & Self::FG_INDEXED_FLAG != 0;
+        let bg_indexed = glyph_id & Self::BG_INDEXED_FLAG != 0;
+        let mut glyph_id = glyph_id & !Self::COLOR_FLAGS;
+        if bg_indexed {
+            glyph_id |= Self::FG_INDEXED_FLAG;
+        }
+        if fg_indexed {
+            glyph_id |= Self::BG_INDEXED_FLAG;
+        }
+        self.set_glyph_id(glyph_id);
     }
 
     /// Sets the foreground color of the cell.
     pub fn fg_color(&mut self, fg: u32) {
-        let fg = fg.to_le_bytes();
-        self.data[2] = fg[2]; // R
-        self.data[3] = fg[1]; // G
-        self.data[4] = fg[0]; // B
+        self.set_fg(Color::Rgb(fg));
     }
 
     /// Sets the background color of the cell.
     pub fn bg_color(&mut self, bg: u32) {
-        let bg = bg.to_le_bytes();
-        self.data[5] = bg[2]; // R
-        self.data[6] = bg[1]; // G
-        self.data[7] = bg[0]; // B
+        self.set_bg(Color::Rgb(bg));
+    }
+
+    /// Sets the foreground color of the cell.
+    pub fn set_fg(&mut self, fg: Color) {
+        let (bytes, indexed) = Self::encode_color(fg);
+        self.data[2..5].copy_from_slice(&bytes);
+        self.set_color_flag(Self::FG_INDEXED_FLAG, indexed);
+    }
+
+    /// Sets the background color of the cell.
+    pub fn set_bg(&mut self, bg: Color) {
+        let (bytes, indexed) = Self::encode_color(bg);
+        self.data[5..8].copy_from_slice(&bytes);
+        self.set_color_flag(Self::BG_INDEXED_FLAG, indexed);
+    }
+
+    /// Returns the foreground color of the cell.
+    pub fn fg(&self) -> Color {
+        let indexed = self.glyph_id() & Self::FG_INDEXED_FLAG != 0;
+        Self::decode_color([self.data[2], self.data[3], self.data[4]], indexed)
+    }
+
+    /// Returns the background color of the cell.
+    pub fn bg(&self) -> Color {
+        let indexed = self.glyph_id() & Self::BG_INDEXED_FLAG != 0;
+        Self::decode_color([self.data[5], self.data[6], self.data[7]], indexed)
     }
 
     /// Returns foreground color as a packed RGB value.
+    ///
+    /// For palette colors, this is the raw channel value; use [`CellDynamic::fg`]
+    /// to get the [`Color`].
     pub fn get_fg_color(&self) -> u32 {
         // unpack foreground color from data
         ((self.data[2] as u32) << 16) | ((self.data[3] as u32) << 8) | (self.data[4] as u32)
     }
 
     /// Returns background color as a packed RGB value.
+    ///
+    /// For palette colors, this is the raw channel value; use [`CellDynamic::bg`]
+    /// to get the [`Color`].
     pub fn get_bg_color(&self) -> u32 {
         // unpack background color from data
         ((self.data[5] as u32) << 16) | ((self.data[6] as u32) << 8) | (self.data[7] as u32)
@@ -844,19 +1402,56 @@ impl CellDynamic {
 
     pub fn get_style(&self) -> u16 {
         // unpack glyph ID from the first two bytes
-        self.glyph_id() & !(Glyph::GLYPH_ID_MASK | Glyph::EMOJI_FLAG)
+        self.glyph_id() & !(Glyph::GLYPH_ID_MASK | Glyph::EMOJI_FLAG | Self::COLOR_FLAGS)
     }
 
-    fn glyph_id(&self) -> u16 {
+    pub(crate) fn glyph_id(&self) -> u16 {
         u16::from_le_bytes([self.data[0], self.data[1]])
     }
+
+    fn set_color_flag(&mut self, flag: u16, enabled: bool) {
+        let glyph_id = self.glyph_id() & !flag;
+        self.set_glyph_id(if enabled { glyph_id | flag } else { glyph_id });
+    }
+
+    /// Encodes a color into the three bytes of a color channel.
+    fn encode_color(color: Color) -> ([u8; 3], bool) {
+        match color.palette_slot() {
+            // palette slots are stored little-endian in the first two bytes
+            Some(slot) => {
+                let [lo, hi] = slot.to_le_bytes();
+                ([lo, hi, 0], true)
+            },
+            None => {
+                let [b, g, r, _] = color.to_packed().to_le_bytes();
+                ([r, g, b], false)
+            },
+        }
+    }
+
+    fn decode_color(bytes: [u8; 3], indexed: bool) -> Color {
+        if indexed {
+            let slot = u16::from_le_bytes([bytes[0], bytes[1]]);
+            // slots are only written by `encode_color`
+            Color::from_palette_slot(slot).unwrap_or(Color::DefaultBg)
+        } else {
+            Color::Rgb(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+        }
+    }
+
+    fn set_glyph_id(&mut self, glyph_id: u16) {
+        let glyph_id = glyph_id.to_le_bytes();
+        self.data[0] = glyph_id[0];
+        self.data[1] = glyph_id[1];
+    }
 }
 
 #[repr(C, align(16))] // std140 layout requires proper alignment
 struct CellVertexUbo {
     pub projection: [f32; 16], // mat4
     pub cell_size: [f32; 2],   // vec2 - screen cell size
-    pub _padding: [f32; 2],
+    pub row_offset: f32,       // instance buffer row holding the top viewport row
+    pub grid_rows: f32,        // number of rows in the instance buffer
 }
 
 #[repr(C, align(16))] // std140 layout requires proper alignment
@@ -869,16 +1464,44 @@ struct CellFragmentUbo {
     pub _padding: [f32; 2],
 }
 
+#[repr(C, align(16))] // std140 layout requires proper alignment
+struct CellPaletteUbo {
+    pub colors: [[f32; 4]; Palette::SLOT_COUNT], // vec4[258] - indexed colors, default fg/bg
+}
+
+#[repr(C, align(16))] // std140 layout requires proper alignment
+struct CellCursorUbo {
+    pub color: [f32; 4],      // vec4 - cursor color; a = 0.0 uses the cell foreground
+    pub text_color: [f32; 4], // vec4 - text under block cursor; a = 0.0 uses the cell background
+    pub position: [f32; 2],   // vec2 - cursor cell position; negative when hidden
+    pub shape: f32,           // cursor shape (0 = block, 1 = hollow, 2 = bar, 3 = underline)
+    pub blink_interval: f32,  // duration of each blink phase in seconds; 0.0 = steady
+    pub thickness: [f32; 2],  // vec2 - stroke width as fraction of cell size
+    pub time: f32,            // seconds since the start of the blink cycle
+    pub _padding: f32,
+}
+
+#[repr(C, align(16))] // std140 layout requires proper alignment
+struct CellSelectionUbo {
+    pub color: [f32; 4],      // vec4 - highlight color; a = opacity
+    pub text_color: [f32; 4], // vec4 - text of selected cells; a = 0.0 keeps the cell foreground
+    pub range: [f32; 4],      // vec4 - first and last selected cell as (col, row, col, row)
+    pub link_range: [f32; 4], // vec4 - first and last cell of the hovered link; negative if none
+    pub mode: f32,            // -1 = no selection, 0 = block, 1 = linear
+    pub _padding: [f32; 3],
+}
+
 impl CellVertexUbo {
     pub const BINDING_POINT: u32 = 0;
 
-    fn new(canvas_size: (i32, i32), cell_size: (i32, i32)) -> Self {
+    fn new(canvas_size: (i32, i32), cell_size: (i32, i32), row_ring: RowRing) -> Self {
         let projection =
             Mat4::orthographic_from_size(canvas_size.0 as f32, canvas_size.1 as f32).data;
         Self {
             projection,
             cell_size: [cell_size.0 as f32, cell_size.1 as f32],
-            _padding: [0.0; 2], // padding to ensure proper alignment
+            row_offset: row_ring.offset() as f32,
+            grid_rows: row_ring.rows().max(1) as f32,
         }
     }
 }
@@ -904,11 +1527,118 @@ impl CellFragmentUbo {
     }
 }
 
-fn create_terminal_cell_data(cols: i32, rows: i32, fill_glyph: &[u16]) -> Vec<CellDynamic> {
-    let glyph_len = fill_glyph.len();
-    (0..cols * rows)
-        .map(|i| CellDynamic::new(fill_glyph[i as usize % glyph_len], 0x00ff_ffff, 0x0000_0000))
-        .collect()
+impl CellPaletteUbo {
+    pub const BINDING_POINT: u32 = 2;
+
+    fn new(palette: &Palette) -> Self {
+        let mut colors = [[0.0; 4]; Palette::SLOT_COUNT];
+        for (slot, color) in colors.iter_mut().enumerate() {
+            let rgb = palette.slot(slot);
+            *color = [
+                ((rgb >> 16) & 0xFF) as f32 / 255.0,
+                ((rgb >> 8) & 0xFF) as f32 / 255.0,
+                (rgb & 0xFF) as f32 / 255.0,
+                1.0,
+            ];
+        }
+
+        Self { colors }
+    }
+}
+
+impl CellCursorUbo {
+    pub const BINDING_POINT: u32 = 3;
+
+    fn new(
+        cursor: &Cursor,
+        palette: &Palette,
+        cell_size: (i32, i32),
+        viewport_offset: usize,
+        time: f32,
+    ) -> Self {
+        let rgba = |rgb: Option<u32>| match rgb {
+            Some(rgb) => [
+                ((rgb >> 16) & 0xFF) as f32 / 255.0,
+                ((rgb >> 8) & 0xFF) as f32 / 255.0,
+                (rgb & 0xFF) as f32 / 255.0,
+                1.0,
+            ],
+            None => [0.0; 4],
+        };
+
+        let position = if cursor.is_visible() {
+            // the cursor moves down with the screen when scrolled back
+            let (col, row) = cursor.position();
+            [col as f32, (row as usize + viewport_offset) as f32]
+        } else {
+            [-1.0, -1.0]
+        };
+
+        let shape = match cursor.effective_shape() {
+            CursorShape::Block => 0.0,
+            CursorShape::HollowBlock => 1.0,
+            CursorShape::Bar => 2.0,
+            CursorShape::Underline => 3.0,
+        };
+
+        Self {
+            color: rgba(cursor.resolved_color(palette)),
+            text_color: rgba(palette.cursor_text()),
+            position,
+            shape,
+            blink_interval: cursor.effective_blink_interval().unwrap_or(0) as f32 / 1000.0,
+            thickness: [
+                Cursor::THICKNESS_PX / cell_size.0 as f32,
+                Cursor::THICKNESS_PX / cell_size.1 as f32,
+            ],
+            time,
+            _padding: 0.0,
+        }
+    }
+}
+
+impl CellSelectionUbo {
+    pub const BINDING_POINT: u32 = 4;
+
+    fn new(
+        selection: Option<CellQuery>,
+        link_hover: Option<CellQuery>,
+        highlight: &SelectionHighlight,
+        palette: &Palette,
+    ) -> Self {
+        let rgba = |rgb: u32, a: f32| {
+            [
+                ((rgb >> 16) & 0xFF) as f32 / 255.0,
+                ((rgb >> 8) & 0xFF) as f32 / 255.0,
+                (rgb & 0xFF) as f32 / 255.0,
+                a,
+            ]
+        };
+
+        let (range, mode) = match selection.and_then(|q| q.range().map(|r| (r, q.mode))) {
+            Some(((first, last), mode)) => (
+                [first.0 as f32, first.1 as f32, last.0 as f32, last.1 as f32],
+                match mode {
+                    SelectionMode::Block => 0.0,
+                    SelectionMode::Linear => 1.0,
+                },
+            ),
+            None => ([0.0; 4], -1.0),
+        };
+        let link_range = match link_hover.and_then(|q| q.range()) {
+            Some((first, last)) => [first.0 as f32, first.1 as f32, last.0 as f32, last.1 as f32],
+            None => [-1.0; 4],
+        };
+
+        Self {
+            color: rgba(highlight.resolved_color(palette), highlight.opacity()),
+            text_color: palette.selection_foreground().map_or([0.0; 4], |rgb| rgba(rgb, 1.0)),
+            range,
+            link_range,
+            mode,
+            _padding: [0.0; 3],
+        }
+    }
 }
 
 mod attrib {
@@ -918,3 +1648,77 @@ mod attrib {
     pub const GRID_XY: u32 = 2;
     pub const PACKED_DEPTH_FG_BG: u32 = 3;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_dynamic_color_round_trip() {
+        let colors = [Color::Rgb(0x1a1b26), Color::Indexed(9), Color::DefaultFg, Color::DefaultBg];
+
+        for fg in colors {
+            for bg in colors {
+                let cell = CellDynamic::new_with_colors('A' as u16 | Glyph::BOLD_FLAG, fg, bg);
+                assert_eq!(cell.fg(), fg);
+                assert_eq!(cell.bg(), bg);
+                assert_eq!(cell.get_style(), Glyph::BOLD_FLAG);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cell_dynamic_flip_colors_swaps_palette_flags() {
+        let mut cell =
+            CellDynamic::new_with_colors('A' as u16, Color::Indexed(3), Color::Rgb(0x102030));
+        cell.flip_colors();
+
+        assert_eq!(cell.fg(), Color::Rgb(0x102030));
+        assert_eq!(cell.bg(), Color::Indexed(3));
+    }
+
+    #[test]
+    fn test_cursor_ubo_layout() {
+        assert_eq!(size_of::<CellCursorUbo>(), 64);
+
+        let mut cursor = Cursor::new();
+        let hidden = CellCursorUbo::new(&cursor, &Palette::default(), (10, 20), 0, 0.0);
+        assert_eq!(hidden.position, [-1.0, -1.0]);
+        assert_eq!(hidden.color[3], 0.0);
+
+        cursor.set(5, 2, CursorShape::Underline);
+        cursor.set_color(Some(Color::Rgb(0xff0000)));
+        let ubo = CellCursorUbo::new(&cursor, &Palette::default(), (10, 20), 0, 0.0);
+        assert_eq!(ubo.position, [5.0, 2.0]);
+        assert_eq!(ubo.shape, 3.0);
+        assert_eq!(ubo.color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(ubo.thickness, [0.2, 0.1]);
+
+        let scrolled = CellCursorUbo::new(&cursor, &Palette::default(), (10, 20), 3, 0.0);
+        assert_eq!(scrolled.position, [5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_vertex_ubo_layout() {
+        assert_eq!(size_of::<CellVertexUbo>(), 80);
+
+        let mut ring = RowRing::new(24);
+        ring.rotate(3);
+        let ubo = CellVertexUbo::new((800, 480), (10, 20), ring);
+        assert_eq!((ubo.row_offset, ubo.grid_rows), (3.0, 24.0));
+    }
+
+    #[test]
+    fn test_cell_dynamic_style_preserves_palette_flags() {
+        let mut cell = CellDynamic::new_with_colors('A' as u16, Color::DefaultFg, Color::DefaultBg);
+        cell.style(Glyph::UNDERLINE_FLAG);
+
+        assert_eq!(cell.fg(), Color::DefaultFg);
+        assert_eq!(cell.bg(), Color::DefaultBg);
+        assert_eq!(cell.get_style(), Glyph::UNDERLINE_FLAG);
+
+        cell.fg_color(0xff0000);
+        assert_eq!(cell.fg(), Color::Rgb(0xff0000));
+        assert_eq!(cell.bg(), Color::DefaultBg);
+    }
+}
@@ -2,6 +2,13 @@ use beamterm_data::FontAtlasData;
 
 use crate::{error::Error, gl::GL};
 
+/// RGBA texture holding one texel per cell of the terminal grid, read with
+/// `texelFetch` at the cell position.
+#[derive(Debug)]
+pub(super) struct CellTexture {
+    gl_texture: web_sys::WebGlTexture,
+}
+
 #[derive(Debug)]
 pub(super) struct Texture {
     gl_texture: web_sys::WebGlTexture,
@@ -62,3 +69,48 @@ impl Texture {
         gl.tex_parameteri(GL::TEXTURE_2D_ARRAY, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
     }
 }
+
+impl CellTexture {
+    pub(super) fn new(gl: &web_sys::WebGl2RenderingContext) -> Result<Self, Error> {
+        let gl_texture = gl.create_texture().ok_or(Error::texture_creation_failed())?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&gl_texture));
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+        gl.bind_texture(GL::TEXTURE_2D, None);
+
+        Ok(Self { gl_texture })
+    }
+
+    /// Replaces the texture contents with `texels`, in row-major order for a
+    /// grid of `(cols, rows)` cells.
+    pub(super) fn upload(
+        &self,
+        gl: &web_sys::WebGl2RenderingContext,
+        (cols, rows): (u16, u16),
+        texels: &[[u8; 4]],
+    ) -> Result<(), Error> {
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.gl_texture));
+
+        #[rustfmt::skip]
+        let result = gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            GL::TEXTURE_2D,
+            0, // level
+            GL::RGBA8 as i32,
+            cols as i32, rows as i32,
+            0, // border
+            GL::RGBA,
+            GL::UNSIGNED_BYTE,
+            Some(texels.as_flattened()),
+        );
+
+        gl.bind_texture(GL::TEXTURE_2D, None);
+        result.map_err(|_| Error::texture_creation_failed())
+    }
+
+    pub fn bind(&self, gl: &web_sys::WebGl2RenderingContext, texture_unit: u32) {
+        gl.active_texture(GL::TEXTURE0 + texture_unit);
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.gl_texture));
+    }
+}
@@ -0,0 +1,574 @@
+//! Software reference renderer for rendering terminal cells without WebGL.
+//!
+//! [`HeadlessRenderer`] rasterizes a [`CellBuffer`] into an RGBA image on the
+//! CPU, following the semantics of the cell fragment shader: glyphs are
+//! sampled from the font atlas texture with the same padding and texture
+//! coordinates, emoji take their colors from the atlas, underlines and
+//! strikethroughs are blended in the base foreground color, the hovered link
+//! is underlined, and highlights and selected cells are blended over the
+//! background. This makes it possible to write golden-image
+//! tests on a headless CI, or to render screenshots server-side.
+//!
+//! # Example
+//! ```
+//! use beamterm_renderer::{headless::HeadlessRenderer, CellBuffer, CellData, FontAtlasData, GlyphLookup};
+//!
+//! let renderer = HeadlessRenderer::new(FontAtlasData::default());
+//! let mut buffer = CellBuffer::new(GlyphLookup::new(renderer.atlas()), 20, 2);
+//! buffer.update_cell(0, 0, CellData::new_with_style_bits("$", 0, 0xffffff, 0x000000));
+//!
+//! let image = renderer.render(&buffer);
+//! let (cell_width, cell_height) = renderer.cell_size();
+//! assert_eq!(image.width(), 20 * cell_width as u32);
+//! assert_eq!(image.height(), 2 * cell_height as u32);
+//! ```
+
+use beamterm_data::{FontAtlasData, Glyph};
+
+use crate::gl::{
+    CellBuffer, CellDynamic, CellQuery, Cursor, CursorShape, Highlights, Palette,
+    SelectionHighlight,
+};
+
+/// CPU implementation of the cell shaders; see the [module docs](self).
+#[derive(Debug)]
+pub struct HeadlessRenderer {
+    /// Font atlas providing the glyph texture.
+    atlas: FontAtlasData,
+    /// Palette for resolving indexed colors.
+    palette: Palette,
+    /// Named highlights drawn below the selection.
+    highlights: Highlights,
+    /// Selection drawn highlighted.
+    selection: Option<CellQuery>,
+    /// Appearance of selected cells.
+    selection_highlight: SelectionHighlight,
+    /// Hyperlink drawn underlined, as when hovered.
+    hovered_link: Option<CellQuery>,
+    /// Cursor drawn on top of the cells.
+    cursor: Option<Cursor>,
+    /// Seconds since the start of the cursor blink cycle.
+    time: f32,
+}
+
+/// An RGBA image with 8 bits per channel, stored row by row from the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Uniform state of the fragment shader, in the same units.
+#[derive(Debug, Clone, Copy)]
+struct FragmentParams {
+    padding_frac: [f32; 2],
+    underline: (f32, f32),
+    strikethrough: (f32, f32),
+}
+
+/// Overlays of a cell in the fragment shader.
+#[derive(Debug, Clone, Copy)]
+struct CellOverlays {
+    /// Highlight color; the alpha channel is the opacity.
+    highlight: [u8; 4],
+    selected: bool,
+    link_hovered: bool,
+}
+
+/// Cursor state of the fragment shader, resolved for a frame.
+#[derive(Debug, Clone, Copy)]
+struct CursorParams {
+    position: (u16, usize),
+    shape: CursorShape,
+    color: Option<[f32; 3]>,
+    text_color: Option<[f32; 3]>,
+    thickness: [f32; 2],
+    visible: bool,
+}
+
+impl HeadlessRenderer {
+    /// Creates a renderer for the given font atlas, using the default palette.
+    pub fn new(atlas: FontAtlasData) -> Self {
+        Self {
+            atlas,
+            palette: Palette::default(),
+            highlights: Highlights::default(),
+            selection: None,
+            selection_highlight: SelectionHighlight::new(),
+            hovered_link: None,
+            cursor: None,
+            time: 0.0,
+        }
+    }
+
+    /// Returns the font atlas; build the [`CellBuffer`]'s glyph lookup from it.
+    pub fn atlas(&self) -> &FontAtlasData {
+        &self.atlas
+    }
+
+    /// Returns the unpadded cell dimensions in pixels.
+    pub fn cell_size(&self) -> (i32, i32) {
+        let (w, h) = self.atlas.cell_size;
+        (w - 2 * FontAtlasData::PADDING, h - 2 * FontAtlasData::PADDING)
+    }
+
+    /// Returns the palette used for resolving indexed colors.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Sets the palette used for resolving indexed colors.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Sets the named highlights drawn below the selection.
+    pub fn set_highlights(&mut self, highlights: Highlights) {
+        self.highlights = highlights;
+    }
+
+    /// Sets the selection drawn highlighted.
+    pub fn set_selection(&mut self, selection: Option<CellQuery>) {
+        self.selection = selection;
+    }
+
+    /// Sets the appearance of selected cells.
+    pub fn set_selection_highlight(&mut self, highlight: SelectionHighlight) {
+        self.selection_highlight = highlight;
+    }
+
+    /// Sets the hyperlink drawn underlined, as when hovered; see
+    /// [`CellBuffer::link_at`].
+    pub fn set_hovered_link(&mut self, link: Option<CellQuery>) {
+        self.hovered_link = link;
+    }
+
+    /// Sets the cursor drawn on top of the cells.
+    pub fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+    }
+
+    /// Sets the time in seconds since the start of the cursor blink cycle,
+    /// which determines whether a blinking cursor is drawn.
+    pub fn set_time(&mut self, seconds: f32) {
+        self.time = seconds;
+    }
+
+    /// Renders the cells displayed by the buffer's viewport.
+    pub fn render(&self, buffer: &CellBuffer) -> RgbaImage {
+        let (cols, rows) = buffer.size();
+        let (cell_w, cell_h) = self.cell_size();
+        let (cell_w, cell_h) = (cell_w.max(0) as usize, cell_h.max(0) as usize);
+
+        let width = cols as usize * cell_w;
+        let mut pixels = vec![0; width * rows as usize * cell_h * 4];
+
+        let params = self.fragment_params();
+        let cursor = self.cursor_params(buffer.viewport_offset());
+        let highlights = self.highlights.rasterize((cols, rows), &self.palette);
+
+        for row in 0..rows {
+            for col in 0..cols {
+                let Some(cell) = buffer.displayed_cell(col, row) else {
+                    continue;
+                };
+                let overlays = CellOverlays {
+                    highlight: highlights[row as usize * cols as usize + col as usize],
+                    selected: self.selection.is_some_and(|q| q.contains(col, row, cols)),
+                    link_hovered: self.hovered_link.is_some_and(|q| q.contains(col, row, cols)),
+                };
+
+                let cursor = cursor.filter(|c| c.position == (col, row as usize));
+                for y in 0..cell_h {
+                    for x in 0..cell_w {
+                        let tex_coord =
+                            [(x as f32 + 0.5) / cell_w as f32, (y as f32 + 0.5) / cell_h as f32];
+                        let rgb = self.shade(&cell, tex_coord, &params, overlays, cursor);
+
+                        let px = (row as usize * cell_h + y) * width + col as usize * cell_w + x;
+                        pixels[px * 4..px * 4 + 4].copy_from_slice(&to_rgba8(rgb));
+                    }
+                }
+            }
+        }
+
+        RgbaImage {
+            width: width as u32,
+            height: (rows as usize * cell_h) as u32,
+            pixels,
+        }
+    }
+
+    fn fragment_params(&self) -> FragmentParams {
+        let (w, h) = self.cell_size();
+        let line = |d: beamterm_data::LineDecoration| (d.position, d.thickness);
+        FragmentParams {
+            padding_frac: [
+                FontAtlasData::PADDING as f32 / w as f32,
+                FontAtlasData::PADDING as f32 / h as f32,
+            ],
+            underline: line(self.atlas.underline),
+            strikethrough: line(self.atlas.strikethrough),
+        }
+    }
+
+    fn cursor_params(&self, viewport_offset: usize) -> Option<CursorParams> {
+        let cursor = self.cursor.as_ref().filter(|c| c.is_visible())?;
+        let (w, h) = self.cell_size();
+
+        // blinking cursors are visible during the first half of each cycle
+        let visible = match cursor.effective_blink_interval() {
+            Some(ms) => {
+                let interval = ms as f32 / 1000.0;
+                self.time.rem_euclid(2.0 * interval) < interval
+            },
+            None => true,
+        };
+
+        // the cursor moves down with the screen when scrolled back
+        let (col, row) = cursor.position();
+        Some(CursorParams {
+            position: (col, row as usize + viewport_offset),
+            shape: cursor.effective_shape(),
+            color: cursor.resolved_color(&self.palette).map(unpack_rgb),
+            text_color: self.palette.cursor_text().map(unpack_rgb),
+            thickness: [Cursor::THICKNESS_PX / w as f32, Cursor::THICKNESS_PX / h as f32],
+            visible,
+        })
+    }
+
+    /// Computes the color of a fragment at `tex_coord` within a cell, as
+    /// `cell.frag` does.
+    fn shade(
+        &self,
+        cell: &CellDynamic,
+        tex_coord: [f32; 2],
+        params: &FragmentParams,
+        overlays: CellOverlays,
+        cursor: Option<CursorParams>,
+    ) -> [f32; 3] {
+        let glyph_id = cell.glyph_id();
+        let flag = |mask: u16| if glyph_id & mask != 0 { 1.0 } else { 0.0 };
+
+        let underline = horizontal_line(tex_coord, params.underline);
+        let line_alpha = f32::max(
+            underline * flag(Glyph::UNDERLINE_FLAG),
+            horizontal_line(tex_coord, params.strikethrough) * flag(Glyph::STRIKETHROUGH_FLAG),
+        );
+
+        // the hovered link is underlined
+        let line_alpha = if overlays.link_hovered { line_alpha.max(underline) } else { line_alpha };
+
+        let glyph = self.sample(glyph_id, tex_coord, params.padding_frac);
+        let emoji_factor = flag(Glyph::EMOJI_FLAG);
+
+        // emoji colors are sampled from the texture; lines use the base foreground
+        let base_fg = unpack_rgb(self.palette.resolve(cell.fg()));
+        let fg = mix3(base_fg, [glyph[0], glyph[1], glyph[2]], emoji_factor);
+        let mut fg = mix3(fg, base_fg, line_alpha);
+        let a = glyph[3].max(line_alpha);
+
+        let mut bg = unpack_rgb(self.palette.resolve(cell.bg()));
+
+        // highlights and the selection are blended over the background; emoji keep their colors
+        let [r, g, b, alpha] = overlays.highlight.map(|c| c as f32 / 255.0);
+        bg = mix3(bg, [r, g, b], alpha);
+
+        if overlays.selected {
+            let highlight = unpack_rgb(self.selection_highlight.resolved_color(&self.palette));
+            bg = mix3(bg, highlight, self.selection_highlight.opacity());
+            if let Some(text) = self.palette.selection_foreground() {
+                fg = mix3(fg, unpack_rgb(text), 1.0 - emoji_factor);
+            }
+        }
+
+        let Some(cursor) = cursor else {
+            return mix3(bg, fg, a);
+        };
+
+        let coverage = cursor_coverage(&cursor, tex_coord);
+        let cursor_color = cursor.color.unwrap_or(base_fg);
+        if cursor.shape == CursorShape::Block {
+            // block cursor: fill the cell, drawing the glyph in the cursor text color
+            let cursor_text = cursor.text_color.unwrap_or(bg);
+            bg = mix3(bg, cursor_color, coverage);
+            fg = mix3(fg, cursor_text, coverage * (1.0 - emoji_factor));
+            mix3(bg, fg, a)
+        } else {
+            mix3(mix3(bg, fg, a), cursor_color, coverage)
+        }
+    }
+
+    /// Samples the atlas texture with nearest filtering, returning normalized RGBA.
+    fn sample(&self, glyph_id: u16, tex_coord: [f32; 2], padding_frac: [f32; 2]) -> [f32; 4] {
+        let (tex_w, tex_h, layers) = self.atlas.texture_dimensions;
+        if tex_w <= 0 || tex_h <= 0 || layers <= 0 {
+            return [0.0; 4];
+        }
+
+        let glyph_index = glyph_id as u32;
+        let layer = ((glyph_index & 0x0FFF) >> 4).min(layers as u32 - 1) as usize;
+        let pos_in_layer = (glyph_index & 0x0F) as f32;
+
+        let inner = [
+            tex_coord[0] * (1.0 - 2.0 * padding_frac[0]) + padding_frac[0],
+            tex_coord[1] * (1.0 - 2.0 * padding_frac[1]) + padding_frac[1],
+        ];
+        let u = (pos_in_layer + inner[0] + 0.001) * 0.0625;
+        let v = inner[1] + 0.001;
+
+        // clamp-to-edge wrapping
+        let x = ((u * tex_w as f32) as usize).min(tex_w as usize - 1);
+        let y = ((v * tex_h as f32) as usize).min(tex_h as usize - 1);
+
+        let idx = ((layer * tex_h as usize + y) * tex_w as usize + x) * 4;
+        match self.atlas.texture_data.get(idx..idx + 4) {
+            Some(&[r, g, b, a]) => [r, g, b, a].map(|c| c as f32 / 255.0),
+            _ => [0.0; 4],
+        }
+    }
+}
+
+impl RgbaImage {
+    /// Creates an image from RGBA bytes, row by row from the top.
+    ///
+    /// # Panics
+    /// Panics if `pixels` does not hold exactly `width * height * 4` bytes.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(pixels.len(), width as usize * height as usize * 4, "invalid image size");
+        Self { width, height, pixels }
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the pixel data as RGBA bytes, row by row from the top.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the pixel at `(x, y)`, or `None` if outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let idx = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[idx..idx + 4].try_into().ok()
+    }
+
+    /// Consumes the image, returning the RGBA bytes.
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+}
+
+fn horizontal_line(tex_coord: [f32; 2], (center, thickness): (f32, f32)) -> f32 {
+    1.0 - smoothstep(0.0, thickness, (tex_coord[1] - center).abs())
+}
+
+fn cursor_coverage(cursor: &CursorParams, tex_coord: [f32; 2]) -> f32 {
+    if !cursor.visible {
+        return 0.0;
+    }
+
+    let t = cursor.thickness;
+    let covered = match cursor.shape {
+        CursorShape::Block => true,
+        CursorShape::HollowBlock => {
+            tex_coord[0] < t[0]
+                || tex_coord[1] < t[1]
+                || tex_coord[0] > 1.0 - t[0]
+                || tex_coord[1] > 1.0 - t[1]
+        },
+        CursorShape::Bar => tex_coord[0] < t[0],
+        CursorShape::Underline => tex_coord[1] > 1.0 - t[1],
+    };
+
+    if covered {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+fn unpack_rgb(rgb: u32) -> [f32; 3] {
+    [16, 8, 0].map(|shift| ((rgb >> shift) & 0xFF) as f32 / 255.0)
+}
+
+fn to_rgba8(rgb: [f32; 3]) -> [u8; 4] {
+    let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    [r, g, b, 0xFF]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::{select, CellData, Color, GlyphLookup, Highlight, SelectionMode};
+
+    const FG: u32 = 0xff8800;
+    const BG: u32 = 0x102030;
+
+    fn setup(cols: u16, rows: u16) -> (HeadlessRenderer, CellBuffer) {
+        let renderer = HeadlessRenderer::new(FontAtlasData::default());
+        let mut buffer = CellBuffer::new(GlyphLookup::new(renderer.atlas()), cols, rows);
+        let blank = CellData::new_with_style_bits(" ", 0, FG, BG);
+        buffer.update_cells(std::iter::repeat_n(blank, cols as usize * rows as usize));
+        (renderer, buffer)
+    }
+
+    fn rgb(pixel: [u8; 4]) -> u32 {
+        u32::from_be_bytes([0, pixel[0], pixel[1], pixel[2]])
+    }
+
+    /// Returns the distinct colors of a cell, in order of appearance.
+    fn cell_colors(renderer: &HeadlessRenderer, image: &RgbaImage, col: u32, row: u32) -> Vec<u32> {
+        let (w, h) = renderer.cell_size();
+        let mut colors = Vec::new();
+        for y in row * h as u32..(row + 1) * h as u32 {
+            for x in col * w as u32..(col + 1) * w as u32 {
+                let color = rgb(image.pixel(x, y).unwrap());
+                if !colors.contains(&color) {
+                    colors.push(color);
+                }
+            }
+        }
+        colors
+    }
+
+    #[test]
+    fn test_blank_cells_are_background() {
+        let (renderer, buffer) = setup(3, 2);
+        let image = renderer.render(&buffer);
+
+        let (w, h) = renderer.cell_size();
+        assert_eq!((image.width(), image.height()), (3 * w as u32, 2 * h as u32));
+        assert_eq!(image.pixels().len(), (image.width() * image.height() * 4) as usize);
+        assert!(image.pixels().chunks(4).all(|p| rgb(p.try_into().unwrap()) == BG));
+    }
+
+    #[test]
+    fn test_glyphs_and_lines_use_foreground() {
+        let (mut renderer, mut buffer) = setup(3, 1);
+        buffer.update_cell(0, 0, CellData::new_with_style_bits("#", 0, FG, BG));
+        buffer.update_cell(1, 0, CellData::new_with_style_bits(" ", Glyph::UNDERLINE_FLAG, FG, BG));
+        let image = renderer.render(&buffer);
+
+        // anti-aliased glyph edges blend between the two colors
+        let glyph = cell_colors(&renderer, &image, 0, 0);
+        assert!(glyph.contains(&BG) && glyph.contains(&FG), "{glyph:x?}");
+
+        // the underline of a blank cell is drawn near the bottom
+        let (w, h) = renderer.cell_size();
+        let underline_rows: Vec<_> = (0..h as u32)
+            .filter(|&y| rgb(image.pixel(w as u32 + w as u32 / 2, y).unwrap()) != BG)
+            .collect();
+        assert!(!underline_rows.is_empty());
+        assert!(underline_rows.iter().all(|&y| y > h as u32 / 2), "{underline_rows:?}");
+
+        assert_eq!(cell_colors(&renderer, &image, 2, 0), [BG]);
+
+        // the hovered link is underlined like underlined cells
+        renderer.set_hovered_link(Some(select(SelectionMode::Linear).start((2, 0)).end((2, 0))));
+        let image = renderer.render(&buffer);
+        assert_eq!(cell_colors(&renderer, &image, 2, 0), cell_colors(&renderer, &image, 1, 0));
+    }
+
+    #[test]
+    fn test_selection_highlight() {
+        let (mut renderer, mut buffer) = setup(3, 1);
+        renderer.set_selection(Some(select(SelectionMode::Linear).start((1, 0)).end((2, 0))));
+
+        // inverted cells are highlighted like any other
+        let mut inverted = buffer.resolve_cell(CellData::new_with_style_bits(" ", 0, FG, BG));
+        inverted.flip_colors();
+        *buffer.cell_mut(2, 0).unwrap() = inverted;
+
+        let mut highlight = SelectionHighlight::new();
+        highlight.set_color(Some(Color::Rgb(0x00ff00)));
+        highlight.set_opacity(1.0);
+        renderer.set_selection_highlight(highlight);
+        let image = renderer.render(&buffer);
+
+        assert_eq!(cell_colors(&renderer, &image, 0, 0), [BG]);
+        assert_eq!(cell_colors(&renderer, &image, 1, 0), [0x00ff00]);
+        assert_eq!(cell_colors(&renderer, &image, 2, 0), [0x00ff00]);
+
+        // by default, the palette selection background is blended over the cells
+        let mut palette = Palette::default();
+        palette.set_selection_background(Some(0xffffff));
+        renderer.set_palette(palette);
+        renderer.set_selection_highlight(SelectionHighlight::new());
+        let image = renderer.render(&buffer);
+
+        assert_eq!(cell_colors(&renderer, &image, 1, 0), [0x707983]);
+    }
+
+    #[test]
+    fn test_highlights_below_selection() {
+        let (mut renderer, buffer) = setup(3, 1);
+
+        let mut highlights = Highlights::default();
+        let region = select(SelectionMode::Linear).start((0, 0)).end((1, 0));
+        highlights
+            .insert("match", Highlight::new(Color::Rgb(0x0000ff)).region(region).opacity(1.0));
+        renderer.set_highlights(highlights);
+
+        let mut selection = SelectionHighlight::new();
+        selection.set_color(Some(Color::Rgb(0x00ff00)));
+        selection.set_opacity(1.0);
+        renderer.set_selection_highlight(selection);
+        renderer.set_selection(Some(select(SelectionMode::Linear).start((1, 0)).end((1, 0))));
+        let image = renderer.render(&buffer);
+
+        assert_eq!(cell_colors(&renderer, &image, 0, 0), [0x0000ff]);
+        assert_eq!(cell_colors(&renderer, &image, 1, 0), [0x00ff00]);
+        assert_eq!(cell_colors(&renderer, &image, 2, 0), [BG]);
+    }
+
+    #[test]
+    fn test_cursor_shapes() {
+        let (mut renderer, buffer) = setup(2, 1);
+        let mut palette = Palette::default();
+        palette.set_cursor(Some(0x00ff00));
+        renderer.set_palette(palette);
+
+        let mut cursor = Cursor::new();
+        cursor.set(1, 0, CursorShape::Block);
+        renderer.set_cursor(Some(cursor.clone()));
+        let image = renderer.render(&buffer);
+        assert_eq!(cell_colors(&renderer, &image, 0, 0), [BG]);
+        assert_eq!(cell_colors(&renderer, &image, 1, 0), [0x00ff00]);
+
+        cursor.set(0, 0, CursorShape::Bar);
+        renderer.set_cursor(Some(cursor.clone()));
+        let image = renderer.render(&buffer);
+        assert_eq!(rgb(image.pixel(0, 0).unwrap()), 0x00ff00);
+        assert_eq!(rgb(image.pixel(5, 0).unwrap()), BG);
+
+        // blinking cursors are hidden during the second half of the cycle
+        cursor.set(1, 0, CursorShape::Block);
+        cursor.set_blinking(true);
+        renderer.set_cursor(Some(cursor));
+        renderer.set_time(Cursor::BLINK_INTERVAL_MS as f32 * 1.5 / 1000.0);
+        let image = renderer.render(&buffer);
+        assert_eq!(cell_colors(&renderer, &image, 1, 0), [BG]);
+    }
+}
@@ -0,0 +1,337 @@
+//! HiDPI rendering with font atlases for several pixel scales.
+//!
+//! A font atlas is rasterized at a fixed pixel size, so an atlas made for
+//! standard displays is either blurry or tiny on displays with a
+//! `window.devicePixelRatio` of 2 or more. [`ScaledAtlases`] holds atlases of
+//! the same font rasterized for different pixel scales, and picks the one
+//! closest to the device pixel ratio. The canvas backing store is then sized
+//! at that scale relative to its CSS size, so the text keeps its displayed size
+//! while being drawn at the resolution of the display.
+//!
+//! [`PixelRatioHandler`] watches the device pixel ratio, which changes when a
+//! window moves between monitors or the page is zoomed. The terminal switches
+//! atlases on the next frame, and mouse positions, reported by the browser in
+//! CSS pixels, are mapped to cells accordingly.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::{FontAtlasData, Terminal};
+//!
+//! let atlas_1x: FontAtlasData = unimplemented!("atlas rasterized at 16px");
+//! let atlas_2x: FontAtlasData = unimplemented!("atlas rasterized at 32px");
+//!
+//! let mut terminal = Terminal::builder("#canvas")
+//!     .scaled_font_atlas(1.0, atlas_1x)
+//!     .scaled_font_atlas(2.0, atlas_2x)
+//!     .pixel_ratio_handler(|ratio| { /* terminal size may change; render a frame */ })
+//!     .build()?;
+//!
+//! // sizes are in CSS pixels
+//! terminal.resize(800, 600)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use beamterm_data::FontAtlasData;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::{js, Error, FontAtlas, TerminalGrid};
+
+/// Type alias for boxed pixel ratio callback functions.
+///
+/// Callbacks receive the new device pixel ratio, and are invoked synchronously
+/// in the browser's event loop.
+pub type PixelRatioCallback = Box<dyn FnMut(f32) + 'static>;
+
+/// Font atlases of the same font and glyphs, rasterized for different pixel
+/// scales; see the [module docs](self).
+#[derive(Debug, Default)]
+pub struct ScaledAtlases {
+    /// Atlases by ascending scale.
+    atlases: Vec<ScaledAtlas>,
+    /// Index of the atlas loaded to the GPU.
+    active: Option<usize>,
+}
+
+#[derive(Debug)]
+struct ScaledAtlas {
+    scale: f32,
+    /// Atlas data, taken while the atlas is loaded to the GPU.
+    data: Option<FontAtlasData>,
+}
+
+impl ScaledAtlases {
+    /// Adds an atlas rasterized for `scale` device pixels per CSS pixel,
+    /// replacing any atlas of the same scale.
+    pub fn insert(&mut self, scale: f32, data: FontAtlasData) {
+        let atlas = ScaledAtlas { scale, data: Some(data) };
+        match self.atlases.binary_search_by(|a| a.scale.total_cmp(&scale)) {
+            Ok(idx) => self.atlases[idx] = atlas,
+            Err(idx) => self.atlases.insert(idx, atlas),
+        }
+    }
+
+    /// Returns `true` if no atlases were added.
+    pub fn is_empty(&self) -> bool {
+        self.atlases.is_empty()
+    }
+
+    /// Returns the scales of the atlases, in ascending order.
+    pub fn scales(&self) -> Vec<f32> {
+        self.atlases.iter().map(|a| a.scale).collect()
+    }
+
+    /// Returns the scale of the atlas loaded to the GPU.
+    pub fn active_scale(&self) -> Option<f32> {
+        self.active.map(|idx| self.atlases[idx].scale)
+    }
+
+    /// Returns an error unless all atlases contain the same glyphs, as the
+    /// cells keep their glyph ids when switching atlases.
+    pub fn validate(&self) -> Result<(), Error> {
+        let glyphs = |data: &FontAtlasData| {
+            let mut glyphs: Vec<_> = data.glyphs.iter().map(|g| (g.id, g.symbol.clone())).collect();
+            glyphs.sort_unstable();
+            glyphs
+        };
+
+        let mut atlases = self.atlases.iter().filter_map(|a| a.data.as_ref());
+        let Some(first) = atlases.next().map(glyphs) else {
+            return Ok(());
+        };
+        match atlases.all(|data| glyphs(data) == first) {
+            true => Ok(()),
+            false => Err(Error::Data("Scaled font atlases must contain the same glyphs".into())),
+        }
+    }
+
+    /// Loads the atlas closest to the device pixel ratio, returning it and
+    /// its scale.
+    ///
+    /// # Errors
+    /// Returns an error if no atlases were added or the atlas fails to load.
+    pub(crate) fn load(
+        &mut self,
+        gl: &web_sys::WebGl2RenderingContext,
+        device_pixel_ratio: f32,
+    ) -> Result<(FontAtlas, f32), Error> {
+        let idx = select_scale(&self.scales(), device_pixel_ratio)
+            .ok_or_else(|| Error::Data("No scaled font atlases".into()))?;
+        let atlas = self.load_atlas(gl, idx)?;
+
+        self.active = Some(idx);
+        Ok((atlas, self.atlases[idx].scale))
+    }
+
+    /// Switches the grid to the atlas closest to the device pixel ratio,
+    /// returning its scale, or `None` if the loaded atlas is already closest.
+    ///
+    /// # Errors
+    /// Returns an error if the atlas fails to load, in which case the grid
+    /// keeps the loaded atlas.
+    pub(crate) fn switch(
+        &mut self,
+        gl: &web_sys::WebGl2RenderingContext,
+        grid: &mut TerminalGrid,
+        device_pixel_ratio: f32,
+    ) -> Result<Option<f32>, Error> {
+        let idx = select_scale(&self.scales(), device_pixel_ratio);
+        let (Some(previous), Some(idx)) = (self.active, idx) else {
+            return Ok(None);
+        };
+        if previous == idx {
+            return Ok(None);
+        }
+
+        let mut atlas = self.load_atlas(gl, idx)?;
+        if let Err(e) = grid.swap_atlas(gl, &mut atlas) {
+            // the grid keeps the previous atlas, and the new one is put back
+            self.atlases[idx].data = Some(atlas.into_data(gl));
+            return Err(e);
+        }
+
+        self.atlases[previous].data = Some(atlas.into_data(gl));
+        self.active = Some(idx);
+        Ok(Some(self.atlases[idx].scale))
+    }
+
+    /// Loads the atlas at `idx` to the GPU, taking its data.
+    fn load_atlas(
+        &mut self,
+        gl: &web_sys::WebGl2RenderingContext,
+        idx: usize,
+    ) -> Result<FontAtlas, Error> {
+        let data = self.atlases[idx]
+            .data
+            .take()
+            .ok_or_else(|| Error::Data("Scaled font atlas is already loaded".into()))?;
+        FontAtlas::load(gl, data)
+    }
+}
+
+/// Returns the index of the scale closest to the device pixel ratio, the
+/// larger scale if two are equally close.
+pub fn select_scale(scales: &[f32], device_pixel_ratio: f32) -> Option<usize> {
+    let distance = |scale: f32| (scale - device_pixel_ratio).abs();
+    (0..scales.len()).reduce(|best, idx| {
+        let (a, b) = (distance(scales[best]), distance(scales[idx]));
+        if b < a || (b == a && scales[idx] > scales[best]) {
+            idx
+        } else {
+            best
+        }
+    })
+}
+
+/// Watches `window.devicePixelRatio` for changes; see the [module docs](self).
+///
+/// The ratio is watched with a `(resolution)` media query, which is replaced
+/// by one for the new ratio on every change.
+pub struct PixelRatioHandler {
+    /// Closure for `change` events of the media query.
+    on_change: Closure<dyn FnMut()>,
+    /// Pixel ratio state shared with the event closure.
+    state: Rc<PixelRatioState>,
+}
+
+/// Watched media query, pending change and the application callback.
+#[derive(Default)]
+struct PixelRatioState {
+    query: RefCell<Option<web_sys::MediaQueryList>>,
+    /// The `change` listener of the media query.
+    listener: RefCell<Option<js_sys::Function>>,
+    changed: Cell<bool>,
+    callback: RefCell<Option<PixelRatioCallback>>,
+}
+
+impl PixelRatioHandler {
+    /// Starts watching the device pixel ratio.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if the media query cannot be watched.
+    pub fn new() -> Result<Self, Error> {
+        let state = Rc::new(PixelRatioState::default());
+
+        let change_state = state.clone();
+        let on_change = Closure::wrap(Box::new(move || {
+            change_state.changed.set(true);
+            let _ = change_state.watch();
+
+            let ratio = js::device_pixel_ratio() as f32;
+            if let Some(callback) = change_state.callback.borrow_mut().as_mut() {
+                callback(ratio);
+            }
+        }) as Box<dyn FnMut()>);
+
+        *state.listener.borrow_mut() =
+            Some(on_change.as_ref().unchecked_ref::<js_sys::Function>().clone());
+        state.watch()?;
+
+        Ok(Self { on_change, state })
+    }
+
+    /// Sets the callback notified when the device pixel ratio changes.
+    pub fn set_callback<F>(&self, callback: F)
+    where
+        F: FnMut(f32) + 'static,
+    {
+        *self.state.callback.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Returns `true`, once, if the device pixel ratio changed since the last
+    /// call.
+    pub(crate) fn take_changed(&self) -> bool {
+        self.state.changed.replace(false)
+    }
+
+    /// Stops watching the device pixel ratio.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        self.state.unwatch();
+    }
+}
+
+impl PixelRatioState {
+    /// Replaces the watched media query with one matching the current ratio.
+    fn watch(&self) -> Result<(), Error> {
+        self.unwatch();
+
+        let ratio = js::device_pixel_ratio();
+        let query = web_sys::window()
+            .and_then(|w| w.match_media(&format!("(resolution: {ratio}dppx)")).ok().flatten())
+            .ok_or_else(|| Error::Callback("Failed to watch the device pixel ratio".into()))?;
+        if let Some(listener) = self.listener.borrow().as_ref() {
+            query
+                .add_event_listener_with_callback("change", listener)
+                .map_err(|_| Error::Callback("Failed to add pixel ratio listener".into()))?;
+        }
+
+        *self.query.borrow_mut() = Some(query);
+        Ok(())
+    }
+
+    /// Removes the listener from the watched media query, if any.
+    fn unwatch(&self) {
+        if let (Some(query), Some(listener)) =
+            (self.query.borrow_mut().take(), self.listener.borrow().as_ref())
+        {
+            let _ = query.remove_event_listener_with_callback("change", listener);
+        }
+    }
+}
+
+impl Drop for PixelRatioHandler {
+    /// Automatically removes the media query listener when dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for PixelRatioHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PixelRatioHandler {{ changed: {} }}", self.state.changed.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selects_closest_scale() {
+        let scales = [1.0, 2.0, 3.0];
+        assert_eq!(select_scale(&scales, 1.0), Some(0));
+        assert_eq!(select_scale(&scales, 1.25), Some(0));
+        assert_eq!(select_scale(&scales, 1.5), Some(1));
+        assert_eq!(select_scale(&scales, 2.75), Some(2));
+        assert_eq!(select_scale(&scales, 4.0), Some(2));
+        assert_eq!(select_scale(&scales, 0.5), Some(0));
+        assert_eq!(select_scale(&[], 1.0), None);
+    }
+
+    #[test]
+    fn test_atlases_are_ordered_by_scale() {
+        let mut atlases = ScaledAtlases::default();
+        atlases.insert(2.0, FontAtlasData::default());
+        atlases.insert(1.0, FontAtlasData::default());
+        atlases.insert(2.0, FontAtlasData::default());
+
+        assert_eq!(atlases.scales(), [1.0, 2.0]);
+        assert_eq!(atlases.active_scale(), None);
+        assert!(atlases.validate().is_ok());
+
+        let mut other = FontAtlasData::default();
+        other.glyphs.pop();
+        atlases.insert(3.0, other);
+        assert!(atlases.validate().is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! IME composition and text input via an off-screen textarea.
+//!
+//! Input method editors, used for typing CJK text, and the soft keyboards of
+//! mobile devices only work with editable elements, not with a canvas.
+//! [`TerminalImeHandler`] therefore manages a transparent `<textarea>` placed
+//! next to the canvas, over the caret cell chosen by the application, so that
+//! IME candidate windows appear next to the text being composed.
+//!
+//! While composing, the in-progress text is drawn underlined over the cells
+//! starting at the caret, without modifying the cell data. Committed text is
+//! delivered through a callback, and should be forwarded to the application
+//! like keyboard input.
+//!
+//! Focusing the canvas moves focus on to the textarea; combined with a
+//! [`crate::keyboard::TerminalKeyboardHandler`], key events are then taken
+//! from the textarea.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::Terminal;
+//!
+//! let mut terminal = Terminal::builder("#canvas")
+//!     .keyboard_input_handler(|bytes| { /* send to the application */ })
+//!     .text_input_handler(|text| { /* send text.as_bytes() to the application */ })
+//!     .build()?;
+//!
+//! // keep the IME next to the application's cursor
+//! terminal.set_ime_caret(4, 2);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::{Error, TerminalGrid};
+
+/// Type alias for boxed text input callback functions.
+///
+/// Callbacks receive committed text, and are invoked synchronously in the
+/// browser's event loop.
+pub type TextInputCallback = Box<dyn FnMut(&str) + 'static>;
+
+/// Manages IME composition and text input for the terminal canvas; see the
+/// [module docs](self).
+///
+/// Text entered on soft keyboards arrives as input events rather than key
+/// events; it is delivered through the same callback. Soft keyboard line
+/// breaks are delivered as `"\r"` and deletions as DEL (`"\x7f"`).
+pub struct TerminalImeHandler {
+    /// The canvas element the textarea is positioned over.
+    canvas: web_sys::HtmlCanvasElement,
+    /// Off-screen textarea receiving composition and text input.
+    textarea: web_sys::HtmlTextAreaElement,
+    /// Terminal grid drawing the composition text.
+    grid: Rc<RefCell<TerminalGrid>>,
+    /// Caret cell as `(col, row)`, shared with the event closures.
+    caret: Rc<Cell<(u16, u16)>>,
+    /// Ratio of canvas pixels, in which cells are sized, to CSS pixels.
+    pixel_ratio: Cell<f32>,
+    /// Registered event listeners, removed on cleanup.
+    listeners: Vec<Listener>,
+}
+
+/// An event listener attached to an element.
+struct Listener {
+    target: web_sys::EventTarget,
+    event: &'static str,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl TerminalImeHandler {
+    /// Creates the textarea next to the canvas and attaches the input listeners.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if the textarea cannot be created or event
+    /// listeners cannot be attached.
+    pub fn new<F>(
+        canvas: &web_sys::HtmlCanvasElement,
+        grid: Rc<RefCell<TerminalGrid>>,
+        callback: F,
+    ) -> Result<Self, Error>
+    where
+        F: FnMut(&str) + 'static,
+    {
+        let textarea = create_textarea(canvas)?;
+        let caret = Rc::new(Cell::new((0, 0)));
+        let callback: Rc<RefCell<TextInputCallback>> = Rc::new(RefCell::new(Box::new(callback)));
+
+        let mut handler = Self {
+            canvas: canvas.clone(),
+            textarea: textarea.clone(),
+            grid: grid.clone(),
+            caret: caret.clone(),
+            pixel_ratio: Cell::new(1.0),
+            listeners: Vec::new(),
+        };
+        handler.update_textarea_position();
+
+        // the textarea takes over focus, which brings up soft keyboards
+        let focus_target = textarea.clone();
+        handler.listen(canvas, "focus", move |_| {
+            let _ = focus_target.focus();
+        })?;
+
+        let focus_grid = grid.clone();
+        handler.listen(&textarea, "focus", move |_| focus_grid.borrow_mut().set_focused(true))?;
+        let blur_grid = grid.clone();
+        handler.listen(&textarea, "blur", move |_| blur_grid.borrow_mut().set_focused(false))?;
+
+        let update_grid = grid.clone();
+        let update_caret = caret.clone();
+        handler.listen(&textarea, "compositionupdate", move |event| {
+            let text = composition_data(&event);
+            update_grid.borrow_mut().set_composition(update_caret.get(), &text);
+        })?;
+
+        let end_callback = callback.clone();
+        let end_textarea = textarea.clone();
+        handler.listen(&textarea, "compositionend", move |event| {
+            grid.borrow_mut().clear_composition();
+            end_textarea.set_value("");
+
+            let text = composition_data(&event);
+            if !text.is_empty() {
+                end_callback.borrow_mut()(&text);
+            }
+        })?;
+
+        let input_textarea = textarea.clone();
+        handler.listen(&textarea, "input", move |event| {
+            let Some(event) = event.dyn_ref::<web_sys::InputEvent>() else {
+                return;
+            };
+            if event.is_composing() {
+                return; // committed on compositionend
+            }
+
+            let value = input_textarea.value();
+            input_textarea.set_value("");
+
+            let text = match event.input_type().as_str() {
+                "insertText" | "insertReplacementText" => event.data().unwrap_or(value),
+                "insertLineBreak" => "\r".into(),
+                "deleteContentBackward" => "\x7f".into(),
+                _ => return,
+            };
+            if !text.is_empty() {
+                callback.borrow_mut()(&text);
+            }
+        })?;
+
+        Ok(handler)
+    }
+
+    /// Returns the textarea receiving text input.
+    pub fn textarea(&self) -> &web_sys::HtmlTextAreaElement {
+        &self.textarea
+    }
+
+    /// Returns the caret cell as `(col, row)`.
+    pub fn caret(&self) -> (u16, u16) {
+        self.caret.get()
+    }
+
+    /// Moves the caret to the given cell. The textarea follows the caret, so
+    /// that IME candidate windows open next to it.
+    pub fn set_caret(&self, col: u16, row: u16) {
+        if self.caret.get() != (col, row) {
+            self.caret.set((col, row));
+            self.update_textarea_position();
+        }
+    }
+
+    /// Repositions the textarea after the canvas was resized or the ratio of
+    /// canvas pixels to CSS pixels changed, such as with scaled font atlases.
+    pub fn update_layout(&self, pixel_ratio: f32) {
+        self.pixel_ratio.set(pixel_ratio);
+        self.update_textarea_position();
+    }
+
+    /// Moves input focus to the textarea.
+    pub fn focus(&self) {
+        let _ = self.textarea.focus();
+    }
+
+    /// Removes all event listeners and the textarea.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        for listener in &self.listeners {
+            let _ = listener.target.remove_event_listener_with_callback(
+                listener.event,
+                listener.closure.as_ref().unchecked_ref(),
+            );
+        }
+        self.textarea.remove();
+        self.grid.borrow_mut().clear_composition();
+    }
+
+    /// Places the textarea over the caret cell, sized to a single cell.
+    fn update_textarea_position(&self) {
+        // cells are sized in canvas pixels, the textarea in CSS pixels
+        let (cell_width, cell_height) = self.grid.borrow().cell_size();
+        let ratio = self.pixel_ratio.get();
+        let (cell_width, cell_height) = (cell_width as f32 / ratio, cell_height as f32 / ratio);
+
+        let (col, row) = self.caret.get();
+        let left = self.canvas.offset_left() as f32 + col as f32 * cell_width;
+        let top = self.canvas.offset_top() as f32 + row as f32 * cell_height;
+
+        let style = self.textarea.style();
+        let _ = style.set_property("left", &format!("{left}px"));
+        let _ = style.set_property("top", &format!("{top}px"));
+        let _ = style.set_property("width", &format!("{cell_width}px"));
+        let _ = style.set_property("height", &format!("{cell_height}px"));
+        let _ = style.set_property("font-size", &format!("{cell_height}px"));
+    }
+
+    fn listen(
+        &mut self,
+        target: &web_sys::EventTarget,
+        event: &'static str,
+        handler: impl FnMut(web_sys::Event) + 'static,
+    ) -> Result<(), Error> {
+        let closure = Closure::wrap(Box::new(handler) as Box<dyn FnMut(_)>);
+        target
+            .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback(format!("Failed to add {event} listener")))?;
+
+        self.listeners.push(Listener { target: target.clone(), event, closure });
+        Ok(())
+    }
+}
+
+/// Creates the off-screen textarea and inserts it after the canvas.
+fn create_textarea(
+    canvas: &web_sys::HtmlCanvasElement,
+) -> Result<web_sys::HtmlTextAreaElement, Error> {
+    let document = crate::js::document()?;
+    let textarea = document
+        .create_element("textarea")
+        .ok()
+        .and_then(|e| e.dyn_into::<web_sys::HtmlTextAreaElement>().ok())
+        .ok_or_else(|| Error::Callback("Failed to create IME textarea".into()))?;
+
+    for (name, value) in [
+        ("autocapitalize", "off"),
+        ("autocomplete", "off"),
+        ("autocorrect", "off"),
+        ("spellcheck", "false"),
+        ("tabindex", "-1"),
+        ("aria-label", "Terminal input"),
+    ] {
+        let _ = textarea.set_attribute(name, value);
+    }
+
+    let style = textarea.style();
+    for (property, value) in [
+        ("position", "absolute"),
+        ("opacity", "0"),
+        ("padding", "0"),
+        ("margin", "0"),
+        ("border", "0"),
+        ("outline", "none"),
+        ("resize", "none"),
+        ("overflow", "hidden"),
+        ("white-space", "nowrap"),
+        ("color", "transparent"),
+        ("background", "transparent"),
+        ("caret-color", "transparent"),
+        ("pointer-events", "none"),
+    ] {
+        let _ = style.set_property(property, value);
+    }
+
+    let inserted = match canvas.parent_node() {
+        Some(parent) => parent.insert_before(&textarea, canvas.next_sibling().as_ref()),
+        None => document
+            .body()
+            .ok_or_else(|| Error::Callback("Document has no body".into()))?
+            .append_child(&textarea),
+    };
+    inserted.map_err(|_| Error::Callback("Failed to insert IME textarea".into()))?;
+
+    Ok(textarea)
+}
+
+fn composition_data(event: &web_sys::Event) -> String {
+    event
+        .dyn_ref::<web_sys::CompositionEvent>()
+        .and_then(web_sys::CompositionEvent::data)
+        .unwrap_or_default()
+}
+
+impl Drop for TerminalImeHandler {
+    /// Automatically removes event listeners and the textarea when dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for TerminalImeHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TerminalImeHandler")
+    }
+}
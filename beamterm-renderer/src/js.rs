@@ -29,3 +29,16 @@ pub(crate) fn get_webgl2_context(
         .dyn_into::<web_sys::WebGl2RenderingContext>()
         .map_err(|_| Error::webgl_context_failed())
 }
+
+/// Returns the current time in milliseconds from `performance.now()`.
+pub(crate) fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or_default()
+}
+
+/// Returns `window.devicePixelRatio`, or `1.0` without a window.
+pub(crate) fn device_pixel_ratio() -> f64 {
+    web_sys::window().map(|w| w.device_pixel_ratio()).unwrap_or(1.0)
+}
@@ -0,0 +1,641 @@
+use compact_str::CompactString;
+
+/// A key event in terms of the DOM `KeyboardEvent` properties.
+///
+/// Constructed from browser events by [`super::TerminalKeyboardHandler`], but
+/// independent of `web_sys`, so that key encoding can be tested natively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// Value of `KeyboardEvent.key`: the produced text, such as `"a"` or `"A"`,
+    /// or a named key, such as `"ArrowUp"` or `"F5"`.
+    pub key: CompactString,
+    /// Value of `KeyboardEvent.code`: the physical key, such as `"KeyA"` or
+    /// `"Numpad1"`. May be empty when unknown.
+    pub code: CompactString,
+    /// Modifier keys held during the event.
+    pub modifiers: Modifiers,
+    /// Whether the key was pressed, auto-repeated or released.
+    pub kind: KeyEventKind,
+}
+
+/// Modifier keys held during a key event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    /// Shift key.
+    pub shift: bool,
+    /// Alt (Option) key.
+    pub alt: bool,
+    /// Control key.
+    pub ctrl: bool,
+    /// Meta key: the Windows or Command key.
+    pub meta: bool,
+}
+
+/// Kind of a key event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    #[default]
+    Press,
+    /// The key is held down and auto-repeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+/// Keyboard modes requested by the terminal application.
+///
+/// With the `emulator` feature, the modes follow the application's requests
+/// automatically when output is fed through `Terminal::write`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyboardModes {
+    /// Cursor keys send `SS3` rather than `CSI` sequences (`DECCKM`).
+    pub application_cursor: bool,
+    /// Keypad keys send `SS3` sequences (`DECKPAM`).
+    pub application_keypad: bool,
+    /// Enhancement flags of the kitty keyboard protocol; see [`KittyFlags`].
+    pub kitty_flags: u8,
+}
+
+/// Enhancement flags of the [kitty keyboard protocol], as set by `CSI > flags u`.
+///
+/// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+#[derive(Debug, Clone, Copy)]
+pub struct KittyFlags;
+
+impl KittyFlags {
+    /// Keys that are ambiguous in the legacy encoding, such as Escape or
+    /// Ctrl/Alt combinations, are sent as `CSI u` sequences.
+    pub const DISAMBIGUATE: u8 = 0b1;
+    /// Repeat and release events are reported.
+    pub const REPORT_EVENT_TYPES: u8 = 0b10;
+    /// Shifted keys additionally report their unshifted key code.
+    pub const REPORT_ALTERNATE_KEYS: u8 = 0b100;
+    /// All keys, including text keys and modifiers, are sent as escape sequences.
+    pub const REPORT_ALL_KEYS: u8 = 0b1000;
+    /// Text produced by a key is reported along with the escape sequence.
+    pub const REPORT_TEXT: u8 = 0b1_0000;
+    /// All flags supported by the encoder.
+    pub const ALL: u8 = 0b1_1111;
+}
+
+/// Converts key events into the byte sequences terminal applications expect.
+///
+/// Without kitty enhancement flags, keys are encoded like xterm does: text is
+/// sent as UTF-8, Ctrl combinations as C0 controls, Alt prefixes the key with
+/// `ESC`, and named keys as `CSI`/`SS3` sequences with an xterm modifier
+/// parameter. Key combinations involving the Meta key are left to the browser
+/// in all modes, as they are usually bound to browser or system shortcuts. The
+/// paste shortcuts can be left to the browser as well, see
+/// [`KeyEncoder::set_browser_paste`].
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::keyboard::{KeyEncoder, KeyEvent, KeyboardModes};
+///
+/// let mut encoder = KeyEncoder::new();
+/// let up = KeyEvent::new("ArrowUp");
+/// assert_eq!(encoder.encode(&up).as_deref(), Some(&b"\x1b[A"[..]));
+///
+/// encoder.set_modes(KeyboardModes { application_cursor: true, ..Default::default() });
+/// assert_eq!(encoder.encode(&up).as_deref(), Some(&b"\x1bOA"[..]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyEncoder {
+    modes: KeyboardModes,
+    /// Whether the paste shortcuts are left to the browser.
+    browser_paste: bool,
+}
+
+/// Encoding of a named (non-text) key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NamedKey {
+    /// `CSI [1;mods] final`, or `SS3 final` in application mode.
+    Cursor(u8),
+    /// `CSI number [;mods] ~`
+    Tilde(u16),
+    /// `SS3 final` without modifiers, `CSI 1;mods final` with modifiers.
+    Function(u8),
+    /// Key sending a single control character.
+    Control(u8),
+    /// Key that is only reported in the kitty protocol, with its key code.
+    KittyOnly(u32),
+}
+
+impl KeyEvent {
+    /// Creates a key press event without modifiers.
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.into(),
+            code: CompactString::default(),
+            modifiers: Modifiers::default(),
+            kind: KeyEventKind::Press,
+        }
+    }
+
+    /// Sets the physical key code.
+    pub fn code(mut self, code: &str) -> Self {
+        self.code = code.into();
+        self
+    }
+
+    /// Sets the modifier keys.
+    pub fn modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Sets the event kind.
+    pub fn kind(mut self, kind: KeyEventKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Returns whether the event is Ctrl+V, Ctrl+Shift+V or Shift+Insert, on
+    /// which browsers fire `paste` events.
+    fn is_paste_shortcut(&self) -> bool {
+        let mods = self.modifiers;
+        match self.key.as_str() {
+            "v" | "V" => mods.ctrl && !mods.alt,
+            "Insert" => mods == Modifiers::SHIFT,
+            _ => false,
+        }
+    }
+
+    /// Returns the text produced by the key, if it is a text key.
+    fn text(&self) -> Option<&str> {
+        // named keys are multi-character ascii words like "Enter" or "F1"
+        let mut chars = self.key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(_), None) => Some(&self.key),
+            (Some(c), Some(_)) if !c.is_ascii_alphabetic() => Some(&self.key),
+            _ => None,
+        }
+    }
+}
+
+impl Modifiers {
+    /// Shift modifier only.
+    pub const SHIFT: Self = Self {
+        shift: true,
+        alt: false,
+        ctrl: false,
+        meta: false,
+    };
+    /// Alt modifier only.
+    pub const ALT: Self = Self {
+        shift: false,
+        alt: true,
+        ctrl: false,
+        meta: false,
+    };
+    /// Control modifier only.
+    pub const CTRL: Self = Self {
+        shift: false,
+        alt: false,
+        ctrl: true,
+        meta: false,
+    };
+
+    /// Returns whether no modifier is held.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the modifier parameter used by xterm and the kitty protocol:
+    /// one plus the bitmask of shift (1), alt (2), ctrl (4) and meta (8).
+    pub fn parameter(&self) -> u8 {
+        1 + self.shift as u8 + 2 * self.alt as u8 + 4 * self.ctrl as u8 + 8 * self.meta as u8
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl From<&crate::emulator::Modes> for KeyboardModes {
+    fn from(modes: &crate::emulator::Modes) -> Self {
+        Self {
+            application_cursor: modes.application_cursor,
+            application_keypad: modes.application_keypad,
+            kitty_flags: modes.kitty_keyboard_flags,
+        }
+    }
+}
+
+impl KeyEncoder {
+    /// Creates an encoder for the default keyboard modes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the keyboard modes.
+    pub fn modes(&self) -> KeyboardModes {
+        self.modes
+    }
+
+    /// Sets the keyboard modes.
+    pub fn set_modes(&mut self, modes: KeyboardModes) {
+        self.modes = modes;
+    }
+
+    /// Returns whether the paste shortcuts are left to the browser.
+    pub fn browser_paste(&self) -> bool {
+        self.browser_paste
+    }
+
+    /// Sets whether the paste shortcuts, Ctrl+V, Ctrl+Shift+V and Shift+Insert,
+    /// are left to the browser rather than encoded, so that the browser fires
+    /// `paste` events for them. Enabled when a paste handler is installed.
+    pub fn set_browser_paste(&mut self, enabled: bool) {
+        self.browser_paste = enabled;
+    }
+
+    /// Encodes a key event, returning `None` for events that aren't sent to
+    /// the application, such as key releases or modifier keys outside of the
+    /// kitty protocol, Meta key combinations and paste shortcuts left to the
+    /// browser.
+    pub fn encode(&self, event: &KeyEvent) -> Option<Vec<u8>> {
+        let flags = self.modes.kitty_flags;
+        if event.kind == KeyEventKind::Release && flags & KittyFlags::REPORT_EVENT_TYPES == 0 {
+            return None;
+        }
+
+        // browser and system shortcuts take precedence, also in the kitty protocol
+        if event.modifiers.meta || (self.browser_paste && event.is_paste_shortcut()) {
+            return None;
+        }
+
+        if flags != 0 {
+            return self.encode_kitty(event, flags);
+        }
+
+        if self.modes.application_keypad && event.modifiers.is_empty() {
+            if let Some(final_byte) = keypad_application_final(&event.code) {
+                return Some(vec![0x1B, b'O', final_byte]);
+            }
+        }
+
+        match event.text() {
+            Some(text) => self.encode_legacy_text(text, event.modifiers),
+            None => self.encode_legacy_named(event),
+        }
+    }
+
+    fn encode_legacy_text(&self, text: &str, mods: Modifiers) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len() + 1);
+        if mods.alt {
+            bytes.push(0x1B);
+        }
+
+        match (mods.ctrl, ctrl_code(text)) {
+            (true, Some(code)) => bytes.push(code),
+            _ => bytes.extend_from_slice(text.as_bytes()),
+        }
+        Some(bytes)
+    }
+
+    fn encode_legacy_named(&self, event: &KeyEvent) -> Option<Vec<u8>> {
+        let mods = event.modifiers;
+        let param = mods.parameter();
+
+        let bytes = match named_key(&event.key)? {
+            NamedKey::Cursor(final_byte) if param > 1 => csi_with_mods(1, param, final_byte),
+            NamedKey::Cursor(final_byte) if self.modes.application_cursor => {
+                vec![0x1B, b'O', final_byte]
+            },
+            NamedKey::Cursor(final_byte) => vec![0x1B, b'[', final_byte],
+            NamedKey::Function(final_byte) if param > 1 => csi_with_mods(1, param, final_byte),
+            NamedKey::Function(final_byte) => vec![0x1B, b'O', final_byte],
+            NamedKey::Tilde(number) if param > 1 => csi_with_mods(number, param, b'~'),
+            NamedKey::Tilde(number) => format!("\x1b[{number}~").into_bytes(),
+            NamedKey::Control(b'\t') if mods.shift => b"\x1b[Z".to_vec(),
+            NamedKey::Control(0x7F) if mods.ctrl => alt_prefixed(mods.alt, 0x08),
+            NamedKey::Control(byte) => alt_prefixed(mods.alt, byte),
+            NamedKey::KittyOnly(_) => return None,
+        };
+        Some(bytes)
+    }
+
+    fn encode_kitty(&self, event: &KeyEvent, flags: u8) -> Option<Vec<u8>> {
+        let mods = event.modifiers;
+        let report_all = flags & KittyFlags::REPORT_ALL_KEYS != 0;
+        let event_type = match event.kind {
+            _ if flags & KittyFlags::REPORT_EVENT_TYPES == 0 => None,
+            KeyEventKind::Press => None,
+            KeyEventKind::Repeat => Some(2),
+            KeyEventKind::Release => Some(3),
+        };
+
+        let mut shifted_key = None;
+        let (key_code, final_byte) = match event.text() {
+            Some(text) => {
+                // unmodified text and shifted text are sent as is
+                let plain = !mods.ctrl && !mods.alt;
+                if !report_all && plain {
+                    return (event.kind != KeyEventKind::Release).then(|| text.as_bytes().to_vec());
+                }
+
+                let ch = text.chars().next()?;
+                let base = if mods.shift { ch.to_lowercase().next().unwrap_or(ch) } else { ch };
+                if base != ch && flags & KittyFlags::REPORT_ALTERNATE_KEYS != 0 {
+                    shifted_key = Some(ch as u32);
+                }
+                (base as u32, b'u')
+            },
+            None => match named_key(&event.key)? {
+                // Enter, Tab and Backspace keep their legacy encoding unless all
+                // keys are reported
+                NamedKey::Control(b'\r' | b'\t' | 0x7F) if !report_all && event_type.is_none() => {
+                    let legacy = KeyEncoder {
+                        modes: KeyboardModes { kitty_flags: 0, ..self.modes },
+                        browser_paste: self.browser_paste,
+                    };
+                    return legacy.encode_legacy_named(event);
+                },
+                NamedKey::Control(byte) => (byte as u32, b'u'),
+                NamedKey::KittyOnly(_) if !report_all => return None,
+                NamedKey::KittyOnly(code) => (code, b'u'),
+                NamedKey::Cursor(final_byte) | NamedKey::Function(final_byte) => (1, final_byte),
+                NamedKey::Tilde(number) => (number as u32, b'~'),
+            },
+        };
+
+        let param = mods.parameter();
+        let has_mods = param > 1 || event_type.is_some();
+
+        let mut sequence = String::from("\x1b[");
+        match final_byte {
+            b'u' | b'~' => {
+                sequence.push_str(&key_code.to_string());
+                if let Some(shifted_key) = shifted_key {
+                    sequence.push_str(&format!(":{shifted_key}"));
+                }
+            },
+            // cursor and function keys only carry a key number with modifiers
+            _ if has_mods => sequence.push('1'),
+            _ => {},
+        }
+        if has_mods {
+            sequence.push_str(&format!(";{param}"));
+        }
+        if let Some(event_type) = event_type {
+            sequence.push_str(&format!(":{event_type}"));
+        }
+
+        let text = event.text().filter(|_| event.kind != KeyEventKind::Release && !mods.ctrl);
+        if let Some(text) = text.filter(|_| flags & KittyFlags::REPORT_TEXT != 0) {
+            if final_byte == b'u' {
+                let codepoints: Vec<_> = text.chars().map(|c| (c as u32).to_string()).collect();
+                let separator = if has_mods { ";" } else { ";;" };
+                sequence.push_str(separator);
+                sequence.push_str(&codepoints.join(":"));
+            }
+        }
+        sequence.push(final_byte as char);
+
+        Some(sequence.into_bytes())
+    }
+}
+
+/// Returns the C0 control sent for Ctrl plus `text`, following xterm.
+fn ctrl_code(text: &str) -> Option<u8> {
+    let [byte] = text.as_bytes() else {
+        return None;
+    };
+
+    match byte.to_ascii_lowercase() {
+        b @ b'a'..=b'z' => Some(b - b'a' + 1),
+        b'@' | b' ' | b'2' => Some(0x00),
+        b'[' | b'3' => Some(0x1B),
+        b'\\' | b'4' => Some(0x1C),
+        b']' | b'5' => Some(0x1D),
+        b'^' | b'6' => Some(0x1E),
+        b'_' | b'-' | b'7' | b'/' => Some(0x1F),
+        b'?' | b'8' => Some(0x7F),
+        _ => None,
+    }
+}
+
+/// Maps a `KeyboardEvent.key` name to its encoding.
+fn named_key(key: &str) -> Option<NamedKey> {
+    use NamedKey::*;
+
+    let named = match key {
+        "ArrowUp" => Cursor(b'A'),
+        "ArrowDown" => Cursor(b'B'),
+        "ArrowRight" => Cursor(b'C'),
+        "ArrowLeft" => Cursor(b'D'),
+        "Home" => Cursor(b'H'),
+        "End" => Cursor(b'F'),
+        "Insert" => Tilde(2),
+        "Delete" => Tilde(3),
+        "PageUp" => Tilde(5),
+        "PageDown" => Tilde(6),
+        "F1" => Function(b'P'),
+        "F2" => Function(b'Q'),
+        "F3" => Function(b'R'),
+        "F4" => Function(b'S'),
+        "F5" => Tilde(15),
+        "F6" => Tilde(17),
+        "F7" => Tilde(18),
+        "F8" => Tilde(19),
+        "F9" => Tilde(20),
+        "F10" => Tilde(21),
+        "F11" => Tilde(23),
+        "F12" => Tilde(24),
+        "Enter" => Control(b'\r'),
+        "Tab" => Control(b'\t'),
+        "Backspace" => Control(0x7F),
+        "Escape" => Control(0x1B),
+        "CapsLock" => KittyOnly(57358),
+        "ScrollLock" => KittyOnly(57359),
+        "NumLock" => KittyOnly(57360),
+        "PrintScreen" => KittyOnly(57361),
+        "Pause" => KittyOnly(57362),
+        "ContextMenu" => KittyOnly(57363),
+        "Shift" => KittyOnly(57441),
+        "Control" => KittyOnly(57442),
+        "Alt" => KittyOnly(57443),
+        "Meta" => KittyOnly(57444),
+        _ => return None,
+    };
+    Some(named)
+}
+
+/// Returns the `SS3` final byte of a keypad key in application keypad mode.
+fn keypad_application_final(code: &str) -> Option<u8> {
+    let key = code.strip_prefix("Numpad")?;
+    let final_byte = match key {
+        "0" => b'p',
+        "1" => b'q',
+        "2" => b'r',
+        "3" => b's',
+        "4" => b't',
+        "5" => b'u',
+        "6" => b'v',
+        "7" => b'w',
+        "8" => b'x',
+        "9" => b'y',
+        "Decimal" => b'n',
+        "Add" => b'k',
+        "Subtract" => b'm',
+        "Multiply" => b'j',
+        "Divide" => b'o',
+        "Enter" => b'M',
+        _ => return None,
+    };
+    Some(final_byte)
+}
+
+fn csi_with_mods(number: u16, param: u8, final_byte: u8) -> Vec<u8> {
+    format!("\x1b[{number};{param}{}", final_byte as char).into_bytes()
+}
+
+fn alt_prefixed(alt: bool, byte: u8) -> Vec<u8> {
+    if alt {
+        vec![0x1B, byte]
+    } else {
+        vec![byte]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(modes: KeyboardModes, event: KeyEvent) -> Option<String> {
+        let mut encoder = KeyEncoder::new();
+        encoder.set_modes(modes);
+        encoder.encode(&event).map(|bytes| String::from_utf8(bytes).unwrap())
+    }
+
+    fn legacy(event: KeyEvent) -> Option<String> {
+        encode(KeyboardModes::default(), event)
+    }
+
+    fn kitty(flags: u8, event: KeyEvent) -> Option<String> {
+        encode(KeyboardModes { kitty_flags: flags, ..Default::default() }, event)
+    }
+
+    fn key(key: &str, modifiers: Modifiers) -> KeyEvent {
+        KeyEvent::new(key).modifiers(modifiers)
+    }
+
+    #[test]
+    fn test_legacy_text_keys() {
+        assert_eq!(legacy(KeyEvent::new("a")).as_deref(), Some("a"));
+        assert_eq!(legacy(KeyEvent::new("é")).as_deref(), Some("é"));
+        assert_eq!(legacy(key("c", Modifiers::CTRL)).as_deref(), Some("\x03"));
+        assert_eq!(legacy(key("[", Modifiers::CTRL)).as_deref(), Some("\x1b"));
+        assert_eq!(legacy(key(" ", Modifiers::CTRL)).as_deref(), Some("\0"));
+        assert_eq!(legacy(key("x", Modifiers::ALT)).as_deref(), Some("\x1bx"));
+
+        let ctrl_alt = Modifiers {
+            ctrl: true,
+            alt: true,
+            ..Default::default()
+        };
+        assert_eq!(legacy(key("b", ctrl_alt)).as_deref(), Some("\x1b\x02"));
+
+        let meta = Modifiers { meta: true, ..Default::default() };
+        assert_eq!(legacy(key("c", meta)), None);
+    }
+
+    #[test]
+    fn test_legacy_named_keys() {
+        assert_eq!(legacy(KeyEvent::new("Enter")).as_deref(), Some("\r"));
+        assert_eq!(legacy(KeyEvent::new("Backspace")).as_deref(), Some("\x7f"));
+        assert_eq!(legacy(key("Backspace", Modifiers::CTRL)).as_deref(), Some("\x08"));
+        assert_eq!(legacy(key("Tab", Modifiers::SHIFT)).as_deref(), Some("\x1b[Z"));
+        assert_eq!(legacy(KeyEvent::new("F1")).as_deref(), Some("\x1bOP"));
+        assert_eq!(legacy(KeyEvent::new("F5")).as_deref(), Some("\x1b[15~"));
+        assert_eq!(legacy(key("F5", Modifiers::CTRL)).as_deref(), Some("\x1b[15;5~"));
+        assert_eq!(legacy(key("ArrowLeft", Modifiers::SHIFT)).as_deref(), Some("\x1b[1;2D"));
+        assert_eq!(legacy(key("F2", Modifiers::ALT)).as_deref(), Some("\x1b[1;3Q"));
+        assert_eq!(legacy(KeyEvent::new("Delete")).as_deref(), Some("\x1b[3~"));
+        assert_eq!(legacy(KeyEvent::new("Shift")), None);
+        assert_eq!(legacy(KeyEvent::new("Dead")), None);
+        assert_eq!(legacy(KeyEvent::new("a").kind(KeyEventKind::Release)), None);
+    }
+
+    #[test]
+    fn test_application_modes() {
+        let modes = KeyboardModes {
+            application_cursor: true,
+            application_keypad: true,
+            ..Default::default()
+        };
+
+        assert_eq!(encode(modes, KeyEvent::new("Home")).as_deref(), Some("\x1bOH"));
+        assert_eq!(encode(modes, key("ArrowUp", Modifiers::CTRL)).as_deref(), Some("\x1b[1;5A"));
+        assert_eq!(encode(modes, KeyEvent::new("5").code("Numpad5")).as_deref(), Some("\x1bOu"));
+        assert_eq!(encode(modes, KeyEvent::new("5").code("Digit5")).as_deref(), Some("5"));
+        let enter = KeyEvent::new("Enter").code("NumpadEnter");
+        assert_eq!(encode(modes, enter).as_deref(), Some("\x1bOM"));
+    }
+
+    #[test]
+    fn test_kitty_disambiguate() {
+        let flags = KittyFlags::DISAMBIGUATE;
+        assert_eq!(kitty(flags, KeyEvent::new("a")).as_deref(), Some("a"));
+        assert_eq!(kitty(flags, KeyEvent::new("Escape")).as_deref(), Some("\x1b[27u"));
+        assert_eq!(kitty(flags, key("c", Modifiers::CTRL)).as_deref(), Some("\x1b[99;5u"));
+        assert_eq!(
+            kitty(flags, key("A", Modifiers { shift: true, ..Modifiers::ALT })).as_deref(),
+            Some("\x1b[97;4u")
+        );
+        assert_eq!(kitty(flags, KeyEvent::new("Enter")).as_deref(), Some("\r"));
+        assert_eq!(kitty(flags, KeyEvent::new("ArrowUp")).as_deref(), Some("\x1b[A"));
+        assert_eq!(kitty(flags, key("ArrowUp", Modifiers::CTRL)).as_deref(), Some("\x1b[1;5A"));
+        assert_eq!(kitty(flags, key("F5", Modifiers::SHIFT)).as_deref(), Some("\x1b[15;2~"));
+        assert_eq!(kitty(flags, KeyEvent::new("Shift")), None);
+
+        let meta = Modifiers { meta: true, ..Default::default() };
+        assert_eq!(kitty(flags, key("c", meta)), None);
+        assert_eq!(kitty(KittyFlags::ALL, key("v", meta)), None);
+    }
+
+    #[test]
+    fn test_kitty_event_types_and_all_keys() {
+        let flags = KittyFlags::DISAMBIGUATE | KittyFlags::REPORT_EVENT_TYPES;
+        let release = KeyEvent::new("ArrowUp").kind(KeyEventKind::Release);
+        assert_eq!(kitty(flags, release).as_deref(), Some("\x1b[1;1:3A"));
+        let repeat = key("x", Modifiers::CTRL).kind(KeyEventKind::Repeat);
+        assert_eq!(kitty(flags, repeat).as_deref(), Some("\x1b[120;5:2u"));
+        assert_eq!(kitty(flags, KeyEvent::new("x").kind(KeyEventKind::Release)), None);
+
+        let flags = KittyFlags::DISAMBIGUATE | KittyFlags::REPORT_ALL_KEYS;
+        assert_eq!(kitty(flags, KeyEvent::new("a")).as_deref(), Some("\x1b[97u"));
+        assert_eq!(kitty(flags, KeyEvent::new("Enter")).as_deref(), Some("\x1b[13u"));
+        assert_eq!(kitty(flags, key("Shift", Modifiers::SHIFT)).as_deref(), Some("\x1b[57441;2u"));
+
+        let flags = KittyFlags::REPORT_ALL_KEYS | KittyFlags::REPORT_ALTERNATE_KEYS;
+        let shifted = key("A", Modifiers::SHIFT);
+        assert_eq!(kitty(flags, shifted).as_deref(), Some("\x1b[97:65;2u"));
+
+        let flags = KittyFlags::REPORT_ALL_KEYS | KittyFlags::REPORT_TEXT;
+        assert_eq!(kitty(flags, KeyEvent::new("é")).as_deref(), Some("\x1b[233;;233u"));
+    }
+
+    #[test]
+    fn test_browser_paste_shortcuts() {
+        let ctrl_shift = Modifiers { shift: true, ..Modifiers::CTRL };
+        let shortcuts =
+            [key("v", Modifiers::CTRL), key("V", ctrl_shift), key("Insert", Modifiers::SHIFT)];
+
+        let mut encoder = KeyEncoder::new();
+        assert_eq!(encoder.encode(&shortcuts[0]).as_deref(), Some(&b"\x16"[..]));
+        assert_eq!(encoder.encode(&shortcuts[2]).as_deref(), Some(&b"\x1b[2;2~"[..]));
+
+        encoder.set_browser_paste(true);
+        for shortcut in &shortcuts {
+            assert_eq!(encoder.encode(shortcut), None);
+        }
+        assert_eq!(encoder.encode(&key("v", Modifiers::ALT)).as_deref(), Some(&b"\x1bv"[..]));
+        assert_eq!(encoder.encode(&KeyEvent::new("Insert")).as_deref(), Some(&b"\x1b[2~"[..]));
+
+        encoder.set_modes(KeyboardModes {
+            kitty_flags: KittyFlags::ALL,
+            ..Default::default()
+        });
+        assert_eq!(encoder.encode(&shortcuts[0]), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Keyboard input handling for terminal applications.
+//!
+//! [`TerminalKeyboardHandler`] listens to key events on the terminal canvas
+//! and converts them into the byte sequences a terminal application reads from
+//! its input, such as `ESC [ A` for the up arrow. The conversion itself is done
+//! by [`KeyEncoder`], which has no browser dependencies and can be used and
+//! tested natively.
+//!
+//! Elements only receive key events while focused; the handler makes the
+//! element focusable by assigning a `tabindex` unless one is already set. When
+//! IME input is enabled, the handler is attached to the IME textarea instead of
+//! the canvas, see [`crate::ime`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::Terminal;
+//!
+//! let terminal = Terminal::builder("#canvas")
+//!     .keyboard_input_handler(|bytes| {
+//!         // forward to the application, e.g. over a WebSocket to a PTY
+//!     })
+//!     .build()?;
+//! terminal.focus();
+//! # Ok(())
+//! # }
+//! ```
+
+mod encoder;
+
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+pub use encoder::{KeyEncoder, KeyEvent, KeyEventKind, KeyboardModes, KittyFlags, Modifiers};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::Error;
+
+/// Type alias for boxed keyboard input callback functions.
+///
+/// Callbacks receive the encoded bytes of each key event, and are invoked
+/// synchronously in the browser's event loop.
+pub type KeyboardEventCallback = Box<dyn FnMut(&[u8]) + 'static>;
+
+/// Handles keyboard input events for the terminal canvas or IME textarea.
+///
+/// Key events that encode to a byte sequence have their default browser action
+/// prevented; events that don't, such as Meta key shortcuts or paste shortcuts
+/// while a paste handler is installed, are left to the browser. Key events
+/// belonging to an IME composition are ignored.
+pub struct TerminalKeyboardHandler {
+    /// The element this handler is attached to.
+    target: web_sys::HtmlElement,
+    /// Closure for keydown events.
+    on_key_down: Closure<dyn FnMut(web_sys::KeyboardEvent)>,
+    /// Closure for keyup events.
+    on_key_up: Closure<dyn FnMut(web_sys::KeyboardEvent)>,
+    /// Key encoder shared with the event closures.
+    encoder: Rc<RefCell<KeyEncoder>>,
+}
+
+impl TerminalKeyboardHandler {
+    /// Creates a keyboard handler for the given element, usually the canvas.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if event listeners cannot be attached to the element.
+    pub fn new<F>(target: &web_sys::HtmlElement, callback: F) -> Result<Self, Error>
+    where
+        F: FnMut(&[u8]) + 'static,
+    {
+        if !target.has_attribute("tabindex") {
+            target.set_tab_index(0);
+        }
+
+        let encoder = Rc::new(RefCell::new(KeyEncoder::new()));
+        let callback: Rc<RefCell<KeyboardEventCallback>> =
+            Rc::new(RefCell::new(Box::new(callback)));
+
+        let on_key_down = create_key_event_closure(encoder.clone(), callback.clone());
+        let on_key_up = create_key_event_closure(encoder.clone(), callback);
+
+        target
+            .add_event_listener_with_callback("keydown", on_key_down.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add keydown listener".into()))?;
+        target
+            .add_event_listener_with_callback("keyup", on_key_up.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add keyup listener".into()))?;
+
+        Ok(Self {
+            target: target.clone(),
+            on_key_down,
+            on_key_up,
+            encoder,
+        })
+    }
+
+    /// Returns the keyboard modes used for encoding.
+    pub fn modes(&self) -> KeyboardModes {
+        self.encoder.borrow().modes()
+    }
+
+    /// Sets the keyboard modes used for encoding, as requested by the application.
+    pub fn set_modes(&self, modes: KeyboardModes) {
+        self.encoder.borrow_mut().set_modes(modes);
+    }
+
+    /// Sets whether the paste shortcuts are left to the browser, so that they
+    /// reach a paste handler; see [`KeyEncoder::set_browser_paste`].
+    pub fn set_browser_paste(&self, enabled: bool) {
+        self.encoder.borrow_mut().set_browser_paste(enabled);
+    }
+
+    /// Moves input focus to the element.
+    pub fn focus(&self) {
+        let _ = self.target.focus();
+    }
+
+    /// Removes all event listeners from the element.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        let _ = self.target.remove_event_listener_with_callback(
+            "keydown",
+            self.on_key_down.as_ref().unchecked_ref(),
+        );
+        let _ = self
+            .target
+            .remove_event_listener_with_callback("keyup", self.on_key_up.as_ref().unchecked_ref());
+    }
+}
+
+impl From<&web_sys::KeyboardEvent> for KeyEvent {
+    fn from(event: &web_sys::KeyboardEvent) -> Self {
+        let kind = match event.type_().as_str() {
+            "keyup" => KeyEventKind::Release,
+            _ if event.repeat() => KeyEventKind::Repeat,
+            _ => KeyEventKind::Press,
+        };
+
+        KeyEvent::new(&event.key())
+            .code(&event.code())
+            .modifiers(Modifiers {
+                shift: event.shift_key(),
+                alt: event.alt_key(),
+                ctrl: event.ctrl_key(),
+                meta: event.meta_key(),
+            })
+            .kind(kind)
+    }
+}
+
+fn create_key_event_closure(
+    encoder: Rc<RefCell<KeyEncoder>>,
+    callback: Rc<RefCell<KeyboardEventCallback>>,
+) -> Closure<dyn FnMut(web_sys::KeyboardEvent)> {
+    Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+        // composition input is delivered by the IME, not as key events
+        if event.is_composing() || event.key() == "Process" {
+            return;
+        }
+
+        if let Some(bytes) = encoder.borrow().encode(&KeyEvent::from(&event)) {
+            event.prevent_default();
+            callback.borrow_mut()(&bytes);
+        }
+    }) as Box<dyn FnMut(_)>)
+}
+
+impl Drop for TerminalKeyboardHandler {
+    /// Automatically removes event listeners when handler is dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for TerminalKeyboardHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TerminalKeyboardHandler")
+    }
+}
@@ -1,4 +1,5 @@
 mod error;
+mod focus;
 mod gl;
 mod mat4;
 mod terminal;
@@ -8,7 +9,19 @@ pub(crate) mod js;
 #[cfg(feature = "js-api")]
 pub mod wasm;
 
+#[cfg(feature = "emulator")]
+pub mod emulator;
+
+pub mod accessibility;
+pub mod context_loss;
+pub mod export;
+pub mod headless;
+pub mod hidpi;
+pub mod ime;
+pub mod keyboard;
 pub mod mouse;
+pub mod paste;
+pub mod theme;
 
 pub use ::beamterm_data::{FontAtlasData, GlyphEffect};
 pub use beamterm_data::FontStyle;
@@ -0,0 +1,461 @@
+use super::{MouseEventType, TerminalMouseEvent};
+use crate::keyboard::Modifiers;
+
+/// Mouse tracking mode requested by the terminal application, selecting which
+/// mouse events are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseTracking {
+    /// Mouse events are not reported.
+    #[default]
+    Off,
+    /// Mode 9: button presses are reported, without modifiers.
+    X10,
+    /// Mode 1000: button presses, releases and wheel events are reported.
+    Normal,
+    /// Mode 1002: additionally reports motion while a button is held.
+    ButtonEvent,
+    /// Mode 1003: additionally reports all motion.
+    AnyEvent,
+}
+
+/// Encoding of mouse reports requested by the terminal application.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseEncoding {
+    /// `CSI M Cb Cx Cy`, with each value as a single byte offset by 32. Cells
+    /// beyond column or row 223 cannot be reported.
+    #[default]
+    Default,
+    /// Mode 1006: `CSI < Cb ; Cx ; Cy M`, or `m` for releases, with decimal values.
+    Sgr,
+    /// Mode 1015: `CSI Cb ; Cx ; Cy M`, with decimal values offset like the default encoding.
+    Urxvt,
+    /// Mode 1016: like [`MouseEncoding::Sgr`], but reporting pixel rather than
+    /// cell coordinates.
+    SgrPixels,
+}
+
+/// Mouse reporting modes requested by the terminal application.
+///
+/// With the `emulator` feature, the modes follow the application's requests
+/// automatically when output is fed through `Terminal::write`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MouseModes {
+    /// Which mouse events are reported.
+    pub tracking: MouseTracking,
+    /// How mouse reports are encoded.
+    pub encoding: MouseEncoding,
+}
+
+/// Mouse button of a mouse report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    /// Primary button, usually the left one.
+    Left,
+    /// Auxiliary button, usually the wheel.
+    Middle,
+    /// Secondary button, usually the right one.
+    Right,
+}
+
+/// Scroll direction of a wheel event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WheelDirection {
+    /// Scrolled up, away from the user.
+    Up,
+    /// Scrolled down, towards the user.
+    Down,
+    /// Scrolled left.
+    Left,
+    /// Scrolled right.
+    Right,
+}
+
+/// Action reported by a mouse report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseAction {
+    /// A button was pressed.
+    Press(MouseButton),
+    /// A button was released.
+    Release(MouseButton),
+    /// The pointer moved.
+    Motion,
+    /// The wheel was scrolled.
+    Wheel(WheelDirection),
+}
+
+/// A mouse event to be reported to the terminal application.
+///
+/// Created from a [`TerminalMouseEvent`] with [`MouseReport::from_event`], or
+/// directly for events without a `TerminalMouseEvent` counterpart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseReport {
+    /// The reported action.
+    pub action: MouseAction,
+    /// Column in the terminal grid (0-based).
+    pub col: u16,
+    /// Row in the terminal grid (0-based).
+    pub row: u16,
+    /// Position in pixels relative to the canvas, for [`MouseEncoding::SgrPixels`].
+    pub pixel_position: (u16, u16),
+    /// Modifier keys held during the event. Meta is not reported.
+    pub modifiers: Modifiers,
+}
+
+/// Converts mouse events into xterm mouse reports, according to the
+/// [`MouseModes`] requested by the terminal application.
+///
+/// The encoder keeps track of the held button, which is reported with motion
+/// events, and drops motion events that don't change the reported position.
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::mouse::{
+///     MouseAction, MouseButton, MouseEncoder, MouseEncoding, MouseModes, MouseReport,
+///     MouseTracking,
+/// };
+///
+/// let mut encoder = MouseEncoder::new();
+/// let press = MouseReport::new(MouseAction::Press(MouseButton::Left), 4, 2);
+/// assert_eq!(encoder.encode(&press), None);
+///
+/// encoder.set_modes(MouseModes {
+///     tracking: MouseTracking::Normal,
+///     encoding: MouseEncoding::Sgr,
+/// });
+/// assert_eq!(encoder.encode(&press).as_deref(), Some(&b"\x1b[<0;5;3M"[..]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MouseEncoder {
+    modes: MouseModes,
+    /// Button held since the last press, reported with motion events.
+    pressed: Option<MouseButton>,
+    /// Position of the last report, in the units of the encoding.
+    last_position: Option<(u16, u16)>,
+}
+
+impl MouseTracking {
+    /// Returns the `DECSET` mode number enabling this tracking mode, if any.
+    pub fn mode(&self) -> Option<u16> {
+        match self {
+            MouseTracking::Off => None,
+            MouseTracking::X10 => Some(9),
+            MouseTracking::Normal => Some(1000),
+            MouseTracking::ButtonEvent => Some(1002),
+            MouseTracking::AnyEvent => Some(1003),
+        }
+    }
+}
+
+impl MouseModes {
+    /// Returns whether mouse events are reported to the application.
+    pub fn is_reporting(&self) -> bool {
+        self.tracking != MouseTracking::Off
+    }
+}
+
+impl MouseButton {
+    /// Returns the button for a DOM `MouseEvent.button` value.
+    pub fn from_dom(button: i16) -> Option<Self> {
+        match button {
+            0 => Some(MouseButton::Left),
+            1 => Some(MouseButton::Middle),
+            2 => Some(MouseButton::Right),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> u16 {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Middle => 1,
+            MouseButton::Right => 2,
+        }
+    }
+}
+
+impl MouseReport {
+    /// Creates a report without modifiers, at pixel position `(0, 0)`.
+    pub fn new(action: MouseAction, col: u16, row: u16) -> Self {
+        Self {
+            action,
+            col,
+            row,
+            pixel_position: (0, 0),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// Sets the pixel position.
+    pub fn pixel_position(mut self, x: u16, y: u16) -> Self {
+        self.pixel_position = (x, y);
+        self
+    }
+
+    /// Sets the modifier keys.
+    pub fn modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Creates a report from a terminal mouse event. Returns `None` for
+    /// buttons without a report code, such as the browser back button, and
+    /// for touch gestures. Wheel events report a single step.
+    pub fn from_event(event: &TerminalMouseEvent) -> Option<Self> {
+        let action = match event.event_type {
+            MouseEventType::MouseDown => MouseAction::Press(MouseButton::from_dom(event.button)?),
+            MouseEventType::MouseUp => MouseAction::Release(MouseButton::from_dom(event.button)?),
+            MouseEventType::MouseMove => MouseAction::Motion,
+            MouseEventType::Wheel => {
+                let direction = match event.wheel_delta {
+                    (_, rows) if rows < 0 => WheelDirection::Up,
+                    (_, rows) if rows > 0 => WheelDirection::Down,
+                    (cols, _) if cols < 0 => WheelDirection::Left,
+                    (cols, _) if cols > 0 => WheelDirection::Right,
+                    _ => return None,
+                };
+                MouseAction::Wheel(direction)
+            },
+            // touch gestures have no report
+            MouseEventType::Tap
+            | MouseEventType::LongPress
+            | MouseEventType::TouchMove
+            | MouseEventType::TouchEnd => return None,
+        };
+
+        let (x, y) = event.pixel_position();
+        let report = MouseReport::new(action, event.col, event.row)
+            .pixel_position(x.clamp(0, u16::MAX as i32) as u16, y.clamp(0, u16::MAX as i32) as u16)
+            .modifiers(Modifiers {
+                shift: event.shift_key,
+                alt: event.alt_key,
+                ctrl: event.ctrl_key,
+                meta: false,
+            });
+
+        Some(report)
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl From<&crate::emulator::Modes> for MouseModes {
+    fn from(modes: &crate::emulator::Modes) -> Self {
+        Self {
+            tracking: modes.mouse_tracking,
+            encoding: modes.mouse_encoding,
+        }
+    }
+}
+
+impl MouseEncoder {
+    /// Creates an encoder with mouse reporting disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the mouse reporting modes.
+    pub fn modes(&self) -> MouseModes {
+        self.modes
+    }
+
+    /// Sets the mouse reporting modes.
+    pub fn set_modes(&mut self, modes: MouseModes) {
+        self.modes = modes;
+    }
+
+    /// Encodes a mouse report, returning `None` for events that aren't
+    /// reported in the current tracking mode, or whose position cannot be
+    /// represented in the current encoding.
+    pub fn encode(&mut self, report: &MouseReport) -> Option<Vec<u8>> {
+        let position = match self.modes.encoding {
+            MouseEncoding::SgrPixels => report.pixel_position,
+            _ => (report.col, report.row),
+        };
+
+        // the held button is tracked even while reporting is off, so that
+        // enabling it mid-drag reports the right button
+        let (code, release) = match report.action {
+            MouseAction::Press(button) => {
+                self.pressed = Some(button);
+                (button.code(), false)
+            },
+            MouseAction::Release(button) => {
+                self.pressed = None;
+                (button.code(), true)
+            },
+            MouseAction::Motion => {
+                let reported = match self.modes.tracking {
+                    MouseTracking::ButtonEvent => self.pressed.is_some(),
+                    MouseTracking::AnyEvent => true,
+                    _ => false,
+                };
+                if !reported || self.last_position == Some(position) {
+                    return None;
+                }
+                (32 + self.pressed.map_or(3, MouseButton::code), false)
+            },
+            MouseAction::Wheel(direction) => (64 + direction as u16, false),
+        };
+
+        match self.modes.tracking {
+            MouseTracking::Off => return None,
+            MouseTracking::X10 if release => return None,
+            _ => {},
+        }
+        self.last_position = Some(position);
+
+        let code = match self.modes.tracking {
+            MouseTracking::X10 => code,
+            _ => code + modifier_bits(report.modifiers),
+        };
+        let (x, y) = (position.0 as u32 + 1, position.1 as u32 + 1);
+
+        match self.modes.encoding {
+            MouseEncoding::Default => {
+                // releases don't identify the button in the legacy encodings
+                let code = if release { code & !0b11 | 3 } else { code };
+                if x > 223 || y > 223 {
+                    return None;
+                }
+                let mut bytes = b"\x1b[M".to_vec();
+                bytes.extend([(32 + code) as u8, (32 + x) as u8, (32 + y) as u8]);
+                Some(bytes)
+            },
+            MouseEncoding::Urxvt => {
+                let code = if release { code & !0b11 | 3 } else { code };
+                Some(format!("\x1b[{};{x};{y}M", 32 + code).into_bytes())
+            },
+            MouseEncoding::Sgr | MouseEncoding::SgrPixels => {
+                let suffix = if release { 'm' } else { 'M' };
+                Some(format!("\x1b[<{code};{x};{y}{suffix}").into_bytes())
+            },
+        }
+    }
+}
+
+/// Returns the modifier bits of the button code: shift (4), meta (8), as which
+/// xterm reports Alt, and control (16).
+fn modifier_bits(modifiers: Modifiers) -> u16 {
+    4 * modifiers.shift as u16 + 8 * modifiers.alt as u16 + 16 * modifiers.ctrl as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mouse::PointerType;
+
+    fn encoder(tracking: MouseTracking, encoding: MouseEncoding) -> MouseEncoder {
+        let mut encoder = MouseEncoder::new();
+        encoder.set_modes(MouseModes { tracking, encoding });
+        encoder
+    }
+
+    fn encode(encoder: &mut MouseEncoder, report: MouseReport) -> Option<String> {
+        encoder
+            .encode(&report)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    const LEFT_DOWN: MouseAction = MouseAction::Press(MouseButton::Left);
+    const LEFT_UP: MouseAction = MouseAction::Release(MouseButton::Left);
+
+    #[test]
+    fn test_tracking_modes() {
+        let mut off = encoder(MouseTracking::Off, MouseEncoding::Sgr);
+        assert_eq!(encode(&mut off, MouseReport::new(LEFT_DOWN, 0, 0)), None);
+
+        let mut x10 = encoder(MouseTracking::X10, MouseEncoding::Sgr);
+        let shifted = MouseReport::new(LEFT_DOWN, 0, 0).modifiers(Modifiers::SHIFT);
+        assert_eq!(encode(&mut x10, shifted).as_deref(), Some("\x1b[<0;1;1M"));
+        assert_eq!(encode(&mut x10, MouseReport::new(LEFT_UP, 0, 0)), None);
+
+        let mut normal = encoder(MouseTracking::Normal, MouseEncoding::Sgr);
+        assert_eq!(
+            encode(&mut normal, MouseReport::new(LEFT_DOWN, 0, 0)).as_deref(),
+            Some("\x1b[<0;1;1M")
+        );
+        assert_eq!(encode(&mut normal, MouseReport::new(MouseAction::Motion, 1, 0)), None);
+        assert_eq!(
+            encode(&mut normal, MouseReport::new(LEFT_UP, 1, 0)).as_deref(),
+            Some("\x1b[<0;2;1m")
+        );
+
+        let mut button = encoder(MouseTracking::ButtonEvent, MouseEncoding::Sgr);
+        assert_eq!(encode(&mut button, MouseReport::new(MouseAction::Motion, 1, 0)), None);
+        encode(&mut button, MouseReport::new(MouseAction::Press(MouseButton::Right), 1, 0));
+        assert_eq!(encode(&mut button, MouseReport::new(MouseAction::Motion, 1, 0)), None);
+        assert_eq!(
+            encode(&mut button, MouseReport::new(MouseAction::Motion, 2, 0)).as_deref(),
+            Some("\x1b[<34;3;1M")
+        );
+
+        let mut any = encoder(MouseTracking::AnyEvent, MouseEncoding::Sgr);
+        assert_eq!(
+            encode(&mut any, MouseReport::new(MouseAction::Motion, 2, 3)).as_deref(),
+            Some("\x1b[<35;3;4M")
+        );
+    }
+
+    #[test]
+    fn test_encodings() {
+        let report = MouseReport::new(MouseAction::Press(MouseButton::Middle), 9, 4)
+            .pixel_position(95, 41)
+            .modifiers(Modifiers {
+                ctrl: true,
+                alt: true,
+                ..Modifiers::default()
+            });
+        let release = MouseReport::new(MouseAction::Release(MouseButton::Middle), 9, 4);
+
+        let mut default = encoder(MouseTracking::Normal, MouseEncoding::Default);
+        assert_eq!(encode(&mut default, report).as_deref(), Some("\x1b[M9*%"));
+        assert_eq!(encode(&mut default, release).as_deref(), Some("\x1b[M#*%"));
+        assert_eq!(encode(&mut default, MouseReport::new(LEFT_DOWN, 223, 0)), None);
+
+        let mut urxvt = encoder(MouseTracking::Normal, MouseEncoding::Urxvt);
+        assert_eq!(encode(&mut urxvt, report).as_deref(), Some("\x1b[57;10;5M"));
+        assert_eq!(encode(&mut urxvt, release).as_deref(), Some("\x1b[35;10;5M"));
+        assert_eq!(
+            encode(&mut urxvt, MouseReport::new(LEFT_DOWN, 300, 0)).as_deref(),
+            Some("\x1b[32;301;1M")
+        );
+
+        let mut sgr = encoder(MouseTracking::Normal, MouseEncoding::Sgr);
+        assert_eq!(encode(&mut sgr, report).as_deref(), Some("\x1b[<25;10;5M"));
+        assert_eq!(encode(&mut sgr, release).as_deref(), Some("\x1b[<1;10;5m"));
+
+        let mut pixels = encoder(MouseTracking::Normal, MouseEncoding::SgrPixels);
+        assert_eq!(encode(&mut pixels, report).as_deref(), Some("\x1b[<25;96;42M"));
+    }
+
+    #[test]
+    fn test_wheel() {
+        let mut sgr = encoder(MouseTracking::Normal, MouseEncoding::Sgr);
+        let up = MouseReport::new(MouseAction::Wheel(WheelDirection::Up), 0, 0);
+        let right = MouseReport::new(MouseAction::Wheel(WheelDirection::Right), 0, 0);
+        assert_eq!(encode(&mut sgr, up).as_deref(), Some("\x1b[<64;1;1M"));
+        assert_eq!(encode(&mut sgr, up).as_deref(), Some("\x1b[<64;1;1M"));
+        assert_eq!(
+            encode(&mut sgr, right.modifiers(Modifiers::CTRL)).as_deref(),
+            Some("\x1b[<83;1;1M")
+        );
+
+        let mut default = encoder(MouseTracking::Normal, MouseEncoding::Default);
+        assert_eq!(encode(&mut default, up).as_deref(), Some("\x1b[M`!!"));
+
+        let event = TerminalMouseEvent {
+            event_type: MouseEventType::Wheel,
+            col: 0,
+            row: 0,
+            button: 0,
+            ctrl_key: false,
+            shift_key: false,
+            alt_key: false,
+            meta_key: false,
+            pixel_position: (0, 0),
+            wheel_delta: (1, -3),
+            pointer_type: PointerType::Touch,
+            click_count: 0,
+        };
+        assert_eq!(MouseReport::from_event(&event), Some(up));
+    }
+}
@@ -0,0 +1,230 @@
+use super::MouseEventType;
+
+/// Distance in pixels a touch may move before it becomes a scroll gesture.
+pub(super) const TOUCH_SLOP: i32 = 8;
+
+/// Duration in milliseconds a touch must be held for a long press.
+pub(super) const LONG_PRESS_MS: i32 = 500;
+
+/// Output of the touch gesture recognizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Gesture {
+    /// A touch event at the given cell.
+    Event(MouseEventType, (u16, u16)),
+    /// Scroll by whole rows at the given cell, reported as a wheel event.
+    Scroll((u16, u16), i16),
+}
+
+/// Recognizes taps, long presses, selection drags and scrolling from the
+/// pointer events of a single touch.
+///
+/// A touch held still for [`LONG_PRESS_MS`] starts a selection, and a touch
+/// starting on a selection handle drags it right away. Touches moving further
+/// than [`TOUCH_SLOP`] before that scroll; all others are taps. Additional
+/// touches are ignored while one is tracked.
+#[derive(Debug, Default)]
+pub(super) struct TouchGesture {
+    state: TouchState,
+    /// Scroll distance not yet emitted as whole rows.
+    scroll: WheelAccumulator,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum TouchState {
+    #[default]
+    Idle,
+    /// Touch down, until it moves or is held long enough.
+    Pending {
+        id: i32,
+        origin: (i32, i32),
+        cell: (u16, u16),
+    },
+    /// Touch moved before the long press; vertical movement scrolls.
+    Scrolling { id: i32, last_y: i32, cell: (u16, u16) },
+    /// Long press or selection handle drag in progress.
+    Selecting { id: i32, cell: (u16, u16) },
+}
+
+/// Accumulates fractional wheel and scroll deltas into whole cells.
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct WheelAccumulator {
+    cols: f32,
+    rows: f32,
+}
+
+impl TouchGesture {
+    /// Returns whether no touch is tracked.
+    pub(super) fn is_idle(&self) -> bool {
+        self.state == TouchState::Idle
+    }
+
+    /// Handles a touch starting at `position`. Returns `true` if the long
+    /// press timer should be started.
+    pub(super) fn down(
+        &mut self,
+        id: i32,
+        position: (i32, i32),
+        cell: (u16, u16),
+        on_selection_handle: bool,
+    ) -> bool {
+        if self.state != TouchState::Idle {
+            return false;
+        }
+
+        if on_selection_handle {
+            self.state = TouchState::Selecting { id, cell };
+            false
+        } else {
+            self.state = TouchState::Pending { id, origin: position, cell };
+            true
+        }
+    }
+
+    /// Handles a touch moving to `position`, over `cell`.
+    pub(super) fn moved(
+        &mut self,
+        id: i32,
+        position: (i32, i32),
+        cell: (u16, u16),
+        cell_height: f32,
+    ) -> Option<Gesture> {
+        match self.state {
+            TouchState::Pending { id: touch, origin, .. } if touch == id => {
+                let (dx, dy) = (position.0 - origin.0, position.1 - origin.1);
+                if dx.abs().max(dy.abs()) > TOUCH_SLOP {
+                    self.scroll = WheelAccumulator::default();
+                    self.state = TouchState::Scrolling { id, last_y: origin.1, cell };
+                    return self.moved(id, position, cell, cell_height);
+                }
+                None
+            },
+            TouchState::Scrolling { id: touch, last_y, .. } if touch == id => {
+                self.state = TouchState::Scrolling { id, last_y: position.1, cell };
+
+                // content follows the finger: dragging up scrolls down
+                let rows = (last_y - position.1) as f32 / cell_height.max(1.0);
+                match self.scroll.add(0.0, rows) {
+                    (_, 0) => None,
+                    (_, rows) => Some(Gesture::Scroll(cell, rows)),
+                }
+            },
+            TouchState::Selecting { id: touch, cell: last } if touch == id => {
+                self.state = TouchState::Selecting { id, cell };
+                (cell != last).then_some(Gesture::Event(MouseEventType::TouchMove, cell))
+            },
+            _ => None,
+        }
+    }
+
+    /// Handles the long press timer firing.
+    pub(super) fn long_press(&mut self) -> Option<Gesture> {
+        match self.state {
+            TouchState::Pending { id, cell, .. } => {
+                self.state = TouchState::Selecting { id, cell };
+                Some(Gesture::Event(MouseEventType::LongPress, cell))
+            },
+            _ => None,
+        }
+    }
+
+    /// Handles a touch being lifted.
+    pub(super) fn up(&mut self, id: i32) -> Option<Gesture> {
+        match self.state {
+            TouchState::Pending { id: touch, cell, .. } if touch == id => {
+                self.state = TouchState::Idle;
+                Some(Gesture::Event(MouseEventType::Tap, cell))
+            },
+            _ => self.cancel(id),
+        }
+    }
+
+    /// Handles a touch being canceled by the browser.
+    pub(super) fn cancel(&mut self, id: i32) -> Option<Gesture> {
+        match self.state {
+            TouchState::Pending { id: touch, .. } | TouchState::Scrolling { id: touch, .. }
+                if touch == id =>
+            {
+                self.state = TouchState::Idle;
+                None
+            },
+            TouchState::Selecting { id: touch, cell } if touch == id => {
+                self.state = TouchState::Idle;
+                Some(Gesture::Event(MouseEventType::TouchEnd, cell))
+            },
+            _ => None,
+        }
+    }
+}
+
+impl WheelAccumulator {
+    /// Adds a delta in cells, returning the whole cells scrolled as
+    /// `(cols, rows)` and keeping the remainder for later.
+    pub(super) fn add(&mut self, cols: f32, rows: f32) -> (i16, i16) {
+        self.cols += cols;
+        self.rows += rows;
+
+        let whole = (self.cols.trunc(), self.rows.trunc());
+        self.cols -= whole.0;
+        self.rows -= whole.1;
+
+        (whole.0 as i16, whole.1 as i16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tap_and_long_press() {
+        let mut gesture = TouchGesture::default();
+        assert!(gesture.down(1, (10, 10), (1, 0), false));
+        assert!(!gesture.down(2, (50, 10), (5, 0), false));
+        assert_eq!(gesture.moved(1, (14, 12), (1, 0), 16.0), None);
+        assert_eq!(gesture.up(2), None);
+        assert_eq!(gesture.up(1), Some(Gesture::Event(MouseEventType::Tap, (1, 0))));
+        assert_eq!(gesture.long_press(), None);
+
+        gesture.down(1, (10, 10), (1, 0), false);
+        assert_eq!(gesture.long_press(), Some(Gesture::Event(MouseEventType::LongPress, (1, 0))));
+        assert_eq!(gesture.moved(1, (12, 10), (1, 0), 16.0), None);
+        assert_eq!(
+            gesture.moved(1, (40, 10), (4, 0), 16.0),
+            Some(Gesture::Event(MouseEventType::TouchMove, (4, 0)))
+        );
+        assert_eq!(gesture.up(1), Some(Gesture::Event(MouseEventType::TouchEnd, (4, 0))));
+    }
+
+    #[test]
+    fn test_selection_handle_drag() {
+        let mut gesture = TouchGesture::default();
+        assert!(!gesture.down(3, (10, 10), (1, 0), true));
+        assert_eq!(
+            gesture.moved(3, (10, 40), (1, 2), 16.0),
+            Some(Gesture::Event(MouseEventType::TouchMove, (1, 2)))
+        );
+        assert_eq!(gesture.cancel(3), Some(Gesture::Event(MouseEventType::TouchEnd, (1, 2))));
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut gesture = TouchGesture::default();
+        gesture.down(1, (10, 100), (1, 6), false);
+
+        // past the slop, scrolling counts from the touch origin
+        assert_eq!(gesture.moved(1, (10, 90), (1, 5), 16.0), None);
+        assert_eq!(gesture.moved(1, (10, 80), (1, 5), 16.0), Some(Gesture::Scroll((1, 5), 1)));
+        assert_eq!(gesture.moved(1, (10, 140), (1, 8), 16.0), Some(Gesture::Scroll((1, 8), -3)));
+        assert_eq!(gesture.long_press(), None);
+        assert_eq!(gesture.up(1), None);
+    }
+
+    #[test]
+    fn test_wheel_accumulator() {
+        let mut wheel = WheelAccumulator::default();
+        assert_eq!(wheel.add(0.0, 0.75), (0, 0));
+        assert_eq!(wheel.add(0.0, 0.5), (0, 1));
+        assert_eq!(wheel.add(-2.5, -0.25), (-2, 0));
+        assert_eq!(wheel.add(-0.5, -3.0), (-1, -3));
+    }
+}
@@ -0,0 +1,259 @@
+use std::ops::Range;
+
+use compact_str::CompactString;
+
+use super::{MouseEventType, TerminalMouseEvent};
+
+/// A rectangle of cells receiving mouse events, such as a button or a tab.
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::mouse::HitRegion;
+///
+/// let button = HitRegion::new(2..10, 0..1).cursor("pointer");
+/// assert!(button.contains(9, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitRegion {
+    /// Columns of the region.
+    cols: Range<u16>,
+    /// Rows of the region.
+    rows: Range<u16>,
+    /// CSS cursor shown while hovering the region.
+    cursor: Option<CompactString>,
+}
+
+/// Hit regions by id, tracking the hovered and pressed region.
+///
+/// Regions registered later are on top of earlier ones where they overlap.
+/// Mouse events produce [`HitRegionEvent`]s when the pointer enters or leaves
+/// a region, and when a region is clicked: pressed and released, or tapped.
+#[derive(Debug, Clone, Default)]
+pub struct HitRegions {
+    /// Regions in registration order.
+    regions: Vec<(CompactString, HitRegion)>,
+    /// Id of the region under the pointer.
+    hovered: Option<CompactString>,
+    /// Id of the region a button was pressed in.
+    pressed: Option<CompactString>,
+}
+
+/// Event of a [`HitRegion`], with the mouse event causing it.
+#[derive(Debug, Clone)]
+pub struct HitRegionEvent {
+    /// Type of hit region event.
+    pub event_type: HitRegionEventType,
+    /// Id of the hit region.
+    pub id: CompactString,
+    /// Mouse event causing the hit region event.
+    pub mouse: TerminalMouseEvent,
+}
+
+/// Types of hit region events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HitRegionEventType {
+    /// The pointer moved into the region.
+    Enter = 0,
+    /// The pointer moved out of the region.
+    Leave = 1,
+    /// A button was pressed and released in the region, or it was tapped.
+    Click = 2,
+}
+
+impl HitRegion {
+    /// Creates a region covering the cells `cols` × `rows`.
+    pub fn new(cols: Range<u16>, rows: Range<u16>) -> Self {
+        Self { cols, rows, cursor: None }
+    }
+
+    /// Sets the CSS cursor shown while hovering the region, e.g. `"pointer"`.
+    pub fn cursor(mut self, cursor: &str) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Returns the columns of the region.
+    pub fn cols(&self) -> Range<u16> {
+        self.cols.clone()
+    }
+
+    /// Returns the rows of the region.
+    pub fn rows(&self) -> Range<u16> {
+        self.rows.clone()
+    }
+
+    /// Returns the CSS cursor shown while hovering the region.
+    pub fn css_cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Returns whether the region covers a cell.
+    pub fn contains(&self, col: u16, row: u16) -> bool {
+        self.cols.contains(&col) && self.rows.contains(&row)
+    }
+}
+
+impl HitRegions {
+    /// Adds a region under `id`, replacing and returning any region with the
+    /// same id. A new region is placed on top of all others.
+    pub fn insert(&mut self, id: &str, region: HitRegion) -> Option<HitRegion> {
+        let previous = self.remove(id);
+        self.regions.push((id.into(), region));
+        previous
+    }
+
+    /// Removes and returns the region with the given id.
+    pub fn remove(&mut self, id: &str) -> Option<HitRegion> {
+        let idx = self.regions.iter().position(|(region_id, _)| region_id == id)?;
+        Some(self.regions.remove(idx).1)
+    }
+
+    /// Returns the region with the given id.
+    pub fn get(&self, id: &str) -> Option<&HitRegion> {
+        self.regions
+            .iter()
+            .find(|(region_id, _)| region_id == id)
+            .map(|(_, region)| region)
+    }
+
+    /// Removes all regions.
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Returns the number of regions.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Returns `true` if there are no regions.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Returns the id of the topmost region covering a cell.
+    pub fn hit(&self, col: u16, row: u16) -> Option<&str> {
+        let mut regions = self.regions.iter().rev();
+        regions.find(|(_, region)| region.contains(col, row)).map(|(id, _)| id.as_str())
+    }
+
+    /// Returns the id of the region under the pointer.
+    pub fn hovered(&self) -> Option<&str> {
+        self.hovered.as_deref()
+    }
+
+    /// Returns the CSS cursor of the region under the pointer.
+    pub(crate) fn hovered_cursor(&self) -> Option<&str> {
+        self.get(self.hovered.as_deref()?)?.css_cursor()
+    }
+
+    /// Tracks the hovered and pressed region, returning the resulting events.
+    pub(crate) fn update(&mut self, event: &TerminalMouseEvent) -> Vec<HitRegionEvent> {
+        let hit = self.hit(event.col, event.row).map(CompactString::from);
+        let mut events = Vec::new();
+        let mut emit = |event_type, id: CompactString| {
+            events.push(HitRegionEvent { event_type, id, mouse: *event });
+        };
+
+        let moved = matches!(
+            event.event_type,
+            MouseEventType::MouseMove | MouseEventType::MouseDown | MouseEventType::MouseUp
+        );
+        if moved && hit != self.hovered {
+            if let Some(id) = std::mem::replace(&mut self.hovered, hit.clone()) {
+                emit(HitRegionEventType::Leave, id);
+            }
+            if let Some(id) = hit.clone() {
+                emit(HitRegionEventType::Enter, id);
+            }
+        }
+
+        match event.event_type {
+            MouseEventType::MouseDown => self.pressed = hit,
+            MouseEventType::MouseUp => {
+                if let Some(id) = self.pressed.take().filter(|id| hit.as_ref() == Some(id)) {
+                    emit(HitRegionEventType::Click, id);
+                }
+            },
+            MouseEventType::Tap => {
+                if let Some(id) = hit {
+                    emit(HitRegionEventType::Click, id);
+                }
+            },
+            _ => {},
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mouse::PointerType;
+
+    fn event(event_type: MouseEventType, col: u16, row: u16) -> TerminalMouseEvent {
+        TerminalMouseEvent {
+            event_type,
+            col,
+            row,
+            button: 0,
+            ctrl_key: false,
+            shift_key: false,
+            alt_key: false,
+            meta_key: false,
+            pixel_position: (0, 0),
+            wheel_delta: (0, 0),
+            pointer_type: PointerType::Mouse,
+            click_count: 1,
+        }
+    }
+
+    fn update(regions: &mut HitRegions, event_type: MouseEventType, col: u16) -> Vec<String> {
+        let events = regions.update(&event(event_type, col, 0));
+        events.iter().map(|e| format!("{:?} {}", e.event_type, e.id)).collect()
+    }
+
+    #[test]
+    fn test_topmost_region_is_hit() {
+        let mut regions = HitRegions::default();
+        regions.insert("tabs", HitRegion::new(0..10, 0..1));
+        regions.insert("close", HitRegion::new(8..9, 0..1).cursor("pointer"));
+
+        assert_eq!(regions.hit(8, 0), Some("close"));
+        assert_eq!(regions.hit(7, 0), Some("tabs"));
+        assert_eq!(regions.hit(8, 1), None);
+
+        // replacing a region moves it on top
+        regions.insert("tabs", HitRegion::new(0..10, 0..1));
+        assert_eq!(regions.hit(8, 0), Some("tabs"));
+        assert_eq!(regions.remove("close"), Some(HitRegion::new(8..9, 0..1).cursor("pointer")));
+        assert_eq!(regions.len(), 1);
+    }
+
+    #[test]
+    fn test_enter_leave_and_click() {
+        use MouseEventType::*;
+
+        let mut regions = HitRegions::default();
+        regions.insert("ok", HitRegion::new(0..4, 0..1).cursor("pointer"));
+        regions.insert("cancel", HitRegion::new(5..9, 0..1));
+
+        assert_eq!(update(&mut regions, MouseMove, 1), ["Enter ok"]);
+        assert_eq!(regions.hovered_cursor(), Some("pointer"));
+        assert!(update(&mut regions, MouseMove, 2).is_empty());
+        assert!(update(&mut regions, MouseDown, 2).is_empty());
+        assert_eq!(update(&mut regions, MouseUp, 3), ["Click ok"]);
+
+        // releasing outside the pressed region doesn't click
+        assert!(update(&mut regions, MouseDown, 3).is_empty());
+        assert_eq!(update(&mut regions, MouseUp, 6), ["Leave ok", "Enter cancel"]);
+        assert_eq!(regions.hovered_cursor(), None);
+        assert_eq!(update(&mut regions, MouseMove, 4), ["Leave cancel"]);
+
+        // taps click without hovering
+        assert_eq!(update(&mut regions, Tap, 7), ["Click cancel"]);
+        assert_eq!(regions.hovered(), None);
+    }
+}
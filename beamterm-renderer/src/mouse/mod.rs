@@ -0,0 +1,1293 @@
+//! Mouse input handling for the beamterm terminal renderer.
+//!
+//! This module provides mouse event handling infrastructure for the terminal,
+//! including coordinate conversion from pixel space to terminal grid space,
+//! text selection with automatic clipboard integration, and customizable
+//! event handling.
+//!
+//! Besides mouse buttons and motion, the handler reports wheel scrolling in
+//! whole cells, pen input as mouse events, and touch gestures: taps, scrolling
+//! by dragging, and long presses starting a selection whose ends can then be
+//! dragged.
+//!
+//! # Architecture
+//!
+//! The mouse handling system consists of:
+//! - [`TerminalMouseHandler`] - Main event handler that attaches to a canvas
+//! - [`TerminalMouseEvent`] - Mouse events translated to terminal coordinates
+//! - [`DefaultSelectionHandler`] - Built-in text selection implementation
+//! - [`MouseEncoder`] - Encodes mouse events as xterm mouse reports
+//! - [`HitRegions`] - Rectangles of cells receiving enter, leave and click events
+//! - Internal state tracking for selection operations
+//!
+//! # Mouse Reporting
+//!
+//! Terminal applications may request mouse events to be reported to them as
+//! escape sequences. With a mouse reporting handler, events are encoded by a
+//! [`MouseEncoder`] according to the requested [`MouseModes`] and passed to the
+//! handler, while the selection handler only sees events with Shift held. When
+//! mouse reporting is off, all events go to the selection handler.
+//!
+//! # Hyperlinks
+//!
+//! With a link handler, hovering a hyperlink underlines it and shows a pointer
+//! cursor, and Ctrl/Cmd+click passes its URL to the handler instead of the
+//! other handlers. Hyperlinks are set per cell, as by OSC 8, or detected in the
+//! text; see [`CellBuffer::link_at`](crate::CellBuffer::link_at).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::{Terminal, SelectionMode};
+//!
+//! // Enable default selection handler
+//! let terminal = Terminal::builder("#canvas")
+//!     .default_mouse_input_handler(SelectionMode::Linear, true)
+//!     .build()?;
+//!
+//! // Or provide custom mouse handling
+//! let terminal = Terminal::builder("#canvas")
+//!     .mouse_input_handler(|event, grid| {
+//!         println!("Mouse event at ({}, {})", event.col, event.row);
+//!     })
+//!     .build()?;
+//!
+//! // Or report mouse events to the application, selecting text with Shift held
+//! let terminal = Terminal::builder("#canvas")
+//!     .default_mouse_input_handler(SelectionMode::Linear, true)
+//!     .mouse_reporting_handler(|bytes| { /* send to the application */ })
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod encoder;
+mod gesture;
+mod hit_region;
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use compact_str::CompactString;
+pub use encoder::{
+    MouseAction, MouseButton, MouseEncoder, MouseEncoding, MouseModes, MouseReport, MouseTracking,
+    WheelDirection,
+};
+pub use hit_region::{HitRegion, HitRegionEvent, HitRegionEventType, HitRegions};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::console;
+
+use self::gesture::{Gesture, TouchGesture, WheelAccumulator, LONG_PRESS_MS};
+use crate::{
+    export::ScreenExport,
+    gl::{SelectionTracker, TerminalDimensions},
+    select, CellQuery, Error, SelectionMode, TerminalGrid,
+};
+
+/// Type alias for boxed mouse event callback functions.
+///
+/// Callbacks are invoked synchronously in the browser's event loop
+pub type MouseEventCallback = Box<dyn FnMut(TerminalMouseEvent, &TerminalGrid) + 'static>;
+
+/// Type alias for boxed mouse report callback functions.
+///
+/// Callbacks receive encoded mouse reports, and are invoked synchronously in
+/// the browser's event loop.
+pub type MouseReportCallback = Box<dyn FnMut(&[u8]) + 'static>;
+
+/// Type alias for boxed hyperlink callback functions.
+///
+/// Callbacks receive the URL of a Ctrl/Cmd-clicked hyperlink, and are invoked
+/// synchronously in the browser's event loop.
+pub type LinkCallback = Box<dyn FnMut(&str) + 'static>;
+
+/// Type alias for boxed hit region callback functions.
+///
+/// Callbacks receive the enter, leave and click events of hit regions, and are
+/// invoked synchronously in the browser's event loop.
+pub type HitRegionCallback = Box<dyn FnMut(HitRegionEvent) + 'static>;
+
+/// Characters ending a word selected by double-click, besides whitespace.
+pub const DEFAULT_WORD_SEPARATORS: &str = ",│`|:\"'()[]{}<>";
+
+/// Internal type for shared event handler wrapped in Rc<RefCell>.
+type EventHandler = Rc<RefCell<dyn FnMut(TerminalMouseEvent, &TerminalGrid) + 'static>>;
+
+/// Handles mouse input events for a terminal grid.
+///
+/// Converts browser mouse events into terminal grid coordinates and manages
+/// event handlers for mouse interactions. Maintains terminal dimensions for
+/// accurate coordinate mapping.
+///
+pub struct TerminalMouseHandler {
+    /// The canvas element this handler is attached to.
+    canvas: web_sys::HtmlCanvasElement,
+    /// Closure for mousedown events.
+    on_mouse_down: Closure<dyn FnMut(web_sys::MouseEvent)>,
+    /// Closure for mouseup events.
+    on_mouse_up: Closure<dyn FnMut(web_sys::MouseEvent)>,
+    /// Closure for mousemove events.
+    on_mouse_move: Closure<dyn FnMut(web_sys::MouseEvent)>,
+    /// Closure for wheel events.
+    on_wheel: Closure<dyn FnMut(web_sys::WheelEvent)>,
+    /// Closure for pen and touch pointer events.
+    on_pointer: Closure<dyn FnMut(web_sys::PointerEvent)>,
+    /// Long press timer of touch gestures, cleared on cleanup.
+    long_press: LongPressTimer,
+    /// Cached terminal dimensions for coordinate conversion.
+    terminal_dimensions: crate::gl::TerminalDimensions,
+    /// Cell size in CSS pixels for coordinate conversion.
+    cell_size: Rc<Cell<(f32, f32)>>,
+    /// Optional default selection handler.
+    pub(crate) default_input_handler: Option<DefaultSelectionHandler>,
+}
+
+/// Mouse event data with terminal cell coordinates.
+///
+/// Represents a mouse event translated from pixel coordinates to terminal
+/// grid coordinates, including modifier key states.`col` and `row` are 0-based
+/// terminal grid coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalMouseEvent {
+    /// Type of mouse event.
+    pub event_type: MouseEventType,
+    /// Column in the terminal grid (0-based).
+    pub col: u16,
+    /// Row in the terminal grid (0-based).
+    pub row: u16,
+    /// Mouse button pressed (0 = left, 1 = middle, 2 = right).
+    button: i16,
+    /// Whether Ctrl key was pressed during the event.
+    ctrl_key: bool,
+    /// Whether Shift key was pressed during the event.
+    shift_key: bool,
+    /// Whether Alt key was pressed during the event.
+    alt_key: bool,
+    /// Whether Meta (Cmd on macOS) key was pressed during the event.
+    meta_key: bool,
+    /// Position in pixels relative to the canvas.
+    pixel_position: (i32, i32),
+    /// Cells scrolled by a wheel event, as `(cols, rows)`.
+    wheel_delta: (i16, i16),
+    /// Kind of pointing device producing the event.
+    pointer_type: PointerType,
+    /// Number of consecutive clicks, for presses and releases.
+    click_count: u8,
+}
+
+impl TerminalMouseEvent {
+    /// Returns the mouse button pressed during the event.
+    pub fn button(&self) -> i16 {
+        self.button
+    }
+
+    /// Creates a new mouse event with the given parameters.
+    pub fn ctrl_key(&self) -> bool {
+        self.ctrl_key
+    }
+
+    /// Returns whether the Ctrl key was pressed during the event.
+    pub fn shift_key(&self) -> bool {
+        self.shift_key
+    }
+
+    /// Returns whether the Shift key was pressed during the event.
+    pub fn alt_key(&self) -> bool {
+        self.alt_key
+    }
+
+    /// Returns whether Meta (Cmd on macOS) key was pressed during the event.
+    pub fn meta_key(&self) -> bool {
+        self.meta_key
+    }
+
+    /// Returns the position of the event in pixels, relative to the canvas.
+    pub fn pixel_position(&self) -> (i32, i32) {
+        self.pixel_position
+    }
+
+    /// Returns the cells scrolled by a [`MouseEventType::Wheel`] event as
+    /// `(cols, rows)`. Positive values scroll right and down.
+    pub fn wheel_delta(&self) -> (i16, i16) {
+        self.wheel_delta
+    }
+
+    /// Returns the kind of pointing device producing the event.
+    pub fn pointer_type(&self) -> PointerType {
+        self.pointer_type
+    }
+
+    /// Returns the number of consecutive clicks in the same place, as counted
+    /// by the browser: `2` for the press and release of a double-click. Other
+    /// events return `0`.
+    pub fn click_count(&self) -> u8 {
+        self.click_count
+    }
+}
+
+/// Types of mouse events that can occur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MouseEventType {
+    /// Mouse button was pressed.
+    MouseDown = 0,
+    /// Mouse button was released.
+    MouseUp = 1,
+    /// Mouse moved while over the terminal.
+    MouseMove = 2,
+    /// Mouse wheel scrolled, or touch dragged, by at least one cell; see
+    /// [`TerminalMouseEvent::wheel_delta`].
+    Wheel = 3,
+    /// Touch lifted without moving or being held.
+    Tap = 4,
+    /// Touch held without moving, starting a selection.
+    LongPress = 5,
+    /// Touch moved after a long press, or while dragging a selection end.
+    TouchMove = 6,
+    /// Touch lifted after a long press, or after dragging a selection end.
+    TouchEnd = 7,
+}
+
+/// Kind of pointing device producing a mouse event.
+///
+/// Pen input produces the same events as a mouse, while touch input produces
+/// wheel and touch events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PointerType {
+    /// A mouse or touchpad.
+    #[default]
+    Mouse,
+    /// A pen or stylus.
+    Pen,
+    /// A finger on a touch screen.
+    Touch,
+}
+
+impl TerminalMouseHandler {
+    /// Creates a new mouse handler for the given canvas and terminal grid.
+    ///
+    /// Sets up mouse event listeners on the canvas and converts pixel coordinates
+    /// to terminal cell coordinates before invoking the provided event handler.
+    ///
+    /// # Arguments
+    /// * `canvas` - The HTML canvas element to attach mouse listeners to
+    /// * `grid` - The terminal grid for coordinate calculations
+    /// * `event_handler` - Callback invoked for each mouse event
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if event listeners cannot be attached to the canvas.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use beamterm_renderer::{mouse::TerminalMouseHandler, TerminalGrid};
+    ///
+    /// let canvas: web_sys::HtmlCanvasElement = unimplemented!("canvas");
+    /// let grid: Rc<RefCell<TerminalGrid>> = unimplemented!("TerminalGrid");
+    ///
+    /// let handler = TerminalMouseHandler::new(
+    ///     &canvas,
+    ///     grid.clone(),
+    ///     |event, grid| {
+    ///         println!("Click at ({}, {})", event.col, event.row);
+    ///     }
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new<F>(
+        canvas: &web_sys::HtmlCanvasElement,
+        grid: Rc<RefCell<TerminalGrid>>,
+        event_handler: F,
+    ) -> Result<Self, Error>
+    where
+        F: FnMut(TerminalMouseEvent, &TerminalGrid) + 'static,
+    {
+        Self::new_internal(canvas, grid, Box::new(event_handler))
+    }
+
+    /// Internal constructor that accepts a boxed event handler.
+    ///
+    /// # Implementation Details
+    /// - Wraps handler in Rc<RefCell> for sharing between event closures
+    /// - Caches terminal dimensions for fast coordinate conversion
+    /// - Creates one closure per mouse event type, and shared closures for
+    ///   wheel and pointer events, all sharing the handler
+    fn new_internal(
+        canvas: &web_sys::HtmlCanvasElement,
+        grid: Rc<RefCell<TerminalGrid>>,
+        event_handler: MouseEventCallback,
+    ) -> Result<Self, Error> {
+        // Wrap the handler in Rc<RefCell> for sharing between closures
+        let shared_handler = Rc::new(RefCell::new(event_handler));
+
+        // Get grid metrics for coordinate conversion
+        let (cell_width, cell_height) = grid.borrow().cell_size();
+        let (cols, rows) = grid.borrow().terminal_size();
+        let terminal_dimensions = TerminalDimensions::new(cols, rows);
+        let cell_size = Rc::new(Cell::new((cell_width as f32, cell_height as f32)));
+
+        // Create pixel-to-cell coordinate converter
+        let dimensions_ref = terminal_dimensions.clone_ref();
+        let cell_size_ref = cell_size.clone();
+        let pixel_to_cell = move |event: &web_sys::MouseEvent| -> Option<(u16, u16)> {
+            let x = event.offset_x() as f32;
+            let y = event.offset_y() as f32;
+
+            let (cell_width, cell_height) = cell_size_ref.get();
+            let col = (x / cell_width).floor() as u16;
+            let row = (y / cell_height).floor() as u16;
+
+            let (max_cols, max_rows) = *dimensions_ref.borrow();
+            if col < max_cols && row < max_rows {
+                Some((col, row))
+            } else {
+                None
+            }
+        };
+
+        // Create event handlers
+        use MouseEventType::*;
+        let on_mouse_down = create_mouse_event_closure(
+            MouseDown,
+            grid.clone(),
+            shared_handler.clone(),
+            pixel_to_cell.clone(),
+        );
+        let on_mouse_up = create_mouse_event_closure(
+            MouseUp,
+            grid.clone(),
+            shared_handler.clone(),
+            pixel_to_cell.clone(),
+        );
+        let on_mouse_move = create_mouse_event_closure(
+            MouseMove,
+            grid.clone(),
+            shared_handler.clone(),
+            pixel_to_cell.clone(),
+        );
+        let on_wheel = create_wheel_closure(
+            grid.clone(),
+            shared_handler.clone(),
+            pixel_to_cell.clone(),
+            cell_size.clone(),
+        );
+
+        // touch gestures share their state with the long press timer
+        let touch_gesture = Rc::new(RefCell::new(TouchGesture::default()));
+        let long_press_handle = Rc::new(Cell::new(None));
+        let long_press = LongPressTimer {
+            callback: Rc::new(create_long_press_closure(
+                grid.clone(),
+                shared_handler.clone(),
+                cell_size.clone(),
+                touch_gesture.clone(),
+                long_press_handle.clone(),
+            )),
+            handle: long_press_handle,
+        };
+        let on_pointer = create_pointer_closure(
+            canvas.clone(),
+            grid,
+            shared_handler,
+            pixel_to_cell,
+            cell_size.clone(),
+            touch_gesture,
+            long_press.clone(),
+        );
+
+        // Attach event listeners
+        canvas
+            .add_event_listener_with_callback("mousedown", on_mouse_down.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add mousedown listener".into()))?;
+        canvas
+            .add_event_listener_with_callback("mouseup", on_mouse_up.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add mouseup listener".into()))?;
+        canvas
+            .add_event_listener_with_callback("mousemove", on_mouse_move.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add mousemove listener".into()))?;
+        canvas
+            .add_event_listener_with_callback("wheel", on_wheel.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add wheel listener".into()))?;
+        for event in POINTER_EVENTS {
+            canvas
+                .add_event_listener_with_callback(event, on_pointer.as_ref().unchecked_ref())
+                .map_err(|_| Error::Callback(format!("Failed to add {event} listener")))?;
+        }
+
+        // touch input is handled here instead of panning or zooming the page
+        let _ = canvas.style().set_property("touch-action", "none");
+
+        Ok(Self {
+            canvas: canvas.clone(),
+            on_mouse_down,
+            on_mouse_up,
+            on_mouse_move,
+            on_wheel,
+            on_pointer,
+            long_press,
+            terminal_dimensions,
+            cell_size,
+            default_input_handler: None,
+        })
+    }
+
+    /// Removes all event listeners from the canvas.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "mousedown",
+            self.on_mouse_down.as_ref().unchecked_ref(),
+        );
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "mouseup",
+            self.on_mouse_up.as_ref().unchecked_ref(),
+        );
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "mousemove",
+            self.on_mouse_move.as_ref().unchecked_ref(),
+        );
+        let _ = self
+            .canvas
+            .remove_event_listener_with_callback("wheel", self.on_wheel.as_ref().unchecked_ref());
+        for event in POINTER_EVENTS {
+            let _ = self.canvas.remove_event_listener_with_callback(
+                event,
+                self.on_pointer.as_ref().unchecked_ref(),
+            );
+        }
+        let _ = self.canvas.style().remove_property("touch-action");
+
+        // the timer must not fire once its closure is dropped
+        self.long_press.clear();
+    }
+
+    /// Updates the cached terminal dimensions.
+    ///
+    /// Should be called when the terminal is resized to ensure accurate
+    /// coordinate conversion.
+    ///
+    /// # Arguments
+    /// * `cols` - New column count
+    /// * `rows` - New row count
+    pub fn update_dimensions(&mut self, cols: u16, rows: u16) {
+        self.terminal_dimensions.set(cols, rows);
+    }
+
+    /// Updates the cell size used for coordinate conversion, in CSS pixels.
+    ///
+    /// Should be called when the font atlas or the canvas pixel ratio changes,
+    /// as mouse events are positioned in CSS pixels while cells are sized in
+    /// canvas pixels.
+    pub fn update_cell_size(&mut self, width: f32, height: f32) {
+        self.cell_size.set((width, height));
+    }
+}
+
+/// Default mouse selection handler with clipboard integration.
+///
+/// Provides text selection functionality with automatic clipboard copying
+/// on selection completion. Supports both linear (text flow) and block
+/// (rectangular) selection modes.
+///
+/// # Features
+/// - Click and drag to select text
+/// - Double-click to select a word, triple-click to select a line
+/// - Shift+click to extend the selection
+/// - Automatic clipboard copy on mouse release
+/// - Configurable selection modes (Linear/Block)
+/// - Optional trailing whitespace trimming
+pub(crate) struct DefaultSelectionHandler {
+    /// Current selection state machine.
+    selection_state: Rc<RefCell<SelectionState>>,
+    /// Terminal grid reference for text extraction.
+    grid: Rc<RefCell<TerminalGrid>>,
+    /// Selection mode (Linear or Block).
+    query_mode: SelectionMode,
+    /// Whether to trim trailing whitespace from selections.
+    trim_trailing_whitespace: bool,
+    /// Characters ending a word selected by double-click, besides whitespace.
+    word_separators: CompactString,
+}
+
+impl DefaultSelectionHandler {
+    /// Creates a new selection handler for the given terminal grid.
+    ///
+    /// # Arguments
+    /// * `grid` - Terminal grid for text extraction
+    /// * `query_mode` - Selection mode (Linear follows text flow, Block is rectangular)
+    /// * `trim_trailing_whitespace` - Whether to remove trailing spaces from selected text
+    pub(crate) fn new(
+        grid: Rc<RefCell<TerminalGrid>>,
+        query_mode: SelectionMode,
+        trim_trailing_whitespace: bool,
+    ) -> Self {
+        Self {
+            grid,
+            selection_state: Rc::new(RefCell::new(SelectionState::Idle)),
+            query_mode,
+            trim_trailing_whitespace,
+            word_separators: DEFAULT_WORD_SEPARATORS.into(),
+        }
+    }
+
+    /// Sets the characters ending a word selected by double-click, besides
+    /// whitespace. Defaults to [`DEFAULT_WORD_SEPARATORS`].
+    pub(crate) fn with_word_separators(mut self, separators: &str) -> Self {
+        self.word_separators = separators.into();
+        self
+    }
+
+    /// Creates the mouse event handler closure for this selection handler.
+    ///
+    /// Returns a boxed closure that handles mouse events, tracks selection state,
+    /// and copies selected text to the clipboard on completion.
+    ///
+    /// # Arguments
+    /// * `active_selection` - Selection tracker for visual feedback
+    ///
+    /// # Algorithm
+    /// 1. MouseDown: Begin new selection or replace existing
+    /// 2. MouseMove: Update selection end point if selecting
+    /// 3. MouseUp: Complete selection and copy to clipboard
+    ///
+    /// Repeated single-cell clicks cancel selection rather than selecting one cell.
+    /// Double- and triple-clicks select the word or logical line under the
+    /// cursor right away, and Shift+click resumes a completed selection from
+    /// its end farther from the cursor.
+    pub fn create_event_handler(&self, active_selection: SelectionTracker) -> MouseEventCallback {
+        let selection_state = self.selection_state.clone();
+        let query_mode = self.query_mode;
+        let trim_trailing = self.trim_trailing_whitespace;
+        let word_separators = self.word_separators.clone();
+
+        Box::new(move |event: TerminalMouseEvent, grid: &TerminalGrid| {
+            let mut state = selection_state.borrow_mut();
+
+            match event.event_type {
+                // only handle left mouse button events
+                MouseEventType::MouseDown if event.button == 0 && event.click_count >= 2 => {
+                    let buffer = grid.cell_buffer();
+                    let (start, end) = match event.click_count {
+                        2 => buffer.word_bounds((event.col, event.row), &word_separators),
+                        _ => buffer.line_bounds(event.row),
+                    };
+                    state.select(start, end);
+
+                    // words and lines follow the text flow, whatever the mode
+                    let query = select(SelectionMode::Linear)
+                        .start(start)
+                        .end(end)
+                        .trim_trailing_whitespace(trim_trailing);
+
+                    active_selection.set_query(query);
+                    copy_selection(grid, query);
+                },
+                MouseEventType::MouseDown
+                    if event.button == 0 && event.shift_key && state.is_complete() =>
+                {
+                    let Some(anchor) = state.resume_from_far_end(event.col, event.row) else {
+                        return;
+                    };
+
+                    let query = select(query_mode)
+                        .start(anchor)
+                        .end((event.col, event.row))
+                        .trim_trailing_whitespace(trim_trailing);
+
+                    active_selection.set_query(query);
+                },
+                MouseEventType::MouseDown if event.button == 0 => {
+                    // mouse down always begins a new *potential* selection
+                    if state.is_complete() {
+                        // the existing (completed) selection is replaced with
+                        // a new selection which will be canceled if the mouse
+                        // up event is fired on the same cell.
+                        state.maybe_selecting(event.col, event.row);
+                    } else {
+                        // begins a new selection from a blank state
+                        state.begin_selection(event.col, event.row);
+                    }
+
+                    let query = select(query_mode)
+                        .start((event.col, event.row))
+                        .trim_trailing_whitespace(trim_trailing);
+
+                    active_selection.set_query(query);
+                },
+                MouseEventType::MouseMove if state.is_selecting() => {
+                    state.update_selection(event.col, event.row);
+                    active_selection.update_selection_end((event.col, event.row));
+                },
+                // word and line selections complete on mouse down
+                MouseEventType::MouseUp if event.button == 0 && event.click_count < 2 => {
+                    // at this point, we're either at:
+                    // a) the user has finished making the selection
+                    // b) the selection was canceled by a click inside a single cell
+                    if let Some((_start, _end)) = state.complete_selection(event.col, event.row) {
+                        active_selection.update_selection_end((event.col, event.row));
+                        copy_selection(grid, active_selection.query());
+                    } else {
+                        state.clear();
+                        active_selection.clear();
+                    }
+                },
+                // touch: a long press begins a selection, whose ends can
+                // afterwards be dragged; a tap clears it
+                MouseEventType::LongPress => {
+                    state.clear();
+                    state.begin_selection(event.col, event.row);
+
+                    let query = select(query_mode)
+                        .start((event.col, event.row))
+                        .end((event.col, event.row))
+                        .trim_trailing_whitespace(trim_trailing);
+
+                    active_selection.set_query(query);
+                },
+                MouseEventType::TouchMove => {
+                    if let Some(anchor) = state.resume_from_far_end(event.col, event.row) {
+                        let query = select(query_mode)
+                            .start(anchor)
+                            .end((event.col, event.row))
+                            .trim_trailing_whitespace(trim_trailing);
+
+                        active_selection.set_query(query);
+                    } else if state.is_selecting() {
+                        state.update_selection(event.col, event.row);
+                        active_selection.update_selection_end((event.col, event.row));
+                    }
+                },
+                MouseEventType::TouchEnd => {
+                    let Some(_) = state.complete_selection(event.col, event.row) else {
+                        return;
+                    };
+
+                    active_selection.update_selection_end((event.col, event.row));
+                    copy_selection(grid, active_selection.query());
+                },
+                MouseEventType::Tap => {
+                    state.clear();
+                    active_selection.clear();
+                },
+                _ => {}, // ignore non-left button and wheel events
+            }
+        })
+    }
+}
+
+/// Internal state machine for tracking mouse selection operations.
+///
+/// Manages the lifecycle of a selection from initial click through dragging
+/// to final release. Handles edge cases like single-cell clicks that should
+/// cancel rather than select.
+///
+/// # State Transitions
+/// ```text
+///        ┌──────────┐
+///    ┌──▶│   Idle   │
+///    │   └────┬─────┘
+///    │        │ begin_selection
+///    │        ▼
+///    │   ┌──────────┐
+///    │   │Selecting │◀────────────┐
+///    │   └────┬─────┘             │
+///    │        │ complete_selection│
+///    │        ▼                   │
+///    │   ┌──────────┐             │
+///    │   │ Complete │             │
+///    │   └────┬─────┘             │
+///    │        │ maybe_selecting   │
+///    │        ▼                   │
+///    │   ┌──────────────┐         │
+///    └───│MaybeSelecting│─────────┘
+/// mouse  └──────────────┘  update_selection
+///   up       
+///          
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SelectionState {
+    /// No selection in progress.
+    Idle,
+    /// Active selection with start point and current cursor position.
+    Selecting {
+        start: (u16, u16),
+        current: Option<(u16, u16)>,
+    },
+    /// MouseDown in a cell while selection exists.
+    /// Will become Selecting on MouseMove or Idle on MouseUp.
+    MaybeSelecting { start: (u16, u16) },
+    /// Selection completed, contains start and end coordinates.
+    Complete { start: (u16, u16), end: (u16, u16) },
+}
+
+impl SelectionState {
+    /// Begins a new selection from idle state.
+    ///
+    /// # Panics
+    /// Panics if called when not in Idle state (debug only).
+    fn begin_selection(&mut self, col: u16, row: u16) {
+        debug_assert!(matches!(self, SelectionState::Idle));
+        *self = SelectionState::Selecting { start: (col, row), current: None };
+    }
+
+    /// Updates selection end point during drag.
+    ///
+    /// Transitions MaybeSelecting to Selecting if needed.
+    fn update_selection(&mut self, col: u16, row: u16) {
+        use SelectionState::*;
+
+        match self {
+            Selecting { current, .. } => {
+                *current = Some((col, row));
+            },
+            MaybeSelecting { start } if (col, row) != *start => {
+                *self = Selecting { start: *start, current: Some((col, row)) };
+            },
+            _ => {},
+        }
+    }
+
+    /// Completes the selection on mouse release.
+    ///
+    /// # Returns
+    /// - `Some((start, end))` if selection completed
+    /// - `None` if selection was canceled (single cell click)
+    fn complete_selection(&mut self, col: u16, row: u16) -> Option<((u16, u16), (u16, u16))> {
+        match self {
+            SelectionState::Selecting { start, .. } => {
+                let result = Some((*start, (col, row)));
+                *self = SelectionState::Complete { start: *start, end: (col, row) };
+                result
+            },
+            _ => None,
+        }
+    }
+
+    /// Clears the selection state back to idle.
+    fn clear(&mut self) {
+        *self = SelectionState::Idle;
+    }
+
+    /// Checks if currently in selecting state.
+    fn is_selecting(&self) -> bool {
+        matches!(self, SelectionState::Selecting { .. } | SelectionState::MaybeSelecting { .. })
+    }
+
+    /// Begins potential new selection while one exists.
+    ///
+    /// Used when clicking while a selection is complete.
+    fn maybe_selecting(&mut self, col: u16, row: u16) {
+        *self = SelectionState::MaybeSelecting { start: (col, row) };
+    }
+
+    /// Completes a selection of the given range right away, replacing any
+    /// selection in progress.
+    fn select(&mut self, start: (u16, u16), end: (u16, u16)) {
+        *self = SelectionState::Complete { start, end };
+    }
+
+    /// Checks if a selection has been completed.
+    fn is_complete(&self) -> bool {
+        matches!(self, SelectionState::Complete { .. })
+    }
+
+    /// Resumes a completed selection when one of its ends is dragged by touch.
+    ///
+    /// The end farther from `(col, row)` becomes the new start, which is returned.
+    fn resume_from_far_end(&mut self, col: u16, row: u16) -> Option<(u16, u16)> {
+        let SelectionState::Complete { start, end } = *self else {
+            return None;
+        };
+
+        let distance = |(c, r): (u16, u16)| c.abs_diff(col).max(r.abs_diff(row));
+        let anchor = if distance(start) >= distance(end) { start } else { end };
+        *self = SelectionState::Selecting { start: anchor, current: Some((col, row)) };
+        Some(anchor)
+    }
+}
+
+/// Creates a mouse event handler reporting events to the application while
+/// mouse reporting is enabled.
+///
+/// Events with Shift held, and all events while reporting is off, are passed
+/// to `fallback`, typically the selection handler. Every press, drag and
+/// release sequence goes to the same destination, decided on mouse down.
+pub(crate) fn reporting_event_handler(
+    encoder: Rc<RefCell<MouseEncoder>>,
+    mut report: MouseReportCallback,
+    mut fallback: Option<MouseEventCallback>,
+) -> MouseEventCallback {
+    // destination of the current press, `true` for the fallback handler
+    let mut pressed_to_fallback: Option<bool> = None;
+
+    Box::new(move |event: TerminalMouseEvent, grid: &TerminalGrid| {
+        let to_fallback = !encoder.borrow().modes().is_reporting() || event.shift_key;
+        let to_fallback = match event.event_type {
+            MouseEventType::MouseDown => *pressed_to_fallback.insert(to_fallback),
+            MouseEventType::MouseMove => pressed_to_fallback.unwrap_or(to_fallback),
+            MouseEventType::MouseUp => pressed_to_fallback.take().unwrap_or(to_fallback),
+            MouseEventType::Wheel => to_fallback,
+            // touch gestures are only used for selecting text
+            _ => true,
+        };
+
+        if to_fallback {
+            if let Some(fallback) = &mut fallback {
+                fallback(event, grid);
+            }
+        } else if let Some(mouse_report) = MouseReport::from_event(&event) {
+            // each wheel step is reported individually
+            let repeat = match event.wheel_delta {
+                (_, rows) if rows != 0 => rows.unsigned_abs(),
+                (cols, _) => cols.unsigned_abs().max(1),
+            };
+
+            let mut encoder = encoder.borrow_mut();
+            for _ in 0..repeat {
+                if let Some(bytes) = encoder.encode(&mouse_report) {
+                    report(&bytes);
+                }
+            }
+        }
+    })
+}
+
+/// Creates a mouse event handler for hyperlinks, passing other events to `inner`.
+///
+/// Moving the mouse over a hyperlink underlines it through `hover` and shows a
+/// pointer cursor over the canvas. Pressing the left button on a hyperlink with
+/// Ctrl or Meta held passes its URL to `callback` instead of `inner`.
+pub(crate) fn link_event_handler(
+    canvas: web_sys::HtmlCanvasElement,
+    hover: SelectionTracker,
+    mut callback: LinkCallback,
+    mut inner: Option<MouseEventCallback>,
+) -> MouseEventCallback {
+    Box::new(move |event: TerminalMouseEvent, grid: &TerminalGrid| {
+        let link = || grid.link_at((event.col, event.row));
+        match event.event_type {
+            MouseEventType::MouseMove if event.pointer_type != PointerType::Touch => {
+                let hovered = link().map(|(query, _)| query);
+                if hovered != hover.get_query() {
+                    let style = canvas.style();
+                    let _ = match hovered {
+                        Some(query) => {
+                            hover.set_query(query);
+                            style.set_property("cursor", "pointer")
+                        },
+                        None => {
+                            hover.clear();
+                            style.remove_property("cursor").map(|_| ())
+                        },
+                    };
+                }
+            },
+            MouseEventType::MouseDown
+                if event.button == 0 && (event.ctrl_key || event.meta_key) =>
+            {
+                if let Some((_, url)) = link() {
+                    callback(&url);
+                    return;
+                }
+            },
+            _ => {},
+        }
+
+        if let Some(inner) = &mut inner {
+            inner(event, grid);
+        }
+    })
+}
+
+/// Creates a mouse event handler reporting the events of hit regions to
+/// `callback`, and passing all mouse events on to `inner`.
+///
+/// The canvas shows the CSS cursor of the hovered region, if it has one.
+pub(crate) fn hit_region_event_handler(
+    canvas: web_sys::HtmlCanvasElement,
+    regions: Rc<RefCell<HitRegions>>,
+    mut callback: HitRegionCallback,
+    mut inner: Option<MouseEventCallback>,
+) -> MouseEventCallback {
+    Box::new(move |event: TerminalMouseEvent, grid: &TerminalGrid| {
+        let events = regions.borrow_mut().update(&event);
+        if events.iter().any(|e| e.event_type != HitRegionEventType::Click) {
+            let style = canvas.style();
+            let _ = match regions.borrow().hovered_cursor() {
+                Some(cursor) => style.set_property("cursor", cursor),
+                None => style.remove_property("cursor").map(|_| ()),
+            };
+        }
+
+        events.into_iter().for_each(&mut callback);
+        if let Some(inner) = &mut inner {
+            inner(event, grid);
+        }
+    })
+}
+
+/// Pointer events handled for pen and touch input.
+const POINTER_EVENTS: [&str; 4] = ["pointerdown", "pointermove", "pointerup", "pointercancel"];
+
+/// Long press timer of touch gestures, shared between the pointer event closure
+/// and the mouse handler.
+#[derive(Clone)]
+struct LongPressTimer {
+    /// Closure invoked when the timer fires.
+    callback: Rc<Closure<dyn FnMut()>>,
+    /// Handle of the pending timer.
+    handle: Rc<Cell<Option<i32>>>,
+}
+
+impl LongPressTimer {
+    /// Starts the timer, replacing any pending one.
+    fn start(&self) {
+        self.clear();
+        if let Some(window) = web_sys::window() {
+            let handle = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                self.callback.as_ref().as_ref().unchecked_ref(),
+                LONG_PRESS_MS,
+            );
+            self.handle.set(handle.ok());
+        }
+    }
+
+    /// Clears the pending timer, if any.
+    fn clear(&self) {
+        if let (Some(handle), Some(window)) = (self.handle.take(), web_sys::window()) {
+            window.clear_timeout_with_handle(handle);
+        }
+    }
+}
+
+impl TerminalMouseEvent {
+    /// Creates a terminal event for a browser mouse event at the given cell.
+    fn from_dom(
+        event_type: MouseEventType,
+        (col, row): (u16, u16),
+        event: &web_sys::MouseEvent,
+        pointer_type: PointerType,
+    ) -> Self {
+        Self {
+            event_type,
+            col,
+            row,
+            button: event.button(),
+            ctrl_key: event.ctrl_key(),
+            shift_key: event.shift_key(),
+            alt_key: event.alt_key(),
+            meta_key: event.meta_key(),
+            pixel_position: (event.offset_x(), event.offset_y()),
+            wheel_delta: (0, 0),
+            pointer_type,
+            click_count: event.detail().clamp(0, u8::MAX as i32) as u8,
+        }
+    }
+
+    /// Creates a terminal event for a touch gesture, positioned at the top
+    /// left corner of the cell; the cell size is in CSS pixels.
+    fn from_gesture(gesture: Gesture, (cell_width, cell_height): (f32, f32)) -> Self {
+        let (event_type, (col, row), wheel_delta) = match gesture {
+            Gesture::Event(event_type, cell) => (event_type, cell, (0, 0)),
+            Gesture::Scroll(cell, rows) => (MouseEventType::Wheel, cell, (0, rows)),
+        };
+
+        Self {
+            event_type,
+            col,
+            row,
+            button: 0,
+            ctrl_key: false,
+            shift_key: false,
+            alt_key: false,
+            meta_key: false,
+            pixel_position: ((col as f32 * cell_width) as i32, (row as f32 * cell_height) as i32),
+            wheel_delta,
+            pointer_type: PointerType::Touch,
+            click_count: 0,
+        }
+    }
+}
+
+/// Creates a closure that handles browser mouse events and converts them to terminal events.
+///
+/// Wraps the event handler with coordinate conversion and terminal event creation logic.
+fn create_mouse_event_closure(
+    event_type: MouseEventType,
+    grid: Rc<RefCell<TerminalGrid>>,
+    event_handler: EventHandler,
+    pixel_to_cell: impl Fn(&web_sys::MouseEvent) -> Option<(u16, u16)> + 'static,
+) -> Closure<dyn FnMut(web_sys::MouseEvent)> {
+    Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+        if let Some(cell) = pixel_to_cell(&event) {
+            let terminal_event =
+                TerminalMouseEvent::from_dom(event_type, cell, &event, PointerType::Mouse);
+            let grid_ref = grid.borrow();
+            event_handler.borrow_mut()(terminal_event, &grid_ref);
+        }
+    }) as Box<dyn FnMut(_)>)
+}
+
+/// Creates a closure converting wheel events into [`MouseEventType::Wheel`]
+/// events, once the accumulated delta amounts to at least one cell.
+///
+/// Pixel deltas are divided by the cell size in CSS pixels and page deltas
+/// multiplied by the terminal size; line deltas count as cells.
+fn create_wheel_closure(
+    grid: Rc<RefCell<TerminalGrid>>,
+    event_handler: EventHandler,
+    pixel_to_cell: impl Fn(&web_sys::MouseEvent) -> Option<(u16, u16)> + 'static,
+    cell_size: Rc<Cell<(f32, f32)>>,
+) -> Closure<dyn FnMut(web_sys::WheelEvent)> {
+    let mut accumulator = WheelAccumulator::default();
+
+    Closure::wrap(Box::new(move |event: web_sys::WheelEvent| {
+        let Some(cell) = pixel_to_cell(&event) else {
+            return;
+        };
+
+        let grid_ref = grid.borrow();
+        let (cell_width, cell_height) = cell_size.get();
+        let (cols, rows) = grid_ref.terminal_size();
+
+        let (dx, dy) = (event.delta_x() as f32, event.delta_y() as f32);
+        let (dx, dy) = match event.delta_mode() {
+            web_sys::WheelEvent::DOM_DELTA_LINE => (dx, dy),
+            web_sys::WheelEvent::DOM_DELTA_PAGE => (dx * cols as f32, dy * rows as f32),
+            _ => (dx / cell_width, dy / cell_height),
+        };
+
+        let wheel_delta = accumulator.add(dx, dy);
+        if wheel_delta != (0, 0) {
+            let mut terminal_event = TerminalMouseEvent::from_dom(
+                MouseEventType::Wheel,
+                cell,
+                &event,
+                PointerType::Mouse,
+            );
+            terminal_event.wheel_delta = wheel_delta;
+            event_handler.borrow_mut()(terminal_event, &grid_ref);
+        }
+    }) as Box<dyn FnMut(_)>)
+}
+
+/// Creates a closure handling pen and touch pointer events.
+///
+/// Pen input is converted into mouse events, while touch input is fed to the
+/// touch gesture recognizer. Mouse pointers are ignored, as they are handled
+/// by the mouse event closures.
+fn create_pointer_closure(
+    canvas: web_sys::HtmlCanvasElement,
+    grid: Rc<RefCell<TerminalGrid>>,
+    event_handler: EventHandler,
+    pixel_to_cell: impl Fn(&web_sys::MouseEvent) -> Option<(u16, u16)> + 'static,
+    cell_size: Rc<Cell<(f32, f32)>>,
+    gesture: Rc<RefCell<TouchGesture>>,
+    long_press: LongPressTimer,
+) -> Closure<dyn FnMut(web_sys::PointerEvent)> {
+    Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+        let pointer_type = match event.pointer_type().as_str() {
+            "pen" => PointerType::Pen,
+            "touch" => PointerType::Touch,
+            _ => return,
+        };
+
+        let event_type = event.type_();
+        let position = (event.offset_x(), event.offset_y());
+        let cell = if event_type == "pointerdown" {
+            let Some(cell) = pixel_to_cell(&event) else {
+                return;
+            };
+
+            // suppresses the emulated mouse events, and keeps the pointer
+            // reporting to the canvas when dragged outside of it
+            event.prevent_default();
+            let _ = canvas.set_pointer_capture(event.pointer_id());
+            cell
+        } else {
+            nearest_cell(grid.borrow().terminal_size(), cell_size.get(), position)
+        };
+
+        if pointer_type == PointerType::Pen {
+            let event_type = match event_type.as_str() {
+                "pointerdown" => {
+                    let _ = canvas.focus();
+                    MouseEventType::MouseDown
+                },
+                "pointermove" if event.buttons() != 0 => MouseEventType::MouseMove,
+                "pointerup" | "pointercancel" => MouseEventType::MouseUp,
+                _ => return,
+            };
+
+            let terminal_event =
+                TerminalMouseEvent::from_dom(event_type, cell, &event, pointer_type);
+            let grid_ref = grid.borrow();
+            event_handler.borrow_mut()(terminal_event, &grid_ref);
+            return;
+        }
+
+        let id = event.pointer_id();
+        let output = match event_type.as_str() {
+            "pointerdown" => {
+                let on_selection_end = is_selection_end(&grid.borrow(), cell);
+                if gesture.borrow_mut().down(id, position, cell, on_selection_end) {
+                    long_press.start();
+                }
+                None
+            },
+            "pointermove" => {
+                let (_, cell_height) = cell_size.get();
+                gesture.borrow_mut().moved(id, position, cell, cell_height)
+            },
+            "pointerup" => gesture.borrow_mut().up(id),
+            _ => gesture.borrow_mut().cancel(id),
+        };
+
+        if gesture.borrow().is_idle() {
+            long_press.clear();
+        }
+        if let Some(output) = output {
+            if output == Gesture::Event(MouseEventType::Tap, cell) {
+                let _ = canvas.focus();
+            }
+            dispatch_gesture(output, &grid, &event_handler, cell_size.get());
+        }
+    }) as Box<dyn FnMut(_)>)
+}
+
+/// Creates the closure invoked by the long press timer.
+fn create_long_press_closure(
+    grid: Rc<RefCell<TerminalGrid>>,
+    event_handler: EventHandler,
+    cell_size: Rc<Cell<(f32, f32)>>,
+    gesture: Rc<RefCell<TouchGesture>>,
+    handle: Rc<Cell<Option<i32>>>,
+) -> Closure<dyn FnMut()> {
+    Closure::wrap(Box::new(move || {
+        handle.set(None);
+        let long_press = gesture.borrow_mut().long_press();
+        if let Some(long_press) = long_press {
+            dispatch_gesture(long_press, &grid, &event_handler, cell_size.get());
+        }
+    }) as Box<dyn FnMut()>)
+}
+
+/// Passes a recognized touch gesture to the event handler.
+fn dispatch_gesture(
+    gesture: Gesture,
+    grid: &Rc<RefCell<TerminalGrid>>,
+    event_handler: &EventHandler,
+    cell_size: (f32, f32),
+) {
+    let grid_ref = grid.borrow();
+    let terminal_event = TerminalMouseEvent::from_gesture(gesture, cell_size);
+    event_handler.borrow_mut()(terminal_event, &grid_ref);
+}
+
+/// Returns the cell at a position in CSS pixels, clamped to the grid.
+fn nearest_cell(
+    (cols, rows): (u16, u16),
+    (cell_width, cell_height): (f32, f32),
+    (x, y): (i32, i32),
+) -> (u16, u16) {
+    let col = (x.max(0) as f32 / cell_width.max(1.0)) as u16;
+    let row = (y.max(0) as f32 / cell_height.max(1.0)) as u16;
+    (col.min(cols.saturating_sub(1)), row.min(rows.saturating_sub(1)))
+}
+
+/// Returns whether `cell` is at, or next to, either end of the active
+/// selection, so that touching it drags that end.
+fn is_selection_end(grid: &TerminalGrid, (col, row): (u16, u16)) -> bool {
+    let query = grid.selection_tracker().get_query();
+    let Some((start, end)) = query.and_then(|q| q.endpoints()) else {
+        return false;
+    };
+
+    [start, end].iter().any(|&(c, r)| c.abs_diff(col) <= 1 && r.abs_diff(row) <= 1)
+}
+
+/// Copies a selection to the system clipboard, as plain text and as HTML
+/// preserving the colors and styles of the cells.
+pub(crate) fn copy_selection(grid: &TerminalGrid, query: CellQuery) {
+    let html = ScreenExport::from_grid(grid).region(query).to_html();
+    copy_to_clipboard(grid.get_text(query), Some(html));
+}
+
+/// Copies text to the system clipboard using the browser's async clipboard API.
+///
+/// With `html`, a clipboard item offering both `text/plain` and `text/html` is
+/// written, falling back to plain text where clipboard items are unsupported.
+/// Spawns an async task to handle the clipboard write operation. Logs failure
+/// to the console.
+///
+/// # Security
+/// Browser may require user gesture or HTTPS for clipboard access.
+pub(crate) fn copy_to_clipboard(text: CompactString, html: Option<String>) {
+    spawn_local(async move {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let clipboard = window.navigator().clipboard();
+
+        if let Some(html) = html {
+            let item = clipboard_item(&[("text/plain", &text), ("text/html", &html)]);
+            let write = item.map(|item| clipboard.write(&js_sys::Array::of1(&item)));
+            if let Ok(write) = write {
+                if JsFuture::from(write).await.is_ok() {
+                    return;
+                }
+            }
+        }
+
+        if let Err(err) = JsFuture::from(clipboard.write_text(&text)).await {
+            console::error_1(&format!("Failed to copy to clipboard: {err:?}").into());
+        }
+    });
+}
+
+/// Creates a clipboard item holding data of several MIME types.
+fn clipboard_item(data: &[(&str, &str)]) -> Result<web_sys::ClipboardItem, JsValue> {
+    let items = js_sys::Object::new();
+    for &(mime_type, content) in data {
+        let options = web_sys::BlobPropertyBag::new();
+        options.set_type(mime_type);
+        let parts = js_sys::Array::of1(&content.into());
+        let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+        js_sys::Reflect::set(&items, &mime_type.into(), &blob)?;
+    }
+
+    web_sys::ClipboardItem::new_with_record_from_str_to_blob_promise(&items)
+}
+
+impl Drop for TerminalMouseHandler {
+    /// Automatically removes event listeners when handler is dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for TerminalMouseHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (cols, rows) = self.terminal_dimensions.get();
+        write!(f, "TerminalMouseHandler {{ dimensions: {cols}x{rows} }}")
+    }
+}
+
+impl Debug for DefaultSelectionHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (cols, rows) = self.grid.borrow().terminal_size();
+        write!(
+            f,
+            "DefaultSelectionHandler {{ mode: {:?}, trim_whitespace: {}, grid: {}x{} }}",
+            self.query_mode, self.trim_trailing_whitespace, cols, rows
+        )
+    }
+}
@@ -0,0 +1,241 @@
+//! Paste handling for terminal applications.
+//!
+//! [`TerminalPasteHandler`] listens to `paste` events on the terminal canvas,
+//! or the IME textarea when IME input is enabled, and delivers the pasted text
+//! as terminal input bytes through a callback. Line breaks are normalized to
+//! carriage returns, as typed by the Enter key. While the application has
+//! enabled bracketed paste (mode 2004), the text is wrapped in `ESC [ 200 ~`
+//! and `ESC [ 201 ~`, letting the application tell pasted text from typed text.
+//! The conversion itself is done by [`encode_paste`], which has no browser
+//! dependencies. Keyboard handlers leave the paste shortcuts to the browser
+//! while a paste handler is installed, so that they fire `paste` events; see
+//! [`crate::keyboard::KeyEncoder::set_browser_paste`].
+//!
+//! Clicking the middle mouse button pastes the active selection, emulating the
+//! primary selection of X11 terminals. While the application has requested
+//! mouse reporting, middle clicks are reported unless Shift is held.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use beamterm_renderer::Terminal;
+//!
+//! let terminal = Terminal::builder("#canvas")
+//!     .keyboard_input_handler(|bytes| { /* send to the application */ })
+//!     .paste_handler(|bytes| { /* send to the application */ })
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::{
+    mouse::{MouseEventCallback, MouseEventType, TerminalMouseEvent},
+    Error, TerminalGrid,
+};
+
+/// Type alias for boxed paste callback functions.
+///
+/// Callbacks receive the encoded bytes of each paste, and are invoked
+/// synchronously in the browser's event loop.
+pub type PasteCallback = Box<dyn FnMut(&[u8]) + 'static>;
+
+/// Sequence starting bracketed pasted text.
+const PASTE_START: &str = "\x1b[200~";
+/// Sequence ending bracketed pasted text.
+const PASTE_END: &str = "\x1b[201~";
+
+/// Handles paste events for the terminal canvas or IME textarea; see the
+/// [module docs](self).
+pub struct TerminalPasteHandler {
+    /// The element this handler is attached to.
+    target: web_sys::HtmlElement,
+    /// Closure for paste events.
+    on_paste: Closure<dyn FnMut(web_sys::ClipboardEvent)>,
+    /// Paste state shared with the event closures.
+    sink: Rc<PasteSink>,
+}
+
+/// Encodes pasted text and passes it to the callback.
+struct PasteSink {
+    /// Whether the application has enabled bracketed paste.
+    bracketed: Cell<bool>,
+    callback: RefCell<PasteCallback>,
+}
+
+impl TerminalPasteHandler {
+    /// Creates a paste handler for the given element, usually the canvas.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if the event listener cannot be attached to the element.
+    pub fn new<F>(target: &web_sys::HtmlElement, callback: F) -> Result<Self, Error>
+    where
+        F: FnMut(&[u8]) + 'static,
+    {
+        let sink = Rc::new(PasteSink {
+            bracketed: Cell::new(false),
+            callback: RefCell::new(Box::new(callback)),
+        });
+
+        let paste_sink = sink.clone();
+        let on_paste = Closure::wrap(Box::new(move |event: web_sys::ClipboardEvent| {
+            let text = event.clipboard_data().and_then(|data| data.get_data("text/plain").ok());
+            if let Some(text) = text {
+                // keeps the text out of the IME textarea
+                event.prevent_default();
+                paste_sink.paste(&text);
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        target
+            .add_event_listener_with_callback("paste", on_paste.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add paste listener".into()))?;
+
+        Ok(Self { target: target.clone(), on_paste, sink })
+    }
+
+    /// Returns whether pasted text is wrapped in bracketed paste sequences.
+    pub fn is_bracketed(&self) -> bool {
+        self.sink.bracketed.get()
+    }
+
+    /// Sets whether pasted text is wrapped in bracketed paste sequences, as
+    /// requested by the application with mode 2004.
+    pub fn set_bracketed(&self, enabled: bool) {
+        self.sink.bracketed.set(enabled);
+    }
+
+    /// Pastes text as if from the clipboard, such as text read with the async
+    /// clipboard API from a context menu.
+    pub fn paste(&self, text: &str) {
+        self.sink.paste(text);
+    }
+
+    /// Creates a mouse event handler pasting the active selection on middle
+    /// clicks, passing all events on to `inner`.
+    ///
+    /// Installed below any mouse reporting handler, so that middle clicks are
+    /// reported while the application requests mouse reporting.
+    pub(crate) fn primary_selection_handler(
+        &self,
+        mut inner: Option<MouseEventCallback>,
+    ) -> MouseEventCallback {
+        let sink = self.sink.clone();
+
+        Box::new(move |event: TerminalMouseEvent, grid: &TerminalGrid| {
+            if event.event_type == MouseEventType::MouseDown && event.button() == 1 {
+                let query = grid.selection_tracker().get_query();
+                let text = query.map(|query| grid.get_text(query)).unwrap_or_default();
+                if !text.is_empty() {
+                    sink.paste(&text);
+                }
+            }
+
+            if let Some(inner) = &mut inner {
+                inner(event, grid);
+            }
+        })
+    }
+
+    /// Moves the event listener to another element, such as the IME textarea
+    /// once IME input is enabled.
+    ///
+    /// # Errors
+    /// Returns `Error::Callback` if the event listener cannot be attached to the element.
+    pub(crate) fn retarget(&mut self, target: &web_sys::HtmlElement) -> Result<(), Error> {
+        self.cleanup();
+        target
+            .add_event_listener_with_callback("paste", self.on_paste.as_ref().unchecked_ref())
+            .map_err(|_| Error::Callback("Failed to add paste listener".into()))?;
+
+        self.target = target.clone();
+        Ok(())
+    }
+
+    /// Removes the event listener from the element.
+    ///
+    /// Called automatically on drop. Safe to call multiple times.
+    pub fn cleanup(&self) {
+        let _ = self
+            .target
+            .remove_event_listener_with_callback("paste", self.on_paste.as_ref().unchecked_ref());
+    }
+}
+
+impl PasteSink {
+    fn paste(&self, text: &str) {
+        let bytes = encode_paste(text, self.bracketed.get());
+        if !bytes.is_empty() {
+            self.callback.borrow_mut()(&bytes);
+        }
+    }
+}
+
+/// Encodes pasted text as terminal input.
+///
+/// Line breaks (`\r\n` and `\n`) become `\r`. With `bracketed`, the text is
+/// wrapped in bracketed paste sequences, and any such sequences in the text
+/// are removed so that it cannot end the paste early. Empty text encodes to
+/// nothing.
+///
+/// # Example
+/// ```
+/// use beamterm_renderer::paste::encode_paste;
+///
+/// assert_eq!(encode_paste("ls\n", false), b"ls\r");
+/// assert_eq!(encode_paste("ls\n", true), b"\x1b[200~ls\r\x1b[201~");
+/// ```
+pub fn encode_paste(text: &str, bracketed: bool) -> Vec<u8> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+
+    let mut text = text.replace("\r\n", "\r").replace('\n', "\r");
+    if !bracketed {
+        return text.into_bytes();
+    }
+
+    while text.contains(PASTE_START) || text.contains(PASTE_END) {
+        text = text.replace(PASTE_START, "").replace(PASTE_END, "");
+    }
+    format!("{PASTE_START}{text}{PASTE_END}").into_bytes()
+}
+
+impl Drop for TerminalPasteHandler {
+    /// Automatically removes the event listener when handler is dropped.
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+impl Debug for TerminalPasteHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TerminalPasteHandler {{ bracketed: {} }}", self.is_bracketed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newlines_are_normalized() {
+        assert_eq!(encode_paste("a\r\nb\nc\rd", false), b"a\rb\rc\rd");
+        assert!(encode_paste("", true).is_empty());
+    }
+
+    #[test]
+    fn test_bracketed_paste_cannot_be_ended_early() {
+        assert_eq!(encode_paste("echo\x1b[201~; rm", true), b"\x1b[200~echo; rm\x1b[201~");
+        assert_eq!(encode_paste("\x1b[20\x1b[201~1~x", true), b"\x1b[200~x\x1b[201~");
+        assert_eq!(encode_paste("\x1b[201~", false), b"\x1b[201~");
+    }
+}
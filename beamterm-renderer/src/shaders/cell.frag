@@ -4,6 +4,7 @@ precision mediump float;
 
 // uniforms
 uniform mediump sampler2DArray u_sampler;
+uniform mediump sampler2D u_highlights;   // highlight color per viewport cell; a = opacity
 layout(std140) uniform FragUbo {
     vec2 u_padding_frac;             // padding as fraction of cell size
     float u_underline_pos;           // underline position (0.0 = top, 1.0 = bottom)
@@ -11,11 +12,30 @@ layout(std140) uniform FragUbo {
     float u_strikethrough_pos;       // strikethrough position (0.0 = top, 1.0 = bottom)
     float u_strikethrough_thickness; // strikethrough thickness as fraction of cell height
 };
-
+layout(std140) uniform PaletteUbo {
+    vec4 u_palette[258];             // 256 indexed colors, default fg, default bg
+};
+layout(std140) uniform CursorUbo {
+    vec4 u_cursor_color;             // a = 0.0: use the cell foreground color
+    vec4 u_cursor_text_color;        // a = 0.0: use the cell background color
+    vec2 u_cursor_pos;               // cursor cell position; negative when hidden
+    float u_cursor_shape;            // 0 = block, 1 = hollow block, 2 = bar, 3 = underline
+    float u_cursor_blink_interval;   // duration of each blink phase in seconds; 0.0 = steady
+    vec2 u_cursor_thickness;         // stroke width as fraction of cell size
+    float u_time;                    // seconds since the start of the blink cycle
+};
+layout(std140) uniform SelectionUbo {
+    vec4 u_selection_color;          // highlight color; a = opacity
+    vec4 u_selection_text_color;     // a = 0.0: keep the cell foreground color
+    vec4 u_selection_range;          // first (xy) and last (zw) selected cell
+    vec4 u_link_range;               // first (xy) and last (zw) cell of the hovered link; negative if none
+    float u_selection_mode;          // -1 = no selection, 0 = block, 1 = linear
+};
 
 // packs 8b: 2b glyph id, 3b fg.rgb, 3b bg.rgb
 // ref: https://github.com/junkdog/term-webgl2?tab=readme-ov-file#glyph-id-bit-layout-16-bit
 flat in uvec2 v_packed_data;
+flat in uvec2 v_grid_pos;
 in vec2 v_tex_coord;
 
 out vec4 FragColor;
@@ -28,6 +48,63 @@ float normalize_lsb(uint value) {
     return (float(value & 0xFFu)) * 0.003921568627451; // = 1.0 / 255.0;
 }
 
+// cursor coverage of the current fragment: 1.0 inside the cursor shape, 0.0 outside
+float cursor_coverage(vec2 tex_coord) {
+    if (any(notEqual(vec2(v_grid_pos), u_cursor_pos))) {
+        return 0.0;
+    }
+
+    // blinking cursors are visible during the first half of each cycle
+    float blink_on = u_cursor_blink_interval > 0.0
+        ? 1.0 - step(u_cursor_blink_interval, mod(u_time, 2.0 * u_cursor_blink_interval))
+        : 1.0;
+
+    vec2 t = u_cursor_thickness;
+    float shape_mask;
+    if (u_cursor_shape < 0.5) {         // block
+        shape_mask = 1.0;
+    } else if (u_cursor_shape < 1.5) {  // hollow block
+        shape_mask = float(any(lessThan(tex_coord, t)) || any(greaterThan(tex_coord, 1.0 - t)));
+    } else if (u_cursor_shape < 2.5) {  // bar
+        shape_mask = float(tex_coord.x < t.x);
+    } else {                            // underline
+        shape_mask = float(tex_coord.y > 1.0 - t.y);
+    }
+
+    return shape_mask * blink_on;
+}
+
+// 1.0 if the current cell is between first and last in reading order, 0.0 otherwise
+float linear_coverage(vec2 first, vec2 last) {
+    vec2 pos = vec2(v_grid_pos);
+    bool after_first = pos.y > first.y || (pos.y == first.y && pos.x >= first.x);
+    bool before_last = pos.y < last.y || (pos.y == last.y && pos.x <= last.x);
+    return float(after_first && before_last);
+}
+
+// 1.0 if the current cell is selected, 0.0 otherwise
+float selection_coverage() {
+    if (u_selection_mode < -0.5) {
+        return 0.0;
+    }
+
+    vec2 pos = vec2(v_grid_pos);
+    vec2 first = u_selection_range.xy;
+    vec2 last = u_selection_range.zw;
+    if (u_selection_mode < 0.5) { // block
+        return float(all(greaterThanEqual(pos, first)) && all(lessThanEqual(pos, last)));
+    }
+
+    return linear_coverage(first, last);
+}
+
+// resolves a packed color: either rgb, or a little-endian palette slot in r and g
+vec3 unpack_color(uint r, uint g, uint b, uint is_indexed) {
+    vec3 rgb = vec3(normalize_lsb(r), normalize_lsb(g), normalize_lsb(b));
+    uint slot = min((r & 0xFFu) | ((g & 0xFFu) << 8), 257u);
+    return mix(rgb, u_palette[slot].rgb, float(is_indexed));
+}
+
 void main() {
     // extract sequential glyph index from packed data
     uint glyph_index = v_packed_data.x & 0xFFFFu;
@@ -38,11 +115,15 @@ void main() {
 
     // apply strikethrough or underline if the glyph has either bit set
     // (it's easier to do this before we recalculate the tex_coord)
+    float underline = horizontal_line(v_tex_coord, u_underline_pos, u_underline_thickness);
     float line_alpha = max(
-        horizontal_line(v_tex_coord, u_underline_pos, u_underline_thickness) * float((glyph_index >> 12) & 0x1u),
+        underline * float((glyph_index >> 12) & 0x1u),
         horizontal_line(v_tex_coord, u_strikethrough_pos, u_strikethrough_thickness) * float((glyph_index >> 13) & 0x1u)
     );
 
+    // the hovered link is underlined; a negative range never covers a cell
+    line_alpha = max(line_alpha, underline * linear_coverage(u_link_range.xy, u_link_range.zw));
+
     vec2 inner_tex_coord = v_tex_coord * (1.0 - 2.0 * u_padding_frac) + u_padding_frac;
     vec3 tex_coord = vec3(
         (float(pos_in_layer) + inner_tex_coord.x + 0.001) * 0.0625, // 0.0625 = 1/16
@@ -51,10 +132,11 @@ void main() {
     );
 
     // the base foreground color is used for normal glyphs and underlines/strikethroughs
-    vec3 base_fg = vec3(
-        normalize_lsb(v_packed_data.x >> 16),
-        normalize_lsb(v_packed_data.x >> 24),
-        normalize_lsb(v_packed_data.y)
+    vec3 base_fg = unpack_color(
+        v_packed_data.x >> 16,
+        v_packed_data.x >> 24,
+        v_packed_data.y,
+        (glyph_index >> 14) & 0x1u
     );
 
     vec4 glyph = texture(u_sampler, tex_coord);
@@ -74,11 +156,31 @@ void main() {
     // make sure to set the alpha when drawing a line
     float a = max(glyph.a, line_alpha);
 
-    vec3 bg = vec3(
-        normalize_lsb(v_packed_data.y >> 8),
-        normalize_lsb(v_packed_data.y >> 16),
-        normalize_lsb(v_packed_data.y >> 24)
+    vec3 bg = unpack_color(
+        v_packed_data.y >> 8,
+        v_packed_data.y >> 16,
+        v_packed_data.y >> 24,
+        (glyph_index >> 15) & 0x1u
     );
 
-    FragColor = vec4(mix(bg, fg, a), 1.0);
+    // highlights and the selection are blended over the background; emoji keep their colors
+    vec4 highlight = texelFetch(u_highlights, ivec2(v_grid_pos), 0);
+    bg = mix(bg, highlight.rgb, highlight.a);
+
+    float selected = selection_coverage();
+    bg = mix(bg, u_selection_color.rgb, selected * u_selection_color.a);
+    fg = mix(fg, u_selection_text_color.rgb, selected * u_selection_text_color.a * (1.0 - emoji_factor));
+
+    // the cursor is drawn over the cell without modifying the cell data
+    float cursor = cursor_coverage(v_tex_coord);
+    vec3 cursor_color = mix(base_fg, u_cursor_color.rgb, u_cursor_color.a);
+    if (u_cursor_shape < 0.5) {
+        // block cursor: fill the cell, drawing the glyph in the cursor text color
+        vec3 cursor_text = mix(bg, u_cursor_text_color.rgb, u_cursor_text_color.a);
+        bg = mix(bg, cursor_color, cursor);
+        fg = mix(fg, cursor_text, cursor * (1.0 - emoji_factor));
+        FragColor = vec4(mix(bg, fg, a), 1.0);
+    } else {
+        FragColor = vec4(mix(mix(bg, fg, a), cursor_color, cursor), 1.0);
+    }
 }
\ No newline at end of file
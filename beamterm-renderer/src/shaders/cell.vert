@@ -12,20 +12,28 @@ layout(location = 3) in uvec2 a_packed_data;
 // uniforms
 layout(std140) uniform VertUbo {
     mat4 u_projection;
-    vec2 u_cell_size; // unpadded cell size in pixels
+    vec2 u_cell_size;   // unpadded cell size in pixels
+    float u_row_offset; // instance row holding the top viewport row
+    float u_grid_rows;  // number of rows in the instance buffer
 };
 
 // packs 8b: 2b layer, 3b fg.rgb, 3b bg.rgb
 flat out uvec2 v_packed_data;
+flat out uvec2 v_grid_pos;
 out vec2 v_tex_coord;
 
 void main() {
     v_tex_coord = a_tex_coord;
     v_packed_data = a_packed_data;
 
+    // instance rows form a ring buffer; resolve the viewport row
+    uint rows = uint(u_grid_rows);
+    uint row = (a_instance_pos.y + rows - uint(u_row_offset)) % rows;
+    v_grid_pos = uvec2(a_instance_pos.x, row);
+
     vec2 offset = vec2(
         floor(float(a_instance_pos.x) * u_cell_size.x + 0.5), // pixel-snapped
-        floor(float(a_instance_pos.y) * u_cell_size.y + 0.5)  // pixel-snapped
+        floor(float(row) * u_cell_size.y + 0.5)               // pixel-snapped
     );
 
     gl_Position = u_projection * vec4(a_pos + offset, 0.0, 1.0);
@@ -1,15 +1,35 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, future::Future, rc::Rc};
 
 use beamterm_data::FontAtlasData;
 use compact_str::CompactString;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 
+#[cfg(feature = "emulator")]
+use crate::emulator::Emulator;
 use crate::{
+    accessibility::TerminalAccessibility,
+    context_loss::{ContextEvent, ContextEventCallback, ContextLossHandler},
+    export::{self, ScreenExport},
+    focus::CanvasFocusHandler,
     gl::{CellQuery, SelectionMode},
+    headless::RgbaImage,
+    hidpi::{PixelRatioCallback, PixelRatioHandler, ScaledAtlases},
+    ime::{TerminalImeHandler, TextInputCallback},
+    js,
+    keyboard::{KeyboardEventCallback, KeyboardModes, TerminalKeyboardHandler},
     mouse::{
-        DefaultSelectionHandler, MouseEventCallback, TerminalMouseEvent, TerminalMouseHandler,
+        hit_region_event_handler, link_event_handler, reporting_event_handler,
+        DefaultSelectionHandler, HitRegion, HitRegionCallback, HitRegionEvent, HitRegions,
+        LinkCallback, MouseEncoder, MouseEventCallback, MouseModes, MouseReportCallback,
+        TerminalMouseEvent, TerminalMouseHandler,
     },
-    CellData, Error, FontAtlas, Renderer, TerminalGrid,
+    paste::{PasteCallback, TerminalPasteHandler},
+    CellData, Color, Cursor, CursorShape, Error, FontAtlas, Highlight, Highlights, HyperlinkId,
+    Palette, Renderer, SelectionHighlight, TerminalGrid,
 };
+#[cfg(feature = "regex")]
+use crate::{SearchQuery, SearchResults};
 
 /// High-performance WebGL2 terminal renderer.
 ///
@@ -21,7 +41,8 @@ use crate::{
 /// The renderer supports mouse-driven text selection with automatic clipboard
 /// integration:
 ///
-/// ```rust
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// // Enable default selection handler
 /// use beamterm_renderer::{SelectionMode, Terminal};
 ///
@@ -35,11 +56,47 @@ use crate::{
 ///         // Custom handler logic
 ///     })
 ///     .build()?;
+/// # Ok(())
+/// # }
 ///```
 ///
+/// ## Scrollback
+///
+/// Lines pushed off the top of the screen are kept in an optional scrollback,
+/// which the viewport can be scrolled into:
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use beamterm_renderer::Terminal;
+///
+/// let mut terminal = Terminal::builder("#canvas").scrollback(10_000).build()?;
+/// terminal.push_lines_to_scrollback(1);
+/// terminal.scroll_viewport_by(5); // five lines back
+/// terminal.scroll_viewport_to(0); // back to the screen
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Cursor
+///
+/// The cursor is drawn by the renderer on top of the cell grid, so applications
+/// don't need to modify cell colors to display it:
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use beamterm_renderer::{CursorShape, Terminal};
+///
+/// let mut terminal = Terminal::builder("#canvas").build()?;
+/// terminal.set_cursor(0, 0, CursorShape::Bar);
+/// terminal.set_cursor_blinking(true);
+/// # Ok(())
+/// # }
+/// ```
+///
 /// # Examples
 ///
-/// ```rust
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// use beamterm_renderer::{CellData, Terminal};
 ///
 /// // Create and render a simple terminal
@@ -55,12 +112,28 @@ use crate::{
 /// // Handle window resize
 /// let (new_width, new_height) = (800, 600);
 /// terminal.resize(new_width, new_height)?;
+/// # Ok(())
+/// # }
 /// ```
 #[derive(Debug)]
 pub struct Terminal {
     renderer: Renderer,
     grid: Rc<RefCell<TerminalGrid>>,
     mouse_handler: Option<TerminalMouseHandler>, // 🐀
+    mouse_encoder: Option<Rc<RefCell<MouseEncoder>>>,
+    hit_regions: Rc<RefCell<HitRegions>>,
+    keyboard_handler: Option<TerminalKeyboardHandler>,
+    ime_handler: Option<TerminalImeHandler>,
+    paste_handler: Option<TerminalPasteHandler>,
+    accessibility: Option<TerminalAccessibility>,
+    focus_handler: Option<CanvasFocusHandler>,
+    context_handler: ContextLossHandler,
+    atlases: ScaledAtlases,
+    pixel_ratio_handler: Option<PixelRatioHandler>,
+    #[cfg(feature = "regex")]
+    search: SearchResults,
+    #[cfg(feature = "emulator")]
+    emulator: Emulator,
 }
 
 impl Terminal {
@@ -71,7 +144,8 @@ impl Terminal {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// // Using CSS selector
     /// use web_sys::HtmlCanvasElement;
     /// use beamterm_renderer::Terminal;
@@ -81,6 +155,8 @@ impl Terminal {
     /// // Using canvas element
     /// let canvas: &HtmlCanvasElement = unimplemented!("document.get_element_by_id(...)");
     /// let terminal = Terminal::builder(canvas).build()?;
+    /// # Ok(())
+    /// # }
     /// ```
     #[allow(private_bounds)]
     pub fn builder(canvas: impl Into<CanvasSource>) -> TerminalBuilder {
@@ -98,7 +174,7 @@ impl Terminal {
         &mut self,
         cells: impl Iterator<Item = CellData<'a>>,
     ) -> Result<(), Error> {
-        self.grid.borrow_mut().update_cells(self.renderer.gl(), cells)
+        self.grid.borrow_mut().update_cells(cells)
     }
 
     /// Updates terminal cell content efficiently.
@@ -112,7 +188,7 @@ impl Terminal {
         &mut self,
         cells: impl Iterator<Item = (u16, u16, CellData<'a>)>,
     ) -> Result<(), Error> {
-        self.grid.borrow_mut().update_cells_by_position(self.renderer.gl(), cells)
+        self.grid.borrow_mut().update_cells_by_position(cells)
     }
 
     /// Returns the WebGL2 rendering context.
@@ -127,23 +203,235 @@ impl Terminal {
     /// recalculated based on the cell size from the font atlas.
     ///
     /// Combines [`Renderer::resize`] and [`TerminalGrid::resize`] operations.
+    /// With scaled font atlases, see [`TerminalBuilder::scaled_font_atlas`],
+    /// the size is in CSS pixels and the canvas backing store is scaled by
+    /// [`Terminal::pixel_ratio`], as with [`Renderer::resize_css`].
     pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
-        self.renderer.resize(width, height);
-        self.grid.borrow_mut().resize(self.renderer.gl(), (width, height))?;
-
-        if let Some(mouse_input) = &mut self.mouse_handler {
-            let (cols, rows) = self.grid.borrow_mut().terminal_size();
-            mouse_input.update_dimensions(cols, rows);
+        match self.renderer.css_size() {
+            Some(_) => self.renderer.resize_css(width, height),
+            None => self.renderer.resize(width, height),
         }
+        let canvas_size = self.renderer.canvas_size();
+        self.grid.borrow_mut().resize(self.renderer.gl(), canvas_size)?;
+
+        self.update_mouse_metrics();
+        self.update_ime_layout();
+        Ok(())
+    }
+
+    /// Returns the ratio of canvas backing store pixels to CSS pixels: the
+    /// scale of the font atlas in use, or `1.0` without scaled font atlases.
+    pub fn pixel_ratio(&self) -> f32 {
+        self.renderer.pixel_ratio()
+    }
 
+    /// Switches to the scaled font atlas closest to the current device pixel
+    /// ratio, resizing the canvas backing store to its scale.
+    fn apply_pixel_ratio(&mut self) -> Result<(), Error> {
+        let device_pixel_ratio = js::device_pixel_ratio() as f32;
+        let mut grid = self.grid.borrow_mut();
+        let Some(scale) = self.atlases.switch(self.renderer.gl(), &mut grid, device_pixel_ratio)?
+        else {
+            return Ok(());
+        };
+
+        self.renderer.set_pixel_ratio(scale);
+        grid.resize(self.renderer.gl(), self.renderer.canvas_size())?;
+        drop(grid);
+
+        self.update_mouse_metrics();
+        self.update_ime_layout();
         Ok(())
     }
 
+    /// Repositions the IME textarea, which is sized in CSS pixels, over the
+    /// caret cell.
+    fn update_ime_layout(&self) {
+        if let Some(ime) = &self.ime_handler {
+            ime.update_layout(self.renderer.pixel_ratio());
+        }
+    }
+
+    /// Updates the terminal size and the cell size in CSS pixels used by the
+    /// mouse handler for coordinate conversion.
+    fn update_mouse_metrics(&mut self) {
+        let Some(mouse_input) = &mut self.mouse_handler else {
+            return;
+        };
+
+        let grid = self.grid.borrow();
+        let (cols, rows) = grid.terminal_size();
+        mouse_input.update_dimensions(cols, rows);
+
+        let (cell_width, cell_height) = grid.cell_size();
+        let ratio = self.renderer.pixel_ratio();
+        mouse_input.update_cell_size(cell_width as f32 / ratio, cell_height as f32 / ratio);
+    }
+
     /// Returns the terminal dimensions in cells.
     pub fn terminal_size(&self) -> (u16, u16) {
         self.grid.borrow().terminal_size()
     }
 
+    /// Returns a copy of the color palette used for indexed cell colors.
+    pub fn palette(&self) -> Palette {
+        self.grid.borrow().palette().clone()
+    }
+
+    /// Replaces the color palette used for indexed cell colors.
+    ///
+    /// All cells using [`crate::Color::Indexed`], [`crate::Color::DefaultFg`] or
+    /// [`crate::Color::DefaultBg`] are recolored on the next frame, without
+    /// touching or re-uploading any cell data.
+    ///
+    /// Delegates to [`TerminalGrid::set_palette`].
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.grid.borrow_mut().set_palette(self.renderer.gl(), palette);
+    }
+
+    /// Returns a copy of the cursor state.
+    pub fn cursor(&self) -> Cursor {
+        self.grid.borrow().cursor().clone()
+    }
+
+    /// Moves the cursor to the given cell, sets its shape and makes it visible.
+    ///
+    /// The cursor is drawn on top of the cell without modifying the cell data.
+    /// While the canvas is unfocused, the cursor is drawn as a hollow block.
+    pub fn set_cursor(&mut self, col: u16, row: u16, shape: CursorShape) {
+        self.grid.borrow_mut().cursor_mut().set(col, row, shape);
+    }
+
+    /// Shows or hides the cursor.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.grid.borrow_mut().cursor_mut().set_visible(visible);
+    }
+
+    /// Sets the cursor color.
+    ///
+    /// With `None`, the palette's cursor color is used if the palette defines one,
+    /// otherwise the foreground color of the cell under the cursor.
+    pub fn set_cursor_color(&mut self, color: Option<Color>) {
+        self.grid.borrow_mut().cursor_mut().set_color(color);
+    }
+
+    /// Enables or disables cursor blinking. The blink cycle restarts whenever
+    /// the cursor is moved.
+    pub fn set_cursor_blinking(&mut self, blinking: bool) {
+        self.grid.borrow_mut().cursor_mut().set_blinking(blinking);
+    }
+
+    /// Returns the appearance of selected cells.
+    pub fn selection_highlight(&self) -> SelectionHighlight {
+        *self.grid.borrow().selection_highlight()
+    }
+
+    /// Sets the appearance of selected cells, which are highlighted by the
+    /// shader without modifying the cell data.
+    ///
+    /// Delegates to [`TerminalGrid::set_selection_highlight`].
+    pub fn set_selection_highlight(&mut self, highlight: SelectionHighlight) {
+        self.grid.borrow_mut().set_selection_highlight(highlight);
+    }
+
+    /// Returns the named highlights drawn over the cells.
+    pub fn highlights(&self) -> Highlights {
+        self.grid.borrow().highlights().clone()
+    }
+
+    /// Adds a highlight under `name`, replacing and returning any highlight
+    /// of the same name. Highlights are drawn by the shader below the
+    /// selection, without modifying the cell data.
+    pub fn set_highlight(&mut self, name: &str, highlight: Highlight) -> Option<Highlight> {
+        self.grid.borrow_mut().highlights_mut().insert(name, highlight)
+    }
+
+    /// Removes and returns the highlight named `name`.
+    pub fn remove_highlight(&mut self, name: &str) -> Option<Highlight> {
+        self.grid.borrow_mut().highlights_mut().remove(name)
+    }
+
+    /// Removes all highlights.
+    pub fn clear_highlights(&mut self) {
+        self.grid.borrow_mut().highlights_mut().clear();
+    }
+
+    /// Returns the id of the hyperlink to `url`, registering it if new.
+    ///
+    /// Delegates to [`CellBuffer::register_hyperlink`](crate::CellBuffer::register_hyperlink).
+    pub fn register_hyperlink(&mut self, url: &str, id: Option<&str>) -> Option<HyperlinkId> {
+        self.grid.borrow_mut().cell_buffer_mut().register_hyperlink(url, id)
+    }
+
+    /// Sets or clears the hyperlink of a screen cell. Writing to a cell
+    /// clears its hyperlink.
+    pub fn set_hyperlink(&mut self, x: u16, y: u16, link: Option<HyperlinkId>) {
+        self.grid.borrow_mut().cell_buffer_mut().set_hyperlink(x, y, link);
+    }
+
+    /// Returns the hit regions receiving mouse events.
+    pub fn hit_regions(&self) -> HitRegions {
+        self.hit_regions.borrow().clone()
+    }
+
+    /// Adds a hit region under `id`, replacing and returning any region of the
+    /// same id. Its events are passed to the
+    /// [hit region handler](TerminalBuilder::hit_region_handler).
+    pub fn set_hit_region(&mut self, id: &str, region: HitRegion) -> Option<HitRegion> {
+        self.hit_regions.borrow_mut().insert(id, region)
+    }
+
+    /// Removes and returns the hit region `id`.
+    pub fn remove_hit_region(&mut self, id: &str) -> Option<HitRegion> {
+        self.hit_regions.borrow_mut().remove(id)
+    }
+
+    /// Removes all hit regions.
+    pub fn clear_hit_regions(&mut self) {
+        self.hit_regions.borrow_mut().clear();
+    }
+
+    /// Scrolls the screen content up by `count` lines, pushing the top lines
+    /// into the scrollback. The rows exposed at the bottom are cleared.
+    ///
+    /// Delegates to [`TerminalGrid::push_lines_to_scrollback`].
+    pub fn push_lines_to_scrollback(&mut self, count: u16) {
+        self.grid.borrow_mut().push_lines_to_scrollback(count);
+    }
+
+    /// Sets the maximum number of lines retained in the scrollback; `0`
+    /// disables the scrollback.
+    pub fn set_scrollback_capacity(&mut self, lines: usize) {
+        self.grid.borrow_mut().set_scrollback_capacity(lines);
+    }
+
+    /// Returns the number of lines currently held in the scrollback.
+    pub fn scrollback_len(&self) -> usize {
+        self.grid.borrow().scrollback_len()
+    }
+
+    /// Returns the number of lines the viewport is scrolled back into the
+    /// scrollback; `0` when the screen is displayed.
+    pub fn viewport_offset(&self) -> usize {
+        self.grid.borrow().viewport_offset()
+    }
+
+    /// Scrolls the viewport by `lines`; positive values scroll back towards
+    /// older lines.
+    ///
+    /// Delegates to [`TerminalGrid::scroll_viewport_by`].
+    pub fn scroll_viewport_by(&mut self, lines: i32) {
+        self.grid.borrow_mut().scroll_viewport_by(lines);
+    }
+
+    /// Scrolls the viewport to an absolute number of lines back into the
+    /// scrollback; `0` displays the screen.
+    ///
+    /// Delegates to [`TerminalGrid::scroll_viewport_to`].
+    pub fn scroll_viewport_to(&mut self, offset: usize) {
+        self.grid.borrow_mut().scroll_viewport_to(offset);
+    }
+
     /// Returns the total number of cells in the terminal grid.
     pub fn cell_count(&self) -> usize {
         self.grid.borrow().cell_count()
@@ -159,6 +447,65 @@ impl Terminal {
         self.grid.borrow().cell_size()
     }
 
+    /// Moves input focus to the terminal canvas, so that it receives key events.
+    /// With IME input enabled, focus moves on to the IME textarea.
+    pub fn focus(&self) {
+        let _ = self.renderer.canvas().focus();
+    }
+
+    /// Sets the mouse reporting modes requested by the terminal application.
+    /// Has no effect without a mouse reporting handler.
+    ///
+    /// With the `emulator` feature, `Terminal::write` updates the modes
+    /// automatically.
+    pub fn set_mouse_modes(&mut self, modes: MouseModes) {
+        if let Some(encoder) = &self.mouse_encoder {
+            encoder.borrow_mut().set_modes(modes);
+        }
+    }
+
+    /// Moves the IME caret to the given cell, where composition text is drawn
+    /// and IME candidate windows open. Has no effect without a text input handler.
+    ///
+    /// With the `emulator` feature, `Terminal::write` keeps the caret at the
+    /// cursor position.
+    pub fn set_ime_caret(&mut self, col: u16, row: u16) {
+        if let Some(ime) = &self.ime_handler {
+            ime.set_caret(col, row);
+        }
+    }
+
+    /// Sets the keyboard modes used for encoding key events, as requested by
+    /// the terminal application. Has no effect without a keyboard input handler.
+    ///
+    /// With the `emulator` feature, `Terminal::write` updates the modes
+    /// automatically.
+    pub fn set_keyboard_modes(&mut self, modes: KeyboardModes) {
+        if let Some(keyboard) = &self.keyboard_handler {
+            keyboard.set_modes(modes);
+        }
+    }
+
+    /// Sets whether pasted text is wrapped in bracketed paste sequences, as
+    /// requested by the terminal application. Has no effect without a paste
+    /// handler.
+    ///
+    /// With the `emulator` feature, `Terminal::write` updates the mode
+    /// automatically.
+    pub fn set_bracketed_paste(&mut self, enabled: bool) {
+        if let Some(paste) = &self.paste_handler {
+            paste.set_bracketed(enabled);
+        }
+    }
+
+    /// Pastes text as if from the clipboard, delivering it to the paste
+    /// handler. Has no effect without a paste handler.
+    pub fn paste(&self, text: &str) {
+        if let Some(paste) = &self.paste_handler {
+            paste.paste(text);
+        }
+    }
+
     /// Returns a reference to the HTML canvas element used for rendering.
     pub fn canvas(&self) -> &web_sys::HtmlCanvasElement {
         self.renderer.canvas()
@@ -174,11 +521,72 @@ impl Terminal {
         self.grid.clone()
     }
 
+    /// Finds the matches of a query among the displayed cells, replacing the
+    /// results of any earlier search, and returns the number of matches.
+    ///
+    /// Step through the matches with [`Terminal::find_next`] and
+    /// [`Terminal::find_previous`].
+    ///
+    /// # Errors
+    /// Returns an error if the query is an invalid regular expression.
+    #[cfg(feature = "regex")]
+    pub fn find(&mut self, query: &SearchQuery) -> Result<usize, Error> {
+        self.search = self.grid.borrow().find(query)?;
+        Ok(self.search.len())
+    }
+
+    /// Selects and returns the next match of the last search, wrapping
+    /// around after the last one.
+    #[cfg(feature = "regex")]
+    pub fn find_next(&mut self) -> Option<CellQuery> {
+        self.search.refresh(self.grid.borrow().cell_buffer());
+        let found = self.search.next_match();
+        self.select_match(found)
+    }
+
+    /// Selects and returns the previous match of the last search, wrapping
+    /// around before the first one.
+    #[cfg(feature = "regex")]
+    pub fn find_previous(&mut self) -> Option<CellQuery> {
+        self.search.refresh(self.grid.borrow().cell_buffer());
+        let found = self.search.previous_match();
+        self.select_match(found)
+    }
+
+    /// Returns the results of the last search, which are outdated if the
+    /// displayed cells changed since; see [`SearchResults::refresh`].
+    #[cfg(feature = "regex")]
+    pub fn search_results(&self) -> &SearchResults {
+        &self.search
+    }
+
+    /// Makes a match the active selection, which is highlighted and can be
+    /// copied like a selection made with the mouse.
+    #[cfg(feature = "regex")]
+    fn select_match(&self, found: Option<CellQuery>) -> Option<CellQuery> {
+        let query = found?;
+        self.grid.borrow().selection_tracker().set_query(query);
+        Some(query)
+    }
+
     /// Returns the textual content of the specified cell selection.
     pub fn get_text(&self, selection: CellQuery) -> CompactString {
         self.grid.borrow().get_text(selection)
     }
 
+    /// Returns the content of the specified cell selection as text with ANSI
+    /// escape sequences for its colors and styles.
+    ///
+    /// See [`ScreenExport::to_ansi`].
+    pub fn get_ansi_text(&self, selection: CellQuery) -> String {
+        ScreenExport::from_grid(&self.grid.borrow()).region(selection).to_ansi()
+    }
+
+    /// Returns `true` while the WebGL context is lost.
+    pub fn is_context_lost(&self) -> bool {
+        self.context_handler.is_lost()
+    }
+
     /// Renders the current terminal state to the canvas.
     ///
     /// This method performs the complete render pipeline: frame setup, grid rendering,
@@ -186,14 +594,127 @@ impl Terminal {
     /// the changes.
     ///
     /// Combines [`Renderer::begin_frame`], [`Renderer::render`], and [`Renderer::end_frame`].
+    /// With [`TerminalBuilder::accessibility`] enabled, also updates the screen
+    /// reader mirror.
+    ///
+    /// Nothing is drawn while the WebGL context is lost. The first frame after
+    /// the context is restored recreates all GL resources; see
+    /// [`crate::context_loss`]. With scaled font atlases, the first frame
+    /// after the device pixel ratio changed switches atlases; see
+    /// [`crate::hidpi`].
     pub fn render_frame(&mut self) -> Result<(), Error> {
+        if self.context_handler.is_lost() {
+            return Ok(());
+        }
+        if self.context_handler.take_restore() {
+            self.renderer.restore();
+            self.grid.borrow_mut().restore(self.renderer.gl())?;
+        }
+        if self.pixel_ratio_handler.as_ref().is_some_and(PixelRatioHandler::take_changed) {
+            self.apply_pixel_ratio()?;
+        }
+
         self.grid.borrow_mut().flush_cells(self.renderer.gl())?;
+        self.grid.borrow_mut().update_cursor(self.renderer.gl(), js::now_ms());
 
         self.renderer.begin_frame();
         self.renderer.render(&*self.grid.borrow());
         self.renderer.end_frame();
+
+        if let Some(accessibility) = &mut self.accessibility {
+            accessibility.update(&self.grid.borrow());
+        }
         Ok(())
     }
+
+    /// Feeds terminal application output, such as bytes read from a PTY, to the
+    /// terminal's [`Emulator`] and updates the cursor to match.
+    ///
+    /// Replies to queries from the application are available from
+    /// [`Emulator::take_responses`] via [`Terminal::emulator_mut`].
+    #[cfg(feature = "emulator")]
+    pub fn write(&mut self, bytes: &[u8]) {
+        let mut grid = self.grid.borrow_mut();
+        self.emulator.process(bytes, grid.cell_buffer_mut());
+
+        let (col, row) = self.emulator.cursor_position();
+        let shape = self.emulator.cursor_shape();
+        let visible = self.emulator.modes().cursor_visible;
+        let blinking = self.emulator.is_cursor_blinking();
+
+        // only touch the cursor when it changed, as that restarts the blink cycle
+        let cursor = grid.cursor();
+        let changed = cursor.position() != (col, row)
+            || cursor.shape() != shape
+            || cursor.is_visible() != visible
+            || cursor.is_blinking() != blinking;
+        if changed {
+            let cursor = grid.cursor_mut();
+            cursor.set(col, row, shape);
+            cursor.set_visible(visible);
+            cursor.set_blinking(blinking);
+        }
+
+        if let Some(keyboard) = &self.keyboard_handler {
+            keyboard.set_modes(KeyboardModes::from(self.emulator.modes()));
+        }
+        if let Some(encoder) = &self.mouse_encoder {
+            encoder.borrow_mut().set_modes(MouseModes::from(self.emulator.modes()));
+        }
+        if let Some(paste) = &self.paste_handler {
+            paste.set_bracketed(self.emulator.modes().bracketed_paste);
+        }
+        if let Some(ime) = &self.ime_handler {
+            ime.set_caret(col, row);
+        }
+    }
+
+    /// Returns the terminal's escape sequence emulator.
+    #[cfg(feature = "emulator")]
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    /// Returns the terminal's escape sequence emulator for modification.
+    #[cfg(feature = "emulator")]
+    pub fn emulator_mut(&mut self) -> &mut Emulator {
+        &mut self.emulator
+    }
+
+    /// Exports the visible screen as an SVG document, with one `<text>` element
+    /// per run of identically styled cells.
+    pub fn export_svg(&self) -> String {
+        ScreenExport::from_grid(&self.grid.borrow()).to_svg()
+    }
+
+    /// Exports the visible screen as a standalone HTML `<pre>` element with
+    /// inline styles.
+    pub fn export_html(&self) -> String {
+        ScreenExport::from_grid(&self.grid.borrow()).to_html()
+    }
+
+    /// Renders a frame and reads it back from the canvas as an RGBA image.
+    pub fn screenshot(&mut self) -> Result<RgbaImage, Error> {
+        self.render_frame()?;
+        export::read_framebuffer(self.renderer.gl(), self.renderer.canvas_size())
+    }
+
+    /// Renders a frame and encodes it as a PNG file with the browser's
+    /// encoder, resolving to a `Blob` once encoded.
+    ///
+    /// The frame is captured before returning, so later frames don't affect
+    /// the screenshot.
+    pub fn screenshot_png(
+        &mut self,
+    ) -> Result<impl Future<Output = Result<web_sys::Blob, Error>>, Error> {
+        self.render_frame()?;
+        let png = JsFuture::from(export::canvas_png(self.renderer.canvas())?);
+        Ok(async move {
+            png.await
+                .map(JsCast::unchecked_into)
+                .map_err(|_| Error::png_encoding_failed("the browser failed to encode the canvas"))
+        })
+    }
 }
 
 /// Canvas source for terminal initialization.
@@ -214,25 +735,43 @@ enum CanvasSource {
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// // Simple terminal with default configuration
 /// use beamterm_renderer::{FontAtlas, FontAtlasData, Terminal};
 ///
 /// let terminal = Terminal::builder("#canvas").build()?;
 ///
 /// // Terminal with custom font atlas
-/// let atlas = FontAtlasData::from_binary(unimplemented!(".atlas data"))?;
+/// let atlas = FontAtlasData::from_binary(unimplemented!(".atlas data")).map_err(|e| e.message)?;
 /// let terminal = Terminal::builder("#canvas")
 ///     .font_atlas(atlas)
 ///     .fallback_glyph("X".into())
 ///     .build()?;
+/// # Ok(())
+/// # }
 /// ```
 pub struct TerminalBuilder {
     canvas: CanvasSource,
     atlas_data: Option<FontAtlasData>,
+    scaled_atlases: ScaledAtlases,
+    pixel_ratio_callback: Option<PixelRatioCallback>,
     fallback_glyph: Option<CompactString>,
     input_handler: Option<InputHandler>,
+    word_separators: Option<CompactString>,
+    mouse_report_callback: Option<MouseReportCallback>,
+    link_callback: Option<LinkCallback>,
+    hit_region_callback: Option<HitRegionCallback>,
+    keyboard_callback: Option<KeyboardEventCallback>,
+    text_input_callback: Option<TextInputCallback>,
+    paste_callback: Option<PasteCallback>,
+    context_callback: Option<ContextEventCallback>,
     canvas_padding_color: u32,
+    palette: Option<Palette>,
+    selection_highlight: Option<SelectionHighlight>,
+    scrollback_capacity: usize,
+    accessibility: bool,
+    focus_tracking: bool,
 }
 
 impl TerminalBuilder {
@@ -241,9 +780,24 @@ impl TerminalBuilder {
         TerminalBuilder {
             canvas,
             atlas_data: None,
+            scaled_atlases: ScaledAtlases::default(),
+            pixel_ratio_callback: None,
             fallback_glyph: None,
             input_handler: None,
+            word_separators: None,
+            mouse_report_callback: None,
+            link_callback: None,
+            hit_region_callback: None,
+            keyboard_callback: None,
+            text_input_callback: None,
+            paste_callback: None,
+            context_callback: None,
             canvas_padding_color: 0x000000,
+            palette: None,
+            selection_highlight: None,
+            scrollback_capacity: 0,
+            accessibility: false,
+            focus_tracking: false,
         }
     }
 
@@ -256,6 +810,29 @@ impl TerminalBuilder {
         self
     }
 
+    /// Adds a font atlas rasterized for `scale` device pixels per CSS pixel,
+    /// such as `2.0` for an atlas at twice the font size; see [`crate::hidpi`].
+    ///
+    /// The atlas closest to `window.devicePixelRatio` is used, switching when
+    /// the ratio changes. All scaled atlases must contain the same glyphs. When
+    /// any are added, [`TerminalBuilder::font_atlas`] is ignored, and the
+    /// canvas size in pixels is taken as its CSS size.
+    pub fn scaled_font_atlas(mut self, scale: f32, atlas: FontAtlasData) -> Self {
+        self.scaled_atlases.insert(scale, atlas);
+        self
+    }
+
+    /// Sets a callback notified when the device pixel ratio changes, with
+    /// scaled font atlases. The next frame may switch atlases, changing the
+    /// terminal size in cells.
+    pub fn pixel_ratio_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(f32) + 'static,
+    {
+        self.pixel_ratio_callback = Some(Box::new(callback));
+        self
+    }
+
     /// Sets the fallback glyph for missing characters.
     ///
     /// When a character is not found in the font atlas, this glyph will be
@@ -275,6 +852,49 @@ impl TerminalBuilder {
         self
     }
 
+    /// Sets the color palette for resolving indexed cell colors.
+    ///
+    /// Defaults to the xterm palette with white on black default colors.
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Sets the appearance of selected cells.
+    ///
+    /// Defaults to the palette's selection color, or the default foreground
+    /// color, at [`SelectionHighlight::DEFAULT_OPACITY`].
+    pub fn selection_highlight(mut self, highlight: SelectionHighlight) -> Self {
+        self.selection_highlight = Some(highlight);
+        self
+    }
+
+    /// Sets the number of lines retained in the scrollback.
+    ///
+    /// Defaults to `0`, which disables the scrollback.
+    pub fn scrollback(mut self, lines: usize) -> Self {
+        self.scrollback_capacity = lines;
+        self
+    }
+
+    /// Enables the screen reader mirror of the terminal content; see
+    /// [`TerminalAccessibility`].
+    ///
+    /// Defaults to `false`.
+    pub fn accessibility(mut self, enabled: bool) -> Self {
+        self.accessibility = enabled;
+        self
+    }
+
+    /// Tracks input focus of the canvas, drawing the cursor as a hollow block
+    /// while unfocused. Makes the canvas focusable, as a tab stop.
+    ///
+    /// Always enabled with a keyboard or text input handler. Defaults to `false`.
+    pub fn focus_tracking(mut self, enabled: bool) -> Self {
+        self.focus_tracking = enabled;
+        self
+    }
+
     /// Sets a callback for handling terminal mouse input events.
     pub fn mouse_input_handler<F>(mut self, callback: F) -> Self
     where
@@ -297,6 +917,96 @@ impl TerminalBuilder {
         self
     }
 
+    /// Sets the characters ending a word selected by double-clicking with the
+    /// default selection handler, besides whitespace.
+    ///
+    /// Defaults to [`DEFAULT_WORD_SEPARATORS`](crate::mouse::DEFAULT_WORD_SEPARATORS).
+    pub fn word_separators(mut self, separators: &str) -> Self {
+        self.word_separators = Some(separators.into());
+        self
+    }
+
+    /// Sets a callback receiving mouse reports, while the terminal application
+    /// has requested mouse reporting; see [`MouseEncoder`].
+    ///
+    /// While reporting, the mouse input handler only receives events with
+    /// Shift held, so that text can still be selected.
+    pub fn mouse_reporting_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&[u8]) + 'static,
+    {
+        self.mouse_report_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback receiving the URL of hyperlinks clicked with Ctrl or
+    /// Cmd held. Hovered hyperlinks are underlined and show a pointer cursor.
+    ///
+    /// Hyperlinks are set per cell, as by OSC 8, or detected in the text; see
+    /// [`CellBuffer::link_at`](crate::CellBuffer::link_at).
+    pub fn link_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&str) + 'static,
+    {
+        self.link_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback receiving the enter, leave and click events of the hit
+    /// regions added with [`Terminal::set_hit_region`]. Mouse events are
+    /// still passed to the other mouse handlers.
+    pub fn hit_region_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(HitRegionEvent) + 'static,
+    {
+        self.hit_region_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback receiving keyboard input, encoded as the byte sequences
+    /// expected by terminal applications; see [`TerminalKeyboardHandler`].
+    pub fn keyboard_input_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&[u8]) + 'static,
+    {
+        self.keyboard_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Enables IME composition and soft keyboard input, with a callback
+    /// receiving committed text; see [`TerminalImeHandler`].
+    ///
+    /// Key events are then read from the IME textarea rather than the canvas.
+    pub fn text_input_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&str) + 'static,
+    {
+        self.text_input_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback receiving pasted text, encoded as terminal input bytes;
+    /// see [`TerminalPasteHandler`].
+    ///
+    /// Middle-clicking pastes the active selection.
+    pub fn paste_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&[u8]) + 'static,
+    {
+        self.paste_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback notified when the WebGL context is lost or restored;
+    /// see [`ContextLossHandler`].
+    pub fn context_loss_handler<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(ContextEvent) + 'static,
+    {
+        self.context_callback = Some(Box::new(callback));
+        self
+    }
+
     /// Builds the terminal with the configured options.
     pub fn build(self) -> Result<Terminal, Error> {
         // setup renderer
@@ -304,54 +1014,183 @@ impl TerminalBuilder {
             CanvasSource::Id(id) => Renderer::create(&id)?,
             CanvasSource::Element(element) => Renderer::create_with_canvas(element)?,
         };
-        let renderer = renderer.canvas_padding_color(self.canvas_padding_color);
+        let mut renderer = renderer.canvas_padding_color(self.canvas_padding_color);
 
-        // load font atlas
-        let gl = renderer.gl();
-        let atlas = FontAtlas::load(gl, self.atlas_data.unwrap_or_default())?;
+        // load font atlas; scaled atlases are picked by the device pixel ratio
+        let mut atlases = self.scaled_atlases;
+        let atlas = match atlases.is_empty() {
+            true => FontAtlas::load(renderer.gl(), self.atlas_data.unwrap_or_default())?,
+            false => {
+                atlases.validate()?;
+                let device_pixel_ratio = js::device_pixel_ratio() as f32;
+                let (atlas, scale) = atlases.load(renderer.gl(), device_pixel_ratio)?;
+
+                let (width, height) = renderer.canvas_size();
+                renderer.set_pixel_ratio(scale);
+                renderer.resize_css(width, height);
+                atlas
+            },
+        };
+        let pixel_ratio_handler = match atlases.is_empty() {
+            true => None,
+            false => Some(PixelRatioHandler::new()?),
+        };
+        if let (Some(handler), Some(callback)) = (&pixel_ratio_handler, self.pixel_ratio_callback) {
+            handler.set_callback(callback);
+        }
 
         // create terminal grid
+        let gl = renderer.gl();
         let canvas_size = renderer.canvas_size();
         let mut grid = TerminalGrid::new(gl, atlas, canvas_size)?;
         if let Some(fallback) = self.fallback_glyph {
             grid.set_fallback_glyph(&fallback)
         };
+        if let Some(palette) = self.palette {
+            grid.set_palette(gl, palette);
+        }
+        if let Some(highlight) = self.selection_highlight {
+            grid.set_selection_highlight(highlight);
+        }
+        grid.set_scrollback_capacity(self.scrollback_capacity);
         let grid = Rc::new(RefCell::new(grid));
 
+        let accessibility = match self.accessibility {
+            true => Some(TerminalAccessibility::new(renderer.canvas())?),
+            false => None,
+        };
+
+        // track canvas focus for the unfocused cursor style, if the canvas takes input
+        let focus_tracking = self.focus_tracking
+            || self.keyboard_callback.is_some()
+            || self.text_input_callback.is_some();
+        let focus_handler = focus_tracking
+            .then(|| CanvasFocusHandler::new(renderer.canvas(), grid.clone()))
+            .transpose()?;
+
+        // GL resources are recreated after the browser restores a lost context
+        let context_handler = ContextLossHandler::new(renderer.canvas())?;
+        if let Some(callback) = self.context_callback {
+            context_handler.set_callback(callback);
+        }
+
+        let ime_handler = self
+            .text_input_callback
+            .map(|callback| TerminalImeHandler::new(renderer.canvas(), grid.clone(), callback))
+            .transpose()?;
+
+        // key and paste events are delivered to the IME textarea when it holds focus
+        let input_target: &web_sys::HtmlElement = match &ime_handler {
+            Some(ime) => ime.textarea(),
+            None => renderer.canvas(),
+        };
+        let keyboard_handler = self
+            .keyboard_callback
+            .map(|callback| TerminalKeyboardHandler::new(input_target, callback))
+            .transpose()?;
+        let paste_handler = self
+            .paste_callback
+            .map(|callback| TerminalPasteHandler::new(input_target, callback))
+            .transpose()?;
+        // the browser only fires paste events for paste shortcuts it handles
+        if let (Some(keyboard), Some(_)) = (&keyboard_handler, &paste_handler) {
+            keyboard.set_browser_paste(true);
+        }
+
         // initialize mouse handler if needed
         let selection = grid.borrow().selection_tracker();
-        match self.input_handler {
-            None => Ok(Terminal { renderer, grid, mouse_handler: None }),
+        let (mouse_callback, default_input_handler) = match self.input_handler {
+            None => (None, None),
             Some(InputHandler::Internal { selection_mode, trim_trailing_whitespace }) => {
-                let handler = DefaultSelectionHandler::new(
+                let mut handler = DefaultSelectionHandler::new(
                     grid.clone(),
                     selection_mode,
                     trim_trailing_whitespace,
                 );
+                if let Some(separators) = &self.word_separators {
+                    handler = handler.with_word_separators(separators);
+                }
+                (Some(handler.create_event_handler(selection)), Some(handler))
+            },
+            Some(InputHandler::Mouse(callback)) => (Some(callback), None),
+        };
 
-                let mut mouse_input = TerminalMouseHandler::new(
-                    renderer.canvas(),
-                    grid.clone(),
-                    handler.create_event_handler(selection),
-                )?;
-                mouse_input.default_input_handler = Some(handler);
-
-                Ok(Terminal {
-                    renderer,
-                    grid,
-                    mouse_handler: Some(mouse_input),
-                })
+        // middle clicks paste the selection, unless reported
+        let mouse_callback = match &paste_handler {
+            Some(paste) => Some(paste.primary_selection_handler(mouse_callback)),
+            None => mouse_callback,
+        };
+
+        // mouse reports take precedence over the mouse input handler
+        let mouse_encoder = self
+            .mouse_report_callback
+            .is_some()
+            .then(|| Rc::new(RefCell::new(MouseEncoder::new())));
+        let mouse_callback = match (self.mouse_report_callback, &mouse_encoder) {
+            (Some(report), Some(encoder)) => {
+                Some(reporting_event_handler(encoder.clone(), report, mouse_callback))
+            },
+            _ => mouse_callback,
+        };
+
+        let hit_regions = Rc::new(RefCell::new(HitRegions::default()));
+        let mouse_callback = match self.hit_region_callback {
+            Some(callback) => {
+                let canvas = renderer.canvas().clone();
+                Some(hit_region_event_handler(
+                    canvas,
+                    hit_regions.clone(),
+                    callback,
+                    mouse_callback,
+                ))
+            },
+            None => mouse_callback,
+        };
+
+        // links are handled before any other mouse input
+        let mouse_callback = match self.link_callback {
+            Some(callback) => {
+                let hover = grid.borrow().link_hover_tracker();
+                let canvas = renderer.canvas().clone();
+                Some(link_event_handler(canvas, hover, callback, mouse_callback))
             },
-            Some(InputHandler::Mouse(callback)) => {
-                let mouse_input =
+            None => mouse_callback,
+        };
+
+        let mouse_handler = match mouse_callback {
+            Some(callback) => {
+                let mut mouse_input =
                     TerminalMouseHandler::new(renderer.canvas(), grid.clone(), callback)?;
-                Ok(Terminal {
-                    renderer,
-                    grid,
-                    mouse_handler: Some(mouse_input),
-                })
+                mouse_input.default_input_handler = default_input_handler;
+                Some(mouse_input)
             },
-        }
+            None => None,
+        };
+
+        let mut terminal = Terminal {
+            renderer,
+            grid,
+            mouse_handler,
+            mouse_encoder,
+            hit_regions,
+            keyboard_handler,
+            ime_handler,
+            paste_handler,
+            accessibility,
+            focus_handler,
+            context_handler,
+            atlases,
+            pixel_ratio_handler,
+            #[cfg(feature = "regex")]
+            search: SearchResults::default(),
+            #[cfg(feature = "emulator")]
+            emulator: Emulator::new(),
+        };
+
+        // mouse events and the IME textarea are positioned in CSS pixels
+        terminal.update_mouse_metrics();
+        terminal.update_ime_layout();
+        Ok(terminal)
     }
 }
 
@@ -0,0 +1,176 @@
+//! Alacritty color configuration, in TOML and the legacy YAML format.
+
+use super::{
+    flat::{self, Entries},
+    hex, parse_color, require_color, ThemeFormat,
+};
+use crate::{Error, Palette};
+
+/// Names of the eight ANSI colors in the `normal` and `bright` sections.
+const ANSI_NAMES: [&str; 8] =
+    ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+pub(super) fn parse_toml(source: &str) -> Result<Palette, Error> {
+    let format = ThemeFormat::AlacrittyToml;
+    let entries = flat::toml(source).map_err(|e| Error::theme_parse_failed(format.name(), &e))?;
+    palette_from(format, &entries)
+}
+
+pub(super) fn parse_yaml(source: &str) -> Result<Palette, Error> {
+    let format = ThemeFormat::AlacrittyYaml;
+    let entries = flat::yaml(source).map_err(|e| Error::theme_parse_failed(format.name(), &e))?;
+    palette_from(format, &entries)
+}
+
+pub(super) fn write_toml(palette: &Palette) -> String {
+    let mut out = String::new();
+    for (section, values) in sections(palette) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("[colors.{section}]\n"));
+        for (key, color) in values {
+            out.push_str(&format!("{key} = \"{}\"\n", hex(color)));
+        }
+    }
+
+    for (index, color) in indexed_colors(palette) {
+        out.push_str(&format!(
+            "\n[[colors.indexed_colors]]\nindex = {index}\ncolor = \"{}\"\n",
+            hex(color)
+        ));
+    }
+
+    out
+}
+
+pub(super) fn write_yaml(palette: &Palette) -> String {
+    let mut out = String::from("colors:\n");
+    for (section, values) in sections(palette) {
+        out.push_str(&format!("  {section}:\n"));
+        for (key, color) in values {
+            out.push_str(&format!("    {key}: '{}'\n", hex(color)));
+        }
+    }
+
+    let indexed = indexed_colors(palette);
+    if !indexed.is_empty() {
+        out.push_str("  indexed_colors:\n");
+        for (index, color) in indexed {
+            out.push_str(&format!("    - {{ index: {index}, color: '{}' }}\n", hex(color)));
+        }
+    }
+
+    out
+}
+
+fn palette_from(format: ThemeFormat, entries: &Entries) -> Result<Palette, Error> {
+    // colors are usually nested under `colors`, but standalone theme files may omit it
+    let colors: Entries = entries
+        .iter()
+        .map(|(k, v)| (k.strip_prefix("colors.").unwrap_or(k).to_string(), v.clone()))
+        .collect();
+
+    let mut palette = Palette::default();
+    let mut found = false;
+
+    let mut color = |key: &str| -> Result<Option<u32>, Error> {
+        match colors.get(key) {
+            // cursor and selection colors may reference the cell colors instead
+            Some(value) if value.starts_with("Cell") => Ok(None),
+            Some(value) => {
+                found = true;
+                require_color(format, key, value).map(Some)
+            },
+            None => Ok(None),
+        }
+    };
+
+    if let Some(rgb) = color("primary.foreground")? {
+        palette.set_foreground(rgb);
+    }
+    if let Some(rgb) = color("primary.background")? {
+        palette.set_background(rgb);
+    }
+
+    for (i, name) in ANSI_NAMES.iter().enumerate() {
+        if let Some(rgb) = color(&format!("normal.{name}"))? {
+            palette.set_color(i as u8, rgb);
+        }
+        if let Some(rgb) = color(&format!("bright.{name}"))? {
+            palette.set_color(i as u8 + 8, rgb);
+        }
+    }
+
+    palette.set_cursor(color("cursor.cursor")?);
+    palette.set_cursor_text(color("cursor.text")?);
+    palette.set_selection_background(color("selection.background")?);
+    palette.set_selection_foreground(color("selection.text")?);
+
+    for i in 0.. {
+        let Some(index) = colors.get(&format!("indexed_colors.{i}.index")) else {
+            break;
+        };
+        let index = index.parse::<u8>().map_err(|_| {
+            Error::theme_parse_failed(format.name(), &format!("invalid index {index}"))
+        })?;
+        let value = colors.get(&format!("indexed_colors.{i}.color")).map(String::as_str);
+        let rgb = value.and_then(parse_color).ok_or_else(|| {
+            Error::theme_parse_failed(format.name(), &format!("invalid indexed color {index}"))
+        })?;
+
+        palette.set_color(index, rgb);
+        found = true;
+    }
+
+    if found {
+        Ok(palette)
+    } else {
+        Err(Error::theme_parse_failed(format.name(), "no colors found"))
+    }
+}
+
+/// Color sections and their values, in the order Alacritty documents them.
+fn sections(palette: &Palette) -> Vec<(&'static str, Vec<(&'static str, u32)>)> {
+    let mut sections = vec![(
+        "primary",
+        vec![("background", palette.background()), ("foreground", palette.foreground())],
+    )];
+
+    let optional = |values: [(&'static str, Option<u32>); 2]| -> Vec<(&'static str, u32)> {
+        values.into_iter().filter_map(|(k, v)| Some((k, v?))).collect()
+    };
+
+    let cursor = optional([("text", palette.cursor_text()), ("cursor", palette.cursor())]);
+    if !cursor.is_empty() {
+        sections.push(("cursor", cursor));
+    }
+
+    let selection = optional([
+        ("text", palette.selection_foreground()),
+        ("background", palette.selection_background()),
+    ]);
+    if !selection.is_empty() {
+        sections.push(("selection", selection));
+    }
+
+    let ansi = |offset: usize| {
+        ANSI_NAMES
+            .iter()
+            .enumerate()
+            .map(move |(i, name)| (*name, palette.colors()[i + offset]))
+    };
+    sections.push(("normal", ansi(0).collect()));
+    sections.push(("bright", ansi(8).collect()));
+
+    sections
+}
+
+/// Extended colors differing from the xterm defaults.
+fn indexed_colors(palette: &Palette) -> Vec<(usize, u32)> {
+    let defaults = Palette::default();
+    (16..256)
+        .filter(|&i| palette.colors()[i] != defaults.colors()[i])
+        .map(|i| (i, palette.colors()[i]))
+        .collect()
+}
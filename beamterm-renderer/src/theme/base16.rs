@@ -0,0 +1,61 @@
+//! base16 color schemes.
+//!
+//! base16 defines 16 colors by role rather than by ANSI slot; they are mapped to
+//! the palette following the base16-shell conventions: the ANSI colors hold the
+//! accents, while the remaining base colors occupy slots 16-21.
+
+use super::{flat, require_color, ThemeFormat};
+use crate::{Error, Palette};
+
+/// Palette slots of `base00`..`base0F`.
+const SLOTS: [u8; 16] = [0, 18, 19, 8, 20, 7, 21, 15, 1, 16, 3, 2, 6, 4, 5, 17];
+
+/// Bright ANSI slots, which repeat the accent colors of `base08`..`base0E`.
+const BRIGHT_SLOTS: [(usize, u8); 6] =
+    [(0x08, 9), (0x0B, 10), (0x0A, 11), (0x0D, 12), (0x0E, 13), (0x0C, 14)];
+
+pub(super) fn parse(source: &str) -> Result<Palette, Error> {
+    let format = ThemeFormat::Base16;
+    let entries = flat::yaml(source).map_err(|e| Error::theme_parse_failed(format.name(), &e))?;
+
+    let mut base = [0; 16];
+    for (i, rgb) in base.iter_mut().enumerate() {
+        let key = format!("base{i:02X}");
+        // the tinted-theming layout nests the colors under `palette`
+        let value = entries
+            .get(&key)
+            .or_else(|| entries.get(&format!("palette.{key}")))
+            .ok_or_else(|| Error::theme_parse_failed(format.name(), &format!("missing {key}")))?;
+
+        *rgb = require_color(format, &key, value)?;
+    }
+
+    let mut palette = Palette::default();
+    for (rgb, slot) in base.iter().zip(SLOTS) {
+        palette.set_color(slot, *rgb);
+    }
+    for (i, slot) in BRIGHT_SLOTS {
+        palette.set_color(slot, base[i]);
+    }
+
+    palette.set_background(base[0x00]);
+    palette.set_foreground(base[0x05]);
+    palette.set_cursor(Some(base[0x05]));
+    palette.set_selection_background(Some(base[0x02]));
+
+    Ok(palette)
+}
+
+pub(super) fn write(palette: &Palette) -> String {
+    let mut out = String::from("scheme: \"beamterm\"\nauthor: \"\"\n");
+    for (i, slot) in SLOTS.iter().enumerate() {
+        let rgb = match i {
+            0x00 => palette.background(),
+            0x05 => palette.foreground(),
+            _ => palette.color(*slot),
+        };
+        out.push_str(&format!("base{i:02X}: \"{rgb:06x}\"\n"));
+    }
+
+    out
+}
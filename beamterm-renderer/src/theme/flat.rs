@@ -0,0 +1,554 @@
+//! Minimal readers flattening the structured file formats used by terminal themes
+//! into dotted key paths.
+//!
+//! Theme files only use a small subset of TOML, YAML, JSON and plist, so rather
+//! than fully implementing each format, every reader maps nested tables, maps and
+//! arrays into a flat list of entries, e.g. `colors.primary.background` or
+//! `schemes.0.brightBlue`. Array elements are addressed by their index. Scalar
+//! values are unquoted and unescaped, but otherwise kept as strings.
+
+use std::collections::HashMap;
+
+/// Flattened key paths and their scalar values.
+pub(super) type Entries = HashMap<String, String>;
+
+/// Reads a TOML document.
+pub(super) fn toml(source: &str) -> Result<Entries, String> {
+    let mut entries = Entries::new();
+    let mut table = String::new();
+    let mut table_arrays: HashMap<String, usize> = HashMap::new();
+
+    let mut lines = source.lines().enumerate();
+    while let Some((line_no, line)) = lines.next() {
+        let line = strip_comment(line, false);
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+            let name = name.trim().replace(['"', '\''], "");
+            let index = table_arrays.entry(name.clone()).or_default();
+            table = format!("{name}.{index}");
+            *index += 1;
+        } else if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = name.trim().replace(['"', '\''], "");
+        } else {
+            let (key, value) = split_outside_quotes(line, '=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", line_no + 1))?;
+
+            // arrays and inline tables may continue over several lines
+            let mut value = value.to_string();
+            while bracket_depth(&value) > 0 {
+                let (_, next) = lines
+                    .next()
+                    .ok_or_else(|| format!("line {}: unterminated value", line_no + 1))?;
+                value.push('\n');
+                value.push_str(&strip_comment(next, false));
+            }
+
+            let path = join(&table, &unquote_key(key));
+            Flow::new(&value)
+                .value_at(&path, &mut entries)
+                .map_err(|e| format!("line {}: {e}", line_no + 1))?;
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Reads a block-style YAML document, including flow-style `{ }` and `[ ]` values.
+pub(super) fn yaml(source: &str) -> Result<Entries, String> {
+    struct Frame {
+        indent: usize,
+        path: String,
+        next_index: usize,
+    }
+
+    let mut entries = Entries::new();
+    let mut stack: Vec<Frame> = Vec::new();
+
+    for (line_no, raw) in source.lines().enumerate() {
+        let line = strip_comment(raw, true);
+        let content = line.trim();
+        if content.is_empty() || content == "---" || content.starts_with('%') {
+            continue;
+        }
+
+        let mut indent = line.len() - line.trim_start().len();
+        let mut content = content;
+
+        // sequence items: `- value` or `- key: value`, addressed by index
+        while content == "-" || content.starts_with("- ") {
+            while stack.last().is_some_and(|f| f.indent > indent) {
+                stack.pop();
+            }
+
+            let (parent, index) = match stack.last_mut() {
+                Some(frame) => {
+                    frame.next_index += 1;
+                    (frame.path.clone(), frame.next_index - 1)
+                },
+                None => (String::new(), 0),
+            };
+            stack.push(Frame {
+                indent: indent + 1,
+                path: join(&parent, &index.to_string()),
+                next_index: 0,
+            });
+
+            let rest = content[1..].trim_start();
+            indent += content.len() - rest.len();
+            content = rest;
+        }
+
+        if content.is_empty() {
+            continue;
+        }
+
+        let key_value = split_outside_quotes(content, ':')
+            .filter(|_| !content.starts_with(['{', '[']))
+            .filter(|(_, value)| value.is_empty() || value.starts_with([' ', '\t']));
+
+        let Some((key, value)) = key_value else {
+            // scalar or flow collection as sequence item
+            let path = stack.last().map(|f| f.path.clone()).unwrap_or_default();
+            yaml_value(&path, content, &mut entries)
+                .map_err(|e| format!("line {}: {e}", line_no + 1))?;
+            continue;
+        };
+
+        while stack.last().is_some_and(|f| f.indent >= indent) {
+            stack.pop();
+        }
+
+        let parent = stack.last().map(|f| f.path.as_str()).unwrap_or_default();
+        let path = join(parent, &unquote_key(key));
+        let value = value.trim();
+
+        if value.is_empty() {
+            stack.push(Frame { indent, path, next_index: 0 });
+        } else {
+            yaml_value(&path, value, &mut entries)
+                .map_err(|e| format!("line {}: {e}", line_no + 1))?;
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Reads a YAML value in block context, where plain scalars may contain commas.
+fn yaml_value(path: &str, value: &str, entries: &mut Entries) -> Result<(), String> {
+    if value.starts_with(['{', '[', '"', '\'']) {
+        Flow::new(value).value_at(path, entries)
+    } else {
+        entries.insert(path.to_string(), value.to_string());
+        Ok(())
+    }
+}
+
+/// Reads a JSON document; `//` line comments are permitted.
+pub(super) fn json(source: &str) -> Result<Entries, String> {
+    let mut entries = Entries::new();
+    let mut flow = Flow::new(source);
+    flow.value_at("", &mut entries)?;
+
+    flow.skip_whitespace();
+    match flow.peek() {
+        None => Ok(entries),
+        Some(c) => Err(format!("unexpected trailing character '{c}'")),
+    }
+}
+
+/// Reads an XML property list.
+pub(super) fn plist(source: &str) -> Result<Entries, String> {
+    enum Container {
+        Dict { path: String, key: Option<String> },
+        Array { path: String, next_index: usize },
+    }
+
+    fn next_path(stack: &mut [Container]) -> Result<String, String> {
+        match stack.last_mut() {
+            None => Ok(String::new()),
+            Some(Container::Dict { path, key }) => {
+                let key = key.take().ok_or("dict value without <key>")?;
+                Ok(join(path, &key))
+            },
+            Some(Container::Array { path, next_index }) => {
+                *next_index += 1;
+                Ok(join(path, &(*next_index - 1).to_string()))
+            },
+        }
+    }
+
+    let mut entries = Entries::new();
+    let mut stack: Vec<Container> = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").ok_or("unterminated comment")?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+
+        let end = rest.find('>').ok_or("unterminated tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        match name {
+            _ if name.starts_with(['?', '!']) => {},
+            "plist" | "/plist" | "dict/" | "array/" => {
+                if name.ends_with('/') {
+                    next_path(&mut stack)?;
+                }
+            },
+            "dict" => {
+                let path = next_path(&mut stack)?;
+                stack.push(Container::Dict { path, key: None });
+            },
+            "array" => {
+                let path = next_path(&mut stack)?;
+                stack.push(Container::Array { path, next_index: 0 });
+            },
+            "/dict" | "/array" => {
+                stack.pop().ok_or_else(|| format!("unbalanced <{name}>"))?;
+            },
+            "true/" | "false/" => {
+                let path = next_path(&mut stack)?;
+                entries.insert(path, name.trim_end_matches('/').to_string());
+            },
+            "key" | "string" | "real" | "integer" | "date" | "data" => {
+                let close = format!("</{name}>");
+                let end = rest.find(&close).ok_or_else(|| format!("unterminated <{name}>"))?;
+                let text = unescape_xml(rest[..end].trim());
+                rest = &rest[end + close.len()..];
+
+                if name == "key" {
+                    match stack.last_mut() {
+                        Some(Container::Dict { key, .. }) => *key = Some(text),
+                        _ => return Err("<key> outside of <dict>".into()),
+                    }
+                } else {
+                    let path = next_path(&mut stack)?;
+                    entries.insert(path, text);
+                }
+            },
+            _ => return Err(format!("unsupported element <{name}>")),
+        }
+    }
+
+    if stack.is_empty() {
+        Ok(entries)
+    } else {
+        Err("unterminated document".into())
+    }
+}
+
+/// Joins a parent path and a key with a `.`.
+pub(super) fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+/// Parser for flow-style values: JSON, YAML flow collections, TOML inline tables
+/// and arrays, and plain or quoted scalars.
+struct Flow {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Flow {
+    fn new(source: &str) -> Self {
+        Self { chars: source.chars().collect(), pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += 1;
+            } else if c == '/' && self.chars.get(self.pos + 1) == Some(&'/') {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn value_at(&mut self, path: &str, entries: &mut Entries) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.map_at(path, entries),
+            Some('[') => self.array_at(path, entries),
+            Some('"' | '\'') => {
+                let value = self.quoted()?;
+                entries.insert(path.to_string(), value);
+                Ok(())
+            },
+            Some(_) => {
+                let value = self.plain(&[',', '}', ']']);
+                entries.insert(path.to_string(), value);
+                Ok(())
+            },
+            None => Err("missing value".into()),
+        }
+    }
+
+    fn map_at(&mut self, path: &str, entries: &mut Entries) -> Result<(), String> {
+        self.pos += 1;
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(());
+            }
+
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                _ => self.plain(&[':', '=']),
+            };
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(':' | '=') => self.pos += 1,
+                _ => return Err(format!("expected separator after key '{key}'")),
+            }
+
+            self.value_at(&join(path, &key), entries)?;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {},
+                _ => return Err("expected ',' or '}'".into()),
+            }
+        }
+    }
+
+    fn array_at(&mut self, path: &str, entries: &mut Entries) -> Result<(), String> {
+        self.pos += 1;
+        for index in 0.. {
+            self.skip_whitespace();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                break;
+            }
+
+            self.value_at(&join(path, &index.to_string()), entries)?;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {},
+                _ => return Err("expected ',' or ']'".into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let quote = self.chars[self.pos];
+        self.pos += 1;
+
+        let mut value = String::new();
+        loop {
+            let c = self.peek().ok_or("unterminated string")?;
+            self.pos += 1;
+
+            match c {
+                // YAML escapes single quotes by doubling them
+                '\'' if quote == '\'' && self.peek() == Some('\'') => {
+                    self.pos += 1;
+                    value.push('\'');
+                },
+                c if c == quote => return Ok(value),
+                '\\' if quote == '"' => {
+                    let escaped = self.peek().ok_or("unterminated string")?;
+                    self.pos += 1;
+                    match escaped {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        'r' => value.push('\r'),
+                        'u' => {
+                            let code: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            let c = u32::from_str_radix(&code, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or("invalid unicode escape")?;
+                            self.pos += 4;
+                            value.push(c);
+                        },
+                        c => value.push(c),
+                    }
+                },
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn plain(&mut self, terminators: &[char]) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !terminators.contains(&c) && c != '\n') {
+            self.pos += 1;
+        }
+
+        self.chars[start..self.pos].iter().collect::<String>().trim().to_string()
+    }
+}
+
+/// Removes a trailing comment from a line. YAML comments must be preceded by
+/// whitespace, while TOML comments start at any `#` outside of a string.
+fn strip_comment(line: &str, yaml: bool) -> String {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if !yaml || prev.is_whitespace() => return line[..i].to_string(),
+            _ => {},
+        }
+        prev = c;
+    }
+
+    line.to_string()
+}
+
+/// Splits a line at the first separator outside of quotes.
+fn split_outside_quotes(line: &str, separator: char) -> Option<(&str, &str)> {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, c) if c == separator => return Some((&line[..i], &line[i + 1..])),
+            _ => {},
+        }
+    }
+
+    None
+}
+
+/// Returns the number of unclosed brackets and braces outside of quotes.
+fn bracket_depth(value: &str) -> i32 {
+    let mut quote = None;
+    let mut depth = 0;
+    for c in value.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth -= 1,
+            _ => {},
+        }
+    }
+
+    depth
+}
+
+fn unquote_key(key: &str) -> String {
+    key.trim().trim_matches(['"', '\'']).to_string()
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_tables_and_inline_values() {
+        let entries = toml(
+            r##"
+            # comment
+            [colors.primary]
+            background = "#1d1f21" # trailing comment
+            cursor = { text = "#000000", cursor = '#ffffff' }
+
+            [[colors.indexed_colors]]
+            index = 16
+            color = "#ff8800"
+
+            [colors]
+            bright.red = "#ff0000"
+            list = [
+                { index = 17, color = "#00ff00" },
+            ]
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(entries["colors.primary.background"], "#1d1f21");
+        assert_eq!(entries["colors.primary.cursor.cursor"], "#ffffff");
+        assert_eq!(entries["colors.indexed_colors.0.index"], "16");
+        assert_eq!(entries["colors.indexed_colors.0.color"], "#ff8800");
+        assert_eq!(entries["colors.bright.red"], "#ff0000");
+        assert_eq!(entries["colors.list.0.color"], "#00ff00");
+    }
+
+    #[test]
+    fn test_yaml_nesting_and_sequences() {
+        let entries = yaml(
+            r##"
+            colors:
+              primary:
+                background: '#1d1f21' # comment
+                foreground: "0xc5c8c6"
+              indexed_colors:
+                - { index: 16, color: '#ff8800' }
+                - index: 17
+                  color: '#00ff00'
+            scheme: "Name: with colon"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(entries["colors.primary.background"], "#1d1f21");
+        assert_eq!(entries["colors.primary.foreground"], "0xc5c8c6");
+        assert_eq!(entries["colors.indexed_colors.0.color"], "#ff8800");
+        assert_eq!(entries["colors.indexed_colors.1.index"], "17");
+        assert_eq!(entries["colors.indexed_colors.1.color"], "#00ff00");
+        assert_eq!(entries["scheme"], "Name: with colon");
+    }
+
+    #[test]
+    fn test_plist_dicts() {
+        let entries = plist(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+            <plist version="1.0">
+            <dict>
+                <key>Ansi 0 Color</key>
+                <dict>
+                    <key>Red Component</key>
+                    <real>0.5</real>
+                </dict>
+                <key>Flag</key>
+                <true/>
+            </dict>
+            </plist>"#,
+        )
+        .unwrap();
+
+        assert_eq!(entries["Ansi 0 Color.Red Component"], "0.5");
+        assert_eq!(entries["Flag"], "true");
+    }
+}
@@ -0,0 +1,106 @@
+//! iTerm2 `.itermcolors` color presets.
+
+use super::{
+    flat::{self, Entries},
+    ThemeFormat,
+};
+use crate::{Error, Palette};
+
+const CHANNELS: [&str; 3] = ["Red", "Green", "Blue"];
+
+pub(super) fn parse(source: &str) -> Result<Palette, Error> {
+    let format = ThemeFormat::Iterm2;
+    let entries = flat::plist(source).map_err(|e| Error::theme_parse_failed(format.name(), &e))?;
+
+    let mut palette = Palette::default();
+    let mut found = false;
+
+    let mut read = |key: &str| -> Result<Option<u32>, Error> {
+        let rgb = color(&entries, key)?;
+        found |= rgb.is_some();
+        Ok(rgb)
+    };
+
+    for i in 0..16 {
+        if let Some(rgb) = read(&format!("Ansi {i} Color"))? {
+            palette.set_color(i, rgb);
+        }
+    }
+    if let Some(rgb) = read("Foreground Color")? {
+        palette.set_foreground(rgb);
+    }
+    if let Some(rgb) = read("Background Color")? {
+        palette.set_background(rgb);
+    }
+    palette.set_cursor(read("Cursor Color")?);
+    palette.set_cursor_text(read("Cursor Text Color")?);
+    palette.set_selection_background(read("Selection Color")?);
+    palette.set_selection_foreground(read("Selected Text Color")?);
+
+    if found {
+        Ok(palette)
+    } else {
+        Err(Error::theme_parse_failed(format.name(), "no colors found"))
+    }
+}
+
+pub(super) fn write(palette: &Palette) -> String {
+    let mut colors: Vec<(String, u32)> = palette.colors()[..16]
+        .iter()
+        .enumerate()
+        .map(|(i, rgb)| (format!("Ansi {i} Color"), *rgb))
+        .collect();
+
+    colors.push(("Background Color".into(), palette.background()));
+    colors.push(("Foreground Color".into(), palette.foreground()));
+
+    let optional = [
+        ("Cursor Color", palette.cursor()),
+        ("Cursor Text Color", palette.cursor_text()),
+        ("Selected Text Color", palette.selection_foreground()),
+        ("Selection Color", palette.selection_background()),
+    ];
+    colors.extend(optional.into_iter().filter_map(|(key, rgb)| Some((key.into(), rgb?))));
+
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+        "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n",
+        "<dict>\n",
+    ));
+
+    for (key, rgb) in colors {
+        out.push_str(&format!("\t<key>{key}</key>\n\t<dict>\n"));
+        out.push_str("\t\t<key>Alpha Component</key>\n\t\t<real>1</real>\n");
+        for (i, channel) in CHANNELS.iter().enumerate() {
+            let value = ((rgb >> (16 - 8 * i)) & 0xFF) as f64 / 255.0;
+            out.push_str(&format!(
+                "\t\t<key>{channel} Component</key>\n\t\t<real>{value}</real>\n"
+            ));
+        }
+        out.push_str("\t\t<key>Color Space</key>\n\t\t<string>sRGB</string>\n\t</dict>\n");
+    }
+
+    out.push_str("</dict>\n</plist>\n");
+    out
+}
+
+/// Reads a color dict with `Red Component`, `Green Component` and `Blue Component`
+/// values in the range 0.0-1.0.
+fn color(entries: &Entries, key: &str) -> Result<Option<u32>, Error> {
+    let invalid =
+        || Error::theme_parse_failed(ThemeFormat::Iterm2.name(), &format!("invalid color {key}"));
+
+    let mut rgb = 0;
+    for (i, channel) in CHANNELS.iter().enumerate() {
+        let Some(value) = entries.get(&format!("{key}.{channel} Component")) else {
+            return if i == 0 { Ok(None) } else { Err(invalid()) };
+        };
+
+        let value: f64 = value.parse().map_err(|_| invalid())?;
+        rgb = (rgb << 8) | (value.clamp(0.0, 1.0) * 255.0).round() as u32;
+    }
+
+    Ok(Some(rgb))
+}
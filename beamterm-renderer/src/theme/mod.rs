@@ -0,0 +1,288 @@
+//! Import and export of terminal color themes.
+//!
+//! Parses color themes from the formats used by common terminal emulators into a
+//! [`Palette`], which can then be applied with [`crate::Terminal::set_palette`].
+//! Each format can also be written back, allowing palettes to be converted
+//! between formats.
+//!
+//! # Supported Formats
+//!
+//! | Format                    | [`ThemeFormat`]                   | Typical extension |
+//! |---------------------------|-----------------------------------|-------------------|
+//! | Alacritty (TOML)          | [`ThemeFormat::AlacrittyToml`]    | `.toml`           |
+//! | Alacritty (legacy YAML)   | [`ThemeFormat::AlacrittyYaml`]    | `.yml`            |
+//! | Windows Terminal scheme   | [`ThemeFormat::WindowsTerminal`]  | `.json`           |
+//! | iTerm2 color preset       | [`ThemeFormat::Iterm2`]           | `.itermcolors`    |
+//! | base16 scheme             | [`ThemeFormat::Base16`]           | `.yaml`           |
+//! | Xresources                | [`ThemeFormat::Xresources`]       | `.Xresources`     |
+//!
+//! Only the color settings are read; any other configuration in the file is ignored.
+//!
+//! # Example
+//!
+//! ```rust
+//! use beamterm_renderer::theme::{self, ThemeFormat};
+//!
+//! let source = r##"
+//! *.foreground: #c5c8c6
+//! *.background: #1d1f21
+//! *.color1:     #cc6666
+//! "##;
+//!
+//! let palette = theme::parse(ThemeFormat::Xresources, source).unwrap();
+//! assert_eq!(palette.color(1), 0xcc6666);
+//!
+//! // convert to another format
+//! let alacritty = theme::write(ThemeFormat::AlacrittyToml, &palette);
+//! ```
+
+mod alacritty;
+mod base16;
+mod flat;
+mod iterm2;
+mod windows_terminal;
+mod xresources;
+
+use crate::{Error, Palette};
+
+/// Color theme file formats supported by [`parse`] and [`write`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeFormat {
+    /// Alacritty `[colors]` configuration in TOML.
+    AlacrittyToml,
+    /// Alacritty `colors:` configuration in the legacy YAML format.
+    AlacrittyYaml,
+    /// Windows Terminal color scheme, either a single scheme object or a
+    /// `settings.json` with a `schemes` array (the first scheme is used).
+    WindowsTerminal,
+    /// iTerm2 `.itermcolors` property list.
+    Iterm2,
+    /// base16 color scheme, in either the classic or the tinted-theming layout.
+    Base16,
+    /// Xresources color definitions, optionally using `#define` macros.
+    Xresources,
+}
+
+impl ThemeFormat {
+    /// Returns a human-readable name of the format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeFormat::AlacrittyToml => "Alacritty TOML",
+            ThemeFormat::AlacrittyYaml => "Alacritty YAML",
+            ThemeFormat::WindowsTerminal => "Windows Terminal",
+            ThemeFormat::Iterm2 => "iTerm2",
+            ThemeFormat::Base16 => "base16",
+            ThemeFormat::Xresources => "Xresources",
+        }
+    }
+}
+
+/// Parses a color theme into a [`Palette`].
+///
+/// Colors missing from the theme keep their [`Palette::default`] values.
+///
+/// # Errors
+/// Returns [`Error::Data`] if the source is malformed or does not contain a
+/// color theme in the given format.
+pub fn parse(format: ThemeFormat, source: &str) -> Result<Palette, Error> {
+    match format {
+        ThemeFormat::AlacrittyToml => alacritty::parse_toml(source),
+        ThemeFormat::AlacrittyYaml => alacritty::parse_yaml(source),
+        ThemeFormat::WindowsTerminal => windows_terminal::parse(source),
+        ThemeFormat::Iterm2 => iterm2::parse(source),
+        ThemeFormat::Base16 => base16::parse(source),
+        ThemeFormat::Xresources => xresources::parse(source),
+    }
+}
+
+/// Writes a [`Palette`] as a color theme in the given format.
+///
+/// Formats which cannot represent the full 256-color palette only write the
+/// colors they support; typically the 16 ANSI colors, default colors, and the
+/// cursor and selection colors if set.
+pub fn write(format: ThemeFormat, palette: &Palette) -> String {
+    match format {
+        ThemeFormat::AlacrittyToml => alacritty::write_toml(palette),
+        ThemeFormat::AlacrittyYaml => alacritty::write_yaml(palette),
+        ThemeFormat::WindowsTerminal => windows_terminal::write(palette),
+        ThemeFormat::Iterm2 => iterm2::write(palette),
+        ThemeFormat::Base16 => base16::write(palette),
+        ThemeFormat::Xresources => xresources::write(palette),
+    }
+}
+
+/// Parses a color in one of the notations found in theme files: `#rrggbb`,
+/// `0xrrggbb`, `rrggbb`, `#rgb` or the X11 `rgb:rr/gg/bb`.
+fn parse_color(value: &str) -> Option<u32> {
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+
+    if let Some(rgb) = value.strip_prefix("rgb:") {
+        // X11 notation, 1-4 hex digits per channel; keep the most significant byte
+        let mut channels = rgb.split('/').map(|c| {
+            let v = u32::from_str_radix(c, 16).ok()?;
+            let bits = 4 * c.len() as u32;
+            (1..=4).contains(&c.len()).then(|| v * 255 / ((1 << bits) - 1))
+        });
+        let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+        return channels.next().is_none().then_some((r << 16) | (g << 8) | b);
+    }
+
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok(),
+        3 => {
+            let v = u32::from_str_radix(hex, 16).ok()?;
+            let (r, g, b) = ((v >> 8) & 0xF, (v >> 4) & 0xF, v & 0xF);
+            Some(((r * 0x11) << 16) | ((g * 0x11) << 8) | (b * 0x11))
+        },
+        _ => None,
+    }
+}
+
+/// Parses a color, mapping failures to a theme error.
+fn require_color(format: ThemeFormat, key: &str, value: &str) -> Result<u32, Error> {
+    parse_color(value)
+        .ok_or_else(|| Error::theme_parse_failed(format.name(), &format!("invalid color {key}")))
+}
+
+/// Formats a color as `#rrggbb`.
+fn hex(rgb: u32) -> String {
+    format!("#{rgb:06x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: [(ThemeFormat, &str); 6] = [
+        (
+            ThemeFormat::AlacrittyToml,
+            include_str!("../../tests/fixtures/themes/tomorrow-night.toml"),
+        ),
+        (
+            ThemeFormat::AlacrittyYaml,
+            include_str!("../../tests/fixtures/themes/tomorrow-night.yml"),
+        ),
+        (
+            ThemeFormat::WindowsTerminal,
+            include_str!("../../tests/fixtures/themes/campbell.json"),
+        ),
+        (
+            ThemeFormat::Iterm2,
+            include_str!("../../tests/fixtures/themes/solarized-dark.itermcolors"),
+        ),
+        (
+            ThemeFormat::Base16,
+            include_str!("../../tests/fixtures/themes/base16-default-dark.yaml"),
+        ),
+        (
+            ThemeFormat::Xresources,
+            include_str!("../../tests/fixtures/themes/tomorrow-night.Xresources"),
+        ),
+    ];
+
+    #[test]
+    fn test_parse_color_notations() {
+        assert_eq!(parse_color("#1d1f21"), Some(0x1d1f21));
+        assert_eq!(parse_color("'0x1D1F21'"), Some(0x1d1f21));
+        assert_eq!(parse_color("\"1d1f21\""), Some(0x1d1f21));
+        assert_eq!(parse_color("#fa0"), Some(0xffaa00));
+        assert_eq!(parse_color("rgb:ff/80/00"), Some(0xff8000));
+        assert_eq!(parse_color("rgb:ffff/8080/0000"), Some(0xff8000));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("CellForeground"), None);
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        for (format, source) in FIXTURES {
+            let palette = parse(format, source).unwrap_or_else(|e| panic!("{format:?}: {e}"));
+            let written = write(format, &palette);
+            let reparsed = parse(format, &written).unwrap_or_else(|e| panic!("{format:?}: {e}"));
+
+            assert_eq!(palette, reparsed, "{format:?} round-trip mismatch:\n{written}");
+        }
+    }
+
+    #[test]
+    fn test_fixture_values() {
+        let parse_fixture = |i: usize| parse(FIXTURES[i].0, FIXTURES[i].1).unwrap();
+
+        let alacritty = parse_fixture(0);
+        assert_eq!(alacritty.color(17), 0xa3685a);
+        assert_eq!(alacritty.cursor_text(), Some(0x1d1f21));
+        assert_eq!(alacritty.selection_foreground(), None);
+
+        let campbell = parse_fixture(2);
+        assert_eq!(campbell.color(5), 0x881798);
+        assert_eq!(campbell.background(), 0x0c0c0c);
+        assert_eq!(campbell.selection_background(), Some(0xffffff));
+
+        let solarized = parse_fixture(3);
+        assert_eq!(solarized.color(8), 0x002b36);
+        assert_eq!(solarized.cursor_text(), Some(0x073642));
+        assert_eq!(solarized.selection_foreground(), Some(0x93a1a1));
+
+        let base16 = parse_fixture(4);
+        assert_eq!(base16.color(1), 0xab4642);
+        assert_eq!(base16.color(9), 0xab4642);
+        assert_eq!(base16.color(16), 0xdc9656);
+        assert_eq!(base16.background(), 0x181818);
+        assert_eq!(base16.selection_background(), Some(0x383838));
+
+        let xresources = parse_fixture(5);
+        assert_eq!(xresources.selection_background(), Some(0x373b41));
+        assert_eq!(xresources.color(16), Palette::default().color(16));
+    }
+
+    #[test]
+    fn test_invalid_themes() {
+        assert!(parse(ThemeFormat::Xresources, "*.color1: not-a-color").is_err());
+        assert!(parse(ThemeFormat::AlacrittyToml, "[font]\nsize = 12").is_err());
+        assert!(parse(ThemeFormat::Base16, "base00: \"181818\"").is_err());
+        assert!(parse(ThemeFormat::WindowsTerminal, "{ \"profiles\": {} }").is_err());
+        assert!(parse(ThemeFormat::Iterm2, "<plist><dict><key>x</key>").is_err());
+    }
+
+    #[test]
+    fn test_tomorrow_night_formats_agree() {
+        let toml = parse(FIXTURES[0].0, FIXTURES[0].1).unwrap();
+        let yaml = parse(FIXTURES[1].0, FIXTURES[1].1).unwrap();
+        let xresources = parse(FIXTURES[5].0, FIXTURES[5].1).unwrap();
+
+        assert_eq!(toml, yaml);
+        assert_eq!(toml.colors()[..16], xresources.colors()[..16]);
+        assert_eq!(toml.foreground(), xresources.foreground());
+        assert_eq!(toml.background(), xresources.background());
+        assert_eq!(toml.cursor(), xresources.cursor());
+    }
+
+    #[test]
+    fn test_cross_format_conversion() {
+        let (format, source) = FIXTURES[2];
+        let palette = parse(format, source).unwrap();
+
+        // formats holding cursor and selection colors preserve the full 16-color theme
+        for target in [
+            ThemeFormat::AlacrittyToml,
+            ThemeFormat::AlacrittyYaml,
+            ThemeFormat::Iterm2,
+            ThemeFormat::Xresources,
+        ] {
+            let converted = parse(target, &write(target, &palette)).unwrap();
+            assert_eq!(converted.colors()[..16], palette.colors()[..16], "{target:?}");
+            assert_eq!(converted.foreground(), palette.foreground(), "{target:?}");
+            assert_eq!(converted.background(), palette.background(), "{target:?}");
+            assert_eq!(converted.cursor(), palette.cursor(), "{target:?}");
+        }
+    }
+}
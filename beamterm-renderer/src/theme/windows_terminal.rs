@@ -0,0 +1,91 @@
+//! Windows Terminal color schemes.
+
+use super::{flat, hex, require_color, ThemeFormat};
+use crate::{Error, Palette};
+
+/// Keys of the 16 ANSI colors; Windows Terminal calls magenta "purple".
+const ANSI_KEYS: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "purple",
+    "cyan",
+    "white",
+    "brightBlack",
+    "brightRed",
+    "brightGreen",
+    "brightYellow",
+    "brightBlue",
+    "brightPurple",
+    "brightCyan",
+    "brightWhite",
+];
+
+/// Name given to exported schemes.
+const SCHEME_NAME: &str = "beamterm";
+
+pub(super) fn parse(source: &str) -> Result<Palette, Error> {
+    let format = ThemeFormat::WindowsTerminal;
+    let entries = flat::json(source).map_err(|e| Error::theme_parse_failed(format.name(), &e))?;
+
+    // a bare scheme object, an array of schemes, or settings.json with a `schemes` array
+    let prefix = ["", "0.", "schemes.0."]
+        .into_iter()
+        .find(|prefix| {
+            ["foreground", "background", "black"]
+                .iter()
+                .any(|key| entries.contains_key(&format!("{prefix}{key}")))
+        })
+        .ok_or_else(|| Error::theme_parse_failed(format.name(), "no color scheme found"))?;
+
+    let color = |key: &str| -> Result<Option<u32>, Error> {
+        entries
+            .get(&format!("{prefix}{key}"))
+            .map(|value| require_color(format, key, value))
+            .transpose()
+    };
+
+    let mut palette = Palette::default();
+    if let Some(rgb) = color("foreground")? {
+        palette.set_foreground(rgb);
+    }
+    if let Some(rgb) = color("background")? {
+        palette.set_background(rgb);
+    }
+    for (i, key) in ANSI_KEYS.iter().enumerate() {
+        if let Some(rgb) = color(key)? {
+            palette.set_color(i as u8, rgb);
+        }
+    }
+    palette.set_cursor(color("cursorColor")?);
+    palette.set_selection_background(color("selectionBackground")?);
+
+    Ok(palette)
+}
+
+pub(super) fn write(palette: &Palette) -> String {
+    let mut fields = vec![
+        ("name".to_string(), SCHEME_NAME.to_string()),
+        ("background".to_string(), hex(palette.background())),
+        ("foreground".to_string(), hex(palette.foreground())),
+    ];
+    if let Some(rgb) = palette.cursor() {
+        fields.push(("cursorColor".to_string(), hex(rgb)));
+    }
+    if let Some(rgb) = palette.selection_background() {
+        fields.push(("selectionBackground".to_string(), hex(rgb)));
+    }
+    for (key, rgb) in ANSI_KEYS.iter().zip(palette.colors()) {
+        fields.push((key.to_string(), hex(*rgb)));
+    }
+
+    let body = fields
+        .iter()
+        .map(|(key, value)| format!("    \"{key}\": \"{value}\""))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("{{\n{body}\n}}\n")
+}
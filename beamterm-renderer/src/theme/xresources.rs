@@ -0,0 +1,97 @@
+//! Xresources color definitions.
+//!
+//! Reads `*.colorN`, `*.foreground`, `*.background`, `*.cursorColor` and
+//! `*.highlightColor` resources, with or without a class or instance prefix
+//! such as `URxvt*` or `XTerm.vt100.`. Simple `#define` macros are expanded.
+
+use std::collections::HashMap;
+
+use super::{hex, require_color, ThemeFormat};
+use crate::{Error, Palette};
+
+pub(super) fn parse(source: &str) -> Result<Palette, Error> {
+    let format = ThemeFormat::Xresources;
+    let mut defines: HashMap<&str, &str> = HashMap::new();
+    let mut palette = Palette::default();
+    let mut found = false;
+
+    for (line_no, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('!') {
+            continue;
+        }
+
+        if let Some(define) = line.strip_prefix("#define") {
+            let mut parts = define.split_whitespace();
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                defines.insert(name, value);
+            }
+            continue;
+        } else if line.starts_with('#') {
+            // other preprocessor directives, e.g. #include
+            continue;
+        }
+
+        let Some((resource, value)) = line.split_once(':') else {
+            return Err(Error::theme_parse_failed(
+                format.name(),
+                &format!("line {}: expected `resource: value`", line_no + 1),
+            ));
+        };
+
+        // only the last component of the resource name identifies the setting
+        let name = resource.trim().rsplit(['*', '.']).next().unwrap_or_default();
+        let value = value.trim();
+        let value = defines.get(value).copied().unwrap_or(value);
+
+        let color = || require_color(format, name, value);
+        match name {
+            "foreground" => palette.set_foreground(color()?),
+            "background" => palette.set_background(color()?),
+            "cursorColor" => palette.set_cursor(Some(color()?)),
+            "highlightColor" => palette.set_selection_background(Some(color()?)),
+            "highlightTextColor" => palette.set_selection_foreground(Some(color()?)),
+            _ => match name.strip_prefix("color").and_then(|i| i.parse::<u8>().ok()) {
+                Some(index) => palette.set_color(index, color()?),
+                None => continue,
+            },
+        }
+        found = true;
+    }
+
+    if found {
+        Ok(palette)
+    } else {
+        Err(Error::theme_parse_failed(format.name(), "no colors found"))
+    }
+}
+
+pub(super) fn write(palette: &Palette) -> String {
+    let mut resources = vec![
+        ("foreground".to_string(), palette.foreground()),
+        ("background".to_string(), palette.background()),
+    ];
+
+    let optional = [
+        ("cursorColor", palette.cursor()),
+        ("highlightColor", palette.selection_background()),
+        ("highlightTextColor", palette.selection_foreground()),
+    ];
+    resources.extend(optional.into_iter().filter_map(|(name, rgb)| Some((name.to_string(), rgb?))));
+
+    // extended colors are only written when they differ from the xterm defaults
+    let defaults = Palette::default();
+    resources.extend(
+        palette
+            .colors()
+            .iter()
+            .enumerate()
+            .filter(|&(i, rgb)| i < 16 || *rgb != defaults.colors()[i])
+            .map(|(i, rgb)| (format!("color{i}"), *rgb)),
+    );
+
+    resources
+        .iter()
+        .map(|(name, rgb)| format!("*.{name}: {}\n", hex(*rgb)))
+        .collect()
+}
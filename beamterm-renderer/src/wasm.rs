@@ -8,12 +8,29 @@ use wasm_bindgen::prelude::*;
 use web_sys::console;
 
 use crate::{
+    accessibility::TerminalAccessibility,
+    context_loss::{ContextEvent as RustContextEvent, ContextLossHandler},
+    export::{self, ScreenExport},
+    focus::CanvasFocusHandler,
     gl::{
-        select, CellData, CellQuery as RustCellQuery, FontAtlas, Renderer,
+        select, CellData, CellQuery as RustCellQuery, Color, CursorShape as RustCursorShape,
+        FontAtlas, Highlight as RustHighlight, Palette, Renderer, SelectionHighlight,
         SelectionMode as RustSelectionMode, TerminalGrid,
     },
-    mouse::{DefaultSelectionHandler, TerminalMouseEvent, TerminalMouseHandler},
+    ime::TerminalImeHandler,
+    js,
+    keyboard::{KeyboardModes, TerminalKeyboardHandler},
+    mouse::{
+        self, hit_region_event_handler, link_event_handler, reporting_event_handler,
+        DefaultSelectionHandler, HitRegion, HitRegionEvent,
+        HitRegionEventType as RustHitRegionEventType, HitRegions, MouseEncoder,
+        MouseEncoding as RustMouseEncoding, MouseEventCallback, MouseModes,
+        MouseTracking as RustMouseTracking, TerminalMouseEvent, TerminalMouseHandler,
+    },
+    paste::TerminalPasteHandler,
 };
+#[cfg(feature = "regex")]
+use crate::{SearchQuery, SearchResults};
 
 /// JavaScript wrapper for the terminal renderer
 #[wasm_bindgen]
@@ -22,6 +39,21 @@ pub struct BeamtermRenderer {
     renderer: Renderer,
     terminal_grid: Rc<RefCell<TerminalGrid>>,
     mouse_handler: Option<TerminalMouseHandler>,
+    mouse_encoder: Option<Rc<RefCell<MouseEncoder>>>,
+    word_separators: Option<CompactString>,
+    link_callback: Option<js_sys::Function>,
+    hit_regions: Rc<RefCell<HitRegions>>,
+    hit_region_callback: Option<js_sys::Function>,
+    keyboard_handler: Option<TerminalKeyboardHandler>,
+    keyboard_callback: Option<js_sys::Function>,
+    ime_handler: Option<TerminalImeHandler>,
+    paste_handler: Option<TerminalPasteHandler>,
+    paste_callback: Rc<RefCell<Option<js_sys::Function>>>,
+    accessibility: Option<TerminalAccessibility>,
+    focus_handler: Option<CanvasFocusHandler>,
+    context_handler: ContextLossHandler,
+    #[cfg(feature = "regex")]
+    search: SearchResults,
 }
 
 /// JavaScript wrapper for cell data
@@ -66,6 +98,20 @@ pub enum SelectionMode {
     Linear,
 }
 
+/// Shape of the terminal cursor
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum CursorShape {
+    /// Filled cell
+    Block,
+    /// Outline of the cell
+    HollowBlock,
+    /// Vertical bar at the left edge of the cell
+    Bar,
+    /// Horizontal line at the bottom of the cell
+    Underline,
+}
+
 /// Type of mouse event
 #[wasm_bindgen]
 #[derive(Debug, Clone, Copy)]
@@ -76,6 +122,80 @@ pub enum MouseEventType {
     MouseUp,
     /// Mouse moved
     MouseMove,
+    /// Wheel scrolled, or touch dragged, by at least one cell
+    Wheel,
+    /// Touch tapped
+    Tap,
+    /// Touch held, starting a selection
+    LongPress,
+    /// Touch moved after a long press or while dragging a selection end
+    TouchMove,
+    /// Touch lifted after a long press or selection end drag
+    TouchEnd,
+}
+
+/// Kind of pointing device producing a mouse event
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum PointerType {
+    /// Mouse or touchpad
+    Mouse,
+    /// Pen or stylus
+    Pen,
+    /// Touch screen
+    Touch,
+}
+
+/// Type of hit region event
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum HitRegionEventType {
+    /// Pointer moved into the region
+    Enter,
+    /// Pointer moved out of the region
+    Leave,
+    /// Region pressed and released, or tapped
+    Click,
+}
+
+/// Change of the WebGL context state
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum ContextEvent {
+    /// Context lost; nothing is drawn until it is restored
+    Lost,
+    /// Context restored; GL resources are recreated by the next `render`
+    Restored,
+}
+
+/// Mouse events reported to the terminal application
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum MouseTracking {
+    /// Mouse events are not reported
+    Off,
+    /// Button presses only (mode 9)
+    X10,
+    /// Presses, releases and wheel events (mode 1000)
+    Normal,
+    /// Additionally motion while a button is held (mode 1002)
+    ButtonEvent,
+    /// Additionally all motion (mode 1003)
+    AnyEvent,
+}
+
+/// Encoding of mouse reports
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum MouseEncoding {
+    /// Single byte coordinates, up to 223 cells
+    Default,
+    /// Decimal `CSI <` sequences (mode 1006)
+    Sgr,
+    /// Decimal `CSI` sequences (mode 1015)
+    Urxvt,
+    /// SGR encoding with pixel coordinates (mode 1016)
+    SgrPixels,
 }
 
 /// Mouse event data with terminal coordinates
@@ -96,6 +216,16 @@ pub struct MouseEvent {
     pub shift_key: bool,
     /// Whether Alt key was pressed
     pub alt_key: bool,
+    /// Whether Meta (Cmd on macOS) key was pressed
+    pub meta_key: bool,
+    /// Columns scrolled by a wheel event, positive to the right
+    pub wheel_delta_cols: i16,
+    /// Rows scrolled by a wheel event, positive downwards
+    pub wheel_delta_rows: i16,
+    /// Kind of pointing device
+    pub pointer_type: PointerType,
+    /// Number of consecutive clicks of a press or release
+    pub click_count: u8,
 }
 
 /// Query for selecting cells in the terminal
@@ -139,6 +269,30 @@ impl CellQuery {
     }
 }
 
+/// Regions drawn highlighted in a single color, e.g. search matches
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    inner: RustHighlight,
+}
+
+#[wasm_bindgen]
+impl Highlight {
+    /// Create a highlight using the packed color encoding of cell colors, with
+    /// an opacity from 0.0 to 1.0; higher priorities are drawn on top
+    #[wasm_bindgen(constructor)]
+    pub fn new(color: u32, opacity: f32, priority: i32) -> Result<Highlight, JsValue> {
+        let inner = RustHighlight::new(packed_color(color)?).opacity(opacity).priority(priority);
+        Ok(Highlight { inner })
+    }
+
+    /// Add a highlighted region
+    pub fn region(mut self, query: &CellQuery) -> Highlight {
+        self.inner = self.inner.region(query.inner);
+        self
+    }
+}
+
 #[wasm_bindgen]
 pub fn style() -> CellStyle {
     CellStyle::new()
@@ -176,6 +330,34 @@ impl CellStyle {
         self
     }
 
+    /// Sets the foreground color to a palette index (0-255)
+    #[wasm_bindgen(js_name = "fgIndexed")]
+    pub fn fg_indexed(mut self, index: u8) -> CellStyle {
+        self.fg = Color::Indexed(index).to_packed();
+        self
+    }
+
+    /// Sets the background color to a palette index (0-255)
+    #[wasm_bindgen(js_name = "bgIndexed")]
+    pub fn bg_indexed(mut self, index: u8) -> CellStyle {
+        self.bg = Color::Indexed(index).to_packed();
+        self
+    }
+
+    /// Sets the foreground color to the palette's default foreground
+    #[wasm_bindgen(js_name = "defaultFg")]
+    pub fn default_fg(mut self) -> CellStyle {
+        self.fg = Color::DefaultFg.to_packed();
+        self
+    }
+
+    /// Sets the background color to the palette's default background
+    #[wasm_bindgen(js_name = "defaultBg")]
+    pub fn default_bg(mut self) -> CellStyle {
+        self.bg = Color::DefaultBg.to_packed();
+        self
+    }
+
     /// Add bold style
     #[wasm_bindgen]
     pub fn bold(mut self) -> CellStyle {
@@ -225,16 +407,18 @@ impl Default for CellStyle {
 impl Batch {
     /// Updates a single cell at the given position.
     #[wasm_bindgen(js_name = "cell")]
-    pub fn cell(&mut self, x: u16, y: u16, cell_data: &Cell) {
-        self.terminal_grid.borrow_mut().update_cell(x, y, cell_data.as_cell_data());
+    pub fn cell(&mut self, x: u16, y: u16, cell_data: &Cell) -> Result<(), JsValue> {
+        self.terminal_grid.borrow_mut().update_cell(x, y, cell_data.as_cell_data()?);
+        Ok(())
     }
 
     /// Updates a cell by its buffer index.
     #[wasm_bindgen(js_name = "cellByIndex")]
-    pub fn cell_by_index(&mut self, idx: usize, cell_data: &Cell) {
+    pub fn cell_by_index(&mut self, idx: usize, cell_data: &Cell) -> Result<(), JsValue> {
         self.terminal_grid
             .borrow_mut()
-            .update_cell_by_index(idx, cell_data.as_cell_data());
+            .update_cell_by_index(idx, cell_data.as_cell_data()?);
+        Ok(())
     }
 
     /// Updates multiple cells from an array.
@@ -246,11 +430,14 @@ impl Batch {
 
         match updates {
             Ok(cells) => {
-                let cell_data = cells.iter().map(|(x, y, data)| (*x, *y, data.as_cell_data()));
+                let cell_data = cells
+                    .iter()
+                    .map(|(x, y, data)| Ok((*x, *y, data.as_cell_data()?)))
+                    .collect::<Result<Vec<_>, JsValue>>()?;
 
                 let mut terminal_grid = self.terminal_grid.borrow_mut();
                 terminal_grid
-                    .update_cells_by_position(&self.gl, cell_data)
+                    .update_cells_by_position(cell_data.into_iter())
                     .map_err(|e| JsValue::from_str(&e.to_string()))
             },
             e => e.map(|_| ()),
@@ -273,7 +460,9 @@ impl Batch {
                 break;
             }
 
-            let cell = CellData::new_with_style_bits(ch, style.style_bits, style.fg, style.bg);
+            let fg = packed_color(style.fg)?;
+            let bg = packed_color(style.bg)?;
+            let cell = CellData::new_with_colors(ch, style.style_bits, fg, bg);
             terminal_grid.update_cell(current_col, y, cell);
         }
 
@@ -296,7 +485,7 @@ impl Batch {
         let width = (x + width).min(cols).saturating_sub(x);
         let height = (y + height).min(rows).saturating_sub(y);
 
-        let fill_cell = cell_data.as_cell_data();
+        let fill_cell = cell_data.as_cell_data()?;
         for y in y..y + height {
             for x in x..x + width {
                 terminal_grid.update_cell(x, y, fill_cell);
@@ -385,8 +574,10 @@ impl Cell {
 }
 
 impl Cell {
-    pub fn as_cell_data(&self) -> CellData {
-        CellData::new_with_style_bits(&self.symbol, self.style, self.fg, self.bg)
+    pub fn as_cell_data(&self) -> Result<CellData<'_>, JsValue> {
+        let fg = packed_color(self.fg)?;
+        let bg = packed_color(self.bg)?;
+        Ok(CellData::new_with_colors(&self.symbol, self.style, fg, bg))
     }
 }
 
@@ -411,10 +602,28 @@ impl BeamtermRenderer {
 
         console::log_1(&"BeamtermRenderer initialized successfully".into());
         let terminal_grid = Rc::new(RefCell::new(terminal_grid));
+        let context_handler = ContextLossHandler::new(renderer.canvas())
+            .map_err(|e| JsValue::from_str(&format!("Failed to create context handler: {e}")))?;
+
         Ok(BeamtermRenderer {
             renderer,
             terminal_grid,
             mouse_handler: None,
+            mouse_encoder: None,
+            word_separators: None,
+            link_callback: None,
+            hit_regions: Rc::default(),
+            hit_region_callback: None,
+            keyboard_handler: None,
+            keyboard_callback: None,
+            ime_handler: None,
+            paste_handler: None,
+            paste_callback: Rc::default(),
+            accessibility: None,
+            focus_handler: None,
+            context_handler,
+            #[cfg(feature = "regex")]
+            search: SearchResults::default(),
         })
     }
 
@@ -425,34 +634,26 @@ impl BeamtermRenderer {
         mode: SelectionMode,
         trim_whitespace: bool,
     ) -> Result<(), JsValue> {
-        // clean up existing mouse handler if present
-        if let Some(old_handler) = self.mouse_handler.take() {
-            old_handler.cleanup();
-        }
-
         let selection_tracker = self.terminal_grid.borrow().selection_tracker();
-        let handler =
-            DefaultSelectionHandler::new(self.terminal_grid.clone(), mode.into(), trim_whitespace);
+        let handler = self.selection_handler(mode, trim_whitespace);
 
-        let mouse_handler = TerminalMouseHandler::new(
-            self.renderer.canvas(),
-            self.terminal_grid.clone(),
-            handler.create_event_handler(selection_tracker),
-        )
-        .map_err(|e| JsValue::from_str(&format!("Failed to create mouse handler: {e}")))?;
-
-        self.mouse_handler = Some(mouse_handler);
+        let callback =
+            self.with_primary_paste(Some(handler.create_event_handler(selection_tracker)));
+        self.attach_mouse_handler(callback)?;
+        self.mouse_encoder = None;
         Ok(())
     }
 
+    /// Set the characters ending a word selected by double-click, besides
+    /// whitespace. Applies to selection enabled afterwards.
+    #[wasm_bindgen(js_name = "setWordSeparators")]
+    pub fn set_word_separators(&mut self, separators: &str) {
+        self.word_separators = Some(separators.into());
+    }
+
     /// Set a custom mouse event handler
     #[wasm_bindgen(js_name = "setMouseHandler")]
     pub fn set_mouse_handler(&mut self, handler: js_sys::Function) -> Result<(), JsValue> {
-        // Clean up existing mouse handler if present
-        if let Some(old_handler) = self.mouse_handler.take() {
-            old_handler.cleanup();
-        }
-
         let handler_closure = {
             let handler = handler.clone();
             move |event: TerminalMouseEvent, _grid: &TerminalGrid| {
@@ -467,15 +668,237 @@ impl BeamtermRenderer {
             }
         };
 
-        let mouse_handler = TerminalMouseHandler::new(
+        self.attach_mouse_handler(self.with_primary_paste(Some(Box::new(handler_closure))))?;
+        self.mouse_encoder = None;
+        Ok(())
+    }
+
+    /// Report mouse events to a handler as encoded terminal input bytes, while
+    /// enabled with `setMouseModes`. Events with Shift held, and all events
+    /// while reporting is off, select text.
+    #[wasm_bindgen(js_name = "enableMouseReporting")]
+    pub fn enable_mouse_reporting(
+        &mut self,
+        handler: js_sys::Function,
+        mode: SelectionMode,
+        trim_whitespace: bool,
+    ) -> Result<(), JsValue> {
+        let modes = self.mouse_encoder.take().map(|e| e.borrow().modes()).unwrap_or_default();
+        let mut encoder = MouseEncoder::new();
+        encoder.set_modes(modes);
+        let encoder = Rc::new(RefCell::new(encoder));
+
+        let report = move |bytes: &[u8]| {
+            let args = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+            if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                console::error_1(&format!("Mouse report handler error: {e:?}").into());
+            }
+        };
+
+        let selection_tracker = self.terminal_grid.borrow().selection_tracker();
+        let selection = self.selection_handler(mode, trim_whitespace);
+
+        let fallback =
+            self.with_primary_paste(Some(selection.create_event_handler(selection_tracker)));
+        self.attach_mouse_handler(Some(reporting_event_handler(
+            encoder.clone(),
+            Box::new(report),
+            fallback,
+        )))?;
+        self.mouse_encoder = Some(encoder);
+        Ok(())
+    }
+
+    /// Set a handler receiving the URL of hyperlinks clicked with Ctrl or Cmd
+    /// held; hovered hyperlinks are underlined. Applies to mouse handlers set
+    /// afterwards, or handles links only if no mouse handler is set.
+    #[wasm_bindgen(js_name = "setLinkHandler")]
+    pub fn set_link_handler(&mut self, handler: js_sys::Function) -> Result<(), JsValue> {
+        self.link_callback = Some(handler);
+        match self.mouse_handler {
+            Some(_) => Ok(()),
+            None => self.attach_mouse_handler(None),
+        }
+    }
+
+    /// Set a handler receiving the event type, region id and `MouseEvent` of
+    /// hit region events. Applies to mouse handlers set afterwards, or handles
+    /// hit regions only if no mouse handler is set.
+    #[wasm_bindgen(js_name = "setHitRegionHandler")]
+    pub fn set_hit_region_handler(&mut self, handler: js_sys::Function) -> Result<(), JsValue> {
+        self.hit_region_callback = Some(handler);
+        match self.mouse_handler {
+            Some(_) => Ok(()),
+            None => self.attach_mouse_handler(None),
+        }
+    }
+
+    /// Add a hit region of `width` × `height` cells under `id`, replacing any
+    /// region with the same id, showing the CSS `cursor` while hovered
+    #[wasm_bindgen(js_name = "setHitRegion")]
+    pub fn set_hit_region(
+        &mut self,
+        id: &str,
+        col: u16,
+        row: u16,
+        width: u16,
+        height: u16,
+        cursor: Option<String>,
+    ) {
+        let cols = col..col.saturating_add(width);
+        let rows = row..row.saturating_add(height);
+        let region = match cursor {
+            Some(cursor) => HitRegion::new(cols, rows).cursor(&cursor),
+            None => HitRegion::new(cols, rows),
+        };
+        self.hit_regions.borrow_mut().insert(id, region);
+    }
+
+    /// Remove the hit region with the given id; returns whether it existed
+    #[wasm_bindgen(js_name = "removeHitRegion")]
+    pub fn remove_hit_region(&mut self, id: &str) -> bool {
+        self.hit_regions.borrow_mut().remove(id).is_some()
+    }
+
+    /// Remove all hit regions
+    #[wasm_bindgen(js_name = "clearHitRegions")]
+    pub fn clear_hit_regions(&mut self) {
+        self.hit_regions.borrow_mut().clear();
+    }
+
+    /// Set the mouse reporting modes requested by the terminal application
+    #[wasm_bindgen(js_name = "setMouseModes")]
+    pub fn set_mouse_modes(&self, tracking: MouseTracking, encoding: MouseEncoding) {
+        if let Some(encoder) = &self.mouse_encoder {
+            encoder.borrow_mut().set_modes(MouseModes {
+                tracking: tracking.into(),
+                encoding: encoding.into(),
+            });
+        }
+    }
+
+    /// Set a keyboard handler receiving key events encoded as terminal input bytes
+    #[wasm_bindgen(js_name = "setKeyboardHandler")]
+    pub fn set_keyboard_handler(&mut self, handler: js_sys::Function) -> Result<(), JsValue> {
+        self.keyboard_callback = Some(handler);
+        self.enable_focus_tracking()?;
+        self.attach_keyboard_handler()
+    }
+
+    /// Track input focus of the canvas, drawing the cursor as a hollow block
+    /// while unfocused; makes the canvas focusable. Enabled by keyboard and
+    /// text input handlers
+    #[wasm_bindgen(js_name = "enableFocusTracking")]
+    pub fn enable_focus_tracking(&mut self) -> Result<(), JsValue> {
+        if self.focus_handler.is_none() {
+            let handler =
+                CanvasFocusHandler::new(self.renderer.canvas(), self.terminal_grid.clone())
+                    .map_err(|e| {
+                        JsValue::from_str(&format!("Failed to create focus handler: {e}"))
+                    })?;
+            self.focus_handler = Some(handler);
+        }
+        Ok(())
+    }
+
+    /// Enable IME composition and soft keyboard input, with a handler receiving
+    /// committed text as strings
+    #[wasm_bindgen(js_name = "setTextInputHandler")]
+    pub fn set_text_input_handler(&mut self, handler: js_sys::Function) -> Result<(), JsValue> {
+        let caret = self.ime_handler.take().map(|old| old.caret()).unwrap_or_default();
+
+        let handler_closure = move |text: &str| {
+            let args = js_sys::Array::of1(&JsValue::from_str(text));
+            if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                console::error_1(&format!("Text input handler error: {e:?}").into());
+            }
+        };
+
+        let ime_handler = TerminalImeHandler::new(
             self.renderer.canvas(),
             self.terminal_grid.clone(),
             handler_closure,
         )
-        .map_err(|e| JsValue::from_str(&format!("Failed to create mouse handler: {e}")))?;
+        .map_err(|e| JsValue::from_str(&format!("Failed to create text input handler: {e}")))?;
+        ime_handler.set_caret(caret.0, caret.1);
+        ime_handler.update_layout(self.renderer.pixel_ratio());
+        self.ime_handler = Some(ime_handler);
+        self.enable_focus_tracking()?;
+
+        // key and paste events are now delivered to the IME textarea
+        if let (Some(paste), Some(ime)) = (&mut self.paste_handler, &self.ime_handler) {
+            paste
+                .retarget(ime.textarea())
+                .map_err(|e| JsValue::from_str(&format!("Failed to move paste handler: {e}")))?;
+        }
+        self.attach_keyboard_handler()
+    }
 
-        self.mouse_handler = Some(mouse_handler);
-        Ok(())
+    /// Set a handler receiving pasted text encoded as terminal input bytes;
+    /// middle-clicking pastes the active selection. Middle clicks apply to
+    /// mouse handlers set afterwards, or are the only mouse input handled if
+    /// no mouse handler is set.
+    #[wasm_bindgen(js_name = "setPasteHandler")]
+    pub fn set_paste_handler(&mut self, handler: js_sys::Function) -> Result<(), JsValue> {
+        *self.paste_callback.borrow_mut() = Some(handler);
+        if self.paste_handler.is_none() {
+            self.create_paste_handler()?;
+        }
+        if let Some(keyboard) = &self.keyboard_handler {
+            keyboard.set_browser_paste(true);
+        }
+        match self.mouse_handler {
+            Some(_) => Ok(()),
+            None => self.attach_mouse_handler(self.with_primary_paste(None)),
+        }
+    }
+
+    /// Set whether pasted text is wrapped in bracketed paste sequences, as
+    /// requested by the terminal application with mode 2004
+    #[wasm_bindgen(js_name = "setBracketedPaste")]
+    pub fn set_bracketed_paste(&self, enabled: bool) {
+        if let Some(paste) = &self.paste_handler {
+            paste.set_bracketed(enabled);
+        }
+    }
+
+    /// Paste text as if from the clipboard, delivering it to the paste handler
+    #[wasm_bindgen]
+    pub fn paste(&self, text: &str) {
+        if let Some(paste) = &self.paste_handler {
+            paste.paste(text);
+        }
+    }
+
+    /// Move the IME caret, where composition text is drawn, to the given cell
+    #[wasm_bindgen(js_name = "setImeCaret")]
+    pub fn set_ime_caret(&self, col: u16, row: u16) {
+        if let Some(ime) = &self.ime_handler {
+            ime.set_caret(col, row);
+        }
+    }
+
+    /// Set the keyboard modes requested by the terminal application
+    #[wasm_bindgen(js_name = "setKeyboardModes")]
+    pub fn set_keyboard_modes(
+        &self,
+        application_cursor: bool,
+        application_keypad: bool,
+        kitty_flags: u8,
+    ) {
+        if let Some(keyboard) = &self.keyboard_handler {
+            keyboard.set_modes(KeyboardModes {
+                application_cursor,
+                application_keypad,
+                kitty_flags,
+            });
+        }
+    }
+
+    /// Move input focus to the terminal canvas
+    #[wasm_bindgen]
+    pub fn focus(&self) {
+        let _ = self.renderer.canvas().focus();
     }
 
     /// Get selected text based on a cell query
@@ -484,27 +907,67 @@ impl BeamtermRenderer {
         self.terminal_grid.borrow().get_text(query.inner).to_string()
     }
 
+    /// Find text in the displayed cells, optionally case-sensitive or as a
+    /// regular expression; returns the number of matches
+    #[cfg(feature = "regex")]
+    pub fn find(
+        &mut self,
+        pattern: &str,
+        case_sensitive: bool,
+        regex: bool,
+    ) -> Result<usize, JsValue> {
+        let query = SearchQuery::new(pattern).case_sensitive(case_sensitive).regex(regex);
+        self.search = self
+            .terminal_grid
+            .borrow()
+            .find(&query)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(self.search.len())
+    }
+
+    /// Select the next match of the last search, wrapping around after the last one
+    #[wasm_bindgen(js_name = "findNext")]
+    #[cfg(feature = "regex")]
+    pub fn find_next(&mut self) -> Option<CellQuery> {
+        self.search.refresh(self.terminal_grid.borrow().cell_buffer());
+        let found = self.search.next_match();
+        self.select_match(found)
+    }
+
+    /// Select the previous match of the last search, wrapping around before the first one
+    #[wasm_bindgen(js_name = "findPrevious")]
+    #[cfg(feature = "regex")]
+    pub fn find_previous(&mut self) -> Option<CellQuery> {
+        self.search.refresh(self.terminal_grid.borrow().cell_buffer());
+        let found = self.search.previous_match();
+        self.select_match(found)
+    }
+
     /// Copy text to the system clipboard
     #[wasm_bindgen(js_name = "copyToClipboard")]
     pub fn copy_to_clipboard(&self, text: &str) {
-        use wasm_bindgen_futures::spawn_local;
-        let text = text.to_string();
-
-        spawn_local(async move {
-            if let Some(window) = web_sys::window() {
-                let clipboard = window.navigator().clipboard();
-                match wasm_bindgen_futures::JsFuture::from(clipboard.write_text(&text)).await {
-                    Ok(_) => {
-                        console::log_1(
-                            &format!("Copied {} characters to clipboard", text.len()).into(),
-                        );
-                    },
-                    Err(err) => {
-                        console::error_1(&format!("Failed to copy to clipboard: {err:?}").into());
-                    },
-                }
-            }
-        });
+        mouse::copy_to_clipboard(text.into(), None);
+    }
+
+    /// Copy the active selection to the system clipboard as plain text and
+    /// styled HTML; returns whether there was a selection
+    #[wasm_bindgen(js_name = "copySelection")]
+    pub fn copy_selection(&self) -> bool {
+        let grid = self.terminal_grid.borrow();
+        let Some(query) = grid.selection_tracker().get_query() else {
+            return false;
+        };
+
+        mouse::copy_selection(&grid, query);
+        true
+    }
+
+    /// Get the text of a cell query with ANSI escape sequences for its colors and styles
+    #[wasm_bindgen(js_name = "getAnsiText")]
+    pub fn get_ansi_text(&self, query: &CellQuery) -> String {
+        ScreenExport::from_grid(&self.terminal_grid.borrow())
+            .region(query.inner)
+            .to_ansi()
     }
 
     /// Clear any active selection
@@ -534,6 +997,141 @@ impl BeamtermRenderer {
         Size { width: cols, height: rows }
     }
 
+    /// Set the color palette used by indexed colors.
+    ///
+    /// `colors` holds either the 16 ANSI colors or all 256 xterm colors as
+    /// 0xRRGGBB; missing extended colors use the xterm defaults.
+    #[wasm_bindgen(js_name = "setPalette")]
+    pub fn set_palette(
+        &mut self,
+        colors: Vec<u32>,
+        foreground: u32,
+        background: u32,
+    ) -> Result<(), JsValue> {
+        if colors.len() != 16 && colors.len() != 256 {
+            return Err(JsValue::from_str("Palette must contain 16 or 256 colors"));
+        }
+
+        let mut ansi = [0; 16];
+        ansi.copy_from_slice(&colors[..16]);
+        let mut palette = Palette::new(ansi, foreground, background);
+        for (index, &color) in colors.iter().enumerate().skip(16) {
+            palette.set_color(index as u8, color);
+        }
+
+        let gl = self.renderer.gl();
+        self.terminal_grid.borrow_mut().set_palette(gl, palette);
+        Ok(())
+    }
+
+    /// Move the cursor to the given cell and set its shape; makes the cursor visible
+    #[wasm_bindgen(js_name = "setCursor")]
+    pub fn set_cursor(&mut self, col: u16, row: u16, shape: CursorShape) {
+        self.terminal_grid.borrow_mut().cursor_mut().set(col, row, shape.into());
+    }
+
+    /// Show or hide the cursor
+    #[wasm_bindgen(js_name = "setCursorVisible")]
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.terminal_grid.borrow_mut().cursor_mut().set_visible(visible);
+    }
+
+    /// Set the cursor color, using the packed color encoding of cell colors;
+    /// `undefined` uses the palette cursor color or the cell foreground color
+    #[wasm_bindgen(js_name = "setCursorColor")]
+    pub fn set_cursor_color(&mut self, color: Option<u32>) -> Result<(), JsValue> {
+        let color = color.map(packed_color).transpose()?;
+        self.terminal_grid.borrow_mut().cursor_mut().set_color(color);
+        Ok(())
+    }
+
+    /// Set the selection highlight color, using the packed color encoding of cell
+    /// colors, and its opacity from 0.0 to 1.0; `undefined` uses the palette
+    /// selection color or the default foreground color
+    #[wasm_bindgen(js_name = "setSelectionHighlight")]
+    pub fn set_selection_highlight(
+        &mut self,
+        color: Option<u32>,
+        opacity: f32,
+    ) -> Result<(), JsValue> {
+        let mut highlight = SelectionHighlight::new();
+        highlight.set_color(color.map(packed_color).transpose()?);
+        highlight.set_opacity(opacity);
+        self.terminal_grid.borrow_mut().set_selection_highlight(highlight);
+        Ok(())
+    }
+
+    /// Add a highlight under the given name, replacing any highlight of the same name
+    #[wasm_bindgen(js_name = "setHighlight")]
+    pub fn set_highlight(&mut self, name: &str, highlight: &Highlight) {
+        let mut grid = self.terminal_grid.borrow_mut();
+        grid.highlights_mut().insert(name, highlight.inner.clone());
+    }
+
+    /// Remove the highlight with the given name; returns whether it existed
+    #[wasm_bindgen(js_name = "removeHighlight")]
+    pub fn remove_highlight(&mut self, name: &str) -> bool {
+        self.terminal_grid.borrow_mut().highlights_mut().remove(name).is_some()
+    }
+
+    /// Remove all highlights
+    #[wasm_bindgen(js_name = "clearHighlights")]
+    pub fn clear_highlights(&mut self) {
+        self.terminal_grid.borrow_mut().highlights_mut().clear();
+    }
+
+    /// Set the hyperlink of a cell, or clear it with `undefined`; writing to the
+    /// cell clears its hyperlink
+    #[wasm_bindgen(js_name = "setHyperlink")]
+    pub fn set_hyperlink(&mut self, col: u16, row: u16, url: Option<String>) {
+        let mut grid = self.terminal_grid.borrow_mut();
+        let buffer = grid.cell_buffer_mut();
+        let link = url.and_then(|url| buffer.register_hyperlink(&url, None));
+        buffer.set_hyperlink(col, row, link);
+    }
+
+    /// Enable or disable cursor blinking
+    #[wasm_bindgen(js_name = "setCursorBlinking")]
+    pub fn set_cursor_blinking(&mut self, blinking: bool) {
+        self.terminal_grid.borrow_mut().cursor_mut().set_blinking(blinking);
+    }
+
+    /// Set the maximum number of lines retained in the scrollback; 0 disables it
+    #[wasm_bindgen(js_name = "setScrollbackCapacity")]
+    pub fn set_scrollback_capacity(&mut self, lines: usize) {
+        self.terminal_grid.borrow_mut().set_scrollback_capacity(lines);
+    }
+
+    /// Scroll the screen content up, pushing the top lines into the scrollback
+    #[wasm_bindgen(js_name = "pushLinesToScrollback")]
+    pub fn push_lines_to_scrollback(&mut self, count: u16) {
+        self.terminal_grid.borrow_mut().push_lines_to_scrollback(count);
+    }
+
+    /// Get the number of lines held in the scrollback
+    #[wasm_bindgen(js_name = "scrollbackLength")]
+    pub fn scrollback_length(&self) -> usize {
+        self.terminal_grid.borrow().scrollback_len()
+    }
+
+    /// Get the number of lines the viewport is scrolled back; 0 shows the screen
+    #[wasm_bindgen(js_name = "viewportOffset")]
+    pub fn viewport_offset(&self) -> usize {
+        self.terminal_grid.borrow().viewport_offset()
+    }
+
+    /// Scroll the viewport by a number of lines; positive values scroll back
+    #[wasm_bindgen(js_name = "scrollViewportBy")]
+    pub fn scroll_viewport_by(&mut self, lines: i32) {
+        self.terminal_grid.borrow_mut().scroll_viewport_by(lines);
+    }
+
+    /// Scroll the viewport to an absolute offset into the scrollback
+    #[wasm_bindgen(js_name = "scrollViewportTo")]
+    pub fn scroll_viewport_to(&mut self, offset: usize) {
+        self.terminal_grid.borrow_mut().scroll_viewport_to(offset);
+    }
+
     /// Get the cell size in pixels
     #[wasm_bindgen(js_name = "cellSize")]
     pub fn cell_size(&self) -> Size {
@@ -547,12 +1145,84 @@ impl BeamtermRenderer {
     /// Render the terminal to the canvas
     #[wasm_bindgen]
     pub fn render(&mut self) {
+        if self.context_handler.is_lost() {
+            return;
+        }
+
         let mut grid = self.terminal_grid.borrow_mut();
+        if self.context_handler.take_restore() {
+            self.renderer.restore();
+            if let Err(e) = grid.restore(self.renderer.gl()) {
+                console::error_1(&format!("Failed to restore WebGL resources: {e}").into());
+                return;
+            }
+        }
+
         let _ = grid.flush_cells(self.renderer.gl());
+        grid.update_cursor(self.renderer.gl(), js::now_ms());
 
         self.renderer.begin_frame();
         self.renderer.render(&*grid);
         self.renderer.end_frame();
+
+        if let Some(accessibility) = &mut self.accessibility {
+            accessibility.update(&grid);
+        }
+    }
+
+    /// Set a handler receiving a `ContextEvent` when the WebGL context is lost
+    /// or restored; nothing is drawn while the context is lost
+    #[wasm_bindgen(js_name = "setContextLossHandler")]
+    pub fn set_context_loss_handler(&self, handler: js_sys::Function) {
+        self.context_handler.set_callback(move |event: RustContextEvent| {
+            let args = js_sys::Array::of1(&JsValue::from(ContextEvent::from(event)));
+            if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                console::error_1(&format!("Context loss handler error: {e:?}").into());
+            }
+        });
+    }
+
+    /// Check if the WebGL context is lost
+    #[wasm_bindgen(js_name = "isContextLost")]
+    pub fn is_context_lost(&self) -> bool {
+        self.context_handler.is_lost()
+    }
+
+    /// Mirror the terminal content into a visually hidden DOM tree for screen
+    /// readers, announcing changed lines at most once per `intervalMs`, with
+    /// at most `maxLines` lines each; the mirror is updated by `render`
+    #[wasm_bindgen(js_name = "enableAccessibility")]
+    pub fn enable_accessibility(
+        &mut self,
+        interval_ms: f64,
+        max_lines: usize,
+    ) -> Result<(), JsValue> {
+        let accessibility = TerminalAccessibility::new(self.renderer.canvas())
+            .map_err(|e| JsValue::from_str(&format!("Failed to enable accessibility: {e}")))?;
+        accessibility.set_rate_limit(interval_ms, max_lines);
+
+        self.accessibility = Some(accessibility);
+        Ok(())
+    }
+
+    /// Export the visible screen as an SVG document
+    #[wasm_bindgen(js_name = "exportSvg")]
+    pub fn export_svg(&self) -> String {
+        ScreenExport::from_grid(&self.terminal_grid.borrow()).to_svg()
+    }
+
+    /// Export the visible screen as a standalone HTML `<pre>` element
+    #[wasm_bindgen(js_name = "exportHtml")]
+    pub fn export_html(&self) -> String {
+        ScreenExport::from_grid(&self.terminal_grid.borrow()).to_html()
+    }
+
+    /// Render a frame and encode it as a PNG file, returning a promise
+    /// resolving to a `Blob`
+    #[wasm_bindgen(js_name = "screenshotPng")]
+    pub fn screenshot_png(&mut self) -> Result<js_sys::Promise, JsValue> {
+        self.render();
+        export::canvas_png(self.renderer.canvas()).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
     /// Resize the terminal to fit new canvas dimensions
@@ -572,11 +1242,183 @@ impl BeamtermRenderer {
             mouse_handler.update_dimensions(cols, rows);
         }
 
+        if let Some(ime) = &self.ime_handler {
+            ime.update_layout(self.renderer.pixel_ratio());
+        }
+
+        Ok(())
+    }
+}
+
+impl BeamtermRenderer {
+    /// Makes a search match the active selection.
+    #[cfg(feature = "regex")]
+    fn select_match(&self, found: Option<RustCellQuery>) -> Option<CellQuery> {
+        let query = found?;
+        self.terminal_grid.borrow().selection_tracker().set_query(query);
+        Some(CellQuery { inner: query })
+    }
+
+    /// Creates a selection handler using the configured word separators.
+    fn selection_handler(
+        &self,
+        mode: SelectionMode,
+        trim_whitespace: bool,
+    ) -> DefaultSelectionHandler {
+        let handler =
+            DefaultSelectionHandler::new(self.terminal_grid.clone(), mode.into(), trim_whitespace);
+        match &self.word_separators {
+            Some(separators) => handler.with_word_separators(separators),
+            None => handler,
+        }
+    }
+
+    /// (Re)creates the mouse handler with `callback`, handling hit regions
+    /// and hyperlinks before it if their handlers are set.
+    fn attach_mouse_handler(
+        &mut self,
+        callback: Option<MouseEventCallback>,
+    ) -> Result<(), JsValue> {
+        if let Some(old_handler) = self.mouse_handler.take() {
+            old_handler.cleanup();
+        }
+
+        let callback = match self.hit_region_callback.clone() {
+            Some(handler) => {
+                let hit_region_closure = move |event: HitRegionEvent| {
+                    let args = js_sys::Array::of3(
+                        &JsValue::from(HitRegionEventType::from(event.event_type)),
+                        &JsValue::from_str(&event.id),
+                        &JsValue::from(MouseEvent::from(event.mouse)),
+                    );
+                    if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                        console::error_1(&format!("Hit region handler error: {e:?}").into());
+                    }
+                };
+                let canvas = self.renderer.canvas().clone();
+                let regions = self.hit_regions.clone();
+                Some(hit_region_event_handler(
+                    canvas,
+                    regions,
+                    Box::new(hit_region_closure),
+                    callback,
+                ))
+            },
+            None => callback,
+        };
+
+        let callback = match self.link_callback.clone() {
+            Some(handler) => {
+                let link_closure = move |url: &str| {
+                    let args = js_sys::Array::of1(&JsValue::from_str(url));
+                    if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                        console::error_1(&format!("Link handler error: {e:?}").into());
+                    }
+                };
+                let hover = self.terminal_grid.borrow().link_hover_tracker();
+                let canvas = self.renderer.canvas().clone();
+                link_event_handler(canvas, hover, Box::new(link_closure), callback)
+            },
+            None => match callback {
+                Some(callback) => callback,
+                None => return Ok(()),
+            },
+        };
+
+        let mouse_handler =
+            TerminalMouseHandler::new(self.renderer.canvas(), self.terminal_grid.clone(), callback)
+                .map_err(|e| JsValue::from_str(&format!("Failed to create mouse handler: {e}")))?;
+
+        self.mouse_handler = Some(mouse_handler);
+        Ok(())
+    }
+
+    /// Wraps `callback` to paste the active selection on middle clicks, if a
+    /// paste handler is set.
+    fn with_primary_paste(
+        &self,
+        callback: Option<MouseEventCallback>,
+    ) -> Option<MouseEventCallback> {
+        match &self.paste_handler {
+            Some(paste) => Some(paste.primary_selection_handler(callback)),
+            None => callback,
+        }
+    }
+
+    /// Creates the paste handler on the IME textarea, if enabled, or the
+    /// canvas, calling the current paste callback.
+    fn create_paste_handler(&mut self) -> Result<(), JsValue> {
+        let callback = self.paste_callback.clone();
+        let handler_closure = move |bytes: &[u8]| {
+            let Some(handler) = callback.borrow().clone() else {
+                return;
+            };
+            let args = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+            if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                console::error_1(&format!("Paste handler error: {e:?}").into());
+            }
+        };
+
+        let target: &web_sys::HtmlElement = match &self.ime_handler {
+            Some(ime) => ime.textarea(),
+            None => self.renderer.canvas(),
+        };
+        let paste_handler = TerminalPasteHandler::new(target, handler_closure)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create paste handler: {e}")))?;
+
+        self.paste_handler = Some(paste_handler);
+        Ok(())
+    }
+
+    /// (Re)creates the keyboard handler on the IME textarea, if enabled, or
+    /// the canvas, keeping the current keyboard modes.
+    fn attach_keyboard_handler(&mut self) -> Result<(), JsValue> {
+        let modes = self.keyboard_handler.take().map(|old| old.modes()).unwrap_or_default();
+        let Some(handler) = self.keyboard_callback.clone() else {
+            return Ok(());
+        };
+
+        let handler_closure = move |bytes: &[u8]| {
+            let args = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+            if let Err(e) = handler.apply(&JsValue::null(), &args) {
+                console::error_1(&format!("Keyboard handler error: {e:?}").into());
+            }
+        };
+
+        let target: &web_sys::HtmlElement = match &self.ime_handler {
+            Some(ime) => ime.textarea(),
+            None => self.renderer.canvas(),
+        };
+        let keyboard_handler = TerminalKeyboardHandler::new(target, handler_closure)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create keyboard handler: {e}")))?;
+        keyboard_handler.set_modes(modes);
+        keyboard_handler.set_browser_paste(self.paste_handler.is_some());
+
+        self.keyboard_handler = Some(keyboard_handler);
         Ok(())
     }
 }
 
 // Convert between Rust and WASM types
+impl From<RustHitRegionEventType> for HitRegionEventType {
+    fn from(event_type: RustHitRegionEventType) -> Self {
+        match event_type {
+            RustHitRegionEventType::Enter => HitRegionEventType::Enter,
+            RustHitRegionEventType::Leave => HitRegionEventType::Leave,
+            RustHitRegionEventType::Click => HitRegionEventType::Click,
+        }
+    }
+}
+
+impl From<RustContextEvent> for ContextEvent {
+    fn from(event: RustContextEvent) -> Self {
+        match event {
+            RustContextEvent::Lost => ContextEvent::Lost,
+            RustContextEvent::Restored => ContextEvent::Restored,
+        }
+    }
+}
+
 impl From<SelectionMode> for RustSelectionMode {
     fn from(mode: SelectionMode) -> Self {
         match mode {
@@ -595,15 +1437,60 @@ impl From<RustSelectionMode> for SelectionMode {
     }
 }
 
+impl From<CursorShape> for RustCursorShape {
+    fn from(shape: CursorShape) -> Self {
+        match shape {
+            CursorShape::Block => RustCursorShape::Block,
+            CursorShape::HollowBlock => RustCursorShape::HollowBlock,
+            CursorShape::Bar => RustCursorShape::Bar,
+            CursorShape::Underline => RustCursorShape::Underline,
+        }
+    }
+}
+
+impl From<MouseTracking> for RustMouseTracking {
+    fn from(tracking: MouseTracking) -> Self {
+        match tracking {
+            MouseTracking::Off => RustMouseTracking::Off,
+            MouseTracking::X10 => RustMouseTracking::X10,
+            MouseTracking::Normal => RustMouseTracking::Normal,
+            MouseTracking::ButtonEvent => RustMouseTracking::ButtonEvent,
+            MouseTracking::AnyEvent => RustMouseTracking::AnyEvent,
+        }
+    }
+}
+
+impl From<MouseEncoding> for RustMouseEncoding {
+    fn from(encoding: MouseEncoding) -> Self {
+        match encoding {
+            MouseEncoding::Default => RustMouseEncoding::Default,
+            MouseEncoding::Sgr => RustMouseEncoding::Sgr,
+            MouseEncoding::Urxvt => RustMouseEncoding::Urxvt,
+            MouseEncoding::SgrPixels => RustMouseEncoding::SgrPixels,
+        }
+    }
+}
+
 impl From<TerminalMouseEvent> for MouseEvent {
     fn from(event: TerminalMouseEvent) -> Self {
-        use crate::mouse::MouseEventType as RustMouseEventType;
+        use crate::mouse::{MouseEventType as RustMouseEventType, PointerType as RustPointerType};
 
         let event_type = match event.event_type {
             RustMouseEventType::MouseDown => MouseEventType::MouseDown,
             RustMouseEventType::MouseUp => MouseEventType::MouseUp,
             RustMouseEventType::MouseMove => MouseEventType::MouseMove,
+            RustMouseEventType::Wheel => MouseEventType::Wheel,
+            RustMouseEventType::Tap => MouseEventType::Tap,
+            RustMouseEventType::LongPress => MouseEventType::LongPress,
+            RustMouseEventType::TouchMove => MouseEventType::TouchMove,
+            RustMouseEventType::TouchEnd => MouseEventType::TouchEnd,
+        };
+        let pointer_type = match event.pointer_type() {
+            RustPointerType::Mouse => PointerType::Mouse,
+            RustPointerType::Pen => PointerType::Pen,
+            RustPointerType::Touch => PointerType::Touch,
         };
+        let (wheel_delta_cols, wheel_delta_rows) = event.wheel_delta();
 
         MouseEvent {
             event_type,
@@ -613,10 +1500,20 @@ impl From<TerminalMouseEvent> for MouseEvent {
             ctrl_key: event.ctrl_key(),
             shift_key: event.shift_key(),
             alt_key: event.alt_key(),
+            meta_key: event.meta_key(),
+            wheel_delta_cols,
+            wheel_delta_rows,
+            pointer_type,
+            click_count: event.click_count(),
         }
     }
 }
 
+/// Decodes a color from the packed encoding of cell colors.
+fn packed_color(packed: u32) -> Result<Color, JsValue> {
+    Color::from_packed(packed).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
 /// Initialize the WASM module
 #[wasm_bindgen(start)]
 pub fn main() {
@@ -159,6 +159,10 @@ const Beamterm = {
         return initialized ? BeamtermModule.SelectionMode : undefined; 
     },
     
+    get CursorShape() { 
+        return initialized ? BeamtermModule.CursorShape : undefined; 
+    },
+    
     get MouseEventType() { 
         return initialized ? BeamtermModule.MouseEventType : undefined; 
     },